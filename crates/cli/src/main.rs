@@ -1,54 +1,609 @@
 use std::{
-    fs::read_to_string,
-    io::{stderr, stdout, IsTerminal},
-    path::PathBuf,
+    fs::{read_to_string, File},
+    io::{stderr, stdout, BufWriter, IsTerminal, Write},
+    path::{Path, PathBuf},
     process::exit,
     rc::Rc,
 };
 
-use clap::{arg, command, value_parser, Command};
+use clap::{arg, command, value_parser, ArgMatches, Command};
 use fpp_compiler::{
-    lir::s7::{self, WriteAwl},
-    mir,
+    error::Error,
+    hir::Hir,
+    lir::{
+        c::{self, CSource},
+        il::{self, IlSource, WriteIl},
+        logix::{LogixFormat, LogixRoutine},
+        plcopen::{PlcopenBody, PlcopenProject},
+        s7::{
+            self,
+            source::{S7Source, S7SourceKind},
+            S7Lir, S7Mnemonics,
+        },
+        scl::{self, SclLir, SclSource, WriteScl},
+        simatic::SimaticBlock,
+        st::{StMemory, StSource, WriteSt},
+        verilog::{VerilogModule, WriteVerilog},
+    },
+    mir::{self, test::MirTest, Mir},
     parser::Parser,
     util::Source,
 };
+use fpp_simulator::{
+    error::Error as SimulatorError,
+    stimulus::{Stimulus, TimeUnit},
+    test,
+    vcd::{self, Signal, Vcd},
+    verify, Simulator,
+};
 use messages::message::{Message, MessageContent};
 
 fn main() {
     let terminal = stderr().is_terminal();
     fpp_compiler::init(terminal);
     let matches = command!()
+        .subcommand(
+            Command::new("build")
+                .about("Compile F++ to S7 instructions without writing them")
+                .arg(arg!(<FILE> "F++ source file").value_parser(value_parser!(PathBuf)))
+                .arg(arg!(--verify "Check the boolean outputs against their instructions")),
+        )
         .subcommand(
             Command::new("awl")
                 .about("Compile F++ to AWL")
-                .arg(arg!(<FILE> "F++ source file").value_parser(value_parser!(PathBuf))),
+                .arg(arg!(<FILE> "F++ source file").value_parser(value_parser!(PathBuf)))
+                .arg(
+                    arg!(-b --block <BLOCK> "Generated block (`OB1`, `FC10`)")
+                        .value_parser(parse_block)
+                        .default_value("OB1"),
+                )
+                .arg(arg!(--title <TITLE> "Title of the block"))
+                .arg(arg!(--version <VERSION> "Version of the block").default_value("0.1"))
+                .arg(arg!(--verify "Check the boolean outputs against their instructions"))
+                .arg(
+                    arg!(-m --mnemonics <SET> "Mnemonic set of the STEP 7 installation")
+                        .value_parser(["german", "english"])
+                        .default_value("german"),
+                )
+                .arg(
+                    arg!(-o --output <OUTPUT> "Write the source to a file")
+                        .value_parser(value_parser!(PathBuf)),
+                ),
+        )
+        .subcommand(
+            Command::new("scl")
+                .about("Compile F++ to SCL")
+                .arg(arg!(<FILE> "F++ source file").value_parser(value_parser!(PathBuf)))
+                .arg(arg!(--name <NAME> "Name of the block").default_value("Main"))
+                .arg(arg!(--version <VERSION> "Version of the block").default_value("0.1"))
+                .arg(
+                    arg!(-m --mnemonics <SET> "Mnemonic set of the TIA Portal installation")
+                        .value_parser(["german", "english"])
+                        .default_value("english"),
+                )
+                .arg(
+                    arg!(-o --output <OUTPUT> "Write the source to a file")
+                        .value_parser(value_parser!(PathBuf)),
+                ),
+        )
+        .subcommand(
+            Command::new("st")
+                .about("Compile F++ to IEC 61131-3 Structured Text")
+                .arg(arg!(<FILE> "F++ source file").value_parser(value_parser!(PathBuf)))
+                .arg(arg!(--name <NAME> "Name of the program").default_value("Main"))
+                .arg(
+                    arg!(--memory <MEMORY> "Declare memory operands at their address or as variables")
+                        .value_parser(["located", "variables"])
+                        .default_value("variables"),
+                )
+                .arg(
+                    arg!(-o --output <OUTPUT> "Write the source to a file")
+                        .value_parser(value_parser!(PathBuf)),
+                ),
+        )
+        .subcommand(
+            Command::new("plcopen")
+                .about("Compile F++ to PLCopen XML")
+                .arg(arg!(<FILE> "F++ source file").value_parser(value_parser!(PathBuf)))
+                .arg(arg!(--name <NAME> "Name of the program").default_value("Main"))
+                .arg(
+                    arg!(--memory <MEMORY> "Declare memory operands at their address or as variables")
+                        .value_parser(["located", "variables"])
+                        .default_value("variables"),
+                )
+                .arg(
+                    arg!(--body <LANGUAGE> "Language of the program body")
+                        .value_parser(["st", "fbd"])
+                        .default_value("st"),
+                )
+                .arg(
+                    arg!(-o --output <OUTPUT> "Write the project to a file")
+                        .value_parser(value_parser!(PathBuf)),
+                ),
+        )
+        .subcommand(
+            Command::new("simatic")
+                .about("Compile F++ to TIA Portal SimaticML documents and a tag table")
+                .arg(arg!(<FILE> "F++ source file").value_parser(value_parser!(PathBuf)))
+                .arg(
+                    arg!(-b --block <BLOCK> "Generated block (`OB1`, `FC10`)")
+                        .value_parser(parse_block)
+                        .default_value("OB1"),
+                )
+                .arg(arg!(--name <NAME> "Name of the block").default_value("Main"))
+                .arg(arg!(--tags <TAGS> "Name of the tag table").default_value("F++ Tags"))
+                .arg(arg!(--verify "Check the boolean outputs against their instructions"))
+                .arg(
+                    arg!(-o --output <OUTPUT> "Directory of the documents")
+                        .value_parser(value_parser!(PathBuf))
+                        .default_value("."),
+                ),
+        )
+        .subcommand(
+            Command::new("logix")
+                .about("Compile F++ to Allen-Bradley Logix rungs")
+                .arg(arg!(<FILE> "F++ source file").value_parser(value_parser!(PathBuf)))
+                .arg(arg!(--routine <ROUTINE> "Name of the routine").default_value("MainRoutine"))
+                .arg(arg!(--program <PROGRAM> "Program of the routine").default_value("MainProgram"))
+                .arg(
+                    arg!(-f --format <FORMAT> "L5X routine file or neutral rung text")
+                        .value_parser(["l5x", "text"])
+                        .default_value("l5x"),
+                )
+                .arg(
+                    arg!(-o --output <OUTPUT> "Write the routine to a file")
+                        .value_parser(value_parser!(PathBuf)),
+                ),
+        )
+        .subcommand(
+            Command::new("il")
+                .about("Compile F++ to IEC 61131-3 Instruction List")
+                .arg(arg!(<FILE> "F++ source file").value_parser(value_parser!(PathBuf)))
+                .arg(arg!(--name <NAME> "Name of the program").default_value("Main"))
+                .arg(
+                    arg!(--memory <MEMORY> "Declare memory operands at their address or as variables")
+                        .value_parser(["located", "variables"])
+                        .default_value("variables"),
+                )
+                .arg(
+                    arg!(--"max-depth" <DEPTH> "Bracket depth supported by the target")
+                        .value_parser(value_parser!(usize))
+                        .default_value("8"),
+                )
+                .arg(
+                    arg!(-o --output <OUTPUT> "Write the program to a file")
+                        .value_parser(value_parser!(PathBuf)),
+                ),
+        )
+        .subcommand(
+            Command::new("verilog")
+                .about("Compile F++ to synthesizable Verilog")
+                .arg(arg!(<FILE> "F++ source file").value_parser(value_parser!(PathBuf)))
+                .arg(arg!(--name <NAME> "Name of the module").default_value("Main"))
+                .arg(
+                    arg!(--testbench <TESTBENCH> "Write a testbench of the module to a file")
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    arg!(--scans <SCANS> "Random scans of the testbench with over 12 input bits")
+                        .value_parser(value_parser!(u32))
+                        .default_value("256"),
+                )
+                .arg(
+                    arg!(-o --output <OUTPUT> "Write the module to a file")
+                        .value_parser(value_parser!(PathBuf)),
+                ),
+        )
+        .subcommand(
+            Command::new("c")
+                .about("Compile F++ to a C99 scan function for soft-PLCs")
+                .arg(arg!(<FILE> "F++ source file").value_parser(value_parser!(PathBuf)))
+                .arg(arg!(--name <NAME> "Base name of the header and source").default_value("plc"))
+                .arg(
+                    arg!(-m --mnemonics <SET> "Mnemonic set of the documented inputs and outputs")
+                        .value_parser(["german", "english"])
+                        .default_value("german"),
+                )
+                .arg(
+                    arg!(-o --output <OUTPUT> "Directory of the header and source")
+                        .value_parser(value_parser!(PathBuf))
+                        .default_value("."),
+                ),
+        )
+        .subcommand(
+            Command::new("sim")
+                .about("Simulate the scan cycles of the S7 program")
+                .arg(arg!(<FILE> "F++ source file").value_parser(value_parser!(PathBuf)))
+                .arg(
+                    arg!(--stimulus <STIMULUS> "CSV file of input changes per cycle or millisecond")
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    arg!(--vcd <VCD> "Write the inputs, outputs and variables as waveform")
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    arg!(--"cycle-time" <MS> "Duration of a scan in milliseconds")
+                        .value_parser(value_parser!(u32).range(1..))
+                        .default_value("10"),
+                )
+                .arg(
+                    arg!(--scans <SCANS> "Number of scans, by default until the last input change")
+                        .value_parser(value_parser!(u64)),
+                ),
+        )
+        .subcommand(
+            Command::new("test")
+                .about("Run the test blocks against the simulated S7 program")
+                .arg(arg!(<FILE> "F++ source file").value_parser(value_parser!(PathBuf)))
+                .arg(
+                    arg!(--"cycle-time" <MS> "Duration of a scan in milliseconds")
+                        .value_parser(value_parser!(u32).range(1..))
+                        .default_value("10"),
+                ),
         )
         .subcommand_required(true)
         .get_matches();
     match matches.subcommand() {
+        Some(("build", args)) => {
+            let mir = compile(args);
+            let lir = s7::transformer::transform(&mir).unwrap_or_else(|err| fail(err.message()));
+            if args.get_flag("verify") {
+                verify(&mir, &lir);
+            }
+        }
         Some(("awl", args)) => {
-            let file = args.get_one::<PathBuf>("FILE").unwrap();
-            let input = read_to_string(file);
-            if let Err(err) = input {
-                let err = err.to_string();
-                let message = Message::error(MessageContent::None, &err);
-                eprintln!("{message}");
-                exit(1);
-            }
-            let source = Rc::new(Source::new(file.to_string_lossy(), input.unwrap()));
-            let mut parser = Parser::new(source);
-            let hir = parser.parse().unwrap_or_else(|err| {
-                eprintln!("{err}");
-                exit(1);
-            });
-            let mir = mir::transformer::transform(hir).unwrap_or_else(|err| {
-                eprintln!("{err}");
-                exit(1);
-            });
-            let lir = s7::transformer::transform(&mir).expect("S7-LIR");
-            lir.networks[0].write_awl(&mut stdout()).expect("Write AWL");
+            let mir = compile(args);
+            let lir = s7::transformer::transform(&mir).unwrap_or_else(|err| fail(err.message()));
+            if args.get_flag("verify") {
+                verify(&mir, &lir);
+            }
+            let &(kind, number) = args.get_one::<(S7SourceKind, u16)>("block").unwrap();
+            let mut source = S7Source::new(kind, number, &lir);
+            if let Some(title) = args.get_one::<String>("title") {
+                source.title = title.clone();
+            }
+            source.version = args.get_one::<String>("version").unwrap().clone();
+            source
+                .write_source(&mut output(args), mnemonics(args))
+                .expect("Write AWL");
+        }
+        Some(("scl", args)) => {
+            let mir = compile(args);
+            let lir = scl::transformer::transform(&mir).unwrap_or_else(|err| report(err));
+            let mut source = SclSource::new(args.get_one::<String>("name").unwrap(), &lir);
+            source.version = args.get_one::<String>("version").unwrap().clone();
+            source
+                .write_scl(&mut output(args), mnemonics(args))
+                .expect("Write SCL");
+        }
+        Some(("st", args)) => {
+            let mir = compile(args);
+            let lir = scl::transformer::transform(&mir).unwrap_or_else(|err| report(err));
+            let source = st_source(args, &lir);
+            source.write_st(&mut output(args)).expect("Write ST");
+        }
+        Some(("plcopen", args)) => {
+            let mir = compile(args);
+            let lir = scl::transformer::transform(&mir).unwrap_or_else(|err| report(err));
+            let source = st_source(args, &lir);
+            let body = match args.get_one::<String>("body").unwrap().as_str() {
+                "fbd" => PlcopenBody::Fbd,
+                _ => PlcopenBody::St,
+            };
+            let project =
+                PlcopenProject::new(&source, body).unwrap_or_else(|err| fail(err.message()));
+            project.write_xml(&mut output(args)).expect("Write XML");
+        }
+        Some(("simatic", args)) => {
+            let mir = compile(args);
+            let lir = s7::transformer::transform(&mir).unwrap_or_else(|err| fail(err.message()));
+            if args.get_flag("verify") {
+                verify(&mir, &lir);
+            }
+            let &(kind, number) = args.get_one::<(S7SourceKind, u16)>("block").unwrap();
+            let name = args.get_one::<String>("name").unwrap();
+            let block = SimaticBlock::new(kind, number, name.clone(), &lir)
+                .unwrap_or_else(|err| fail(err.message()));
+            let dir = args.get_one::<PathBuf>("output").unwrap();
+            block
+                .write_block(&mut create(&dir.join(format!("{name}.xml"))))
+                .expect("Write XML");
+            for instance in &block.instances {
+                let path = dir.join(format!("{}.xml", instance.name));
+                block
+                    .write_instance(&mut create(&path), instance)
+                    .expect("Write XML");
+            }
+            let tags = args.get_one::<String>("tags").unwrap();
+            block
+                .write_tags(&mut create(&dir.join(format!("{tags}.xml"))), tags)
+                .expect("Write XML");
+        }
+        Some(("logix", args)) => {
+            let mir = compile(args);
+            let lir = scl::transformer::transform(&mir).unwrap_or_else(|err| report(err));
+            let name = args.get_one::<String>("routine").unwrap();
+            let mut routine =
+                LogixRoutine::new(name, &lir).unwrap_or_else(|err| fail(err.message()));
+            routine.program = args.get_one::<String>("program").unwrap().clone();
+            let format = match args.get_one::<String>("format").unwrap().as_str() {
+                "text" => LogixFormat::Text,
+                _ => LogixFormat::L5x,
+            };
+            let mut out = output(args);
+            match format {
+                LogixFormat::L5x => routine.write_l5x(&mut out),
+                LogixFormat::Text => routine.write_text(&mut out),
+            }
+            .expect("Write routine");
+        }
+        Some(("il", args)) => {
+            let mir = compile(args);
+            let max_depth = *args.get_one::<usize>("max-depth").unwrap();
+            let lir = il::transformer::transform(&mir, max_depth).unwrap_or_else(|err| report(err));
+            let name = args.get_one::<String>("name").unwrap();
+            let source =
+                IlSource::new(name, &lir, memory(args)).unwrap_or_else(|err| fail(err.message()));
+            source.write_il(&mut output(args)).expect("Write IL");
+        }
+        Some(("verilog", args)) => {
+            let mir = compile(args);
+            let lir = scl::transformer::transform(&mir).unwrap_or_else(|err| report(err));
+            let name = args.get_one::<String>("name").unwrap();
+            let module = VerilogModule::new(name, &lir).unwrap_or_else(|err| fail(err.message()));
+            module
+                .write_verilog(&mut output(args))
+                .expect("Write Verilog");
+            if let Some(path) = args.get_one::<PathBuf>("testbench") {
+                let scans = *args.get_one::<u32>("scans").unwrap();
+                module
+                    .write_testbench(&mut create(path), scans)
+                    .expect("Write testbench");
+            }
+        }
+        Some(("c", args)) => {
+            let mir = compile(args);
+            let lir = c::transformer::transform(&mir).unwrap_or_else(|err| fail(err.message()));
+            let name = args.get_one::<String>("name").unwrap();
+            let mut source = CSource::new(name, &lir);
+            source.mnemonics = mnemonics(args);
+            let dir = args.get_one::<PathBuf>("output").unwrap();
+            source
+                .write_header(&mut create(&dir.join(format!("{name}.h"))))
+                .expect("Write header");
+            source
+                .write_source(&mut create(&dir.join(format!("{name}.c"))))
+                .expect("Write C");
+        }
+        Some(("sim", args)) => {
+            let mir = compile(args);
+            let lir = s7::transformer::transform(&mir).unwrap_or_else(|err| fail(err.message()));
+            simulate(args, &mir, &lir);
+        }
+        Some(("test", args)) => {
+            let mut hir = parse(args);
+            let tests = std::mem::take(&mut hir.tests);
+            let mut mir = transform(hir);
+            let tests =
+                mir::transformer::test::transform_tests(&mut mir, tests).unwrap_or_else(|err| {
+                    eprintln!("{err}");
+                    exit(1);
+                });
+            let lir = s7::transformer::transform(&mir).unwrap_or_else(|err| fail(err.message()));
+            run_tests(args, &mir, &lir, &tests);
         }
         _ => unreachable!("Unknown subcommand"),
     }
 }
+
+/// Run the scans of `fpp sim` and print the final outputs and variables
+fn simulate(args: &ArgMatches, mir: &Mir, lir: &S7Lir) {
+    let mut simulator = Simulator::new(lir);
+    simulator.cycle_time = *args.get_one::<u32>("cycle-time").unwrap();
+    let variables = vcd::variables(mir);
+    let stimulus = args.get_one::<PathBuf>("stimulus").map(|path| {
+        let text = read_to_string(path).unwrap_or_else(|err| fail(&err.to_string()));
+        let mut stimulus = Stimulus::parse(&text)
+            .unwrap_or_else(|err| fail(&format!("{}: {err}", path.display())));
+        // Inputs are given as operand or by the name of a variable
+        for input in &mut stimulus.inputs {
+            if let Some(signal) = variables.iter().find(|it| it.name == *input) {
+                *input = signal.operand.clone();
+            }
+            if !Simulator::is_input(input) {
+                let err = Simulator::bits(input)
+                    .err()
+                    .unwrap_or_else(|| SimulatorError::NotAnInput(input.clone()));
+                fail(&format!("{}: {err}", path.display()));
+            }
+        }
+        stimulus
+    });
+    let scans = match (args.get_one::<u64>("scans"), &stimulus) {
+        (Some(&scans), _) => scans,
+        (None, Some(stimulus)) if stimulus.unit == TimeUnit::Cycle => stimulus.end() + 1,
+        (None, Some(stimulus)) => stimulus.end().div_ceil(simulator.cycle_time as u64) + 1,
+        (None, None) => 1,
+    };
+    let io = simulator.io();
+    let signals: Vec<_> = io
+        .iter()
+        .map(|operand| Signal::new(operand, operand))
+        .chain(variables.iter().cloned().map(Ok))
+        .collect::<Result<_, _>>()
+        .unwrap_or_else(|err| fail(&err.to_string()));
+    let mut vcd = args.get_one::<PathBuf>("vcd").map(|path| {
+        let out = BufWriter::new(create(path));
+        Vcd::new(out, signals.clone()).unwrap_or_else(|err| fail(&err.to_string()))
+    });
+    let mut next = 0;
+    for _ in 0..scans {
+        if let Some(stimulus) = &stimulus {
+            next = stimulus
+                .apply(&mut simulator, next)
+                .unwrap_or_else(|err| fail(&err.to_string()));
+        }
+        if let Some(vcd) = &mut vcd {
+            vcd.sample(&simulator).expect("Write VCD");
+        }
+        simulator
+            .scan()
+            .unwrap_or_else(|err| fail(&format!("scan {}: {err}", simulator.scans() + 1)));
+    }
+    if let Some(mut vcd) = vcd {
+        vcd.sample(&simulator).expect("Write VCD");
+        vcd.into_inner().flush().expect("Write VCD");
+    }
+    let mut out = stdout();
+    for signal in signals {
+        if Simulator::is_input(&signal.operand) {
+            continue;
+        }
+        let value = simulator
+            .read_bits(&signal.operand)
+            .expect("Signal operand");
+        writeln!(out, "{} = {value}", signal.name).expect("Write output");
+    }
+}
+
+/// Compare the boolean outputs with their networks, exits with 1 on a mismatch
+fn verify(mir: &Mir, lir: &S7Lir) {
+    let source = &mir.source;
+    let report = verify::verify(mir, lir);
+    for quote in &report.too_large {
+        let content = MessageContent::parse(&source.file, &source.code, quote.start, quote.end)
+            .unwrap_or(MessageContent::None);
+        let message = format!(
+            "this statement reads more than {} bits and is not verified",
+            verify::MAX_OPERANDS
+        );
+        eprintln!("{}", Message::warning(content, &message));
+    }
+    for quote in &report.skipped {
+        let content = MessageContent::parse(&source.file, &source.code, quote.start, quote.end)
+            .unwrap_or(MessageContent::None);
+        let message = "this statement is no boolean function of its operands and is not verified";
+        eprintln!("{}", Message::warning(content, message));
+    }
+    for mismatch in &report.mismatches {
+        let content = MessageContent::parse(
+            &source.file,
+            &source.code,
+            mismatch.quote.start,
+            mismatch.quote.end,
+        )
+        .unwrap_or(MessageContent::None);
+        let message = Message::error(
+            content,
+            "internal compiler error: the instructions of this statement do not match its source",
+        );
+        eprintln!("{message}");
+        eprintln!("{}", Message::note(MessageContent::None, &mismatch.message));
+    }
+    if !report.mismatches.is_empty() {
+        exit(1);
+    }
+    let summary = format!("{} of {} outputs verified", report.verified, report.outputs);
+    eprintln!("{}", Message::note(MessageContent::None, &summary));
+}
+
+/// Run every test of `fpp test` on its own simulator, exits with 1 if one of them failed
+fn run_tests(args: &ArgMatches, mir: &Mir, lir: &S7Lir, tests: &[MirTest]) {
+    let cycle_time = *args.get_one::<u32>("cycle-time").unwrap();
+    let source = &mir.source;
+    let mut failed = 0;
+    for mir_test in tests {
+        let name = &source.code[&mir_test.name];
+        let (quote, message) = match test::run(lir, mir_test, cycle_time) {
+            // The span of a passed test is its header
+            Ok(()) => (mir_test.quote.start..mir_test.name.end + 1, None),
+            Err(failure) => (
+                failure.quote.start..failure.quote.end,
+                Some(failure.message),
+            ),
+        };
+        let content = MessageContent::parse(&source.file, &source.code, quote.start, quote.end)
+            .unwrap_or(MessageContent::None);
+        let message = match message {
+            None => Message::success(content, &format!("test `{name}` passed")).to_string(),
+            Some(message) => {
+                failed += 1;
+                Message::error(content, &format!("test `{name}` failed: {message}")).to_string()
+            }
+        };
+        eprintln!("{message}");
+    }
+    let summary = format!("{} passed, {failed} failed", tests.len() - failed);
+    eprintln!("{}", Message::note(MessageContent::None, &summary));
+    if failed > 0 {
+        exit(1);
+    }
+}
+
+fn fail(err: &str) -> ! {
+    let message = Message::error(MessageContent::None, err);
+    eprintln!("{message}");
+    exit(1);
+}
+
+/// Print the error with the quoted source and exit
+fn report(err: Error) -> ! {
+    eprintln!("{err}");
+    exit(1);
+}
+
+/// Parse and transform the `FILE` argument into a [Mir]
+fn compile(args: &ArgMatches) -> Mir {
+    transform(parse(args))
+}
+
+/// Parse the `FILE` argument into a [Hir]
+fn parse(args: &ArgMatches) -> Hir {
+    let file = args.get_one::<PathBuf>("FILE").unwrap();
+    let input = read_to_string(file).unwrap_or_else(|err| fail(&err.to_string()));
+    let source = Rc::new(Source::new(file.to_string_lossy(), input));
+    let mut parser = Parser::new(source);
+    parser.parse().unwrap_or_else(|err| report(err))
+}
+
+fn transform(hir: Hir) -> Mir {
+    mir::transformer::transform(hir).unwrap_or_else(|err| report(err))
+}
+
+/// The `--output` file or stdout
+fn output(args: &ArgMatches) -> Box<dyn Write> {
+    match args.get_one::<PathBuf>("output") {
+        Some(path) => Box::new(create(path)),
+        None => Box::new(stdout()),
+    }
+}
+
+fn create(path: &Path) -> File {
+    File::create(path).unwrap_or_else(|err| fail(&err.to_string()))
+}
+
+/// Declare the variables of the program named by `--name`
+fn st_source<'a>(args: &ArgMatches, lir: &'a SclLir) -> StSource<'a> {
+    let name = args.get_one::<String>("name").unwrap();
+    StSource::new(name, lir, memory(args)).unwrap_or_else(|err| fail(err.message()))
+}
+
+/// Declaration of allocated memory chosen by `--memory`
+fn memory(args: &ArgMatches) -> StMemory {
+    match args.get_one::<String>("memory").unwrap().as_str() {
+        "located" => StMemory::Located,
+        _ => StMemory::Variables,
+    }
+}
+
+fn mnemonics(args: &ArgMatches) -> S7Mnemonics {
+    match args.get_one::<String>("mnemonics").unwrap().as_str() {
+        "english" => S7Mnemonics::English,
+        _ => S7Mnemonics::German,
+    }
+}
+
+fn parse_block(name: &str) -> Result<(S7SourceKind, u16), &'static str> {
+    S7Source::parse_block(name).ok_or("expected `OB` or `FC` followed by a block number")
+}
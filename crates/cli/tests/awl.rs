@@ -0,0 +1,13 @@
+mod common;
+
+use common::fpp;
+
+#[test]
+fn nesting_error_is_reported() {
+    let code = "A0.0 = E0.0 and (E0.1 or (E0.2 and (E0.3 or (E0.4 and (E0.5 or (E0.6 and \
+                (E0.7 or (E1.0 and (E1.1 or E1.2)))))))));";
+    let (status, stderr) = fpp(&["awl"], "nesting.fpp", code);
+    assert_eq!(status, Some(1));
+    assert!(stderr.contains("the nesting of the logic operations is invalid"));
+    assert!(!stderr.contains("panicked"));
+}
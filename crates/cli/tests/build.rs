@@ -0,0 +1,37 @@
+mod common;
+
+use common::fpp;
+
+#[test]
+fn build_verifies_outputs() {
+    let code = "A0.0 = (E0.0 or E0.1) and E0.2;\nA0.1 = sr(E0.0, E0.1);\n";
+    let (status, stderr) = fpp(&["build", "--verify"], "verified.fpp", code);
+    assert_eq!(status, Some(0));
+    assert!(stderr.contains("1 of 2 outputs verified"), "{stderr}");
+    assert!(
+        stderr.contains("this statement is no boolean function of its operands"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("A0.1 = sr(E0.0, E0.1);"), "{stderr}");
+}
+
+#[test]
+fn build_warns_about_large_outputs() {
+    let operands: Vec<_> = (0..17)
+        .map(|bit| format!("E{}.{}", bit / 8, bit % 8))
+        .collect();
+    let code = format!("A0.0 = {};", operands.join(" and "));
+    let (status, stderr) = fpp(&["build", "--verify"], "large.fpp", &code);
+    assert_eq!(status, Some(0));
+    assert!(stderr.contains("reads more than 16 bits and is not verified"));
+}
+
+#[test]
+fn build_reports_errors() {
+    let (status, stderr) = fpp(&["build"], "error.fpp", "A0.0 = ;\n");
+    assert_eq!(status, Some(1));
+    assert!(
+        stderr.contains("this unary operation is invalid"),
+        "{stderr}"
+    );
+}
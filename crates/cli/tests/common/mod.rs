@@ -0,0 +1,18 @@
+//! Helpers shared by the integration tests
+
+#![allow(dead_code)]
+
+use std::{fs::write, path::PathBuf, process::Command};
+
+/// Run `fpp` with the arguments on the program, the exit code and the error output
+pub fn fpp(args: &[&str], name: &str, code: &str) -> (Option<i32>, String) {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    write(&path, code).expect("Write program");
+    let output = Command::new(env!("CARGO_BIN_EXE_fpp"))
+        .args(args)
+        .arg(&path)
+        .output()
+        .expect("Run fpp");
+    let stderr = String::from_utf8(output.stderr).expect("UTF-8 errors");
+    (output.status.code(), stderr)
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use common::fpp;
+
+const PROGRAM: &str = r#"A0.0 = E0.0 and not E0.1;
+
+test "output follows button" {
+    set E0.0 = true;
+    scan;
+    assert A0.0 == true;
+}
+"#;
+
+#[test]
+fn passing_tests_exit_successfully() {
+    let (status, stderr) = fpp(&["test"], "passing.fpp", PROGRAM);
+    assert_eq!(status, Some(0), "{stderr}");
+    assert!(stderr.contains("test `output follows button` passed"));
+    assert!(stderr.contains("1 passed, 0 failed"));
+}
+
+#[test]
+fn failing_test_points_at_assertion() {
+    let code = format!(
+        "{PROGRAM}\ntest \"broken\" {{\n    set E0.0 = true;\n    scan;\n    assert A0.0 == false;\n}}\n"
+    );
+    let (status, stderr) = fpp(&["test"], "failing.fpp", &code);
+    assert_eq!(status, Some(1));
+    assert!(stderr.contains("test `broken` failed: expected `false`, found `true`"));
+    assert!(stderr.contains("    assert A0.0 == false;"));
+    assert!(stderr.contains("1 passed, 1 failed"));
+}
@@ -0,0 +1,68 @@
+//! Embedding of F++ programs in Rust crates, used by `fpp!` and build scripts
+
+use std::{
+    env::var_os,
+    fs::{read_to_string, write},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    error::Error,
+    lir::{
+        self, c,
+        rust::{RustSource, WriteRust},
+    },
+    mir,
+    parser::Parser,
+    util::Source,
+};
+
+/// Error of [generate], errors of the compiler quote the source
+pub enum GenerateError {
+    Source(Error),
+    /// The program has no Rust equivalent, e.g. it uses timers
+    Lir(lir::error::Error),
+}
+
+impl GenerateError {
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Source(err) => err.message(),
+            Self::Lir(err) => err.message(),
+        }
+    }
+}
+
+/// Compile the source into a Rust struct named `name` with typed inputs and outputs
+pub fn generate(source: Rc<Source>, name: &str, visibility: &str) -> Result<String, GenerateError> {
+    let hir = Parser::new(source).parse().map_err(GenerateError::Source)?;
+    let mir = mir::transformer::transform(hir).map_err(GenerateError::Source)?;
+    let lir = c::transformer::transform(&mir).map_err(GenerateError::Lir)?;
+    let mut source = RustSource::new(name, &lir);
+    source.visibility = visibility.to_string();
+    let mut out = Vec::new();
+    source.write_rust(&mut out).expect("Write to memory");
+    Ok(String::from_utf8(out).expect("Generated Rust is UTF-8"))
+}
+
+/// Compile a file in a build script into `$OUT_DIR/{stem}.rs`
+///
+/// The struct is included with `include!(concat!(env!("OUT_DIR"), "/plant.rs"))` and the file
+/// is compiled again whenever it changes.
+pub fn compile(file: impl AsRef<Path>, name: &str) -> Result<PathBuf, String> {
+    let file = file.as_ref();
+    println!("cargo:rerun-if-changed={}", file.display());
+    let code = read_to_string(file).map_err(|err| format!("{}: {err}", file.display()))?;
+    crate::init(false);
+    let source = Rc::new(Source::new(file.to_string_lossy(), code));
+    let code = generate(source, name, "pub").map_err(|err| match err {
+        GenerateError::Source(err) => err.to_string(),
+        GenerateError::Lir(err) => format!("{}: {}", file.display(), err.message()),
+    })?;
+    let dir = var_os("OUT_DIR").ok_or("`OUT_DIR` is only set for build scripts")?;
+    let stem = file.file_stem().ok_or("expected the path of a file")?;
+    let path = Path::new(&dir).join(stem).with_extension("rs");
+    write(&path, code).map_err(|err| format!("{}: {err}", path.display()))?;
+    Ok(path)
+}
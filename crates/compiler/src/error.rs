@@ -5,7 +5,10 @@ use std::{
 
 use messages::message::{Message, MessageContent};
 
-use crate::util::{Quote, Source};
+use crate::{
+    lir,
+    util::{Quote, Source},
+};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -13,6 +16,7 @@ pub struct Error {
     source: Rc<Source>,
     quote: Quote,
     reason: Reason,
+    note: Option<String>,
 }
 
 impl Error {
@@ -21,27 +25,70 @@ impl Error {
             source,
             quote,
             reason,
+            note: None,
         }
     }
 
+    /// Attach a note, e.g. a suggestion on how to fix the error
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+
+    /// Range of the source the error points at
+    pub fn quote(&self) -> &Quote {
+        &self.quote
+    }
+
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
     pub fn message(&self) -> &str {
-        match self.reason {
+        match &self.reason {
             Reason::UnexpectedCharacter => "this character was not expected",
             Reason::InvalidNumber => "this number is not valid",
+            Reason::UnterminatedComment => "this comment is never closed",
+            Reason::UnterminatedString => "this string is never closed",
             Reason::UnexpectedSymbol => "this symbol was not expected",
             Reason::InvalidBitAddressSymbol => "this bit-address is invalid",
+            Reason::InvalidAddressWidth => "this address does not match the width of its operand",
             Reason::InvalidUnaryOperation => "this unary operation is invalid",
             Reason::NoWriteHandler => "there is no write handler available for this variable",
             Reason::InvalidArgsCount => "the amount of args does not match the function signature",
             Reason::InvalidArgType => "the arguments do not match the function signature",
+            Reason::ArgTypeMismatch => "this argument does not match the type of its parameter",
             Reason::UnknownVariable => "this variable does not exist",
             Reason::UnknownFunction => "this function does not exist",
             Reason::UnknownBitAddressType => "this bit-address type is invalid",
             Reason::ValueNotBitReadable => "this value is not readable",
+            Reason::ValueNotBitAccessible => "the bits of this value cannot be accessed",
+            Reason::BitOutOfRange => "this bit is out of range for the width of the value",
+            Reason::TypeMismatch => "the type of this value does not match the target",
+            Reason::InvalidOperandType => "this operation is not defined for its operand types",
+            Reason::ShiftOutOfRange => "this shift amount exceeds the width of the value",
+            Reason::InvalidAddressIndex => "this operand can not be indexed this way",
+            Reason::PointerOutOfRange => "this pointer exceeds the addressable range",
+            Reason::PointerIntoAllocated => "this pointer targets memory reserved for variables",
+            Reason::InvalidAnyPointer => "this is not a valid ANY pointer",
+            Reason::SizeMismatch => "the sizes of these memory areas do not match",
+            Reason::BlockOverlap => "source and destination of this block move overlap",
+            Reason::NoAddHandler => "there is no add handler available for this value",
+            Reason::ImplicitWidening => "this value would be implicitly widened",
+            Reason::ImplicitNarrowing => "this value would be implicitly narrowed",
+            Reason::ImplicitConversion => "this value would be implicitly converted",
+            Reason::InvalidBlockName => "this is not a valid block name",
+            Reason::UnknownDataType => "this data type does not exist",
+            Reason::DuplicateBlock => "this block has already been declared",
+            Reason::DuplicateParam => "this parameter has already been declared",
+            Reason::OperandName => "this name is an operand and can not name a variable",
+            Reason::NotTestable => "this value can not be set or checked by a test",
+            Reason::NotConstant => "this value is not a constant",
+            Reason::Lir(err) => err.message(),
         }
     }
 
-    pub fn content(&self) -> Option<MessageContent> {
+    pub fn content(&self) -> Option<MessageContent<'_>> {
         MessageContent::parse(
             &self.source.file,
             &self.source.code,
@@ -50,7 +97,7 @@ impl Error {
         )
     }
 
-    pub fn to_message(&self) -> Option<Message> {
+    pub fn to_message(&self) -> Option<Message<'_>> {
         let content = self.content()?;
         Some(Message::error(content, self.message()))
     }
@@ -61,7 +108,12 @@ impl Display for Error {
         let Some(message) = self.to_message() else {
             return Ok(());
         };
-        message.fmt(f)
+        message.fmt(f)?;
+        if let Some(note) = &self.note {
+            writeln!(f)?;
+            Message::note(MessageContent::None, note).fmt(f)?;
+        }
+        Ok(())
     }
 }
 
@@ -70,16 +122,45 @@ pub enum Reason {
     // Lexer
     UnexpectedCharacter,
     InvalidNumber,
+    UnterminatedComment,
+    UnterminatedString,
     // Parser
     UnexpectedSymbol,
     InvalidBitAddressSymbol,
+    InvalidAddressWidth,
     InvalidUnaryOperation,
+    InvalidBlockName,
+    UnknownDataType,
+    OperandName,
     // Mir
     NoWriteHandler,
     InvalidArgsCount,
     InvalidArgType,
+    ArgTypeMismatch,
     UnknownVariable,
     UnknownFunction,
     UnknownBitAddressType,
     ValueNotBitReadable,
+    ValueNotBitAccessible,
+    BitOutOfRange,
+    TypeMismatch,
+    InvalidOperandType,
+    ShiftOutOfRange,
+    InvalidAddressIndex,
+    PointerOutOfRange,
+    PointerIntoAllocated,
+    InvalidAnyPointer,
+    SizeMismatch,
+    BlockOverlap,
+    NoAddHandler,
+    ImplicitWidening,
+    ImplicitNarrowing,
+    ImplicitConversion,
+    DuplicateBlock,
+    DuplicateParam,
+    NotTestable,
+    NotConstant,
+    // Lir
+    /// The statement can not be expressed in the target language
+    Lir(lir::error::Error),
 }
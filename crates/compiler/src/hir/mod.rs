@@ -4,12 +4,16 @@ use std::rc::Rc;
 
 use crate::util::{Quote, Source};
 
-use self::value::HirValue;
+use self::value::{HirDataType, HirValue};
 
 #[derive(Debug)]
 pub struct Hir {
     pub source: Rc<Source>,
     pub statements: Vec<HirStatement>,
+    /// `//`, `///` and `/* */` comments in source order, comments of tests are not included
+    pub comments: Vec<HirComment>,
+    /// Test blocks, not part of the compiled program
+    pub tests: Vec<HirTest>,
 }
 
 impl Hir {
@@ -17,15 +21,41 @@ impl Hir {
         Self {
             source,
             statements: Vec::new(),
+            comments: Vec::new(),
+            tests: Vec::new(),
         }
     }
 }
 
+/// Comment, `quote` covers the text after the slashes or between `/*` and `*/`
+#[derive(Clone, Debug)]
+pub struct HirComment {
+    pub quote: Quote,
+    /// `/// Conveyor start`, used as title of the following statement
+    pub doc: bool,
+    /// `/* */` comment, may span several lines
+    pub block: bool,
+}
+
 #[derive(Debug)]
 pub enum HirStatement {
     Let(HirLetStatement),
     Write(HirWriteStatement),
+    Add(HirAddStatement),
     Call(HirCallStatement),
+    Extern(HirExternStatement),
+}
+
+impl HirStatement {
+    pub fn quote(&self) -> &Quote {
+        match self {
+            Self::Let(stmt) => &stmt.quote,
+            Self::Write(stmt) => &stmt.quote,
+            Self::Add(stmt) => &stmt.quote,
+            Self::Call(stmt) => &stmt.quote,
+            Self::Extern(stmt) => &stmt.quote,
+        }
+    }
 }
 
 /// `let name = value;`
@@ -36,11 +66,19 @@ pub struct HirLetStatement {
     pub value: HirValue,
 }
 
-/// `name = value;`
+/// `target = value;`
 #[derive(Debug)]
 pub struct HirWriteStatement {
     pub quote: Quote,
-    pub name: Quote,
+    pub target: HirValue,
+    pub value: HirValue,
+}
+
+/// `target += value;`
+#[derive(Debug)]
+pub struct HirAddStatement {
+    pub quote: Quote,
+    pub target: HirValue,
     pub value: HirValue,
 }
 
@@ -51,3 +89,106 @@ pub struct HirCallStatement {
     pub name: Quote,
     pub args: Vec<HirValue>,
 }
+
+/// `extern fc FC10(in start: bool, out running: bool);`
+#[derive(Debug)]
+pub struct HirExternStatement {
+    pub quote: Quote,
+    pub kind: HirBlockKind,
+    pub name: Quote,
+    pub number: u16,
+    pub params: Vec<HirParam>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HirBlockKind {
+    /// `fc`
+    Function,
+    /// `fb`
+    FunctionBlock,
+}
+
+/// `in start: bool`
+#[derive(Debug)]
+pub struct HirParam {
+    pub direction: HirParamDirection,
+    pub name: Quote,
+    pub r#type: HirParamType,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HirParamDirection {
+    /// `in`
+    Input,
+    /// `out`
+    Output,
+}
+
+/// Data type of a [HirParam]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HirParamType {
+    Data(HirDataType),
+    /// `date_and_time`, `dt`
+    DateAndTime,
+    /// `any`
+    Any,
+}
+
+/// `test "motor stops on fault" { set button = true; scan; assert motor == false; }`
+#[derive(Debug)]
+pub struct HirTest {
+    pub quote: Quote,
+    /// Text between the quotes
+    pub name: Quote,
+    pub steps: Vec<HirTestStep>,
+}
+
+#[derive(Debug)]
+pub enum HirTestStep {
+    Set(HirSetStep),
+    Scan(HirScanStep),
+    Assert(HirAssertStep),
+}
+
+impl HirTestStep {
+    pub fn quote(&self) -> &Quote {
+        match self {
+            Self::Set(step) => &step.quote,
+            Self::Scan(step) => &step.quote,
+            Self::Assert(step) => &step.quote,
+        }
+    }
+}
+
+/// `set target = value;`
+#[derive(Debug)]
+pub struct HirSetStep {
+    pub quote: Quote,
+    pub target: HirValue,
+    pub value: HirValue,
+}
+
+/// `scan;`, `scan 5;`, `scan 10ms;`, `scan T#2s;`
+#[derive(Debug)]
+pub struct HirScanStep {
+    pub quote: Quote,
+    pub length: HirScanLength,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HirScanLength {
+    Scans(usize),
+    /// Milliseconds, rounded up to whole scans
+    Duration(usize),
+}
+
+/// `assert value == expected;`, `assert value != expected;`, `assert value;`
+#[derive(Debug)]
+pub struct HirAssertStep {
+    pub quote: Quote,
+    pub value: HirValue,
+    /// `None` if the value is checked to be `true`
+    pub expected: Option<HirValue>,
+    /// `false` for `!=`
+    pub equal: bool,
+}
@@ -17,10 +17,18 @@ pub enum HirValueType {
     Number(HirNumber),
     Bool(HirBool),
     BitAddress(HirBitAddress),
+    IndirectAddress(Box<HirIndirectAddress>),
+    AnyPointer(HirAnyPointer),
+    AddressRegister,
+    BitAccess(Box<HirBitAccess>),
     Not(Box<HirNot>),
     And(Box<HirAnd>),
     Or(Box<HirOr>),
     Xor(Box<HirXor>),
+    Shl(Box<HirShl>),
+    Shr(Box<HirShr>),
+    Rotl(Box<HirRotl>),
+    Rotr(Box<HirRotr>),
     VarRef(HirVarRef),
     Call(HirCall),
 }
@@ -31,17 +39,151 @@ pub struct HirBool {
     pub value: bool,
 }
 
+/// `123`, `16#FF`, `W#16#FF`
 #[derive(Debug)]
 pub struct HirNumber {
     pub value: usize,
+    pub r#type: HirNumberType,
 }
 
-/// `charptr.bit`
+/// Type of a [HirNumber]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HirNumberType {
+    /// `123`, `16#FF`, `2#1010`, `0b1010`, `0xFF`
+    Untyped,
+    /// `B#16#FF`
+    Byte,
+    /// `W#16#FFFF`, `B#(1,2)`, `C#123`
+    Word,
+    /// `DW#16#FFFF_FFFF`
+    DWord,
+    /// `L#123`
+    DInt,
+    /// `P#2.0`, stored as `byte * 8 + bit`
+    Pointer,
+    /// `T#1m30s`, stored in milliseconds
+    Time,
+}
+
+/// `E0.0`, `MW10`, `PEW256`, `T5`, `DB5.DBX2.1`
 #[derive(Debug)]
 pub struct HirBitAddress {
-    pub char: u8,
+    pub area: HirAddressArea,
+    pub width: HirAddressWidth,
+    /// Number of the data block, only used by [HirAddressArea::DataBlock]
+    pub db: u16,
+    pub ptr: u16,
+    pub bit: u8,
+}
+
+/// `M[MD10]`, `E[MW20]`, `DBX[AR1, P#0.0]`, `DB5.DBW[AR1, P#2.0]`
+#[derive(Debug)]
+pub struct HirIndirectAddress {
+    pub area: HirAddressArea,
+    pub width: HirAddressWidth,
+    /// Number of the data block to open, `0` uses the currently open one
+    pub db: u16,
+    pub index: HirAddressIndex,
+}
+
+/// Index of a [HirIndirectAddress]
+#[derive(Debug)]
+pub enum HirAddressIndex {
+    /// `[MD10]`, the pointer is read from the value
+    Memory(HirValue),
+    /// `[AR1, P#2.0]`, the offset is added to `AR1`
+    Register { ptr: u16, bit: u8 },
+}
+
+/// `P#DB10.DBX0.0 BYTE 20`, `P#M10.0 WORD 4`
+#[derive(Debug)]
+pub struct HirAnyPointer {
+    pub area: HirAddressArea,
+    /// Number of the data block, only used by [HirAddressArea::DataBlock]
+    pub db: u16,
     pub ptr: u16,
     pub bit: u8,
+    pub r#type: HirDataType,
+    pub count: u16,
+}
+
+/// Elementary data type of a [HirAnyPointer]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HirDataType {
+    Bool,
+    Byte,
+    Word,
+    DWord,
+    Int,
+    DInt,
+    Real,
+    Time,
+}
+
+impl HirDataType {
+    /// Size of the type in bits
+    pub fn bits(self) -> usize {
+        match self {
+            Self::Bool => 1,
+            Self::Byte => 8,
+            Self::Word | Self::Int => 16,
+            Self::DWord | Self::DInt | Self::Real | Self::Time => 32,
+        }
+    }
+}
+
+/// Operand area of a [HirBitAddress]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HirAddressArea {
+    /// `0.0`
+    Raw,
+    /// `E`, `I`
+    Input,
+    /// `A`, `Q`
+    Output,
+    /// `M`
+    Memory,
+    /// `PE`, `PI`
+    PeripheralInput,
+    /// `PA`, `PQ`
+    PeripheralOutput,
+    /// `T`
+    Timer,
+    /// `Z`, `C`
+    Counter,
+    /// `DBn.DB`
+    DataBlock,
+}
+
+/// Access width of a [HirBitAddress]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HirAddressWidth {
+    /// `E0.0`, `DBX0.0`, also used for timers and counters
+    Bit,
+    /// `EB0`, `DBB0`
+    Byte,
+    /// `EW0`, `DBW0`
+    Word,
+    /// `ED0`, `DBD0`
+    DWord,
+}
+
+impl HirAddressWidth {
+    /// Size of the operand in bytes
+    pub fn bytes(self) -> usize {
+        match self {
+            Self::Bit | Self::Byte => 1,
+            Self::Word => 2,
+            Self::DWord => 4,
+        }
+    }
+}
+
+/// `value.bit`
+#[derive(Debug)]
+pub struct HirBitAccess {
+    pub value: HirValue,
+    pub bit: u8,
 }
 
 /// `not value`, `!value`
@@ -71,6 +213,34 @@ pub struct HirXor {
     pub right: HirValue,
 }
 
+/// `left << right`
+#[derive(Debug)]
+pub struct HirShl {
+    pub left: HirValue,
+    pub right: HirValue,
+}
+
+/// `left >> right`
+#[derive(Debug)]
+pub struct HirShr {
+    pub left: HirValue,
+    pub right: HirValue,
+}
+
+/// `left rotl right`
+#[derive(Debug)]
+pub struct HirRotl {
+    pub left: HirValue,
+    pub right: HirValue,
+}
+
+/// `left rotr right`
+#[derive(Debug)]
+pub struct HirRotr {
+    pub left: HirValue,
+    pub right: HirValue,
+}
+
 /// `quote`
 #[derive(Debug)]
 pub struct HirVarRef;
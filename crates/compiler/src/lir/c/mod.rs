@@ -0,0 +1,784 @@
+//! Portable C99 for microcontrollers running the program as a soft-PLC
+//!
+//! The instructions are emulated on the status bits and accumulators of the CPU, so
+//! flip-flops, counters and word operations behave exactly as on the PLC. Memory, data blocks
+//! and counters live in a `state_t` kept by the caller between scans.
+
+use std::{io::Write, rc::Rc};
+
+use super::{
+    s7::{S7Address, S7Mnemonics, WriteAwl},
+    st::{area, width, Area},
+};
+
+pub mod transformer;
+
+/// Thing that can be written to a [Write] in C-form
+pub trait WriteC {
+    fn write_c(&self, out: &mut impl Write) -> std::io::Result<()>;
+}
+
+#[derive(Debug, Default)]
+pub struct CLir {
+    /// Size of the input process image in bytes
+    pub input_bytes: u32,
+    pub output_bytes: u32,
+    /// Size of the memory area in bytes, including the memory of the allocator
+    pub memory_bytes: u32,
+    /// Number and size in bytes of the data blocks
+    pub data_blocks: Vec<(u16, u32)>,
+    pub counters: u16,
+    /// Directly addressed inputs and outputs, documented in the header
+    pub io: Vec<S7Address>,
+    /// Local variables of `scan`
+    pub variables: Vec<CVar>,
+    pub networks: Vec<CNetwork>,
+}
+
+#[derive(Debug, Default)]
+pub struct CNetwork {
+    pub title: Rc<str>,
+    pub comments: Vec<Rc<str>>,
+    pub statements: Vec<CStatement>,
+}
+
+impl WriteC for CNetwork {
+    fn write_c(&self, out: &mut impl Write) -> std::io::Result<()> {
+        write_comment(out, &self.title)?;
+        for comment in &self.comments {
+            write_comment(out, comment)?;
+        }
+        for statement in &self.statements {
+            statement.write_c_indented(out, 4)?;
+        }
+        Ok(())
+    }
+}
+
+/// Status bits and registers of the CPU, held in local variables of `scan`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CVar {
+    /// Result of logic operation
+    Rlo,
+    /// Set while an `AND` group before an `OR` was fulfilled
+    Or,
+    /// Result of the bracket being closed
+    Nested,
+    /// RLO before the bracket at the depth
+    NestingRlo(usize),
+    /// OR bit before the bracket at the depth
+    NestingOr(usize),
+    Accu1,
+    Accu2,
+    Ar1,
+}
+
+impl CVar {
+    pub fn r#type(self) -> &'static str {
+        match self {
+            Self::Rlo | Self::Or | Self::Nested | Self::NestingRlo(_) | Self::NestingOr(_) => {
+                "bool"
+            }
+            Self::Accu1 | Self::Accu2 | Self::Ar1 => "uint32_t",
+        }
+    }
+}
+
+impl WriteC for CVar {
+    fn write_c(&self, out: &mut impl Write) -> std::io::Result<()> {
+        match self {
+            Self::Rlo => write!(out, "rlo"),
+            Self::Or => write!(out, "or_bit"),
+            Self::Nested => write!(out, "nested"),
+            Self::NestingRlo(depth) => write!(out, "rlo_{depth}"),
+            Self::NestingOr(depth) => write!(out, "or_bit_{depth}"),
+            Self::Accu1 => write!(out, "accu1"),
+            Self::Accu2 => write!(out, "accu2"),
+            Self::Ar1 => write!(out, "ar1"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CStatement {
+    /// `var = value;`
+    Assign { var: CVar, value: CExpr },
+    /// `set_bit(..);`
+    Expr(CExpr),
+    /// `if (cond) { .. }`
+    If { cond: CExpr, then: Vec<CStatement> },
+}
+
+impl CStatement {
+    fn write_c_indented(&self, out: &mut impl Write, indent: usize) -> std::io::Result<()> {
+        write!(out, "{:indent$}", "")?;
+        match self {
+            Self::Assign { var, value } => {
+                var.write_c(out)?;
+                write!(out, " = ")?;
+                value.write_c(out)?;
+                writeln!(out, ";")
+            }
+            Self::Expr(expr) => {
+                expr.write_c(out)?;
+                writeln!(out, ";")
+            }
+            Self::If { cond, then } => {
+                write!(out, "if (")?;
+                cond.write_c(out)?;
+                writeln!(out, ") {{")?;
+                for statement in then {
+                    statement.write_c_indented(out, indent + 4)?;
+                }
+                writeln!(out, "{:indent$}}}", "")
+            }
+        }
+    }
+
+    /// Calls the function with every expression of the statement
+    pub fn visit(&self, f: &mut impl FnMut(&CExpr)) {
+        match self {
+            Self::Assign { value, .. } | Self::Expr(value) => value.visit(f),
+            Self::If { cond, then } => {
+                cond.visit(f);
+                for statement in then {
+                    statement.visit(f);
+                }
+            }
+        }
+    }
+}
+
+/// Memory area passed to the access functions with its size
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CArea {
+    /// `inputs`, peripheral inputs share the process image
+    Input,
+    Output,
+    /// `state->memory`
+    Memory,
+    /// `state->db5`
+    DataBlock(u16),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum COp {
+    /// `&&`
+    And,
+    /// `||`
+    Or,
+    /// `!=`
+    NotEqual,
+    /// `&`
+    BitAnd,
+    /// `|`
+    BitOr,
+    /// `^`
+    BitXor,
+}
+
+impl COp {
+    pub(crate) fn symbol(self) -> &'static str {
+        match self {
+            Self::And => "&&",
+            Self::Or => "||",
+            Self::NotEqual => "!=",
+            Self::BitAnd => "&",
+            Self::BitOr => "|",
+            Self::BitXor => "^",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum CExpr {
+    Bool(bool),
+    /// `20u`, sizes, shift amounts and numbers
+    Number(u32),
+    /// `0x00FFu`, bit patterns
+    Constant(u32),
+    Var(CVar),
+    /// `!rlo`
+    Not(Box<CExpr>),
+    /// `~accu1`
+    Complement(Box<CExpr>),
+    Binary(Box<CBinary>),
+    /// Function of the runtime (`get_bit`, `count_up`)
+    Call(&'static str, Vec<CExpr>),
+    /// `inputs, SCAN_INPUT_BYTES`, passed as two arguments
+    Area(CArea),
+    /// `P(10, 3)`, area-internal pointer to a bit
+    Pointer {
+        byte: u16,
+        bit: u8,
+    },
+    /// `&state->counters[3]`
+    Counter(u16),
+    /// `state->counters[3].value`
+    CounterValue(u16),
+}
+
+#[derive(Clone, Debug)]
+pub struct CBinary {
+    pub op: COp,
+    pub left: CExpr,
+    pub right: CExpr,
+}
+
+impl CExpr {
+    pub fn binary(op: COp, left: CExpr, right: CExpr) -> Self {
+        Self::Binary(Box::new(CBinary { op, left, right }))
+    }
+
+    pub fn call(function: &'static str, args: impl Into<Vec<CExpr>>) -> Self {
+        Self::Call(function, args.into())
+    }
+
+    /// Calls the function with the expression and all of its operands
+    pub fn visit(&self, f: &mut impl FnMut(&CExpr)) {
+        f(self);
+        match self {
+            Self::Not(value) | Self::Complement(value) => value.visit(f),
+            Self::Binary(binary) => {
+                binary.left.visit(f);
+                binary.right.visit(f);
+            }
+            Self::Call(_, args) => {
+                for arg in args {
+                    arg.visit(f);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Operands of a binary operation are parenthesized unless they are simple
+    fn write_operand(&self, out: &mut impl Write) -> std::io::Result<()> {
+        if let Self::Binary(_) = self {
+            write!(out, "(")?;
+            self.write_c(out)?;
+            return write!(out, ")");
+        }
+        self.write_c(out)
+    }
+}
+
+impl WriteC for CExpr {
+    fn write_c(&self, out: &mut impl Write) -> std::io::Result<()> {
+        match self {
+            Self::Bool(value) => write!(out, "{value}"),
+            Self::Number(value) => write!(out, "{value}u"),
+            Self::Constant(value @ 0..=0xFF) => write!(out, "0x{value:02X}u"),
+            Self::Constant(value @ 0..=0xFFFF) => write!(out, "0x{value:04X}u"),
+            Self::Constant(value) => write!(out, "0x{value:08X}u"),
+            Self::Var(var) => var.write_c(out),
+            Self::Not(value) => {
+                write!(out, "!")?;
+                value.write_operand(out)
+            }
+            Self::Complement(value) => {
+                write!(out, "~")?;
+                value.write_operand(out)
+            }
+            Self::Binary(binary) => {
+                binary.left.write_operand(out)?;
+                write!(out, " {} ", binary.op.symbol())?;
+                binary.right.write_operand(out)
+            }
+            Self::Call(function, args) => {
+                write!(out, "{function}(")?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i != 0 {
+                        write!(out, ", ")?;
+                    }
+                    arg.write_c(out)?;
+                }
+                write!(out, ")")
+            }
+            Self::Area(CArea::Input) => write!(out, "inputs, SCAN_INPUT_BYTES"),
+            Self::Area(CArea::Output) => write!(out, "outputs, SCAN_OUTPUT_BYTES"),
+            Self::Area(CArea::Memory) => write!(out, "state->memory, sizeof state->memory"),
+            Self::Area(CArea::DataBlock(db)) => {
+                write!(out, "state->db{db}, sizeof state->db{db}")
+            }
+            Self::Pointer { byte, bit } => write!(out, "P({byte}, {bit})"),
+            Self::Counter(number) => write!(out, "&state->counters[{number}]"),
+            Self::CounterValue(number) => write!(out, "state->counters[{number}].value"),
+        }
+    }
+}
+
+/// Header and source of the `scan` function
+#[derive(Debug)]
+pub struct CSource<'a> {
+    /// Base name of the files (`plc` for `plc.h` and `plc.c`)
+    pub name: String,
+    /// Mnemonics of the documented inputs and outputs
+    pub mnemonics: S7Mnemonics,
+    pub lir: &'a CLir,
+}
+
+impl<'a> CSource<'a> {
+    pub fn new(name: impl Into<String>, lir: &'a CLir) -> Self {
+        Self {
+            name: name.into(),
+            mnemonics: S7Mnemonics::German,
+            lir,
+        }
+    }
+
+    fn guard(&self) -> String {
+        let name: String = self
+            .name
+            .chars()
+            .map(|it| match it.is_ascii_alphanumeric() {
+                true => it.to_ascii_uppercase(),
+                false => '_',
+            })
+            .collect();
+        format!("{name}_H")
+    }
+
+    /// Write the declarations of `state_t` and `scan` with the layout of the process images
+    pub fn write_header(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let guard = self.guard();
+        writeln!(out, "#ifndef {guard}")?;
+        writeln!(out, "#define {guard}")?;
+        writeln!(out)?;
+        writeln!(out, "#include <stdbool.h>")?;
+        writeln!(out, "#include <stdint.h>")?;
+        writeln!(out)?;
+        writeln!(out, "/*")?;
+        writeln!(out, " * Process images, words are stored big-endian")?;
+        writeln!(out, " *")?;
+        for addr in &self.lir.io {
+            let mut operand = Vec::new();
+            addr.write_awl(&mut operand, self.mnemonics)?;
+            let operand = String::from_utf8_lossy(&operand);
+            let area = match area(addr.r#type) {
+                Area::Input => "inputs",
+                _ => "outputs",
+            };
+            let bytes = width(addr.r#type).div_ceil(8);
+            let location = match (addr.is_bit_address(), bytes) {
+                (true, _) => format!("{area}[{}] bit {}", addr.ptr, addr.bit),
+                (false, 1) => format!("{area}[{}]", addr.ptr),
+                (false, _) => format!("{area}[{}..{}]", addr.ptr, addr.ptr + bytes - 1),
+            };
+            writeln!(out, " * {operand:<12} {location}")?;
+        }
+        writeln!(out, " */")?;
+        writeln!(out, "#define SCAN_INPUT_BYTES {}u", self.lir.input_bytes)?;
+        writeln!(out, "#define SCAN_OUTPUT_BYTES {}u", self.lir.output_bytes)?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "/* S5 counter, counts on rising edges from 0 to 999 */"
+        )?;
+        writeln!(out, "typedef struct {{")?;
+        writeln!(out, "    uint16_t value;")?;
+        writeln!(out, "    /* Edge memories of the count and set inputs */")?;
+        writeln!(out, "    bool up, down, set;")?;
+        writeln!(out, "}} counter_t;")?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "/* Sizes of the directly addressed areas, enlarge for indirect access */"
+        )?;
+        let mut areas = vec![("MEMORY".to_string(), self.lir.memory_bytes.max(1))];
+        for (db, bytes) in &self.lir.data_blocks {
+            areas.push((format!("DB{db}"), *bytes));
+        }
+        for (area, bytes) in &areas {
+            writeln!(out, "#ifndef SCAN_{area}_BYTES")?;
+            writeln!(out, "#define SCAN_{area}_BYTES {bytes}u")?;
+            writeln!(out, "#endif")?;
+        }
+        writeln!(out)?;
+        writeln!(
+            out,
+            "/* Retained between scans, zero-initialize before the first scan */"
+        )?;
+        writeln!(out, "typedef struct {{")?;
+        writeln!(out, "    uint8_t memory[SCAN_MEMORY_BYTES];")?;
+        for (db, _) in &self.lir.data_blocks {
+            writeln!(out, "    uint8_t db{db}[SCAN_DB{db}_BYTES];")?;
+        }
+        writeln!(out, "    counter_t counters[{}];", self.lir.counters.max(1))?;
+        writeln!(out, "}} state_t;")?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "/* Execute one scan, the outputs must be kept between scans */"
+        )?;
+        writeln!(
+            out,
+            "void scan(const uint8_t *inputs, uint8_t *outputs, state_t *state);"
+        )?;
+        writeln!(out)?;
+        writeln!(out, "#endif /* {guard} */")
+    }
+
+    /// Write the runtime and `scan` including the header `{name}.h`
+    pub fn write_source(&self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(out, "#include <string.h>")?;
+        writeln!(out)?;
+        writeln!(out, "#include \"{}.h\"", self.name)?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "/* Area-internal pointer, the byte address is stored above the lower three bits */"
+        )?;
+        writeln!(out, "#define P(byte, bit) ((uint32_t)(byte) * 8u + (bit))")?;
+        let mut functions = Vec::new();
+        let mut areas = Vec::new();
+        let statements = self.lir.networks.iter().flat_map(|it| &it.statements);
+        for statement in statements {
+            statement.visit(&mut |expr| match expr {
+                CExpr::Call(function, _) => functions.push(*function),
+                CExpr::Area(area) => areas.push(*area),
+                CExpr::Counter(_) | CExpr::CounterValue(_) => areas.push(CArea::Memory),
+                _ => {}
+            });
+        }
+        // Functions of the runtime only call functions defined before them
+        for (name, code) in RUNTIME.iter().rev() {
+            if functions.contains(name) {
+                functions.extend(
+                    RUNTIME
+                        .iter()
+                        .map(|it| it.0)
+                        .filter(|it| code.contains(&format!("{it}("))),
+                );
+            }
+        }
+        for (name, code) in RUNTIME {
+            if functions.contains(name) {
+                writeln!(out)?;
+                write!(out, "{code}")?;
+            }
+        }
+        writeln!(out)?;
+        writeln!(
+            out,
+            "void scan(const uint8_t *inputs, uint8_t *outputs, state_t *state)"
+        )?;
+        writeln!(out, "{{")?;
+        for var in &self.lir.variables {
+            write!(out, "    {} ", var.r#type())?;
+            var.write_c(out)?;
+            let zero = if var.r#type() == "bool" { "false" } else { "0" };
+            writeln!(out, " = {zero};")?;
+        }
+        let unused = [
+            ("inputs", CArea::Input),
+            ("outputs", CArea::Output),
+            ("state", CArea::Memory),
+        ];
+        for (param, area) in unused {
+            let used = match area {
+                CArea::Memory => areas
+                    .iter()
+                    .any(|it| matches!(it, CArea::Memory | CArea::DataBlock(_))),
+                area => areas.contains(&area),
+            };
+            if !used {
+                writeln!(out, "    (void){param};")?;
+            }
+        }
+        for network in &self.lir.networks {
+            writeln!(out)?;
+            network.write_c(out)?;
+        }
+        writeln!(out, "}}")
+    }
+}
+
+fn write_comment(out: &mut impl Write, text: &str) -> std::io::Result<()> {
+    writeln!(out, "    /* {} */", text.trim().replace("*/", "* /"))
+}
+
+/// Access functions and operations of the CPU, only the functions used by `scan` are written
+const RUNTIME: &[(&str, &str)] = &[
+    (
+        "get_bit",
+        r#"/* Accesses outside of an area read zero and are ignored, where the CPU would stop */
+static bool get_bit(const uint8_t *area, uint32_t size, uint32_t pointer)
+{
+    uint32_t byte = (pointer >> 3) & 0xFFFFu;
+    return byte < size && (area[byte] >> (pointer & 7u)) & 1u;
+}
+"#,
+    ),
+    (
+        "set_bit",
+        r#"static void set_bit(uint8_t *area, uint32_t size, uint32_t pointer, bool value)
+{
+    uint32_t byte = (pointer >> 3) & 0xFFFFu;
+    if (byte >= size)
+        return;
+    if (value)
+        area[byte] |= (uint8_t)(1u << (pointer & 7u));
+    else
+        area[byte] &= (uint8_t)~(1u << (pointer & 7u));
+}
+"#,
+    ),
+    (
+        "get_bytes",
+        r#"static uint32_t get_bytes(const uint8_t *area, uint32_t size, uint32_t pointer, uint32_t bytes)
+{
+    uint32_t byte = (pointer >> 3) & 0xFFFFu;
+    uint32_t value = 0;
+    uint32_t i;
+    if (byte + bytes > size)
+        return 0;
+    for (i = 0; i < bytes; i++)
+        value = value << 8 | area[byte + i];
+    return value;
+}
+"#,
+    ),
+    (
+        "set_bytes",
+        r#"static void set_bytes(uint8_t *area, uint32_t size, uint32_t pointer, uint32_t bytes,
+                      uint32_t value)
+{
+    uint32_t byte = (pointer >> 3) & 0xFFFFu;
+    uint32_t i;
+    if (byte + bytes > size)
+        return;
+    for (i = bytes; i > 0; i--) {
+        area[byte + i - 1] = (uint8_t)value;
+        value >>= 8;
+    }
+}
+"#,
+    ),
+    (
+        "copy_bytes",
+        r#"static void copy_bytes(uint8_t *dst, uint32_t dst_size, uint32_t dst_pointer,
+                       const uint8_t *src, uint32_t src_size, uint32_t src_pointer,
+                       uint32_t bytes)
+{
+    uint32_t dst_byte = dst_pointer >> 3;
+    uint32_t src_byte = src_pointer >> 3;
+    if (dst_byte + bytes <= dst_size && src_byte + bytes <= src_size)
+        memmove(&dst[dst_byte], &src[src_byte], bytes);
+}
+"#,
+    ),
+    (
+        "fill_bytes",
+        r#"/* Repeat the source until the destination is full */
+static void fill_bytes(uint8_t *dst, uint32_t dst_size, uint32_t dst_pointer,
+                       uint32_t dst_bytes, const uint8_t *src, uint32_t src_size,
+                       uint32_t src_pointer, uint32_t src_bytes)
+{
+    uint32_t dst_byte = dst_pointer >> 3;
+    uint32_t src_byte = src_pointer >> 3;
+    uint32_t i;
+    if (dst_byte + dst_bytes > dst_size || src_byte + src_bytes > src_size)
+        return;
+    for (i = 0; i < dst_bytes; i++)
+        dst[dst_byte + i] = src[src_byte + i % src_bytes];
+}
+"#,
+    ),
+    (
+        "low_word",
+        r#"/* Word operations only change the low word of ACCU1 */
+static uint32_t low_word(uint32_t accu, uint32_t value)
+{
+    return (accu & 0xFFFF0000u) | (value & 0xFFFFu);
+}
+"#,
+    ),
+    (
+        "add_pointer",
+        r#"/* Add a bit offset to an area-internal pointer, keeping the area */
+static uint32_t add_pointer(uint32_t pointer, uint32_t offset)
+{
+    return (pointer & 0xFF000000u) | ((pointer + offset) & 0x00FFFFFFu);
+}
+"#,
+    ),
+    (
+        "shift_left_word",
+        r#"static uint32_t shift_left_word(uint32_t accu, uint32_t amount)
+{
+    return low_word(accu, amount > 15u ? 0u : (accu & 0xFFFFu) << amount);
+}
+"#,
+    ),
+    (
+        "shift_right_word",
+        r#"static uint32_t shift_right_word(uint32_t accu, uint32_t amount)
+{
+    return low_word(accu, amount > 15u ? 0u : (accu & 0xFFFFu) >> amount);
+}
+"#,
+    ),
+    (
+        "shift_left_dword",
+        r#"static uint32_t shift_left_dword(uint32_t accu, uint32_t amount)
+{
+    return amount > 31u ? 0u : accu << amount;
+}
+"#,
+    ),
+    (
+        "shift_right_dword",
+        r#"static uint32_t shift_right_dword(uint32_t accu, uint32_t amount)
+{
+    return amount > 31u ? 0u : accu >> amount;
+}
+"#,
+    ),
+    (
+        "rotate_left_dword",
+        r#"static uint32_t rotate_left_dword(uint32_t accu, uint32_t amount)
+{
+    amount &= 31u;
+    return amount == 0 ? accu : accu << amount | accu >> (32u - amount);
+}
+"#,
+    ),
+    (
+        "rotate_right_dword",
+        r#"static uint32_t rotate_right_dword(uint32_t accu, uint32_t amount)
+{
+    amount &= 31u;
+    return amount == 0 ? accu : accu >> amount | accu << (32u - amount);
+}
+"#,
+    ),
+    (
+        "bcd_to_int",
+        r#"/* Invalid digits leave the accumulator unchanged, where the CPU would stop */
+static uint32_t bcd_to_int(uint32_t accu)
+{
+    int32_t value = 0;
+    int shift;
+    for (shift = 8; shift >= 0; shift -= 4) {
+        uint32_t digit = accu >> shift & 0xFu;
+        if (digit > 9u)
+            return accu;
+        value = value * 10 + (int32_t)digit;
+    }
+    if (accu & 0x8000u)
+        value = -value;
+    return low_word(accu, (uint32_t)value);
+}
+"#,
+    ),
+    (
+        "int_to_bcd",
+        r#"/* Integers outside of -999 to 999 overflow and leave the accumulator unchanged */
+static uint32_t int_to_bcd(uint32_t accu)
+{
+    int32_t value = (int16_t)(accu & 0xFFFFu);
+    uint32_t bcd = value < 0 ? 0xF000u : 0u;
+    if (value < -999 || value > 999)
+        return accu;
+    if (value < 0)
+        value = -value;
+    bcd |= (uint32_t)(value / 100) << 8 | (uint32_t)(value / 10 % 10) << 4 | (uint32_t)(value % 10);
+    return low_word(accu, bcd);
+}
+"#,
+    ),
+    (
+        "int_to_dint",
+        r#"static uint32_t int_to_dint(uint32_t accu)
+{
+    return (uint32_t)(int32_t)(int16_t)(accu & 0xFFFFu);
+}
+"#,
+    ),
+    (
+        "dint_to_real",
+        r#"static uint32_t dint_to_real(uint32_t accu)
+{
+    float real = (float)(int32_t)accu;
+    uint32_t bits;
+    memcpy(&bits, &real, sizeof bits);
+    return bits;
+}
+"#,
+    ),
+    (
+        "real_to_dint",
+        r#"/* Reals outside of the DINT range overflow and leave the accumulator unchanged */
+static uint32_t real_to_dint(uint32_t accu, bool round)
+{
+    float real;
+    float value;
+    memcpy(&real, &accu, sizeof real);
+    if (!(real >= -2147483648.0f && real < 2147483648.0f))
+        return accu;
+    value = (float)(int32_t)real;
+    if (round) {
+        float fraction = real - value;
+        if (fraction > 0.5f || (fraction == 0.5f && (int32_t)value % 2 != 0))
+            value += 1.0f;
+        else if (fraction < -0.5f || (fraction == -0.5f && (int32_t)value % 2 != 0))
+            value -= 1.0f;
+        if (!(value >= -2147483648.0f && value < 2147483648.0f))
+            return accu;
+    }
+    return (uint32_t)(int32_t)value;
+}
+"#,
+    ),
+    (
+        "count_up",
+        r#"static void count_up(counter_t *counter, bool rlo)
+{
+    if (rlo && !counter->up && counter->value < 999u)
+        counter->value++;
+    counter->up = rlo;
+}
+"#,
+    ),
+    (
+        "count_down",
+        r#"static void count_down(counter_t *counter, bool rlo)
+{
+    if (rlo && !counter->down && counter->value > 0u)
+        counter->value--;
+    counter->down = rlo;
+}
+"#,
+    ),
+    (
+        "counter_set",
+        r#"/* Preset with the BCD value in ACCU1 */
+static void counter_set(counter_t *counter, bool rlo, uint32_t accu)
+{
+    if (rlo && !counter->set)
+        counter->value = (uint16_t)((accu >> 8 & 0xFu) * 100u + (accu >> 4 & 0xFu) * 10u +
+                                    (accu & 0xFu));
+    counter->set = rlo;
+}
+"#,
+    ),
+    (
+        "counter_reset",
+        r#"static void counter_reset(counter_t *counter, bool rlo)
+{
+    if (rlo)
+        counter->value = 0;
+}
+"#,
+    ),
+    (
+        "counter_bcd",
+        r#"static uint32_t counter_bcd(const counter_t *counter)
+{
+    uint32_t value = counter->value;
+    return (value / 100u) << 8 | (value / 10u % 10u) << 4 | value % 10u;
+}
+"#,
+    ),
+];
@@ -0,0 +1,691 @@
+//! Translates the instruction lists of a [Mir] into C statements on the status bits
+//!
+//! Every instruction is emulated on the status word like the simulator executes it, the
+//! expressions of the program are not reconstructed. `/ER` and whether the OR bit may be set
+//! only depend on the order of the instructions, so they are resolved here and only the RLO, the
+//! OR bit and the accumulators are computed at runtime.
+
+use crate::{
+    lir::{
+        error::{Error, Result},
+        push_stack,
+        s7::transformer::transform_address,
+        st::{self, Area},
+    },
+    mir::{
+        builtin::system::{BLKMOV, FILL},
+        types::MirType,
+        value::{MirAddress, MirAddressIndex, MirAddressType, MirAnyPointer},
+        Mir, MirAction, MirCallAction, MirInstruction, MirOperand,
+    },
+};
+
+use super::{CArea, CExpr, CLir, CNetwork, COp, CStatement, CVar};
+
+/// Nesting depth of the CPU
+const STACK_DEPTH: usize = 7;
+
+#[derive(Clone, Copy, Debug)]
+enum Logic {
+    And,
+    Or,
+    Xor,
+}
+
+/// Status bits saved by `U(` and friends
+#[derive(Debug)]
+struct Nesting {
+    logic: Logic,
+    negated: bool,
+    er: bool,
+    /// OR bit may be set
+    or: bool,
+}
+
+#[derive(Debug)]
+struct Transformer<'a> {
+    lir: &'a mut CLir,
+    /// `/ER`, cleared at the first check of a logic string
+    er: bool,
+    /// The OR bit may be set at runtime
+    or: bool,
+    stack: Vec<Nesting>,
+    /// Data block opened by the last access to a numbered data block
+    data_block: Option<u16>,
+    /// First byte of the memory area after every allocated and reserved byte
+    temp_base: u16,
+    statements: Vec<CStatement>,
+}
+
+impl<'a> Transformer<'a> {
+    fn new(lir: &'a mut CLir, temp_base: u16) -> Self {
+        Self {
+            lir,
+            er: false,
+            or: false,
+            stack: Vec::new(),
+            data_block: None,
+            temp_base,
+            statements: Vec::new(),
+        }
+    }
+
+    /// Block temporaries are kept in the memory area, behind the memory used by the program
+    fn relocate(&self, addr: MirAddress) -> MirAddress {
+        let r#type = match addr.r#type {
+            MirAddressType::Temp1 => MirAddressType::Memory1,
+            MirAddressType::Temp8 => MirAddressType::Memory8,
+            MirAddressType::Temp16 => MirAddressType::Memory16,
+            MirAddressType::Temp32 => MirAddressType::Memory32,
+            _ => return addr,
+        };
+        MirAddress {
+            r#type,
+            ptr: addr.ptr + self.temp_base,
+            ..addr
+        }
+    }
+
+    fn assign(&mut self, var: CVar, value: CExpr) {
+        if let CExpr::Var(value) = value {
+            if value == var {
+                return;
+            }
+        }
+        self.statements.push(CStatement::Assign { var, value });
+    }
+
+    /// Load a value into `ACCU1`, pushing the previous value into `ACCU2`
+    fn load(&mut self, value: CExpr) {
+        self.assign(CVar::Accu2, var(CVar::Accu1));
+        self.assign(CVar::Accu1, value);
+    }
+
+    /// End the logic string, the next check is a first check
+    fn terminate(&mut self) {
+        self.clear_or();
+        self.er = false;
+    }
+
+    fn clear_or(&mut self) {
+        if self.or {
+            self.assign(CVar::Or, CExpr::Bool(false));
+            self.or = false;
+        }
+    }
+
+    /// Area of the operand, data block operands without number use the opened data block
+    fn area(&mut self, addr: MirAddress) -> Result<CArea> {
+        match area(addr) {
+            CArea::DataBlock(0) => match self.data_block {
+                Some(db) => Ok(CArea::DataBlock(db)),
+                None => Err(Error::Unsupported(
+                    "data block operands without number need an opened data block",
+                )),
+            },
+            CArea::DataBlock(db) => {
+                self.data_block = Some(db);
+                Ok(CArea::DataBlock(db))
+            }
+            area => Ok(area),
+        }
+    }
+
+    /// Record the size of the area holding the operand
+    fn declare(&mut self, addr: MirAddress, bytes: u32) -> Result<CArea> {
+        let addr = self.relocate(addr);
+        let end = match addr.index {
+            MirAddressIndex::Direct => addr.ptr as u32 + bytes,
+            _ => 0,
+        };
+        let area = self.area(addr)?;
+        let lir = &mut *self.lir;
+        match area {
+            CArea::Input => lir.input_bytes = lir.input_bytes.max(end),
+            CArea::Output => lir.output_bytes = lir.output_bytes.max(end),
+            CArea::Memory => lir.memory_bytes = lir.memory_bytes.max(end),
+            CArea::DataBlock(db) => match lir.data_blocks.iter_mut().find(|it| it.0 == db) {
+                Some(block) => block.1 = block.1.max(end.max(1)),
+                None => lir.data_blocks.push((db, end.max(1))),
+            },
+        }
+        let io = matches!(area, CArea::Input | CArea::Output);
+        if io && addr.index == MirAddressIndex::Direct {
+            let addr = transform_address(addr);
+            let documented = self
+                .lir
+                .io
+                .iter()
+                .any(|it| it.r#type == addr.r#type && it.ptr == addr.ptr && it.bit == addr.bit);
+            if !documented {
+                self.lir.io.push(addr);
+            }
+        }
+        Ok(area)
+    }
+
+    /// Area-internal pointer to the operand, computed at runtime for indirect operands
+    fn pointer(&mut self, addr: MirAddress) -> CExpr {
+        let addr = self.relocate(addr);
+        let offset = CExpr::Pointer {
+            byte: addr.ptr,
+            bit: addr.bit,
+        };
+        match addr.index {
+            MirAddressIndex::Direct => offset,
+            MirAddressIndex::Memory { ptr } => {
+                self.lir.memory_bytes = self.lir.memory_bytes.max(ptr as u32 + 4);
+                CExpr::call(
+                    "get_bytes",
+                    [
+                        CExpr::Area(CArea::Memory),
+                        CExpr::Pointer { byte: ptr, bit: 0 },
+                        CExpr::Number(4),
+                    ],
+                )
+            }
+            MirAddressIndex::Register => {
+                let ar1 = CExpr::binary(COp::BitAnd, var(CVar::Ar1), CExpr::Constant(0x7FFFF));
+                if addr.ptr == 0 && addr.bit == 0 {
+                    return ar1;
+                }
+                CExpr::call("add_pointer", [ar1, offset])
+            }
+        }
+    }
+
+    fn counter(&mut self, addr: MirAddress) -> Result<u16> {
+        if addr.r#type != MirAddressType::PhysicalCounter {
+            return Err(Error::NonCounterAddress);
+        }
+        if addr.index != MirAddressIndex::Direct {
+            return Err(Error::Unsupported(
+                "counters can only be addressed directly in C",
+            ));
+        }
+        self.lir.counters = self.lir.counters.max(addr.ptr + 1);
+        Ok(addr.ptr)
+    }
+
+    /// Bit operand of a logic operation, counters are set while their value is not zero
+    fn bit(&mut self, addr: MirAddress) -> Result<CExpr> {
+        match addr.r#type {
+            MirAddressType::PhysicalCounter => Ok(CExpr::binary(
+                COp::NotEqual,
+                CExpr::CounterValue(self.counter(addr)?),
+                CExpr::Number(0),
+            )),
+            MirAddressType::PhysicalTimer => {
+                Err(Error::Unsupported("S5 timers have no C equivalent"))
+            }
+            _ if addr.is_bit_address() => {
+                let area = self.declare(addr, 1)?;
+                let pointer = self.pointer(addr);
+                Ok(CExpr::call("get_bit", [CExpr::Area(area), pointer]))
+            }
+            _ => Err(Error::NonBitAddress),
+        }
+    }
+
+    fn write_bit(&mut self, addr: MirAddress, value: CExpr) -> Result<CStatement> {
+        if !addr.is_bit_address() {
+            return Err(Error::NonBitAddress);
+        }
+        let area = self.declare(addr, 1)?;
+        let pointer = self.pointer(addr);
+        Ok(CStatement::Expr(CExpr::call(
+            "set_bit",
+            [CExpr::Area(area), pointer, value],
+        )))
+    }
+
+    fn bytes(addr: MirAddress) -> Result<u32> {
+        if !addr.is_any_byte_address() {
+            return Err(Error::NonByteAddress);
+        }
+        Ok(addr.r#type.width() as u32 / 8)
+    }
+
+    fn logic(&mut self, logic: Logic, value: CExpr) {
+        let rlo = var(CVar::Rlo);
+        if !self.er {
+            self.er = true;
+            return self.assign(CVar::Rlo, value);
+        }
+        match logic {
+            Logic::And if self.or => {
+                let and = CExpr::binary(COp::And, rlo, value);
+                self.assign(CVar::Rlo, CExpr::binary(COp::Or, var(CVar::Or), and));
+            }
+            Logic::And => self.assign(CVar::Rlo, CExpr::binary(COp::And, rlo, value)),
+            Logic::Or => {
+                self.assign(CVar::Or, rlo.clone());
+                self.assign(CVar::Rlo, CExpr::binary(COp::Or, rlo, value));
+                self.or = true;
+            }
+            Logic::Xor => {
+                self.assign(CVar::Rlo, CExpr::binary(COp::NotEqual, rlo, value));
+                self.clear_or();
+            }
+        }
+    }
+
+    fn start(&mut self, logic: Logic, negated: bool) -> Result<()> {
+        let depth = self.stack.len();
+        let mut stack_depth = depth;
+        push_stack(&mut stack_depth, STACK_DEPTH)?;
+        if self.er {
+            self.assign(CVar::NestingRlo(depth), var(CVar::Rlo));
+        }
+        if self.or {
+            self.assign(CVar::NestingOr(depth), var(CVar::Or));
+            self.assign(CVar::Or, CExpr::Bool(false));
+        }
+        self.stack.push(Nesting {
+            logic,
+            negated,
+            er: self.er,
+            or: self.or,
+        });
+        self.er = false;
+        self.or = false;
+        Ok(())
+    }
+
+    fn end(&mut self) -> Result<()> {
+        let nesting = self.stack.pop().ok_or(Error::InvalidStack)?;
+        let depth = self.stack.len();
+        // The RLO before a first check was not saved, it is overwritten by the nested result
+        let nested = match nesting.er {
+            true => {
+                self.assign(CVar::Nested, var(CVar::Rlo));
+                self.assign(CVar::Rlo, var(CVar::NestingRlo(depth)));
+                var(CVar::Nested)
+            }
+            false => var(CVar::Rlo),
+        };
+        let nested = match nesting.negated {
+            true => not(nested),
+            false => nested,
+        };
+        match nesting.or {
+            true => self.assign(CVar::Or, var(CVar::NestingOr(depth))),
+            false => self.clear_or(),
+        }
+        self.or = nesting.or;
+        self.er = nesting.er;
+        self.logic(nesting.logic, nested);
+        Ok(())
+    }
+
+    fn instruction(&mut self, instruction: MirInstruction) -> Result<()> {
+        match instruction {
+            MirInstruction::Dummy => {}
+            MirInstruction::And { addr } => {
+                let value = self.bit(addr)?;
+                self.logic(Logic::And, value);
+            }
+            MirInstruction::AndNot { addr } => {
+                let value = self.bit(addr)?;
+                self.logic(Logic::And, not(value));
+            }
+            MirInstruction::Or { addr } => {
+                let value = self.bit(addr)?;
+                self.logic(Logic::Or, value);
+            }
+            MirInstruction::OrNot { addr } => {
+                let value = self.bit(addr)?;
+                self.logic(Logic::Or, not(value));
+            }
+            MirInstruction::Xor { addr } => {
+                let value = self.bit(addr)?;
+                self.logic(Logic::Xor, value);
+            }
+            MirInstruction::XorNot { addr } => {
+                let value = self.bit(addr)?;
+                self.logic(Logic::Xor, not(value));
+            }
+            MirInstruction::AndStart => self.start(Logic::And, false)?,
+            MirInstruction::AndNotStart => self.start(Logic::And, true)?,
+            MirInstruction::OrStart => self.start(Logic::Or, false)?,
+            MirInstruction::OrNotStart => self.start(Logic::Or, true)?,
+            MirInstruction::XorStart => self.start(Logic::Xor, false)?,
+            MirInstruction::XorNotStart => self.start(Logic::Xor, true)?,
+            MirInstruction::End => self.end()?,
+            MirInstruction::AssignBit { addr } => {
+                let statement = self.write_bit(addr, var(CVar::Rlo))?;
+                self.statements.push(statement);
+                self.terminate();
+            }
+            MirInstruction::SetBit { addr } | MirInstruction::ResetBit { addr } => {
+                let value = matches!(instruction, MirInstruction::SetBit { .. });
+                let statement = self.write_bit(addr, CExpr::Bool(value))?;
+                self.statements.push(CStatement::If {
+                    cond: var(CVar::Rlo),
+                    then: vec![statement],
+                });
+                self.terminate();
+            }
+            // The OR bit is kept like on the CPU
+            MirInstruction::Not => self.assign(CVar::Rlo, not(var(CVar::Rlo))),
+            MirInstruction::Set | MirInstruction::Clear => {
+                let value = matches!(instruction, MirInstruction::Set);
+                self.assign(CVar::Rlo, CExpr::Bool(value));
+                self.terminate();
+            }
+            // The binary result is only read by the caller of a block
+            MirInstruction::Save => {}
+            MirInstruction::CounterLoadInt { addr } => {
+                let counter = self.counter(addr)?;
+                self.load(CExpr::CounterValue(counter));
+            }
+            MirInstruction::CounterLoadBcd { addr } => {
+                let counter = self.counter(addr)?;
+                self.load(CExpr::call("counter_bcd", [CExpr::Counter(counter)]));
+            }
+            MirInstruction::CounterForward { addr } => self.count(addr, "count_up")?,
+            MirInstruction::CounterBackward { addr } => self.count(addr, "count_down")?,
+            MirInstruction::CounterSet { addr } => self.count(addr, "counter_set")?,
+            MirInstruction::CounterReset { addr } => self.count(addr, "counter_reset")?,
+            MirInstruction::Load { addr } => {
+                let bytes = Self::bytes(addr)?;
+                let area = self.declare(addr, bytes)?;
+                let pointer = self.pointer(addr);
+                self.load(CExpr::call(
+                    "get_bytes",
+                    [CExpr::Area(area), pointer, CExpr::Number(bytes)],
+                ));
+            }
+            MirInstruction::LoadConst { value, r#type } => {
+                let value = match r#type {
+                    MirType::Real => CExpr::Constant((value as f32).to_bits()),
+                    MirType::Int | MirType::DInt | MirType::Time if value < 0x8000 => {
+                        CExpr::Number(value)
+                    }
+                    _ => CExpr::Constant(value),
+                };
+                self.load(value);
+            }
+            MirInstruction::Transfer { addr } => {
+                if !addr.is_writable() {
+                    return Err(Error::Unsupported("inputs can not be written"));
+                }
+                let bytes = Self::bytes(addr)?;
+                let area = self.declare(addr, bytes)?;
+                let pointer = self.pointer(addr);
+                self.statements.push(CStatement::Expr(CExpr::call(
+                    "set_bytes",
+                    [
+                        CExpr::Area(area),
+                        pointer,
+                        CExpr::Number(bytes),
+                        var(CVar::Accu1),
+                    ],
+                )));
+            }
+            MirInstruction::AndWord => self.word_op(COp::BitAnd, true),
+            MirInstruction::OrWord => self.word_op(COp::BitOr, true),
+            MirInstruction::XorWord => self.word_op(COp::BitXor, true),
+            MirInstruction::AndDWord => self.word_op(COp::BitAnd, false),
+            MirInstruction::OrDWord => self.word_op(COp::BitOr, false),
+            MirInstruction::XorDWord => self.word_op(COp::BitXor, false),
+            MirInstruction::InvertInt => self.assign(
+                CVar::Accu1,
+                CExpr::binary(COp::BitXor, var(CVar::Accu1), CExpr::Constant(0xFFFF)),
+            ),
+            MirInstruction::InvertDInt => {
+                self.assign(CVar::Accu1, CExpr::Complement(Box::new(var(CVar::Accu1))))
+            }
+            MirInstruction::ShiftLeftWord { amount } => self.shift("shift_left_word", amount),
+            MirInstruction::ShiftRightWord { amount } => self.shift("shift_right_word", amount),
+            MirInstruction::ShiftLeftDWord { amount } => self.shift("shift_left_dword", amount),
+            MirInstruction::ShiftRightDWord { amount } => self.shift("shift_right_dword", amount),
+            MirInstruction::RotateLeftDWord { amount } => self.shift("rotate_left_dword", amount),
+            MirInstruction::RotateRightDWord { amount } => self.shift("rotate_right_dword", amount),
+            MirInstruction::BcdToInt => self.convert("bcd_to_int", None),
+            MirInstruction::IntToBcd => self.convert("int_to_bcd", None),
+            MirInstruction::IntToDInt => self.convert("int_to_dint", None),
+            MirInstruction::DIntToReal => self.convert("dint_to_real", None),
+            MirInstruction::Round => self.convert("real_to_dint", Some(CExpr::Bool(true))),
+            MirInstruction::Trunc => self.convert("real_to_dint", Some(CExpr::Bool(false))),
+            MirInstruction::LoadAddressRegister => self.assign(CVar::Ar1, var(CVar::Accu1)),
+            MirInstruction::TransferAddressRegister => self.load(var(CVar::Ar1)),
+            MirInstruction::AddAddressRegister { offset } => {
+                let offset = match offset {
+                    Some(offset) => CExpr::Number(offset),
+                    None => CExpr::call("int_to_dint", [var(CVar::Accu1)]),
+                };
+                let value = CExpr::call("add_pointer", [var(CVar::Ar1), offset]);
+                self.assign(CVar::Ar1, value);
+            }
+        }
+        Ok(())
+    }
+
+    /// `ZV`, `ZR`, `S` and `R` on a counter with the RLO
+    fn count(&mut self, addr: MirAddress, function: &'static str) -> Result<()> {
+        let counter = self.counter(addr)?;
+        let mut args = vec![CExpr::Counter(counter), var(CVar::Rlo)];
+        if function == "counter_set" {
+            args.push(var(CVar::Accu1));
+        }
+        self.statements
+            .push(CStatement::Expr(CExpr::call(function, args)));
+        self.terminate();
+        Ok(())
+    }
+
+    /// Combine `ACCU2` and `ACCU1` into `ACCU1`, word operations keep the high word
+    fn word_op(&mut self, op: COp, word: bool) {
+        let right = match (word, op) {
+            (false, _) => var(CVar::Accu2),
+            (true, COp::BitAnd) => {
+                CExpr::binary(COp::BitOr, var(CVar::Accu2), CExpr::Constant(0xFFFF0000))
+            }
+            (true, _) => CExpr::binary(COp::BitAnd, var(CVar::Accu2), CExpr::Constant(0xFFFF)),
+        };
+        self.assign(CVar::Accu1, CExpr::binary(op, var(CVar::Accu1), right));
+    }
+
+    /// Shift or rotate `ACCU1`, the amount is taken from `ACCU2-L-L` if it is not given
+    fn shift(&mut self, function: &'static str, amount: Option<u8>) {
+        let amount = match amount {
+            Some(amount) => CExpr::Number(amount as u32),
+            None => CExpr::binary(COp::BitAnd, var(CVar::Accu2), CExpr::Constant(0xFF)),
+        };
+        self.convert(function, Some(amount));
+    }
+
+    fn convert(&mut self, function: &'static str, arg: Option<CExpr>) {
+        let mut args = vec![var(CVar::Accu1)];
+        args.extend(arg);
+        self.assign(CVar::Accu1, CExpr::call(function, args));
+    }
+
+    fn instructions(&mut self, instructions: &[MirInstruction]) -> Result<()> {
+        for &instruction in instructions {
+            self.instruction(instruction)?;
+        }
+        if !self.stack.is_empty() {
+            return Err(Error::InvalidStack);
+        }
+        Ok(())
+    }
+
+    /// `ANY` pointer as area, pointer and size in bytes
+    fn any(&mut self, operand: MirOperand) -> Result<[CExpr; 3]> {
+        let MirOperand::Any(any) = operand else {
+            return Err(Error::Unsupported("block parameters of C must be areas"));
+        };
+        let MirAnyPointer { addr, .. } = any;
+        let bytes = any.bits().div_ceil(8) as u32;
+        if addr.bit != 0 {
+            return Err(Error::Unsupported("copied areas must start at a byte"));
+        }
+        let area = self.declare(addr, bytes)?;
+        let pointer = self.pointer(addr);
+        Ok([CExpr::Area(area), pointer, CExpr::Number(bytes)])
+    }
+
+    /// Block moves and fills are executed in place, other blocks have no C equivalent
+    fn call(&mut self, call: &MirCallAction) -> Result<()> {
+        let param = |name: &str| {
+            call.params
+                .iter()
+                .find(|it| &*it.name == name)
+                .map(|it| it.value)
+                .expect("System block parameter")
+        };
+        let (function, src, dst) = if call.block == BLKMOV.block {
+            ("copy_bytes", param("SRCBLK"), param("DSTBLK"))
+        } else if call.block == FILL.block {
+            ("fill_bytes", param("BVAL"), param("BLK"))
+        } else {
+            return Err(Error::Unsupported(
+                "only block moves and fills have a C equivalent",
+            ));
+        };
+        let [src_area, src_pointer, src_bytes] = self.any(src)?;
+        let [dst_area, dst_pointer, dst_bytes] = self.any(dst)?;
+        let args = match function {
+            "copy_bytes" => vec![dst_area, dst_pointer, src_area, src_pointer, src_bytes],
+            _ => vec![
+                dst_area,
+                dst_pointer,
+                dst_bytes,
+                src_area,
+                src_pointer,
+                src_bytes,
+            ],
+        };
+        self.statements
+            .push(CStatement::Expr(CExpr::call(function, args)));
+        if let MirOperand::Address(addr) = param("RET_VAL") {
+            let bytes = Self::bytes(addr)?;
+            let area = self.declare(addr, bytes)?;
+            let pointer = self.pointer(addr);
+            self.statements.push(CStatement::Expr(CExpr::call(
+                "set_bytes",
+                [
+                    CExpr::Area(area),
+                    pointer,
+                    CExpr::Number(bytes),
+                    CExpr::Number(0),
+                ],
+            )));
+        }
+        Ok(())
+    }
+
+    fn action(&mut self, action: &MirAction) -> Result<()> {
+        match action {
+            MirAction::Raw(raw) => self.instructions(&raw.instructions)?,
+            MirAction::Output(output) => {
+                self.instructions(&output.instructions)?;
+                self.instruction(MirInstruction::AssignBit {
+                    addr: output.address,
+                })?;
+            }
+            MirAction::Call(call) => self.call(call)?,
+        }
+        Ok(())
+    }
+}
+
+fn var(var: CVar) -> CExpr {
+    CExpr::Var(var)
+}
+
+fn not(value: CExpr) -> CExpr {
+    match value {
+        CExpr::Not(value) => *value,
+        CExpr::Bool(value) => CExpr::Bool(!value),
+        value => CExpr::Not(Box::new(value)),
+    }
+}
+
+fn area(addr: MirAddress) -> CArea {
+    match addr.r#type {
+        MirAddressType::PhysicalInput1
+        | MirAddressType::PhysicalInput8
+        | MirAddressType::PhysicalInput16
+        | MirAddressType::PhysicalInput32
+        | MirAddressType::PeripheralInput8
+        | MirAddressType::PeripheralInput16
+        | MirAddressType::PeripheralInput32 => CArea::Input,
+        MirAddressType::PhysicalOutput1
+        | MirAddressType::PhysicalOutput8
+        | MirAddressType::PhysicalOutput16
+        | MirAddressType::PhysicalOutput32
+        | MirAddressType::PeripheralOutput8
+        | MirAddressType::PeripheralOutput16
+        | MirAddressType::PeripheralOutput32 => CArea::Output,
+        MirAddressType::DataBlock1
+        | MirAddressType::DataBlock8
+        | MirAddressType::DataBlock16
+        | MirAddressType::DataBlock32 => CArea::DataBlock(addr.db),
+        _ => CArea::Memory,
+    }
+}
+
+/// Remove assignments which are overwritten before they are read, returns the used variables
+fn remove_dead_stores(networks: &mut [CNetwork]) -> Vec<CVar> {
+    let mut live: Vec<CVar> = Vec::new();
+    let mut variables = Vec::new();
+    for network in networks.iter_mut().rev() {
+        let mut statements = Vec::with_capacity(network.statements.len());
+        for statement in network.statements.drain(..).rev() {
+            if let CStatement::Assign { var, .. } = statement {
+                if !live.contains(&var) {
+                    continue;
+                }
+                live.retain(|it| *it != var);
+            }
+            statement.visit(&mut |expr| {
+                if let CExpr::Var(var) = expr {
+                    live.push(*var);
+                    variables.push(*var);
+                }
+            });
+            statements.push(statement);
+        }
+        statements.reverse();
+        network.statements = statements;
+    }
+    variables.sort();
+    variables.dedup();
+    variables
+}
+
+pub fn transform(mir: &Mir) -> Result<CLir> {
+    let mut lir = CLir {
+        memory_bytes: mir.allocator.allocated_bytes as u32,
+        counters: mir.allocator.allocated_counters,
+        ..Default::default()
+    };
+    let allocator = &mir.allocator;
+    let temp_base = allocator
+        .reserved
+        .iter()
+        .map(|it| it.end)
+        .fold(allocator.allocated_bytes, usize::max);
+    let mut networks = Vec::with_capacity(mir.networks.len());
+    let mut transformer = Transformer::new(&mut lir, temp_base as u16);
+    for network in &mir.networks {
+        for action in &mir.actions[network.actions.clone()] {
+            transformer.action(action)?;
+        }
+        transformer.terminate();
+        networks.push(CNetwork {
+            title: network.title.clone(),
+            comments: network.comments.clone(),
+            statements: std::mem::take(&mut transformer.statements),
+        });
+    }
+    lir.variables = remove_dead_stores(&mut networks);
+    lir.networks = networks;
+    lir.data_blocks.sort_by_key(|it| it.0);
+    lir.io
+        .sort_by_key(|it| (st::area(it.r#type) == Area::Output, it.ptr, it.bit));
+    Ok(lir)
+}
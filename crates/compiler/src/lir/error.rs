@@ -1,3 +1,10 @@
+use std::rc::Rc;
+
+use crate::{
+    error::Reason,
+    util::{Quote, Source},
+};
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
@@ -6,4 +13,23 @@ pub enum Error {
     NonBitAddress,
     NonByteAddress,
     NonCounterAddress,
+    /// The target language has no equivalent for the instruction or operand
+    Unsupported(&'static str),
+}
+
+impl Error {
+    pub fn message(&self) -> &str {
+        match self {
+            Self::InvalidStack => "the nesting of the logic operations is invalid",
+            Self::NonBitAddress => "expected a bit address",
+            Self::NonByteAddress => "expected a byte, word or double word address",
+            Self::NonCounterAddress => "expected a counter address",
+            Self::Unsupported(message) => message,
+        }
+    }
+
+    /// Attach the statement the error was caused by
+    pub fn at(self, source: Rc<Source>, quote: Quote) -> crate::error::Error {
+        crate::error::Error::new(source, quote, Reason::Lir(self))
+    }
 }
@@ -0,0 +1,286 @@
+//! Vendor-neutral IEC 61131-3 Instruction List
+//!
+//! Operands are declared as variables of the program in the same way as for Structured Text.
+
+use std::{io::Write, rc::Rc};
+
+use super::{
+    error::{Error, Result},
+    s7::{S7Address, S7Constant, S7DataType},
+    scl::{SclBlock, SclInstance},
+    st::{
+        declare_variable, type_temps, variable_name, write_comment, StMemory, StVariable, WriteSt,
+    },
+};
+
+pub mod transformer;
+
+/// Thing that can be written to a [Write] in IL-form
+pub trait WriteIl {
+    fn write_il(&self, out: &mut impl Write) -> std::io::Result<()>;
+}
+
+#[derive(Debug, Default)]
+pub struct IlLir {
+    /// Instances of the called function blocks
+    pub instances: Vec<SclInstance>,
+    /// Block temporaries of the calls, declared with the variables
+    pub temps: Vec<(S7Address, S7DataType)>,
+    pub networks: Vec<IlNetwork>,
+}
+
+#[derive(Debug, Default)]
+pub struct IlNetwork {
+    pub title: Rc<str>,
+    pub comments: Vec<Rc<str>>,
+    pub instructions: Vec<IlInstruction>,
+}
+
+impl WriteIl for IlNetwork {
+    fn write_il(&self, out: &mut impl Write) -> std::io::Result<()> {
+        write_comment(out, &self.title)?;
+        for comment in &self.comments {
+            write_comment(out, comment)?;
+        }
+        for instruction in &self.instructions {
+            write!(out, "    ")?;
+            instruction.write_il(out)?;
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
+
+/// Operator of `AND`, `OR` and `XOR` and their brackets
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IlLogic {
+    And,
+    Or,
+    Xor,
+}
+
+impl IlLogic {
+    fn il_operator(self, negated: bool, bracket: bool) -> &'static str {
+        match (self, negated, bracket) {
+            (Self::And, false, false) => "AND",
+            (Self::And, true, false) => "ANDN",
+            (Self::And, false, true) => "AND(",
+            (Self::And, true, true) => "ANDN(",
+            (Self::Or, false, false) => "OR",
+            (Self::Or, true, false) => "ORN",
+            (Self::Or, false, true) => "OR(",
+            (Self::Or, true, true) => "ORN(",
+            (Self::Xor, false, false) => "XOR",
+            (Self::Xor, true, false) => "XORN",
+            (Self::Xor, false, true) => "XOR(",
+            (Self::Xor, true, true) => "XORN(",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum IlInstruction {
+    /// `LD op`, `LDN op`
+    Load { operand: IlOperand, negated: bool },
+    /// `ST op`
+    Store { operand: IlOperand },
+    /// `S op`
+    Set { operand: IlOperand },
+    /// `R op`
+    Reset { operand: IlOperand },
+    /// `AND op`, `ORN op`
+    Logic {
+        op: IlLogic,
+        negated: bool,
+        operand: IlOperand,
+    },
+    /// `AND(`, `ORN(`, the bracket starts with `LD`
+    Start { op: IlLogic, negated: bool },
+    /// `)`
+    End,
+    /// `NOT`
+    Not,
+    /// `SHL 3`, `BCD_TO_INT`, the current result is the first operand
+    Function {
+        name: &'static str,
+        operand: Option<IlOperand>,
+    },
+    /// `CAL TON_DB100(IN := I0_0)`, `FC10(start := M0_0)`
+    Call(Rc<IlCall>),
+}
+
+impl IlInstruction {
+    pub fn il_operator(&self) -> &'static str {
+        match self {
+            Self::Load { negated: false, .. } => "LD",
+            Self::Load { negated: true, .. } => "LDN",
+            Self::Store { .. } => "ST",
+            Self::Set { .. } => "S",
+            Self::Reset { .. } => "R",
+            Self::Logic { op, negated, .. } => op.il_operator(*negated, false),
+            Self::Start { op, negated } => op.il_operator(*negated, true),
+            Self::End => ")",
+            Self::Not => "NOT",
+            Self::Function { name, .. } => name,
+            Self::Call(call) => match call.target {
+                IlCallTarget::Instance(_) => "CAL",
+                IlCallTarget::Function(_) => "",
+            },
+        }
+    }
+
+    pub fn operand(&self) -> Option<&IlOperand> {
+        match self {
+            Self::Load { operand, .. }
+            | Self::Store { operand }
+            | Self::Set { operand }
+            | Self::Reset { operand }
+            | Self::Logic { operand, .. } => Some(operand),
+            Self::Function { operand, .. } => operand.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl WriteIl for IlInstruction {
+    fn write_il(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let operator = self.il_operator();
+        write!(out, "{operator}")?;
+        if let Some(operand) = self.operand() {
+            write!(out, " ")?;
+            operand.write_il(out)?;
+        }
+        if let Self::Call(call) = self {
+            if !operator.is_empty() {
+                write!(out, " ")?;
+            }
+            call.write_il(out)?;
+        }
+        Ok(())
+    }
+}
+
+/// Formal call of a function or function block instance
+#[derive(Debug)]
+pub struct IlCall {
+    pub target: IlCallTarget,
+    pub params: Vec<IlParam>,
+}
+
+impl WriteIl for IlCall {
+    fn write_il(&self, out: &mut impl Write) -> std::io::Result<()> {
+        match &self.target {
+            IlCallTarget::Function(block) => block.write_st(out)?,
+            IlCallTarget::Instance(instance) => write!(out, "{instance}")?,
+        }
+        write!(out, "(")?;
+        for (i, param) in self.params.iter().enumerate() {
+            if i != 0 {
+                write!(out, ", ")?;
+            }
+            let assign = if param.output { "=>" } else { ":=" };
+            write!(out, "{} {assign} ", param.name)?;
+            param.value.write_il(out)?;
+        }
+        write!(out, ")")
+    }
+}
+
+#[derive(Debug)]
+pub enum IlCallTarget {
+    Function(SclBlock),
+    Instance(Rc<str>),
+}
+
+/// `NAME := value`, outputs are written as `NAME => value`
+#[derive(Debug)]
+pub struct IlParam {
+    pub name: Rc<str>,
+    pub value: IlOperand,
+    pub output: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum IlOperand {
+    Constant(S7Constant),
+    /// `I0_0`
+    Address(S7Address),
+    /// `M4_0`, memory reserved by the allocator
+    Allocated(S7Address),
+}
+
+impl WriteIl for IlOperand {
+    fn write_il(&self, out: &mut impl Write) -> std::io::Result<()> {
+        match self {
+            Self::Constant(constant) => constant.write_st(out),
+            Self::Address(addr) | Self::Allocated(addr) => {
+                write!(out, "{}", variable_name(*addr))
+            }
+        }
+    }
+}
+
+/// `PROGRAM` with its variables
+#[derive(Debug)]
+pub struct IlSource<'a> {
+    pub name: String,
+    pub variables: Vec<StVariable>,
+    pub lir: &'a IlLir,
+}
+
+impl<'a> IlSource<'a> {
+    /// Declare every operand of the instructions
+    pub fn new(name: impl Into<String>, lir: &'a IlLir, memory: StMemory) -> Result<Self> {
+        let mut variables = Vec::new();
+        for instruction in lir.networks.iter().flat_map(|it| &it.instructions) {
+            let operands = match instruction {
+                IlInstruction::Call(call) => call.params.iter().map(|it| &it.value).collect(),
+                instruction => Vec::from_iter(instruction.operand()),
+            };
+            for operand in operands {
+                match *operand {
+                    IlOperand::Constant(S7Constant::Pointer(_)) => {
+                        return Err(Error::Unsupported("pointers are specific to STEP 7"))
+                    }
+                    IlOperand::Constant(_) => {}
+                    IlOperand::Address(addr) => declare_variable(&mut variables, addr, None)?,
+                    IlOperand::Allocated(addr) => {
+                        declare_variable(&mut variables, addr, Some(memory))?
+                    }
+                }
+            }
+        }
+        type_temps(&mut variables, &lir.temps);
+        Ok(Self {
+            name: name.into(),
+            variables,
+            lir,
+        })
+    }
+}
+
+impl WriteIl for IlSource<'_> {
+    fn write_il(&self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(out, "PROGRAM {}", self.name)?;
+        writeln!(out, "VAR")?;
+        for variable in &self.variables {
+            write!(out, "    {}", variable.name)?;
+            if let Some(location) = &variable.location {
+                write!(out, " AT {location}")?;
+            }
+            writeln!(out, " : {};", variable.r#type)?;
+        }
+        for instance in &self.lir.instances {
+            write!(out, "    {} : ", instance.name)?;
+            instance.r#type.write_st(out)?;
+            writeln!(out, ";")?;
+        }
+        writeln!(out, "END_VAR")?;
+        for network in &self.lir.networks {
+            writeln!(out)?;
+            network.write_il(out)?;
+        }
+        writeln!(out)?;
+        writeln!(out, "END_PROGRAM")
+    }
+}
@@ -0,0 +1,252 @@
+//! Lowers the statements of the SCL decompiler into IL
+//!
+//! IL shares the decompiler with the structured languages, so the logic string of the AWL is
+//! only interpreted once. Expressions are written from left to right, operands that are not
+//! single operands become brackets.
+
+use crate::{
+    lir::{
+        error::{Error, Result},
+        pop_stack, push_stack,
+        s7::S7Constant,
+        scl::{self, SclBlock, SclCall, SclCallTarget, SclExpr, SclFunction, SclOp, SclStatement},
+    },
+    mir::Mir,
+};
+
+use super::{IlCall, IlCallTarget, IlInstruction, IlLir, IlLogic, IlNetwork, IlOperand, IlParam};
+
+#[derive(Debug, Default)]
+struct Transformer {
+    instructions: Vec<IlInstruction>,
+}
+
+impl Transformer {
+    fn push(&mut self, instruction: IlInstruction) {
+        self.instructions.push(instruction);
+    }
+
+    fn statement(&mut self, statement: &SclStatement) -> Result<()> {
+        match statement {
+            SclStatement::Assign { target, value } => {
+                let operand = target_operand(target);
+                self.value(value)?;
+                self.push(IlInstruction::Store { operand });
+            }
+            // `S` and `R` are the only conditional statements of the decompiler
+            SclStatement::If { cond, then } => {
+                let [SclStatement::Assign {
+                    target,
+                    value: SclExpr::Bool(value),
+                }] = then.as_slice()
+                else {
+                    return Err(Error::Unsupported(
+                        "IL has no conditional statements besides `S` and `R`",
+                    ));
+                };
+                let operand = target_operand(target);
+                self.value(cond)?;
+                self.push(match value {
+                    true => IlInstruction::Set { operand },
+                    false => IlInstruction::Reset { operand },
+                });
+            }
+            SclStatement::Call { ret_val, call } => self.call(ret_val.as_ref(), call)?,
+        }
+        Ok(())
+    }
+
+    /// Load the value as current result
+    fn value(&mut self, value: &SclExpr) -> Result<()> {
+        if let Some(operand) = single(value)? {
+            self.push(IlInstruction::Load {
+                operand,
+                negated: false,
+            });
+            return Ok(());
+        }
+        match value {
+            SclExpr::Not(value) => match single(value)? {
+                Some(operand) => self.push(IlInstruction::Load {
+                    operand,
+                    negated: true,
+                }),
+                None => {
+                    self.value(value)?;
+                    self.push(IlInstruction::Not);
+                }
+            },
+            SclExpr::Binary(binary) => {
+                let op = match binary.op {
+                    SclOp::And => IlLogic::And,
+                    SclOp::Or => IlLogic::Or,
+                    SclOp::Xor => IlLogic::Xor,
+                    // Comparisons only test the value of S5 counters
+                    SclOp::NotEqual => {
+                        return Err(Error::Unsupported(
+                            "S5 counter values have no IL equivalent",
+                        ))
+                    }
+                };
+                self.value(&binary.left)?;
+                self.logic(op, &binary.right)?;
+            }
+            SclExpr::Call(call) => self.function(call)?,
+            _ => unreachable!("Single operand"),
+        }
+        Ok(())
+    }
+
+    /// Combine the current result with the value, values that are no operand are bracketed
+    fn logic(&mut self, op: IlLogic, value: &SclExpr) -> Result<()> {
+        let (negated, value) = match value {
+            SclExpr::Not(value) => (true, &**value),
+            value => (false, value),
+        };
+        if let Some(operand) = single(value)? {
+            self.push(IlInstruction::Logic {
+                op,
+                negated,
+                operand,
+            });
+            return Ok(());
+        }
+        self.push(IlInstruction::Start { op, negated });
+        self.value(value)?;
+        self.push(IlInstruction::End);
+        Ok(())
+    }
+
+    /// Standard function of the current result, shifts take the amount as operand
+    fn function(&mut self, call: &SclCall) -> Result<()> {
+        let SclCallTarget::Function(function) = call.target else {
+            unreachable!("Blocks are called by statements");
+        };
+        let [value, amount @ ..] = call.params.as_slice() else {
+            return Err(Error::InvalidStack);
+        };
+        self.value(&value.value)?;
+        let operand = match amount {
+            [] => None,
+            [amount] => Some(single(&amount.value)?.ok_or(Error::Unsupported(
+                "the shift amount must be a single operand",
+            ))?),
+            _ => return Err(Error::InvalidStack),
+        };
+        self.push(IlInstruction::Function {
+            name: il_name(function),
+            operand,
+        });
+        Ok(())
+    }
+
+    fn call(&mut self, ret_val: Option<&SclExpr>, call: &SclCall) -> Result<()> {
+        let target = match &call.target {
+            SclCallTarget::Block(SclBlock::System(_)) => {
+                return Err(Error::Unsupported(
+                    "system functions are specific to STEP 7",
+                ));
+            }
+            SclCallTarget::Block(block) => IlCallTarget::Function(block.clone()),
+            SclCallTarget::Instance(instance) => IlCallTarget::Instance(instance.clone()),
+            SclCallTarget::Function(_) => unreachable!("Functions are part of expressions"),
+        };
+        let mut params = Vec::with_capacity(call.params.len());
+        for param in &call.params {
+            let Some(value) = single(&param.value)? else {
+                return Err(Error::Unsupported(
+                    "block parameters of IL must be single operands",
+                ));
+            };
+            params.push(IlParam {
+                name: param.name.clone().expect("Named block parameter"),
+                value,
+                output: param.output,
+            });
+        }
+        self.push(IlInstruction::Call(IlCall { target, params }.into()));
+        if let Some(ret_val) = ret_val {
+            let operand = target_operand(ret_val);
+            self.push(IlInstruction::Store { operand });
+        }
+        Ok(())
+    }
+}
+
+/// Operand of a single `LD`, `None` for values computed from several operands
+fn single(value: &SclExpr) -> Result<Option<IlOperand>> {
+    match value {
+        SclExpr::Bool(value) => Ok(Some(IlOperand::Constant(S7Constant::Bool(*value)))),
+        SclExpr::Constant(constant) => Ok(Some(IlOperand::Constant(*constant))),
+        SclExpr::Address(addr) => Ok(Some(IlOperand::Address(*addr))),
+        SclExpr::Allocated(addr) => Ok(Some(IlOperand::Allocated(*addr))),
+        SclExpr::Any(_) => Err(Error::Unsupported("ANY pointers are specific to STEP 7")),
+        SclExpr::Member { .. } => Err(Error::Unsupported(
+            "S5 counter values have no IL equivalent",
+        )),
+        SclExpr::Not(_) | SclExpr::Binary(_) | SclExpr::Call(_) => Ok(None),
+    }
+}
+
+/// Operand of `ST`, `S` and `R`
+fn target_operand(target: &SclExpr) -> IlOperand {
+    match target {
+        SclExpr::Address(addr) => IlOperand::Address(*addr),
+        SclExpr::Allocated(addr) => IlOperand::Allocated(*addr),
+        _ => unreachable!("Assigned operand"),
+    }
+}
+
+/// Standard function of IEC 61131-3, conversions from `REAL` round to the nearest integer
+fn il_name(function: SclFunction) -> &'static str {
+    match function {
+        SclFunction::Shl => "SHL",
+        SclFunction::Shr => "SHR",
+        SclFunction::Rol => "ROL",
+        SclFunction::Ror => "ROR",
+        SclFunction::BcdToInt => "BCD_TO_INT",
+        SclFunction::IntToBcd => "INT_TO_BCD",
+        SclFunction::IntToDInt => "INT_TO_DINT",
+        SclFunction::DIntToReal => "DINT_TO_REAL",
+        SclFunction::Round => "REAL_TO_DINT",
+        SclFunction::Trunc => "TRUNC",
+    }
+}
+
+/// Brackets of a network, fails if more than `max_depth` brackets are open
+fn check_depth(instructions: &[IlInstruction], max_depth: usize) -> Result<()> {
+    let mut stack_depth = 0;
+    for instruction in instructions {
+        match instruction {
+            IlInstruction::Start { .. } => push_stack(&mut stack_depth, max_depth)?,
+            IlInstruction::End => pop_stack(&mut stack_depth)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Transform the networks into IL, brackets may be nested up to `max_depth` levels
+pub fn transform(mir: &Mir, max_depth: usize) -> crate::error::Result<IlLir> {
+    let scl = scl::transformer::transform(mir)?;
+    let mut lir = IlLir {
+        instances: scl.instances,
+        temps: scl.temps,
+        networks: Vec::with_capacity(scl.networks.len()),
+    };
+    for (network, source) in scl.networks.into_iter().zip(&mir.networks) {
+        let mut transformer = Transformer::default();
+        network
+            .statements
+            .iter()
+            .try_for_each(|it| transformer.statement(it))
+            .and_then(|_| check_depth(&transformer.instructions, max_depth))
+            .map_err(|err| err.at(mir.source.clone(), source.quote.clone()))?;
+        lir.networks.push(IlNetwork {
+            title: network.title,
+            comments: network.comments,
+            instructions: transformer.instructions,
+        });
+    }
+    Ok(lir)
+}
@@ -0,0 +1,477 @@
+//! Allen-Bradley ControlLogix ladder rungs and importable L5X routines
+//!
+//! Uses the statements decompiled for SCL, operands become controller-scope tags.
+
+use std::{io::Write, rc::Rc};
+
+use super::{
+    error::{Error, Result},
+    s7::{S7Address, S7Constant},
+    scl::{
+        SclBlock, SclCall, SclCallTarget, SclExpr, SclFunction, SclInstance, SclLir, SclOp,
+        SclStatement,
+    },
+    st::variable_name,
+};
+
+/// Output of the `logix` backend
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogixFormat {
+    /// `RSLogix5000Content` with the routine and its tags
+    L5x,
+    /// One neutral rung per line
+    Text,
+}
+
+/// Controller-scope tag, overlapping operands no longer share their bits
+#[derive(Debug, PartialEq, Eq)]
+pub struct LogixTag {
+    pub name: String,
+    /// `BOOL`, `DINT`, `TIMER`, `COUNTER`
+    pub r#type: &'static str,
+}
+
+/// `XIC(I0_0)OTE(Q0_0);`
+#[derive(Debug)]
+pub struct LogixRung {
+    /// Title and comments of the network on its first rung
+    pub comment: Option<String>,
+    pub text: String,
+}
+
+/// Ladder routine of a program
+#[derive(Debug)]
+pub struct LogixRoutine {
+    pub name: String,
+    pub program: String,
+    pub tags: Vec<LogixTag>,
+    pub rungs: Vec<LogixRung>,
+}
+
+impl LogixRoutine {
+    /// Convert every statement into rungs, fails on STEP 7 specific operands and blocks
+    pub fn new(name: impl Into<String>, lir: &SclLir) -> Result<Self> {
+        let mut builder = Rungs {
+            instances: &lir.instances,
+            tags: Vec::new(),
+            rungs: Vec::new(),
+        };
+        for instance in &lir.instances {
+            let r#type = match instance_kind(instance)? {
+                InstanceKind::Timer(_) => "TIMER",
+                InstanceKind::Counter => "COUNTER",
+            };
+            builder.tags.push(LogixTag {
+                name: instance.name.to_string(),
+                r#type,
+            });
+        }
+        for network in &lir.networks {
+            let start = builder.rungs.len();
+            for statement in &network.statements {
+                builder.statement(statement)?;
+            }
+            let mut comment = network.title.to_string();
+            for line in &network.comments {
+                comment.push('\n');
+                comment.push_str(line);
+            }
+            if let Some(rung) = builder.rungs.get_mut(start) {
+                rung.comment = Some(comment);
+            }
+        }
+        Ok(Self {
+            name: name.into(),
+            program: "MainProgram".into(),
+            tags: builder.tags,
+            rungs: builder.rungs,
+        })
+    }
+
+    /// Write the rungs as neutral text, one rung per line
+    pub fn write_text(&self, out: &mut impl Write) -> std::io::Result<()> {
+        for rung in &self.rungs {
+            writeln!(out, "{}", rung.text)?;
+        }
+        Ok(())
+    }
+
+    /// Write an L5X file importable as routine, the tags are created in the controller scope
+    pub fn write_l5x(&self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(
+            out,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>"
+        )?;
+        writeln!(
+            out,
+            "<RSLogix5000Content SchemaRevision=\"1.0\" SoftwareRevision=\"32.00\" TargetName=\"{}\" TargetType=\"Routine\" TargetSubType=\"RLL\" ContainsContext=\"true\">",
+            self.name
+        )?;
+        writeln!(out, "<Controller Use=\"Context\" Name=\"FPP\">")?;
+        writeln!(out, "<Tags Use=\"Context\">")?;
+        for tag in &self.tags {
+            let radix = match tag.r#type {
+                "BOOL" | "DINT" => " Radix=\"Decimal\"",
+                _ => "",
+            };
+            writeln!(
+                out,
+                "<Tag Name=\"{}\" TagType=\"Base\" DataType=\"{}\"{radix} ExternalAccess=\"Read/Write\"/>",
+                tag.name, tag.r#type
+            )?;
+        }
+        writeln!(out, "</Tags>")?;
+        writeln!(out, "<Programs Use=\"Context\">")?;
+        writeln!(out, "<Program Use=\"Context\" Name=\"{}\">", self.program)?;
+        writeln!(out, "<Routines Use=\"Context\">")?;
+        writeln!(
+            out,
+            "<Routine Use=\"Target\" Name=\"{}\" Type=\"RLL\">",
+            self.name
+        )?;
+        writeln!(out, "<RLLContent>")?;
+        for (i, rung) in self.rungs.iter().enumerate() {
+            writeln!(out, "<Rung Number=\"{i}\" Type=\"N\">")?;
+            if let Some(comment) = &rung.comment {
+                writeln!(out, "<Comment>")?;
+                writeln!(out, "<![CDATA[{}]]>", cdata(comment))?;
+                writeln!(out, "</Comment>")?;
+            }
+            writeln!(out, "<Text>")?;
+            writeln!(out, "<![CDATA[{}]]>", rung.text)?;
+            writeln!(out, "</Text>")?;
+            writeln!(out, "</Rung>")?;
+        }
+        writeln!(out, "</RLLContent>")?;
+        writeln!(out, "</Routine>")?;
+        writeln!(out, "</Routines>")?;
+        writeln!(out, "</Program>")?;
+        writeln!(out, "</Programs>")?;
+        writeln!(out, "</Controller>")?;
+        writeln!(out, "</RSLogix5000Content>")
+    }
+}
+
+/// Logix equivalent of an IEC instance
+enum InstanceKind {
+    /// `TON` or `TOF`
+    Timer(&'static str),
+    Counter,
+}
+
+fn instance_kind(instance: &SclInstance) -> Result<InstanceKind> {
+    match instance.r#type {
+        SclBlock::System(name @ ("TON" | "TOF")) => Ok(InstanceKind::Timer(name)),
+        SclBlock::System("CTU" | "CTD" | "CTUD") => Ok(InstanceKind::Counter),
+        _ => Err(Error::Unsupported(
+            "Logix only provides TON, TOF and counter instances",
+        )),
+    }
+}
+
+/// Builder of the rungs and tags of a routine
+struct Rungs<'a> {
+    instances: &'a [SclInstance],
+    tags: Vec<LogixTag>,
+    rungs: Vec<LogixRung>,
+}
+
+impl Rungs<'_> {
+    fn push(&mut self, text: String) {
+        self.rungs.push(LogixRung {
+            comment: None,
+            text: format!("{text};"),
+        });
+    }
+
+    fn statement(&mut self, statement: &SclStatement) -> Result<()> {
+        match statement {
+            SclStatement::Assign { target, value } => {
+                let bit = is_bit(target);
+                let target = self.operand(target)?;
+                if bit {
+                    let cond = self.condition(value, false)?;
+                    self.push(format!("{cond}OTE({target})"));
+                } else {
+                    let output = self.word(value, &target)?;
+                    self.push(output);
+                }
+            }
+            SclStatement::If { cond, then } => {
+                let cond = self.condition(cond, false)?;
+                for statement in then {
+                    let SclStatement::Assign {
+                        target,
+                        value: SclExpr::Bool(value),
+                    } = statement
+                    else {
+                        return Err(Error::Unsupported(
+                            "conditional statements other than set and reset have no rung",
+                        ));
+                    };
+                    let target = self.operand(target)?;
+                    let output = if *value { "OTL" } else { "OTU" };
+                    self.push(format!("{cond}{output}({target})"));
+                }
+            }
+            SclStatement::Call { ret_val, call } => {
+                if ret_val.is_some() {
+                    return Err(Error::Unsupported(
+                        "system functions are specific to STEP 7",
+                    ));
+                }
+                self.call(call)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Timer and counter instructions, outputs of timers are copied in their own rungs
+    fn call(&mut self, call: &SclCall) -> Result<()> {
+        let SclCallTarget::Instance(name) = &call.target else {
+            return Err(Error::Unsupported(
+                "Logix only provides TON, TOF and counter instances",
+            ));
+        };
+        let instance = self
+            .instances
+            .iter()
+            .find(|it| it.name == *name)
+            .expect("Declared instance");
+        let param = |name: &str| {
+            call.params
+                .iter()
+                .find(|it| it.name.as_deref() == Some(name))
+                .map(|it| &it.value)
+        };
+        match instance_kind(instance)? {
+            InstanceKind::Timer(instruction) => {
+                let preset = match param("PT") {
+                    Some(SclExpr::Constant(S7Constant::Time(ms))) => ms.to_string(),
+                    Some(value) => {
+                        let value = self.operand(value)?;
+                        self.push(format!("MOV({value},{name}.PRE)"));
+                        "0".into()
+                    }
+                    None => "0".into(),
+                };
+                let cond = match param("IN") {
+                    Some(value) => self.condition(value, false)?,
+                    None => String::new(),
+                };
+                self.push(format!("{cond}{instruction}({name},{preset},0)"));
+                if let Some(value) = param("Q") {
+                    let target = self.operand(value)?;
+                    self.push(format!("XIC({name}.DN)OTE({target})"));
+                }
+                if let Some(value) = param("ET") {
+                    let target = self.operand(value)?;
+                    self.push(format!("MOV({name}.ACC,{target})"));
+                }
+            }
+            InstanceKind::Counter => {
+                let input = call.params.first().expect("Counter input");
+                let cond = self.condition(&input.value, false)?;
+                let output = match input.name.as_deref() {
+                    Some("CU") => format!("CTU({name},0,0)"),
+                    Some("CD") => format!("CTD({name},0,0)"),
+                    Some("R") => format!("RES({name})"),
+                    _ => {
+                        let value = param("PV").expect("Counter value");
+                        format!("MOV({},{name}.ACC)", self.operand(value)?)
+                    }
+                };
+                self.push(format!("{cond}{output}"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Input instructions of a boolean expression, `negated` is pushed down to the operands
+    fn condition(&mut self, expr: &SclExpr, negated: bool) -> Result<String> {
+        Ok(match expr {
+            SclExpr::Bool(value) if *value != negated => String::new(),
+            SclExpr::Bool(_) => "AFI()".into(),
+            SclExpr::Address(_) | SclExpr::Allocated(_) | SclExpr::Member { .. } => {
+                let instruction = if negated { "XIO" } else { "XIC" };
+                format!("{instruction}({})", self.operand(expr)?)
+            }
+            SclExpr::Not(value) => self.condition(value, !negated)?,
+            SclExpr::Binary(binary) => {
+                let left = &binary.left;
+                let right = &binary.right;
+                match (binary.op, negated) {
+                    (SclOp::And, false) | (SclOp::Or, true) => {
+                        let left = self.condition(left, negated)?;
+                        let right = self.condition(right, negated)?;
+                        format!("{left}{right}")
+                    }
+                    (SclOp::Or, false) | (SclOp::And, true) => {
+                        let left = self.condition(left, negated)?;
+                        let right = self.condition(right, negated)?;
+                        branch(&[left, right])
+                    }
+                    (SclOp::Xor, _) => {
+                        let legs = [
+                            self.condition(left, false)? + &self.condition(right, !negated)?,
+                            self.condition(left, true)? + &self.condition(right, negated)?,
+                        ];
+                        branch(&legs)
+                    }
+                    (SclOp::NotEqual, _) => {
+                        let instruction = if negated { "EQU" } else { "NEQ" };
+                        let left = self.operand(left)?;
+                        let right = self.operand(right)?;
+                        format!("{instruction}({left},{right})")
+                    }
+                }
+            }
+            SclExpr::Constant(_) | SclExpr::Any(_) | SclExpr::Call(_) => {
+                return Err(Error::Unsupported("expected a boolean operand"))
+            }
+        })
+    }
+
+    /// Output instructions computing a word into `target`, a nested operation is computed
+    /// into `target` first
+    fn word(&mut self, value: &SclExpr, target: &str) -> Result<String> {
+        let (instruction, operands) = match value {
+            SclExpr::Not(value) => ("NOT", vec![&**value]),
+            SclExpr::Binary(binary) => {
+                let instruction = match binary.op {
+                    SclOp::And => "AND",
+                    SclOp::Or => "OR",
+                    SclOp::Xor => "XOR",
+                    SclOp::NotEqual => unreachable!("Boolean comparison"),
+                };
+                (instruction, vec![&binary.left, &binary.right])
+            }
+            SclExpr::Call(SclCall {
+                target: SclCallTarget::Function(function),
+                params,
+            }) => {
+                let instruction = match function {
+                    SclFunction::BcdToInt => "FRD",
+                    SclFunction::IntToBcd => "TOD",
+                    SclFunction::IntToDInt | SclFunction::DIntToReal | SclFunction::Round => "MOV",
+                    SclFunction::Trunc => "TRN",
+                    SclFunction::Shl | SclFunction::Shr | SclFunction::Rol | SclFunction::Ror => {
+                        return Err(Error::Unsupported("Logix has no shift instructions"))
+                    }
+                };
+                (instruction, params.iter().map(|it| &it.value).collect())
+            }
+            value => ("MOV", vec![value]),
+        };
+        let mut text = String::new();
+        let mut names = Vec::with_capacity(operands.len());
+        for operand in operands {
+            if !matches!(
+                operand,
+                SclExpr::Not(_) | SclExpr::Binary(_) | SclExpr::Call(_)
+            ) {
+                names.push(self.operand(operand)?);
+                continue;
+            }
+            if !text.is_empty() {
+                return Err(Error::Unsupported(
+                    "Logix instructions take a single operand, not an expression",
+                ));
+            }
+            text = self.word(operand, target)?;
+            names.push(target.to_string());
+        }
+        if !text.is_empty() && names.iter().filter(|it| *it == target).count() > 1 {
+            return Err(Error::Unsupported(
+                "a word operand is read after it was used to compute its own value",
+            ));
+        }
+        // Conversions of a value already computed into `target` are implied by its type
+        if text.is_empty() || instruction != "MOV" || names != [target] {
+            text.push_str(&format!("{instruction}({},{target})", names.join(",")));
+        }
+        Ok(text)
+    }
+
+    /// Tag or immediate value of a single operand
+    fn operand(&mut self, expr: &SclExpr) -> Result<String> {
+        match expr {
+            SclExpr::Bool(value) => Ok(u8::from(*value).to_string()),
+            SclExpr::Constant(constant) => constant_text(*constant),
+            SclExpr::Address(addr) | SclExpr::Allocated(addr) => Ok(self.declare(*addr)),
+            SclExpr::Member { instance, member } => Ok(member_text(instance, member)),
+            SclExpr::Any(_) => Err(Error::Unsupported("ANY pointers are specific to STEP 7")),
+            SclExpr::Not(_) | SclExpr::Binary(_) | SclExpr::Call(_) => Err(Error::Unsupported(
+                "Logix instructions take a single operand, not an expression",
+            )),
+        }
+    }
+
+    fn declare(&mut self, addr: S7Address) -> String {
+        let name = variable_name(addr);
+        if self.tags.iter().all(|it| it.name != name) {
+            let r#type = if addr.is_bit_address() {
+                "BOOL"
+            } else {
+                "DINT"
+            };
+            self.tags.push(LogixTag {
+                name: name.clone(),
+                r#type,
+            });
+        }
+        name
+    }
+}
+
+fn is_bit(expr: &SclExpr) -> bool {
+    match expr {
+        SclExpr::Address(addr) | SclExpr::Allocated(addr) => addr.is_bit_address(),
+        _ => false,
+    }
+}
+
+/// `Counter1.ACC`, IEC outputs are mapped to the status bits and accumulator
+fn member_text(instance: &Rc<str>, member: &str) -> String {
+    let member = match member {
+        "Q" => "DN",
+        "IN" => "EN",
+        "PT" | "PV" => "PRE",
+        _ => "ACC",
+    };
+    format!("{instance}.{member}")
+}
+
+/// `[XIC(a) ,XIC(b) ]`, a leg without instructions is always true
+fn branch(legs: &[String]) -> String {
+    let mut text = String::from("[");
+    for (i, leg) in legs.iter().enumerate() {
+        if i != 0 {
+            text.push(',');
+        }
+        text.push_str(leg);
+        text.push(' ');
+    }
+    text.push(']');
+    text
+}
+
+fn constant_text(constant: S7Constant) -> Result<String> {
+    Ok(match constant {
+        S7Constant::Byte(value) => format!("16#{value:02x}"),
+        S7Constant::Word(value) => format!("16#{value:04x}"),
+        S7Constant::DWord(value) => format!("16#{value:08x}"),
+        S7Constant::Int(value) => value.to_string(),
+        S7Constant::DInt(value) | S7Constant::Time(value) => value.to_string(),
+        S7Constant::Real(value) => format!("{value:?}"),
+        S7Constant::Bool(value) => u8::from(value).to_string(),
+        S7Constant::Pointer(_) => {
+            return Err(Error::Unsupported("pointers are specific to STEP 7"))
+        }
+    })
+}
+
+/// Split the end marker of a CDATA section, which may not appear in its text
+fn cdata(text: &str) -> String {
+    text.replace("]]>", "]]]]><![CDATA[>")
+}
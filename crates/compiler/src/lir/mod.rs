@@ -1,2 +1,31 @@
+pub mod c;
 pub mod error;
+pub mod il;
+pub mod logix;
+pub mod plcopen;
+pub mod rust;
 pub mod s7;
+pub mod scl;
+pub mod simatic;
+pub mod st;
+pub mod verilog;
+
+use error::{Error, Result};
+
+/// Open a bracket, fails if more than `limit` brackets are open
+pub(crate) fn push_stack(stack_depth: &mut usize, limit: usize) -> Result<()> {
+    if *stack_depth >= limit {
+        return Err(Error::InvalidStack);
+    }
+    *stack_depth += 1;
+    Ok(())
+}
+
+/// Close a bracket, fails if none is open
+pub(crate) fn pop_stack(stack_depth: &mut usize) -> Result<()> {
+    if *stack_depth == 0 {
+        return Err(Error::InvalidStack);
+    }
+    *stack_depth -= 1;
+    Ok(())
+}
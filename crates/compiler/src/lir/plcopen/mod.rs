@@ -0,0 +1,613 @@
+//! PLCopen XML (TC6) interchange format for TwinCAT, CODESYS and other IEC tools
+
+use std::{
+    io::Write,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{
+    error::{Error, Result},
+    scl::{SclCall, SclCallTarget, SclExpr, SclOp, SclStatement},
+    st::{StSource, WriteSt},
+};
+
+/// Language of the `pou` body
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlcopenBody {
+    /// Structured Text
+    St,
+    /// Function Block Diagram, one network per statement
+    Fbd,
+}
+
+/// `project` with a single program
+#[derive(Debug)]
+pub struct PlcopenProject<'a> {
+    /// `creationDateTime` of the file header
+    pub created: String,
+    pub body: PlcopenBody,
+    pub source: &'a StSource<'a>,
+    /// Elements of the FBD body
+    fbd: Vec<FbdElement>,
+}
+
+impl<'a> PlcopenProject<'a> {
+    pub fn new(source: &'a StSource<'a>, body: PlcopenBody) -> Result<Self> {
+        let mut fbd = Fbd::new(source);
+        if body == PlcopenBody::Fbd {
+            for network in &source.lir.networks {
+                fbd.comment(&network.title, &network.comments);
+                for statement in &network.statements {
+                    fbd.statement(statement)?;
+                }
+            }
+        }
+        Ok(Self {
+            created: timestamp(SystemTime::now()),
+            body,
+            source,
+            fbd: fbd.elements,
+        })
+    }
+
+    fn write_interface(&self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(out, "        <interface>")?;
+        writeln!(out, "          <localVars>")?;
+        for variable in &self.source.variables {
+            write!(out, "            <variable name=\"{}\"", variable.name)?;
+            if let Some(location) = &variable.location {
+                write!(out, " address=\"{location}\"")?;
+            }
+            writeln!(out, ">")?;
+            writeln!(out, "              <type><{}/></type>", variable.r#type)?;
+            writeln!(out, "            </variable>")?;
+        }
+        for instance in &self.source.lir.instances {
+            writeln!(out, "            <variable name=\"{}\">", instance.name)?;
+            writeln!(
+                out,
+                "              <type><derived name=\"{}\"/></type>",
+                escape(&st_text(&instance.r#type))
+            )?;
+            writeln!(out, "            </variable>")?;
+        }
+        writeln!(out, "          </localVars>")?;
+        writeln!(out, "        </interface>")
+    }
+
+    fn write_body(&self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(out, "        <body>")?;
+        match self.body {
+            PlcopenBody::St => {
+                let mut text = Vec::new();
+                self.source.write_body(&mut text)?;
+                writeln!(out, "          <ST>")?;
+                write!(
+                    out,
+                    "            <xhtml xmlns=\"http://www.w3.org/1999/xhtml\">"
+                )?;
+                write!(out, "{}", escape(&String::from_utf8_lossy(&text)))?;
+                writeln!(out, "</xhtml>")?;
+                writeln!(out, "          </ST>")?;
+            }
+            PlcopenBody::Fbd => {
+                writeln!(out, "          <FBD>")?;
+                for element in &self.fbd {
+                    element.write_xml(out)?;
+                }
+                writeln!(out, "          </FBD>")?;
+            }
+        }
+        writeln!(out, "        </body>")
+    }
+
+    pub fn write_xml(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let name = escape(&self.source.name);
+        writeln!(out, "<?xml version=\"1.0\" encoding=\"utf-8\"?>")?;
+        writeln!(
+            out,
+            "<project xmlns=\"http://www.plcopen.org/xml/tc6_0201\">"
+        )?;
+        writeln!(
+            out,
+            "  <fileHeader companyName=\"\" productName=\"F++\" productVersion=\"{}\" creationDateTime=\"{}\"/>",
+            env!("CARGO_PKG_VERSION"),
+            self.created
+        )?;
+        writeln!(out, "  <contentHeader name=\"{name}\">")?;
+        writeln!(out, "    <coordinateInfo>")?;
+        for language in ["fbd", "ld", "sfc"] {
+            writeln!(
+                out,
+                "      <{language}><scaling x=\"1\" y=\"1\"/></{language}>"
+            )?;
+        }
+        writeln!(out, "    </coordinateInfo>")?;
+        writeln!(out, "  </contentHeader>")?;
+        writeln!(out, "  <types>")?;
+        writeln!(out, "    <dataTypes/>")?;
+        writeln!(out, "    <pous>")?;
+        writeln!(out, "      <pou name=\"{name}\" pouType=\"program\">")?;
+        self.write_interface(out)?;
+        self.write_body(out)?;
+        writeln!(out, "      </pou>")?;
+        writeln!(out, "    </pous>")?;
+        writeln!(out, "  </types>")?;
+        writeln!(out, "  <instances>")?;
+        writeln!(out, "    <configurations/>")?;
+        writeln!(out, "  </instances>")?;
+        writeln!(out, "</project>")
+    }
+}
+
+/// Horizontal distance between the columns of a network
+const COLUMN_WIDTH: i32 = 160;
+/// Vertical distance between the inputs of a network
+const ROW_HEIGHT: i32 = 40;
+
+/// Output connection of an element, blocks are referenced by their formal parameter
+#[derive(Clone, Debug)]
+struct Connection {
+    id: usize,
+    formal: Option<String>,
+}
+
+/// Element of a FBD body
+#[derive(Debug)]
+enum FbdElement {
+    Comment {
+        id: usize,
+        y: i32,
+        text: String,
+    },
+    InVariable {
+        id: usize,
+        position: (i32, i32),
+        expression: String,
+    },
+    OutVariable {
+        id: usize,
+        position: (i32, i32),
+        expression: String,
+        input: Connection,
+    },
+    Block {
+        id: usize,
+        position: (i32, i32),
+        type_name: String,
+        instance: Option<String>,
+        inputs: Vec<(String, Connection)>,
+        outputs: Vec<String>,
+    },
+}
+
+impl FbdElement {
+    fn write_xml(&self, out: &mut impl Write) -> std::io::Result<()> {
+        match self {
+            Self::Comment { id, y, text } => {
+                writeln!(
+                    out,
+                    "            <comment localId=\"{id}\" height=\"30\" width=\"600\">"
+                )?;
+                writeln!(out, "              <position x=\"20\" y=\"{y}\"/>")?;
+                write!(out, "              <content>")?;
+                write!(
+                    out,
+                    "<xhtml xmlns=\"http://www.w3.org/1999/xhtml\">{}</xhtml>",
+                    escape(text)
+                )?;
+                writeln!(out, "</content>")?;
+                writeln!(out, "            </comment>")
+            }
+            Self::InVariable {
+                id,
+                position: (x, y),
+                expression,
+            } => {
+                writeln!(
+                    out,
+                    "            <inVariable localId=\"{id}\" height=\"20\" width=\"100\">"
+                )?;
+                writeln!(out, "              <position x=\"{x}\" y=\"{y}\"/>")?;
+                write!(out, "              <connectionPointOut>")?;
+                writeln!(
+                    out,
+                    "<relPosition x=\"100\" y=\"10\"/></connectionPointOut>"
+                )?;
+                writeln!(
+                    out,
+                    "              <expression>{}</expression>",
+                    escape(expression)
+                )?;
+                writeln!(out, "            </inVariable>")
+            }
+            Self::OutVariable {
+                id,
+                position: (x, y),
+                expression,
+                input,
+            } => {
+                writeln!(
+                    out,
+                    "            <outVariable localId=\"{id}\" height=\"20\" width=\"100\">"
+                )?;
+                writeln!(out, "              <position x=\"{x}\" y=\"{y}\"/>")?;
+                write_connection_in(out, "              ", 10, input)?;
+                writeln!(
+                    out,
+                    "              <expression>{}</expression>",
+                    escape(expression)
+                )?;
+                writeln!(out, "            </outVariable>")
+            }
+            Self::Block {
+                id,
+                position: (x, y),
+                type_name,
+                instance,
+                inputs,
+                outputs,
+            } => {
+                let pins = inputs.len().max(outputs.len()) as i32;
+                write!(
+                    out,
+                    "            <block localId=\"{id}\" typeName=\"{}\"",
+                    escape(type_name)
+                )?;
+                if let Some(instance) = instance {
+                    write!(out, " instanceName=\"{}\"", escape(instance))?;
+                }
+                writeln!(out, " height=\"{}\" width=\"100\">", 20 * (pins + 1))?;
+                writeln!(out, "              <position x=\"{x}\" y=\"{y}\"/>")?;
+                writeln!(out, "              <inputVariables>")?;
+                for (i, (formal, connection)) in inputs.iter().enumerate() {
+                    writeln!(
+                        out,
+                        "                <variable formalParameter=\"{formal}\">"
+                    )?;
+                    write_connection_in(out, "                  ", 30 + 20 * i as i32, connection)?;
+                    writeln!(out, "                </variable>")?;
+                }
+                writeln!(out, "              </inputVariables>")?;
+                writeln!(out, "              <inOutVariables/>")?;
+                writeln!(out, "              <outputVariables>")?;
+                for (i, formal) in outputs.iter().enumerate() {
+                    writeln!(
+                        out,
+                        "                <variable formalParameter=\"{formal}\">"
+                    )?;
+                    write!(out, "                  <connectionPointOut>")?;
+                    write!(out, "<relPosition x=\"100\" y=\"{}\"/>", 30 + 20 * i)?;
+                    writeln!(out, "</connectionPointOut>")?;
+                    writeln!(out, "                </variable>")?;
+                }
+                writeln!(out, "              </outputVariables>")?;
+                writeln!(out, "            </block>")
+            }
+        }
+    }
+}
+
+fn write_connection_in(
+    out: &mut impl Write,
+    indent: &str,
+    y: i32,
+    connection: &Connection,
+) -> std::io::Result<()> {
+    writeln!(out, "{indent}<connectionPointIn>")?;
+    writeln!(out, "{indent}  <relPosition x=\"0\" y=\"{y}\"/>")?;
+    write!(
+        out,
+        "{indent}  <connection refLocalId=\"{}\"",
+        connection.id
+    )?;
+    if let Some(formal) = &connection.formal {
+        write!(out, " formalParameter=\"{formal}\"")?;
+    }
+    writeln!(out, "/>")?;
+    writeln!(out, "{indent}</connectionPointIn>")
+}
+
+/// Builder of a FBD body from the statements, laid out from left to right
+#[derive(Debug)]
+struct Fbd<'a> {
+    source: &'a StSource<'a>,
+    elements: Vec<FbdElement>,
+    /// Next free row
+    row: i32,
+}
+
+impl<'a> Fbd<'a> {
+    fn new(source: &'a StSource<'a>) -> Self {
+        Self {
+            source,
+            elements: Vec::new(),
+            row: 0,
+        }
+    }
+
+    fn id(&self) -> usize {
+        self.elements.len() + 1
+    }
+
+    fn y(&self) -> i32 {
+        self.row * ROW_HEIGHT + 20
+    }
+
+    fn comment(&mut self, title: &str, comments: &[Rc<str>]) {
+        let mut text = title.to_owned();
+        for comment in comments {
+            text.push('\n');
+            text.push_str(comment.trim());
+        }
+        self.elements.push(FbdElement::Comment {
+            id: self.id(),
+            y: self.y(),
+            text,
+        });
+        self.row += 1;
+    }
+
+    fn in_variable(&mut self, column: i32, expr: &SclExpr) -> Connection {
+        let id = self.id();
+        self.elements.push(FbdElement::InVariable {
+            id,
+            position: (x(column), self.y()),
+            expression: st_text(expr),
+        });
+        self.row += 1;
+        Connection { id, formal: None }
+    }
+
+    fn out_variable(&mut self, column: i32, y: i32, expr: &SclExpr, input: Connection) {
+        self.elements.push(FbdElement::OutVariable {
+            id: self.id(),
+            position: (x(column), y),
+            expression: st_text(expr),
+            input,
+        });
+    }
+
+    /// Add a block, the rows next to its inputs and outputs are reserved
+    fn block(
+        &mut self,
+        (column, y): (i32, i32),
+        (type_name, instance): (String, Option<String>),
+        inputs: Vec<(String, Connection)>,
+        outputs: Vec<String>,
+    ) -> usize {
+        let id = self.id();
+        let pins = inputs.len().max(outputs.len()) as i32;
+        self.row = self.row.max((y - 20) / ROW_HEIGHT + pins);
+        self.elements.push(FbdElement::Block {
+            id,
+            position: (x(column), y),
+            type_name,
+            instance,
+            inputs,
+            outputs,
+        });
+        id
+    }
+
+    /// Add the expression with its result in `column`, the operands are placed to the left
+    fn expr(&mut self, column: i32, expr: &SclExpr) -> Result<Connection> {
+        let (type_name, inputs) = match expr {
+            SclExpr::Not(value) => ("NOT", vec![("IN".to_owned(), &**value)]),
+            SclExpr::Binary(binary) => {
+                let type_name = match binary.op {
+                    SclOp::And => "AND",
+                    SclOp::Or => "OR",
+                    SclOp::Xor => "XOR",
+                    SclOp::NotEqual => "NE",
+                };
+                let mut operands = Vec::new();
+                flatten(expr, binary.op, &mut operands);
+                let inputs = operands
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, operand)| (format!("IN{}", i + 1), operand))
+                    .collect();
+                (type_name, inputs)
+            }
+            SclExpr::Call(call) => return self.call(column, call, None),
+            SclExpr::Any(_) => return Err(Error::Unsupported("ANY pointers in FBD")),
+            leaf => return Ok(self.in_variable(column, leaf)),
+        };
+        let y = self.y();
+        let mut connections = Vec::with_capacity(inputs.len());
+        for (formal, input) in inputs {
+            connections.push((formal, self.expr(column - 1, input)?));
+        }
+        let id = self.block(
+            (column, y),
+            (type_name.into(), None),
+            connections,
+            vec!["OUT".into()],
+        );
+        Ok(Connection {
+            id,
+            formal: Some("OUT".into()),
+        })
+    }
+
+    /// Add a call in `column`, outputs and the result are connected to variables after it
+    fn call(
+        &mut self,
+        column: i32,
+        call: &SclCall,
+        ret_val: Option<&SclExpr>,
+    ) -> Result<Connection> {
+        let y = self.y();
+        let mut connections = Vec::new();
+        for param in call.params.iter().filter(|it| !it.output) {
+            let formal = param.name.as_deref().unwrap_or("IN").to_owned();
+            connections.push((formal, self.expr(column - 1, &param.value)?));
+        }
+        let (type_name, instance) = match &call.target {
+            SclCallTarget::Function(function) => (function.st_name().to_owned(), None),
+            SclCallTarget::Block(block) => (st_text(block), None),
+            SclCallTarget::Instance(name) => {
+                let instance = self.source.lir.instances.iter().find(|it| it.name == *name);
+                let instance = instance.expect("Declared instance");
+                (st_text(&instance.r#type), Some(name.to_string()))
+            }
+        };
+        // Functions return their result at the first output
+        let mut outputs = Vec::new();
+        if instance.is_none() {
+            outputs.push(("OUT".to_owned(), ret_val));
+        }
+        for param in call.params.iter().filter(|it| it.output) {
+            let formal = param.name.as_deref().unwrap_or("OUT").to_owned();
+            outputs.push((formal, Some(&param.value)));
+        }
+        let formals = outputs.iter().map(|(formal, _)| formal.clone()).collect();
+        let id = self.block((column, y), (type_name, instance), connections, formals);
+        for (i, (formal, value)) in outputs.into_iter().enumerate() {
+            let Some(value) = value else {
+                continue;
+            };
+            let connection = Connection {
+                id,
+                formal: Some(formal),
+            };
+            self.out_variable(column + 1, y + 20 + 20 * i as i32, value, connection);
+        }
+        Ok(Connection {
+            id,
+            formal: Some("OUT".into()),
+        })
+    }
+
+    fn statement(&mut self, statement: &SclStatement) -> Result<()> {
+        match statement {
+            SclStatement::Assign { target, value } => {
+                let y = self.y();
+                let column = depth(value) - 1;
+                let connection = self.expr(column, value)?;
+                // Block outputs start below the header of the block
+                let y = if connection.formal.is_some() {
+                    y + 20
+                } else {
+                    y
+                };
+                self.out_variable(column + 1, y, target, connection);
+            }
+            // `S` and `R` keep the target unless the condition is true
+            SclStatement::If { cond, then } => {
+                for statement in then {
+                    let SclStatement::Assign {
+                        target,
+                        value: SclExpr::Bool(value),
+                    } = statement
+                    else {
+                        return Err(Error::Unsupported("conditional statements in FBD"));
+                    };
+                    let value = match value {
+                        true => SclExpr::binary(SclOp::Or, target.clone(), cond.clone()),
+                        false => SclExpr::binary(
+                            SclOp::And,
+                            target.clone(),
+                            SclExpr::Not(Box::new(cond.clone())),
+                        ),
+                    };
+                    self.statement(&SclStatement::Assign {
+                        target: target.clone(),
+                        value,
+                    })?;
+                }
+            }
+            SclStatement::Call { ret_val, call } => {
+                let inputs = call.params.iter().filter(|it| !it.output);
+                let column = inputs.map(|it| depth(&it.value)).max().unwrap_or(0);
+                self.call(column, call, ret_val.as_ref())?;
+            }
+        }
+        self.row += 1;
+        Ok(())
+    }
+}
+
+fn x(column: i32) -> i32 {
+    20 + column * COLUMN_WIDTH
+}
+
+/// Text of an operand or block type in ST
+fn st_text(value: &impl WriteSt) -> String {
+    let mut text = Vec::new();
+    value.write_st(&mut text).expect("Write to vector");
+    String::from_utf8(text).expect("UTF-8 text")
+}
+
+/// Collect the operands of a chain of the same associative operation
+fn flatten<'a>(expr: &'a SclExpr, op: SclOp, operands: &mut Vec<&'a SclExpr>) {
+    match expr {
+        SclExpr::Binary(binary) if binary.op == op && op != SclOp::NotEqual => {
+            flatten(&binary.left, op, operands);
+            flatten(&binary.right, op, operands);
+        }
+        SclExpr::Binary(binary) if binary.op == op => {
+            operands.push(&binary.left);
+            operands.push(&binary.right);
+        }
+        _ => operands.push(expr),
+    }
+}
+
+/// Number of columns used by the expression
+fn depth(expr: &SclExpr) -> i32 {
+    match expr {
+        SclExpr::Not(value) => depth(value) + 1,
+        SclExpr::Binary(binary) => {
+            let mut operands = Vec::new();
+            flatten(expr, binary.op, &mut operands);
+            operands.into_iter().map(depth).max().unwrap_or(0) + 1
+        }
+        SclExpr::Call(call) => {
+            let inputs = call.params.iter().filter(|it| !it.output);
+            inputs.map(|it| depth(&it.value)).max().unwrap_or(0) + 1
+        }
+        _ => 1,
+    }
+}
+
+/// Escape the text for attributes and element content
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// `2024-01-31T12:00:00` in UTC
+fn timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |it| it.as_secs() as i64);
+    let (days, rest) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    // Civil date from days since 1970-01-01 (Howard Hinnant)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}",
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    )
+}
@@ -0,0 +1,742 @@
+//! Rust struct with typed inputs and outputs, embedded with `fpp!` or a build script
+//!
+//! The code is generated from the [CLir], so the program runs on the same emulated status bits
+//! and accumulators as the C backend. The process images, memory, data blocks and counters are
+//! private fields kept between scans.
+
+use std::io::Write;
+
+use super::{
+    c::{CArea, CBinary, CExpr, CLir, CStatement, CVar},
+    s7::{S7Address, S7Mnemonics, WriteAwl},
+    st::{area, width, Area},
+};
+
+/// Thing that can be written to a [Write] in Rust-form
+pub trait WriteRust {
+    fn write_rust(&self, out: &mut impl Write) -> std::io::Result<()>;
+}
+
+/// Struct of the program with a `scan` method
+#[derive(Debug)]
+pub struct RustSource<'a> {
+    pub name: String,
+    /// Visibility of the struct (`pub`, `pub(crate)` or empty)
+    pub visibility: String,
+    /// Mnemonics of the field names (`e0_0` or `i0_0`)
+    pub mnemonics: S7Mnemonics,
+    pub lir: &'a CLir,
+}
+
+impl<'a> RustSource<'a> {
+    pub fn new(name: impl Into<String>, lir: &'a CLir) -> Self {
+        Self {
+            name: name.into(),
+            visibility: "pub".to_string(),
+            mnemonics: S7Mnemonics::German,
+            lir,
+        }
+    }
+
+    /// Name of the field of an input or output (`EW2` is `ew2`)
+    pub fn field(&self, addr: &S7Address) -> String {
+        let mut operand = Vec::new();
+        addr.write_awl(&mut operand, self.mnemonics)
+            .expect("Write to memory");
+        String::from_utf8_lossy(&operand)
+            .chars()
+            .map(|it| match it.is_ascii_alphanumeric() {
+                true => it.to_ascii_lowercase(),
+                false => '_',
+            })
+            .collect()
+    }
+
+    fn visibility(&self) -> String {
+        match self.visibility.is_empty() {
+            true => String::new(),
+            false => format!("{} ", self.visibility),
+        }
+    }
+
+    /// Private fields of the areas, the size is zero if an area is not accessed
+    fn areas(&self) -> Vec<(String, u32)> {
+        let mut areas = vec![
+            ("inputs".to_string(), self.lir.input_bytes),
+            ("outputs".to_string(), self.lir.output_bytes),
+            ("memory".to_string(), self.lir.memory_bytes),
+        ];
+        for (db, bytes) in &self.lir.data_blocks {
+            areas.push((format!("db{db}"), *bytes));
+        }
+        areas.retain(|it| it.1 != 0);
+        areas
+    }
+
+    fn write_struct(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let name = &self.name;
+        let vis = self.visibility();
+        writeln!(
+            out,
+            "/// F++ program, set the inputs and call [`{name}::scan`] once per cycle"
+        )?;
+        writeln!(out, "#[derive(Clone, Debug)]")?;
+        writeln!(out, "{vis}struct {name} {{")?;
+        for addr in &self.lir.io {
+            let mut operand = Vec::new();
+            addr.write_awl(&mut operand, self.mnemonics)?;
+            writeln!(out, "    /// `{}`", String::from_utf8_lossy(&operand))?;
+            writeln!(out, "    pub {}: {},", self.field(addr), field_type(addr))?;
+        }
+        for (area, bytes) in self.areas() {
+            writeln!(out, "    {area}: [u8; {bytes}],")?;
+        }
+        if self.lir.counters != 0 {
+            writeln!(out, "    counters: [{name}Counter; {}],", self.lir.counters)?;
+        }
+        writeln!(out, "}}")
+    }
+
+    fn write_counter(&self, out: &mut impl Write, functions: &[&str]) -> std::io::Result<()> {
+        writeln!(out)?;
+        writeln!(out, "/// S5 counter, counts on rising edges from 0 to 999")?;
+        writeln!(out, "#[derive(Clone, Copy, Debug, Default)]")?;
+        writeln!(out, "struct {}Counter {{", self.name)?;
+        writeln!(out, "    value: u16,")?;
+        writeln!(out, "    /// Edge memories of the count and set inputs")?;
+        writeln!(out, "    up: bool,")?;
+        writeln!(out, "    down: bool,")?;
+        writeln!(out, "    set: bool,")?;
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+        writeln!(out, "impl {}Counter {{", self.name)?;
+        let mut first = true;
+        for (name, code) in COUNTER_RUNTIME {
+            if functions.contains(name) {
+                if !first {
+                    writeln!(out)?;
+                }
+                first = false;
+                write!(out, "{code}")?;
+            }
+        }
+        writeln!(out, "}}")
+    }
+
+    fn write_new(&self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(out, "    {}fn new() -> Self {{", self.visibility())?;
+        writeln!(out, "        Self {{")?;
+        for addr in &self.lir.io {
+            let zero = match field_type(addr) {
+                "bool" => "false",
+                _ => "0",
+            };
+            writeln!(out, "            {}: {zero},", self.field(addr))?;
+        }
+        for (area, bytes) in self.areas() {
+            writeln!(out, "            {area}: [0; {bytes}],")?;
+        }
+        if self.lir.counters != 0 {
+            writeln!(
+                out,
+                "            counters: [{}Counter {{ value: 0, up: false, down: false, set: false }}; {}],",
+                self.name, self.lir.counters
+            )?;
+        }
+        writeln!(out, "        }}")?;
+        writeln!(out, "    }}")
+    }
+
+    fn write_scan(&self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(out)?;
+        writeln!(
+            out,
+            "    /// Execute one scan, the inputs are read and the outputs written at once"
+        )?;
+        writeln!(
+            out,
+            "    #[allow(unused_mut, unused_assignments, clippy::all)]"
+        )?;
+        writeln!(out, "    {}fn scan(&mut self) {{", self.visibility())?;
+        for var in &self.lir.variables {
+            let (r#type, zero) = match var.r#type() {
+                "bool" => ("bool", "false"),
+                _ => ("u32", "0"),
+            };
+            write!(out, "        let mut ")?;
+            var.write_rust(out)?;
+            writeln!(out, ": {} = {zero};", r#type)?;
+        }
+        for addr in self
+            .lir
+            .io
+            .iter()
+            .filter(|it| area(it.r#type) == Area::Input)
+        {
+            let field = self.field(addr);
+            let pointer = pointer(addr.ptr, addr.bit);
+            match width(addr.r#type) {
+                1 => writeln!(
+                    out,
+                    "        Self::set_bit({pointer}, self.{field}, &mut self.inputs);"
+                )?,
+                bits => writeln!(
+                    out,
+                    "        Self::set_bytes({pointer}, {}, self.{field}.into(), &mut self.inputs);",
+                    bits / 8
+                )?,
+            }
+        }
+        for network in &self.lir.networks {
+            writeln!(out)?;
+            if !network.title.is_empty() {
+                write_comment(out, &network.title)?;
+            }
+            for comment in &network.comments {
+                write_comment(out, comment)?;
+            }
+            for statement in &network.statements {
+                write_statement(out, statement, 8)?;
+            }
+        }
+        let outputs = self
+            .lir
+            .io
+            .iter()
+            .filter(|it| area(it.r#type) == Area::Output);
+        for (i, addr) in outputs.enumerate() {
+            if i == 0 {
+                writeln!(out)?;
+            }
+            let field = self.field(addr);
+            let pointer = pointer(addr.ptr, addr.bit);
+            match width(addr.r#type) {
+                1 => writeln!(
+                    out,
+                    "        self.{field} = Self::get_bit({pointer}, &self.outputs);"
+                )?,
+                bits => writeln!(
+                    out,
+                    "        self.{field} = Self::get_bytes({pointer}, {}, &self.outputs) as {};",
+                    bits / 8,
+                    field_type(addr)
+                )?,
+            }
+        }
+        writeln!(out, "    }}")
+    }
+}
+
+impl WriteRust for RustSource<'_> {
+    fn write_rust(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let mut functions = Vec::new();
+        if !self.lir.io.is_empty() {
+            functions.push("p");
+        }
+        for addr in &self.lir.io {
+            functions.push(match (area(addr.r#type), width(addr.r#type)) {
+                (Area::Input, 1) => "set_bit",
+                (Area::Input, _) => "set_bytes",
+                (_, 1) => "get_bit",
+                _ => "get_bytes",
+            });
+        }
+        let statements = self.lir.networks.iter().flat_map(|it| &it.statements);
+        for statement in statements {
+            statement.visit(&mut |expr| match expr {
+                CExpr::Call(function, args) => functions.push(function_name(function, args)),
+                CExpr::Pointer { .. } => functions.push("p"),
+                _ => {}
+            });
+        }
+        // Functions of the runtime only call functions defined before them
+        for (name, code) in RUNTIME.iter().rev() {
+            if functions.contains(name) {
+                functions.extend(
+                    RUNTIME
+                        .iter()
+                        .map(|it| it.0)
+                        .filter(|it| code.contains(&format!("Self::{it}("))),
+                );
+            }
+        }
+
+        self.write_struct(out)?;
+        if self.lir.counters != 0 {
+            self.write_counter(out, &functions)?;
+        }
+        writeln!(out)?;
+        writeln!(out, "impl Default for {} {{", self.name)?;
+        writeln!(out, "    fn default() -> Self {{")?;
+        writeln!(out, "        Self::new()")?;
+        writeln!(out, "    }}")?;
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+        writeln!(out, "impl {} {{", self.name)?;
+        self.write_new(out)?;
+        self.write_scan(out)?;
+        for (name, code) in RUNTIME {
+            if functions.contains(name) {
+                writeln!(out)?;
+                write!(out, "{code}")?;
+            }
+        }
+        writeln!(out, "}}")
+    }
+}
+
+fn field_type(addr: &S7Address) -> &'static str {
+    match width(addr.r#type) {
+        1 => "bool",
+        8 => "u8",
+        16 => "u16",
+        _ => "u32",
+    }
+}
+
+fn pointer(byte: u16, bit: u8) -> String {
+    format!("Self::p({byte}, {bit})")
+}
+
+fn write_comment(out: &mut impl Write, text: &str) -> std::io::Result<()> {
+    writeln!(out, "        // {}", text.trim())
+}
+
+fn write_statement(
+    out: &mut impl Write,
+    statement: &CStatement,
+    indent: usize,
+) -> std::io::Result<()> {
+    write!(out, "{:indent$}", "")?;
+    match statement {
+        CStatement::Assign { var, value } => {
+            var.write_rust(out)?;
+            write!(out, " = ")?;
+            value.write_rust(out)?;
+            writeln!(out, ";")
+        }
+        CStatement::Expr(expr) => {
+            expr.write_rust(out)?;
+            writeln!(out, ";")
+        }
+        CStatement::If { cond, then } => {
+            write!(out, "if ")?;
+            cond.write_rust(out)?;
+            writeln!(out, " {{")?;
+            for statement in then {
+                write_statement(out, statement, indent + 4)?;
+            }
+            writeln!(out, "{:indent$}}}", "")
+        }
+    }
+}
+
+impl WriteRust for CVar {
+    fn write_rust(&self, out: &mut impl Write) -> std::io::Result<()> {
+        match self {
+            Self::Rlo => write!(out, "rlo"),
+            Self::Or => write!(out, "or_bit"),
+            Self::Nested => write!(out, "nested"),
+            Self::NestingRlo(depth) => write!(out, "rlo_{depth}"),
+            Self::NestingOr(depth) => write!(out, "or_bit_{depth}"),
+            Self::Accu1 => write!(out, "accu1"),
+            Self::Accu2 => write!(out, "accu2"),
+            Self::Ar1 => write!(out, "ar1"),
+        }
+    }
+}
+
+/// Block moves and fills within one area borrow it once
+fn function_name(function: &'static str, args: &[CExpr]) -> &'static str {
+    let mut areas = args.iter().filter_map(|it| match it {
+        CExpr::Area(area) => Some(*area),
+        _ => None,
+    });
+    let same = matches!((areas.next(), areas.next()), (Some(dst), Some(src)) if dst == src);
+    match function {
+        "copy_bytes" if same => "move_bytes",
+        "fill_bytes" if same => "fill_within",
+        function => function,
+    }
+}
+
+fn write_area(out: &mut impl Write, area: CArea, mutable: bool) -> std::io::Result<()> {
+    let reference = if mutable { "&mut " } else { "&" };
+    match area {
+        CArea::Input => write!(out, "{reference}self.inputs"),
+        CArea::Output => write!(out, "{reference}self.outputs"),
+        CArea::Memory => write!(out, "{reference}self.memory"),
+        CArea::DataBlock(db) => write!(out, "{reference}self.db{db}"),
+    }
+}
+
+fn write_operand(out: &mut impl Write, expr: &CExpr) -> std::io::Result<()> {
+    if let CExpr::Binary(_) = expr {
+        write!(out, "(")?;
+        expr.write_rust(out)?;
+        return write!(out, ")");
+    }
+    expr.write_rust(out)
+}
+
+fn write_args(out: &mut impl Write, args: &[&CExpr]) -> std::io::Result<()> {
+    for (i, arg) in args.iter().enumerate() {
+        if i != 0 {
+            write!(out, ", ")?;
+        }
+        arg.write_rust(out)?;
+    }
+    Ok(())
+}
+
+/// Counters are written as method calls, areas are passed after the pointers that may read them
+fn write_call(out: &mut impl Write, function: &'static str, args: &[CExpr]) -> std::io::Result<()> {
+    if let [CExpr::Counter(counter), args @ ..] = args {
+        write!(out, "self.counters[{counter}].{function}(")?;
+        write_args(out, &args.iter().collect::<Vec<_>>())?;
+        return write!(out, ")");
+    }
+    let name = function_name(function, args);
+    let (areas, values): (Vec<_>, Vec<_>) =
+        args.iter().partition(|it| matches!(it, CExpr::Area(_)));
+    write!(out, "Self::{name}(")?;
+    write_args(out, &values)?;
+    let mutable = matches!(
+        name,
+        "set_bit" | "set_bytes" | "copy_bytes" | "move_bytes" | "fill_bytes" | "fill_within"
+    );
+    let areas = match name {
+        "move_bytes" | "fill_within" => &areas[..1],
+        _ => &areas[..],
+    };
+    for (i, area) in areas.iter().enumerate() {
+        let CExpr::Area(area) = area else {
+            unreachable!("Partitioned area")
+        };
+        if !values.is_empty() || i != 0 {
+            write!(out, ", ")?;
+        }
+        write_area(out, *area, mutable && i == 0)?;
+    }
+    write!(out, ")")
+}
+
+impl WriteRust for CExpr {
+    fn write_rust(&self, out: &mut impl Write) -> std::io::Result<()> {
+        match self {
+            Self::Bool(value) => write!(out, "{value}"),
+            Self::Number(value) => write!(out, "{value}"),
+            Self::Constant(value @ 0..=0xFF) => write!(out, "0x{value:02X}"),
+            Self::Constant(value @ 0..=0xFFFF) => write!(out, "0x{value:04X}"),
+            Self::Constant(value) => write!(out, "0x{value:08X}"),
+            Self::Var(var) => var.write_rust(out),
+            Self::Not(value) | Self::Complement(value) => {
+                write!(out, "!")?;
+                write_operand(out, value)
+            }
+            Self::Binary(binary) => {
+                let CBinary { op, left, right } = &**binary;
+                write_operand(out, left)?;
+                write!(out, " {} ", op.symbol())?;
+                write_operand(out, right)
+            }
+            Self::Call(function, args) => write_call(out, function, args),
+            Self::Area(area) => write_area(out, *area, false),
+            Self::Pointer { byte, bit } => write!(out, "{}", pointer(*byte, *bit)),
+            Self::Counter(number) => write!(out, "&mut self.counters[{number}]"),
+            Self::CounterValue(number) => write!(out, "self.counters[{number}].value"),
+        }
+    }
+}
+
+/// Methods of the counter struct
+const COUNTER_RUNTIME: &[(&str, &str)] = &[
+    (
+        "count_up",
+        r#"    fn count_up(&mut self, rlo: bool) {
+        if rlo && !self.up && self.value < 999 {
+            self.value += 1;
+        }
+        self.up = rlo;
+    }
+"#,
+    ),
+    (
+        "count_down",
+        r#"    fn count_down(&mut self, rlo: bool) {
+        if rlo && !self.down && self.value > 0 {
+            self.value -= 1;
+        }
+        self.down = rlo;
+    }
+"#,
+    ),
+    (
+        "counter_set",
+        r#"    /// Preset with the BCD value in ACCU1
+    fn counter_set(&mut self, rlo: bool, accu: u32) {
+        if rlo && !self.set {
+            self.value =
+                ((accu >> 8 & 0xF) * 100 + (accu >> 4 & 0xF) * 10 + (accu & 0xF)) as u16;
+        }
+        self.set = rlo;
+    }
+"#,
+    ),
+    (
+        "counter_reset",
+        r#"    fn counter_reset(&mut self, rlo: bool) {
+        if rlo {
+            self.value = 0;
+        }
+    }
+"#,
+    ),
+    (
+        "counter_bcd",
+        r#"    fn counter_bcd(&self) -> u32 {
+        let value = self.value as u32;
+        ((value / 100) << 8) | ((value / 10 % 10) << 4) | (value % 10)
+    }
+"#,
+    ),
+];
+
+/// Access functions and operations of the CPU, only the functions used by `scan` are written
+const RUNTIME: &[(&str, &str)] = &[
+    (
+        "p",
+        r#"    /// Area-internal pointer, the byte address is stored above the lower three bits
+    const fn p(byte: u32, bit: u32) -> u32 {
+        byte * 8 + bit
+    }
+"#,
+    ),
+    (
+        "get_bit",
+        r#"    /// Accesses outside of an area read zero and are ignored, where the CPU would stop
+    fn get_bit(pointer: u32, area: &[u8]) -> bool {
+        let byte = (pointer >> 3 & 0xFFFF) as usize;
+        area.get(byte).is_some_and(|it| it >> (pointer & 7) & 1 != 0)
+    }
+"#,
+    ),
+    (
+        "set_bit",
+        r#"    fn set_bit(pointer: u32, value: bool, area: &mut [u8]) {
+        let byte = (pointer >> 3 & 0xFFFF) as usize;
+        if let Some(it) = area.get_mut(byte) {
+            match value {
+                true => *it |= 1 << (pointer & 7),
+                false => *it &= !(1 << (pointer & 7)),
+            }
+        }
+    }
+"#,
+    ),
+    (
+        "get_bytes",
+        r#"    fn get_bytes(pointer: u32, bytes: u32, area: &[u8]) -> u32 {
+        let byte = (pointer >> 3 & 0xFFFF) as usize;
+        match area.get(byte..byte + bytes as usize) {
+            Some(bytes) => bytes.iter().fold(0, |value, &it| value << 8 | it as u32),
+            None => 0,
+        }
+    }
+"#,
+    ),
+    (
+        "set_bytes",
+        r#"    fn set_bytes(pointer: u32, bytes: u32, value: u32, area: &mut [u8]) {
+        let byte = (pointer >> 3 & 0xFFFF) as usize;
+        if let Some(area) = area.get_mut(byte..byte + bytes as usize) {
+            area.copy_from_slice(&value.to_be_bytes()[4 - bytes as usize..]);
+        }
+    }
+"#,
+    ),
+    (
+        "copy_bytes",
+        r#"    fn copy_bytes(dst_pointer: u32, src_pointer: u32, bytes: u32, dst: &mut [u8], src: &[u8]) {
+        let dst_byte = (dst_pointer >> 3) as usize;
+        let src_byte = (src_pointer >> 3) as usize;
+        let bytes = bytes as usize;
+        let dst = dst.get_mut(dst_byte..dst_byte + bytes);
+        if let (Some(dst), Some(src)) = (dst, src.get(src_byte..src_byte + bytes)) {
+            dst.copy_from_slice(src);
+        }
+    }
+"#,
+    ),
+    (
+        "move_bytes",
+        r#"    fn move_bytes(dst_pointer: u32, src_pointer: u32, bytes: u32, area: &mut [u8]) {
+        let dst_byte = (dst_pointer >> 3) as usize;
+        let src_byte = (src_pointer >> 3) as usize;
+        let bytes = bytes as usize;
+        if dst_byte.max(src_byte) + bytes <= area.len() {
+            area.copy_within(src_byte..src_byte + bytes, dst_byte);
+        }
+    }
+"#,
+    ),
+    (
+        "fill_bytes",
+        r#"    /// Repeat the source until the destination is full
+    fn fill_bytes(
+        dst_pointer: u32,
+        dst_bytes: u32,
+        src_pointer: u32,
+        src_bytes: u32,
+        dst: &mut [u8],
+        src: &[u8],
+    ) {
+        let dst_byte = (dst_pointer >> 3) as usize;
+        let src_byte = (src_pointer >> 3) as usize;
+        let dst = dst.get_mut(dst_byte..dst_byte + dst_bytes as usize);
+        if let (Some(dst), Some(src)) = (dst, src.get(src_byte..src_byte + src_bytes as usize)) {
+            for (dst, src) in dst.iter_mut().zip(src.iter().cycle()) {
+                *dst = *src;
+            }
+        }
+    }
+"#,
+    ),
+    (
+        "fill_within",
+        r#"    fn fill_within(dst_pointer: u32, dst_bytes: u32, src_pointer: u32, src_bytes: u32, area: &mut [u8]) {
+        let dst_byte = (dst_pointer >> 3) as usize;
+        let src_byte = (src_pointer >> 3) as usize;
+        let (dst_bytes, src_bytes) = (dst_bytes as usize, src_bytes as usize);
+        if src_bytes == 0 || dst_byte + dst_bytes > area.len() || src_byte + src_bytes > area.len() {
+            return;
+        }
+        for i in 0..dst_bytes {
+            area[dst_byte + i] = area[src_byte + i % src_bytes];
+        }
+    }
+"#,
+    ),
+    (
+        "low_word",
+        r#"    /// Word operations only change the low word of ACCU1
+    fn low_word(accu: u32, value: u32) -> u32 {
+        (accu & 0xFFFF0000) | (value & 0xFFFF)
+    }
+"#,
+    ),
+    (
+        "add_pointer",
+        r#"    /// Add a bit offset to an area-internal pointer, keeping the area
+    fn add_pointer(pointer: u32, offset: u32) -> u32 {
+        (pointer & 0xFF000000) | (pointer.wrapping_add(offset) & 0x00FFFFFF)
+    }
+"#,
+    ),
+    (
+        "shift_left_word",
+        r#"    fn shift_left_word(accu: u32, amount: u32) -> u32 {
+        Self::low_word(accu, (accu & 0xFFFF).checked_shl(amount).unwrap_or(0))
+    }
+"#,
+    ),
+    (
+        "shift_right_word",
+        r#"    fn shift_right_word(accu: u32, amount: u32) -> u32 {
+        Self::low_word(accu, (accu & 0xFFFF).checked_shr(amount).unwrap_or(0))
+    }
+"#,
+    ),
+    (
+        "shift_left_dword",
+        r#"    fn shift_left_dword(accu: u32, amount: u32) -> u32 {
+        accu.checked_shl(amount).unwrap_or(0)
+    }
+"#,
+    ),
+    (
+        "shift_right_dword",
+        r#"    fn shift_right_dword(accu: u32, amount: u32) -> u32 {
+        accu.checked_shr(amount).unwrap_or(0)
+    }
+"#,
+    ),
+    (
+        "rotate_left_dword",
+        r#"    fn rotate_left_dword(accu: u32, amount: u32) -> u32 {
+        accu.rotate_left(amount)
+    }
+"#,
+    ),
+    (
+        "rotate_right_dword",
+        r#"    fn rotate_right_dword(accu: u32, amount: u32) -> u32 {
+        accu.rotate_right(amount)
+    }
+"#,
+    ),
+    (
+        "bcd_to_int",
+        r#"    /// Invalid digits leave the accumulator unchanged, where the CPU would stop
+    fn bcd_to_int(accu: u32) -> u32 {
+        let mut value = 0i32;
+        for shift in [8, 4, 0] {
+            let digit = accu >> shift & 0xF;
+            if digit > 9 {
+                return accu;
+            }
+            value = value * 10 + digit as i32;
+        }
+        if accu & 0x8000 != 0 {
+            value = -value;
+        }
+        Self::low_word(accu, value as u32)
+    }
+"#,
+    ),
+    (
+        "int_to_bcd",
+        r#"    /// Integers outside of -999 to 999 overflow and leave the accumulator unchanged
+    fn int_to_bcd(accu: u32) -> u32 {
+        let value = accu as u16 as i16;
+        if !(-999..=999).contains(&value) {
+            return accu;
+        }
+        let sign = if value < 0 { 0xF000 } else { 0 };
+        let value = value.unsigned_abs() as u32;
+        let bcd = ((value / 100) << 8) | ((value / 10 % 10) << 4) | (value % 10);
+        Self::low_word(accu, sign | bcd)
+    }
+"#,
+    ),
+    (
+        "int_to_dint",
+        r#"    fn int_to_dint(accu: u32) -> u32 {
+        accu as u16 as i16 as i32 as u32
+    }
+"#,
+    ),
+    (
+        "dint_to_real",
+        r#"    fn dint_to_real(accu: u32) -> u32 {
+        (accu as i32 as f32).to_bits()
+    }
+"#,
+    ),
+    (
+        "real_to_dint",
+        r#"    /// Reals outside of the DINT range overflow and leave the accumulator unchanged
+    fn real_to_dint(accu: u32, round: bool) -> u32 {
+        let real = f32::from_bits(accu);
+        let value = match round {
+            true => real.round_ties_even(),
+            false => real.trunc(),
+        };
+        match (-2147483648.0..2147483648.0).contains(&value) {
+            true => value as i32 as u32,
+            false => accu,
+        }
+    }
+"#,
+    ),
+];
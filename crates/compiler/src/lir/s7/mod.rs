@@ -1,33 +1,52 @@
-use std::io::Write;
+use std::{io::Write, rc::Rc};
 
+pub mod source;
 pub mod transformer;
 
 /// Thing that can be written to a [Write] in AWL-form
 pub trait WriteAwl {
-    fn write_awl(&self, out: &mut impl Write) -> std::io::Result<()>;
+    fn write_awl(&self, out: &mut impl Write, mnemonics: S7Mnemonics) -> std::io::Result<()>;
+}
+
+/// Mnemonic set of the STEP 7 installation the AWL is written for
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum S7Mnemonics {
+    /// `U E0.0`, `= A0.0`
+    #[default]
+    German,
+    /// `A I0.0`, `= Q0.0`
+    English,
 }
 
 #[derive(Debug, Default)]
 pub struct S7Lir {
     pub networks: Vec<S7Network>,
+    /// Block temporaries (`#T0`) declared in `VAR_TEMP`
+    pub temps: Vec<(S7Address, S7DataType)>,
 }
 
 #[derive(Debug, Default)]
 pub struct S7Network {
+    pub title: Rc<str>,
+    /// Comments written as `//` lines before the instructions
+    pub comments: Vec<Rc<str>>,
     pub instructions: Vec<S7Instruction>,
 }
 
 impl WriteAwl for S7Network {
-    fn write_awl(&self, out: &mut impl Write) -> std::io::Result<()> {
+    fn write_awl(&self, out: &mut impl Write, mnemonics: S7Mnemonics) -> std::io::Result<()> {
+        for comment in &self.comments {
+            writeln!(out, "//{comment}")?;
+        }
         for instruction in &self.instructions {
-            instruction.write_awl(out)?;
+            instruction.write_awl(out, mnemonics)?;
             writeln!(out)?;
         }
         Ok(())
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub enum S7Instruction {
     /// `U op`
     And { addr: S7Address },
@@ -61,7 +80,7 @@ pub enum S7Instruction {
     ResetBit { addr: S7Address },
     /// `S dst`
     SetBit { addr: S7Address },
-    /// `N`
+    /// `NOT`
     Not,
     /// `SET`
     Set,
@@ -81,12 +100,85 @@ pub enum S7Instruction {
     CounterForward { addr: S7Address },
     /// `ZR addr`
     CounterBackward { addr: S7Address },
+    /// `L addr`
+    Load { addr: S7Address },
+    /// `L value`
+    LoadConst { value: S7Constant },
     /// `T addr`
     Transfer { addr: S7Address },
+    /// `UW`
+    AndWord,
+    /// `OW`
+    OrWord,
+    /// `XOW`
+    XorWord,
+    /// `UD`
+    AndDWord,
+    /// `OD`
+    OrDWord,
+    /// `XOD`
+    XorDWord,
+    /// `INVI`
+    InvertInt,
+    /// `INVD`
+    InvertDInt,
+    /// `SLW n`
+    ShiftLeftWord { amount: Option<u8> },
+    /// `SRW n`
+    ShiftRightWord { amount: Option<u8> },
+    /// `SLD n`
+    ShiftLeftDWord { amount: Option<u8> },
+    /// `SRD n`
+    ShiftRightDWord { amount: Option<u8> },
+    /// `RLD n`
+    RotateLeftDWord { amount: Option<u8> },
+    /// `RRD n`
+    RotateRightDWord { amount: Option<u8> },
+    /// `BTI`
+    BcdToInt,
+    /// `ITB`
+    IntToBcd,
+    /// `ITD`
+    IntToDInt,
+    /// `DTR`
+    DIntToReal,
+    /// `RND`
+    Round,
+    /// `TRUNC`
+    Trunc,
+    /// `LAR1`
+    LoadAddressRegister,
+    /// `TAR1`
+    TransferAddressRegister,
+    /// `+AR1 P#n.m`
+    AddAddressRegister { offset: Option<u32> },
+    /// `AUF DBn`
+    OpenDataBlock { db: u16 },
+    /// `CALL SFC 20 (...)`
+    Call(Rc<S7Call>),
 }
 
 impl S7Instruction {
-    pub fn awl_mnemonic(self) -> &'static str {
+    pub fn awl_mnemonic(&self, mnemonics: S7Mnemonics) -> &'static str {
+        let german = self.german_mnemonic();
+        if mnemonics == S7Mnemonics::German {
+            return german;
+        }
+        match german {
+            "U" => "A",
+            "UN" => "AN",
+            "U(" => "A(",
+            "UN(" => "AN(",
+            "ZV" => "CU",
+            "ZR" => "CD",
+            "UW" => "AW",
+            "UD" => "AD",
+            "AUF" => "OPN",
+            mnemonic => mnemonic,
+        }
+    }
+
+    fn german_mnemonic(&self) -> &'static str {
         match self {
             Self::And { .. } => "U",
             Self::AndNot { .. } => "UN",
@@ -104,7 +196,7 @@ impl S7Instruction {
             Self::AssignBit { .. } => "=",
             Self::ResetBit { .. } => "R",
             Self::SetBit { .. } => "S",
-            Self::Not => "N",
+            Self::Not => "NOT",
             Self::Set => "SET",
             Self::Clear => "CLR",
             Self::Save => "SAVE",
@@ -114,11 +206,51 @@ impl S7Instruction {
             Self::CounterSet { .. } => "S",
             Self::CounterForward { .. } => "ZV",
             Self::CounterBackward { .. } => "ZR",
+            Self::Load { .. } => "L",
+            Self::LoadConst { .. } => "L",
             Self::Transfer { .. } => "T",
+            Self::AndWord => "UW",
+            Self::OrWord => "OW",
+            Self::XorWord => "XOW",
+            Self::AndDWord => "UD",
+            Self::OrDWord => "OD",
+            Self::XorDWord => "XOD",
+            Self::InvertInt => "INVI",
+            Self::InvertDInt => "INVD",
+            Self::ShiftLeftWord { .. } => "SLW",
+            Self::ShiftRightWord { .. } => "SRW",
+            Self::ShiftLeftDWord { .. } => "SLD",
+            Self::ShiftRightDWord { .. } => "SRD",
+            Self::RotateLeftDWord { .. } => "RLD",
+            Self::RotateRightDWord { .. } => "RRD",
+            Self::BcdToInt => "BTI",
+            Self::IntToBcd => "ITB",
+            Self::IntToDInt => "ITD",
+            Self::DIntToReal => "DTR",
+            Self::Round => "RND",
+            Self::Trunc => "TRUNC",
+            Self::LoadAddressRegister => "LAR1",
+            Self::TransferAddressRegister => "TAR1",
+            Self::AddAddressRegister { .. } => "+AR1",
+            Self::OpenDataBlock { .. } => "AUF",
+            Self::Call(_) => "CALL",
         }
     }
 
-    pub fn addr(self) -> Option<S7Address> {
+    /// Constant amount of a shift or rotation
+    pub fn amount(&self) -> Option<u8> {
+        match self {
+            Self::ShiftLeftWord { amount }
+            | Self::ShiftRightWord { amount }
+            | Self::ShiftLeftDWord { amount }
+            | Self::ShiftRightDWord { amount }
+            | Self::RotateLeftDWord { amount }
+            | Self::RotateRightDWord { amount } => *amount,
+            _ => None,
+        }
+    }
+
+    pub fn addr(&self) -> Option<S7Address> {
         match self {
             Self::And { addr }
             | Self::AndNot { addr }
@@ -135,55 +267,386 @@ impl S7Instruction {
             | Self::CounterSet { addr }
             | Self::CounterForward { addr }
             | Self::CounterBackward { addr }
-            | Self::Transfer { addr } => Some(addr),
+            | Self::Load { addr }
+            | Self::Transfer { addr } => Some(*addr),
             _ => None,
         }
     }
 }
 
 impl WriteAwl for S7Instruction {
-    fn write_awl(&self, out: &mut impl Write) -> std::io::Result<()> {
-        let mnemonic = self.awl_mnemonic();
+    fn write_awl(&self, out: &mut impl Write, mnemonics: S7Mnemonics) -> std::io::Result<()> {
+        let mnemonic = self.awl_mnemonic(mnemonics);
         write!(out, "{mnemonic}")?;
         if let Some(addr) = self.addr() {
             write!(out, " ")?;
-            addr.write_awl(out)?;
+            addr.write_awl(out, mnemonics)?;
+        }
+        if let Self::LoadConst { value } = self {
+            value.write_awl_prefixed(out, mnemonics)?;
+        }
+        if let Some(amount) = self.amount() {
+            write!(out, " {amount}")?;
+        }
+        match self {
+            Self::AddAddressRegister {
+                offset: Some(offset),
+            } => S7Constant::Pointer(*offset).write_awl_prefixed(out, mnemonics)?,
+            Self::OpenDataBlock { db } => write!(out, " DB{db}")?,
+            Self::Call(call) => call.write_awl(out, mnemonics)?,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Block call with its parameter assignments, written after `CALL`
+#[derive(Debug)]
+pub struct S7Call {
+    pub block: S7Block,
+    /// Instance data block of function blocks
+    pub instance: Option<u16>,
+    pub params: Vec<S7Param>,
+}
+
+impl WriteAwl for S7Call {
+    fn write_awl(&self, out: &mut impl Write, mnemonics: S7Mnemonics) -> std::io::Result<()> {
+        write!(out, " ")?;
+        self.block.write_awl(out, mnemonics)?;
+        if let Some(instance) = self.instance {
+            write!(out, ", DB {instance}")?;
+        }
+        if self.params.is_empty() {
+            return Ok(());
+        }
+        write!(out, " (")?;
+        for (i, param) in self.params.iter().enumerate() {
+            let separator = if i + 1 == self.params.len() {
+                ");"
+            } else {
+                ","
+            };
+            // Parameters are indented one level deeper than the instructions of the block body
+            write!(out, "\n        {} := ", param.name)?;
+            param.value.write_awl(out, mnemonics)?;
+            write!(out, "{separator}")?;
         }
         Ok(())
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct S7Block {
+    pub kind: S7BlockKind,
+    pub number: u16,
+}
+
+impl WriteAwl for S7Block {
+    fn write_awl(&self, out: &mut impl Write, _: S7Mnemonics) -> std::io::Result<()> {
+        let kind = match self.kind {
+            S7BlockKind::Function => "FC",
+            S7BlockKind::FunctionBlock => "FB",
+            S7BlockKind::SystemFunction => "SFC",
+            S7BlockKind::SystemFunctionBlock => "SFB",
+        };
+        write!(out, "{kind} {}", self.number)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum S7BlockKind {
+    Function,
+    FunctionBlock,
+    SystemFunction,
+    SystemFunctionBlock,
+}
+
+/// `NAME := value`
+#[derive(Debug)]
+pub struct S7Param {
+    pub name: Rc<str>,
+    pub value: S7Operand,
+    /// Written by the block, `RET_VAL` and outputs
+    pub output: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum S7Operand {
+    Address(S7Address),
+    Any(S7AnyPointer),
+    Constant(S7Constant),
+}
+
+impl WriteAwl for S7Operand {
+    fn write_awl(&self, out: &mut impl Write, mnemonics: S7Mnemonics) -> std::io::Result<()> {
+        match self {
+            Self::Address(addr) => addr.write_awl(out, mnemonics),
+            Self::Any(any) => any.write_awl(out, mnemonics),
+            Self::Constant(constant) => constant.write_awl(out, mnemonics),
+        }
+    }
+}
+
+/// `P#DB10.DBX 0.0 BYTE 20`
+#[derive(Clone, Copy, Debug)]
+pub struct S7AnyPointer {
+    /// First bit of the area
+    pub addr: S7Address,
+    pub r#type: S7DataType,
+    pub count: u16,
+}
+
+impl WriteAwl for S7AnyPointer {
+    fn write_awl(&self, out: &mut impl Write, mnemonics: S7Mnemonics) -> std::io::Result<()> {
+        write!(out, "P#")?;
+        if self.addr.db != 0 {
+            write!(out, "DB{}.", self.addr.db)?;
+        }
+        self.addr.r#type.write_awl(out, mnemonics)?;
+        write!(out, " {}.{} ", self.addr.ptr, self.addr.bit)?;
+        self.r#type.write_awl(out, mnemonics)?;
+        write!(out, " {}", self.count)
+    }
+}
+
+/// Elementary data types
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum S7DataType {
+    Bool,
+    Byte,
+    Word,
+    DWord,
+    Int,
+    DInt,
+    Real,
+    Time,
+}
+
+impl S7DataType {
+    /// Name of the type, the same in STEP 7 and IEC 61131-3
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Bool => "BOOL",
+            Self::Byte => "BYTE",
+            Self::Word => "WORD",
+            Self::DWord => "DWORD",
+            Self::Int => "INT",
+            Self::DInt => "DINT",
+            Self::Real => "REAL",
+            Self::Time => "TIME",
+        }
+    }
+}
+
+impl WriteAwl for S7DataType {
+    fn write_awl(&self, out: &mut impl Write, _: S7Mnemonics) -> std::io::Result<()> {
+        write!(out, "{}", self.name())
+    }
+}
+
+/// Constant operand of `L`
 #[derive(Clone, Copy, Debug)]
+pub enum S7Constant {
+    /// `B#16#FF`
+    Byte(u8),
+    /// `W#16#FFFF`
+    Word(u16),
+    /// `DW#16#FFFF_FFFF`
+    DWord(u32),
+    /// `-123`
+    Int(i16),
+    /// `L#-123`
+    DInt(i32),
+    /// `1.000000e+001`
+    Real(f32),
+    /// `P#2.0`
+    Pointer(u32),
+    /// `TRUE`, only used as block parameter
+    Bool(bool),
+    /// `T#1M30S`
+    Time(i32),
+}
+
+impl S7Constant {
+    /// Write the constant as an operand following a mnemonic
+    fn write_awl_prefixed(
+        &self,
+        out: &mut impl Write,
+        mnemonics: S7Mnemonics,
+    ) -> std::io::Result<()> {
+        write!(out, " ")?;
+        self.write_awl(out, mnemonics)
+    }
+}
+
+impl WriteAwl for S7Constant {
+    fn write_awl(&self, out: &mut impl Write, _: S7Mnemonics) -> std::io::Result<()> {
+        match self {
+            Self::Byte(value) => write!(out, "B#16#{value:02X}"),
+            Self::Word(value) => write!(out, "W#16#{value:04X}"),
+            Self::DWord(value) => write!(out, "DW#16#{value:08X}"),
+            Self::Int(value) => write!(out, "{value}"),
+            Self::DInt(value) => write!(out, "L#{value}"),
+            Self::Real(value) => {
+                // STEP 7 writes the exponent with a sign and three digits
+                let formatted = format!("{value:.6e}");
+                let (mantissa, exponent) = formatted.split_once('e').expect("Exponent");
+                let exponent: i32 = exponent.parse().expect("Exponent");
+                let sign = if exponent < 0 { '-' } else { '+' };
+                write!(out, "{mantissa}e{sign}{:03}", exponent.abs())
+            }
+            Self::Pointer(value) => write!(out, "P#{}.{}", value / 8, value % 8),
+            Self::Bool(value) => write!(out, "{}", if *value { "TRUE" } else { "FALSE" }),
+            Self::Time(value) => {
+                write!(out, "T#")?;
+                if *value == 0 {
+                    return write!(out, "0MS");
+                }
+                let units = [
+                    ("D", 86_400_000),
+                    ("H", 3_600_000),
+                    ("M", 60_000),
+                    ("S", 1000),
+                ];
+                let mut rest = *value;
+                for (unit, factor) in units {
+                    if rest >= factor {
+                        write!(out, "{}{unit}", rest / factor)?;
+                        rest %= factor;
+                    }
+                }
+                if rest > 0 {
+                    write!(out, "{rest}MS")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum S7AddressType {
     Input1,
+    Input8,
+    Input16,
+    Input32,
     Output1,
+    Output8,
+    Output16,
+    Output32,
     Memory1,
     Memory8,
     Memory16,
     Memory32,
+    PeripheralInput8,
+    PeripheralInput16,
+    PeripheralInput32,
+    PeripheralOutput8,
+    PeripheralOutput16,
+    PeripheralOutput32,
+    Timer,
     Counter,
+    DataBlock1,
+    DataBlock8,
+    DataBlock16,
+    DataBlock32,
+    /// `#T0`, block temporary named by its byte offset
+    Temp1,
+    Temp8,
+    Temp16,
+    Temp32,
+}
+
+impl S7AddressType {
+    /// Type of an operand prefix of either mnemonic set (`EW`, `IW`)
+    pub fn parse(prefix: &str) -> Option<Self> {
+        let r#type = match prefix {
+            "E" | "I" => Self::Input1,
+            "EB" | "IB" => Self::Input8,
+            "EW" | "IW" => Self::Input16,
+            "ED" | "ID" => Self::Input32,
+            "A" | "Q" => Self::Output1,
+            "AB" | "QB" => Self::Output8,
+            "AW" | "QW" => Self::Output16,
+            "AD" | "QD" => Self::Output32,
+            "M" => Self::Memory1,
+            "MB" => Self::Memory8,
+            "MW" => Self::Memory16,
+            "MD" => Self::Memory32,
+            "PEB" | "PIB" => Self::PeripheralInput8,
+            "PEW" | "PIW" => Self::PeripheralInput16,
+            "PED" | "PID" => Self::PeripheralInput32,
+            "PAB" | "PQB" => Self::PeripheralOutput8,
+            "PAW" | "PQW" => Self::PeripheralOutput16,
+            "PAD" | "PQD" => Self::PeripheralOutput32,
+            "T" => Self::Timer,
+            "Z" | "C" => Self::Counter,
+            "DBX" => Self::DataBlock1,
+            "DBB" => Self::DataBlock8,
+            "DBW" => Self::DataBlock16,
+            "DBD" => Self::DataBlock32,
+            _ => return None,
+        };
+        Some(r#type)
+    }
 }
 
 impl WriteAwl for S7AddressType {
-    fn write_awl(&self, out: &mut impl Write) -> std::io::Result<()> {
-        let prefix = match self {
-            Self::Input1 => "E",
-            Self::Output1 => "A",
-            Self::Memory1 => "M",
-            Self::Memory8 => "MB",
-            Self::Memory16 => "MW",
-            Self::Memory32 => "MD",
-            Self::Counter => "C",
+    fn write_awl(&self, out: &mut impl Write, mnemonics: S7Mnemonics) -> std::io::Result<()> {
+        let (german, english) = match self {
+            Self::Input1 => ("E", "I"),
+            Self::Input8 => ("EB", "IB"),
+            Self::Input16 => ("EW", "IW"),
+            Self::Input32 => ("ED", "ID"),
+            Self::Output1 => ("A", "Q"),
+            Self::Output8 => ("AB", "QB"),
+            Self::Output16 => ("AW", "QW"),
+            Self::Output32 => ("AD", "QD"),
+            Self::Memory1 => ("M", "M"),
+            Self::Memory8 => ("MB", "MB"),
+            Self::Memory16 => ("MW", "MW"),
+            Self::Memory32 => ("MD", "MD"),
+            Self::PeripheralInput8 => ("PEB", "PIB"),
+            Self::PeripheralInput16 => ("PEW", "PIW"),
+            Self::PeripheralInput32 => ("PED", "PID"),
+            Self::PeripheralOutput8 => ("PAB", "PQB"),
+            Self::PeripheralOutput16 => ("PAW", "PQW"),
+            Self::PeripheralOutput32 => ("PAD", "PQD"),
+            Self::Timer => ("T", "T"),
+            Self::Counter => ("Z", "C"),
+            Self::DataBlock1 => ("DBX", "DBX"),
+            Self::DataBlock8 => ("DBB", "DBB"),
+            Self::DataBlock16 => ("DBW", "DBW"),
+            Self::DataBlock32 => ("DBD", "DBD"),
+            Self::Temp1 | Self::Temp8 | Self::Temp16 | Self::Temp32 => ("#T", "#T"),
+        };
+        let prefix = match mnemonics {
+            S7Mnemonics::German => german,
+            S7Mnemonics::English => english,
         };
         write!(out, "{prefix}")
     }
 }
 
+/// Indexing of a [S7Address]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum S7AddressIndex {
+    /// `MW10`
+    Direct,
+    /// `MW [MD10]`, timers and counters use `T [MW10]`
+    Memory { ptr: u16 },
+    /// `MW [AR1,P#2.0]`
+    Register,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct S7Address {
     pub r#type: S7AddressType,
+    /// Number of the data block, only used by `DataBlock` types
+    pub db: u16,
     pub ptr: u16,
     pub bit: u8,
+    pub index: S7AddressIndex,
 }
 
 impl S7Address {
@@ -191,23 +654,121 @@ impl S7Address {
     pub fn is_bit_address(self) -> bool {
         matches!(
             self.r#type,
-            S7AddressType::Input1 | S7AddressType::Output1 | S7AddressType::Memory1
+            S7AddressType::Input1
+                | S7AddressType::Output1
+                | S7AddressType::Memory1
+                | S7AddressType::DataBlock1
+                | S7AddressType::Temp1
         )
     }
 
-    /// Checks if the address points to a `BYTE`, `WORD` or `DWORD`
-    pub fn is_any_byte_address(self) -> bool {
+    /// Checks if the address is a block temporary
+    pub fn is_temp(self) -> bool {
         matches!(
             self.r#type,
-            S7AddressType::Memory8 | S7AddressType::Memory16 | S7AddressType::Memory32
+            S7AddressType::Temp1
+                | S7AddressType::Temp8
+                | S7AddressType::Temp16
+                | S7AddressType::Temp32
         )
     }
+
+    /// Checks if the address points to a `BYTE`, `WORD` or `DWORD`
+    pub fn is_any_byte_address(self) -> bool {
+        !self.is_bit_address()
+            && !matches!(self.r#type, S7AddressType::Timer | S7AddressType::Counter)
+    }
+}
+
+impl S7Address {
+    /// Checks if the address is memory- or register-indirect
+    pub fn is_indirect(self) -> bool {
+        self.index != S7AddressIndex::Direct
+    }
+
+    /// Parse a direct operand of either mnemonic set (`E0.0`, `QW2`, `DB5.DBX2.1`, `Z3`)
+    pub fn parse(text: &str) -> Option<Self> {
+        let (db, operand) = match text.strip_prefix("DB") {
+            Some(rest) if rest.starts_with(|c: char| c.is_ascii_digit()) => {
+                let (db, operand) = rest.split_once('.')?;
+                (db.parse::<u16>().ok().filter(|&it| it != 0)?, operand)
+            }
+            _ => (0, text),
+        };
+        let (prefix, number) = operand.split_at(operand.find(|c: char| c.is_ascii_digit())?);
+        let r#type = S7AddressType::parse(prefix)?;
+        let mut addr = Self {
+            r#type,
+            db,
+            ptr: 0,
+            bit: 0,
+            index: S7AddressIndex::Direct,
+        };
+        let is_data_block = matches!(
+            r#type,
+            S7AddressType::DataBlock1
+                | S7AddressType::DataBlock8
+                | S7AddressType::DataBlock16
+                | S7AddressType::DataBlock32
+        );
+        if is_data_block != (db != 0) {
+            return None;
+        }
+        let bytes = match r#type {
+            S7AddressType::Input16
+            | S7AddressType::Output16
+            | S7AddressType::Memory16
+            | S7AddressType::PeripheralInput16
+            | S7AddressType::PeripheralOutput16
+            | S7AddressType::DataBlock16 => 2,
+            S7AddressType::Input32
+            | S7AddressType::Output32
+            | S7AddressType::Memory32
+            | S7AddressType::PeripheralInput32
+            | S7AddressType::PeripheralOutput32
+            | S7AddressType::DataBlock32 => 4,
+            _ => 1,
+        };
+        let number = match addr.is_bit_address() {
+            true => {
+                let (ptr, bit) = number.split_once('.')?;
+                addr.bit = bit.parse().ok().filter(|&it| it < 8)?;
+                ptr
+            }
+            false => number,
+        };
+        if !number.bytes().all(|it| it.is_ascii_digit()) {
+            return None;
+        }
+        addr.ptr = number.parse().ok()?;
+        if addr.ptr as u32 + bytes > u16::MAX as u32 + 1 {
+            return None;
+        }
+        Some(addr)
+    }
 }
 
 impl WriteAwl for S7Address {
-    fn write_awl(&self, out: &mut impl Write) -> std::io::Result<()> {
-        self.r#type.write_awl(out)?;
-        if self.is_bit_address() {
+    fn write_awl(&self, out: &mut impl Write, mnemonics: S7Mnemonics) -> std::io::Result<()> {
+        // Indirect operands can not be qualified, the data block is opened with `AUF`
+        if self.db != 0 && !self.is_indirect() {
+            write!(out, "DB{}.", self.db)?;
+        }
+        self.r#type.write_awl(out, mnemonics)?;
+        match self.index {
+            S7AddressIndex::Direct => {}
+            S7AddressIndex::Memory { ptr } => {
+                let pointer = match self.r#type {
+                    S7AddressType::Timer | S7AddressType::Counter => "MW",
+                    _ => "MD",
+                };
+                return write!(out, " [{pointer}{ptr}]");
+            }
+            S7AddressIndex::Register => {
+                return write!(out, " [AR1,P#{}.{}]", self.ptr, self.bit);
+            }
+        }
+        if self.is_bit_address() && !self.is_temp() {
             write!(out, "{}.{}", self.ptr, self.bit)
         } else {
             write!(out, "{}", self.ptr)
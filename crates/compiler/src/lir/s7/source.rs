@@ -0,0 +1,226 @@
+//! Importable STEP 7 source files (`*.awl`)
+
+use std::io::Write;
+
+use super::{S7Instruction, S7Lir, S7Mnemonics, WriteAwl};
+
+/// Kind of the generated code block
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum S7SourceKind {
+    /// `ORGANIZATION_BLOCK OB n`
+    OrganizationBlock,
+    /// `FUNCTION FC n : VOID`
+    Function,
+}
+
+/// Code block of an external source file, imported by STEP 7 via "Insert > External Source"
+#[derive(Debug)]
+pub struct S7Source<'a> {
+    pub kind: S7SourceKind,
+    pub number: u16,
+    pub title: String,
+    /// `VERSION : 0.1`
+    pub version: String,
+    pub lir: &'a S7Lir,
+}
+
+impl<'a> S7Source<'a> {
+    pub fn new(kind: S7SourceKind, number: u16, lir: &'a S7Lir) -> Self {
+        Self {
+            kind,
+            number,
+            title: String::new(),
+            version: "0.1".into(),
+            lir,
+        }
+    }
+
+    /// Parse a block name (`OB1`, `FC10`)
+    pub fn parse_block(name: &str) -> Option<(S7SourceKind, u16)> {
+        let index = name.find(|c: char| c.is_ascii_digit())?;
+        let (prefix, number) = name.split_at(index);
+        let kind = match prefix.trim().to_ascii_uppercase().as_str() {
+            "OB" => S7SourceKind::OrganizationBlock,
+            "FC" => S7SourceKind::Function,
+            _ => return None,
+        };
+        let number = number.parse::<u16>().ok().filter(|it| *it != 0)?;
+        Some((kind, number))
+    }
+
+    /// Write the source in the Windows-1252 encoding with CRLF line endings
+    pub fn write_source(
+        &self,
+        out: &mut impl Write,
+        mnemonics: S7Mnemonics,
+    ) -> std::io::Result<()> {
+        let mut text = Vec::new();
+        self.write_awl(&mut text, mnemonics)?;
+        out.write_all(&encode_windows_1252(&String::from_utf8_lossy(&text)))
+    }
+
+    /// Instance data blocks of the called function blocks, declared before their first use
+    fn write_instances(&self, out: &mut impl Write, mnemonics: S7Mnemonics) -> std::io::Result<()> {
+        let calls = self.lir.networks.iter().flat_map(|it| &it.instructions);
+        for instruction in calls {
+            let S7Instruction::Call(call) = instruction else {
+                continue;
+            };
+            let Some(instance) = call.instance else {
+                continue;
+            };
+            writeln!(out, "DATA_BLOCK DB {instance}")?;
+            writeln!(out, "TITLE = ")?;
+            writeln!(out, "VERSION : {}", self.version)?;
+            call.block.write_awl(out, mnemonics)?;
+            writeln!(out)?;
+            writeln!(out, "BEGIN")?;
+            writeln!(out, "END_DATA_BLOCK")?;
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Start information every organization block must declare in its first 20 temporary bytes
+    fn write_start_info(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let n = self.number;
+        let fields: [(&str, &str); 10] = if n == 1 {
+            [
+                ("EV_CLASS", "BYTE"),
+                ("SCAN_1", "BYTE"),
+                ("PRIORITY", "BYTE"),
+                ("OB_NUMBR", "BYTE"),
+                ("RESERVED_1", "BYTE"),
+                ("RESERVED_2", "BYTE"),
+                ("PREV_CYCLE", "INT"),
+                ("MIN_CYCLE", "INT"),
+                ("MAX_CYCLE", "INT"),
+                ("DATE_TIME", "DATE_AND_TIME"),
+            ]
+        } else {
+            [
+                ("EV_CLASS", "BYTE"),
+                ("STRT_INF", "BYTE"),
+                ("PRIORITY", "BYTE"),
+                ("OB_NUMBR", "BYTE"),
+                ("RESERVED_1", "BYTE"),
+                ("RESERVED_2", "BYTE"),
+                ("RESERVED_3", "INT"),
+                ("RESERVED_4", "INT"),
+                ("RESERVED_5", "INT"),
+                ("DATE_TIME", "DATE_AND_TIME"),
+            ]
+        };
+        writeln!(out, "VAR_TEMP")?;
+        for (name, r#type) in fields {
+            writeln!(out, "  OB{n}_{name} : {type};")?;
+        }
+        Ok(())
+    }
+
+    /// Temporaries holding call results, declared after the start information
+    fn write_temps(&self, out: &mut impl Write, mnemonics: S7Mnemonics) -> std::io::Result<()> {
+        for (addr, r#type) in &self.lir.temps {
+            write!(out, "  T{} : ", addr.ptr)?;
+            r#type.write_awl(out, mnemonics)?;
+            writeln!(out, ";")?;
+        }
+        Ok(())
+    }
+}
+
+impl WriteAwl for S7Source<'_> {
+    fn write_awl(&self, out: &mut impl Write, mnemonics: S7Mnemonics) -> std::io::Result<()> {
+        self.write_instances(out, mnemonics)?;
+        let end = match self.kind {
+            S7SourceKind::OrganizationBlock => {
+                writeln!(out, "ORGANIZATION_BLOCK OB {}", self.number)?;
+                "END_ORGANIZATION_BLOCK"
+            }
+            S7SourceKind::Function => {
+                writeln!(out, "FUNCTION FC {} : VOID", self.number)?;
+                "END_FUNCTION"
+            }
+        };
+        writeln!(out, "TITLE = {}", self.title)?;
+        writeln!(out, "VERSION : {}", self.version)?;
+        writeln!(out)?;
+        if self.kind == S7SourceKind::OrganizationBlock {
+            self.write_start_info(out)?;
+        } else if !self.lir.temps.is_empty() {
+            writeln!(out, "VAR_TEMP")?;
+        }
+        self.write_temps(out, mnemonics)?;
+        if self.kind == S7SourceKind::OrganizationBlock || !self.lir.temps.is_empty() {
+            writeln!(out, "END_VAR")?;
+        }
+        writeln!(out, "BEGIN")?;
+        for network in &self.lir.networks {
+            writeln!(out, "NETWORK")?;
+            writeln!(out, "TITLE = {}", network.title)?;
+            for comment in &network.comments {
+                writeln!(out, "//{comment}")?;
+            }
+            writeln!(out)?;
+            for instruction in &network.instructions {
+                write!(out, "      ")?;
+                instruction.write_awl(out, mnemonics)?;
+                // `CALL` terminates its parameter list itself
+                match instruction {
+                    S7Instruction::Call(call) if !call.params.is_empty() => writeln!(out)?,
+                    _ => writeln!(out, ";")?,
+                }
+            }
+            writeln!(out)?;
+        }
+        writeln!(out, "{end}")
+    }
+}
+
+/// Characters of Windows-1252 in the range `0x80..=0x9F`, where it differs from Latin-1
+const WINDOWS_1252_HIGH: [(char, u8); 27] = [
+    ('€', 0x80),
+    ('‚', 0x82),
+    ('ƒ', 0x83),
+    ('„', 0x84),
+    ('…', 0x85),
+    ('†', 0x86),
+    ('‡', 0x87),
+    ('ˆ', 0x88),
+    ('‰', 0x89),
+    ('Š', 0x8A),
+    ('‹', 0x8B),
+    ('Œ', 0x8C),
+    ('Ž', 0x8E),
+    ('‘', 0x91),
+    ('’', 0x92),
+    ('“', 0x93),
+    ('”', 0x94),
+    ('•', 0x95),
+    ('–', 0x96),
+    ('—', 0x97),
+    ('˜', 0x98),
+    ('™', 0x99),
+    ('š', 0x9A),
+    ('›', 0x9B),
+    ('œ', 0x9C),
+    ('ž', 0x9E),
+    ('Ÿ', 0x9F),
+];
+
+/// Encode the text as Windows-1252 with CRLF line endings, unknown characters become `?`
+pub fn encode_windows_1252(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\n' => bytes.extend_from_slice(b"\r\n"),
+            '\r' => {}
+            '\0'..='\x7F' | '\u{A0}'..='\u{FF}' => bytes.push(c as u8),
+            _ => {
+                let byte = WINDOWS_1252_HIGH.iter().find(|(it, _)| *it == c);
+                bytes.push(byte.map_or(b'?', |(_, byte)| *byte));
+            }
+        }
+    }
+    bytes
+}
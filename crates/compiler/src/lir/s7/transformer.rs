@@ -1,124 +1,178 @@
+use std::rc::Rc;
+
 use crate::{
-    lir::error::{Error, Result},
+    lir::{
+        error::{Error, Result},
+        pop_stack, push_stack,
+    },
     mir::{
-        value::{MirAddress, MirAddressType},
-        Mir, MirAction, MirInstruction,
+        types::MirType,
+        value::{MirAddress, MirAddressIndex, MirAddressType},
+        Mir, MirAction, MirBlockKind, MirCallAction, MirInstruction, MirOperand,
     },
 };
 
-use super::{S7Address, S7AddressType, S7Instruction, S7Lir, S7Network};
-
-fn push_stack(stack_depth: &mut usize) -> Result<()> {
-    if *stack_depth >= 7 {
-        return Err(Error::InvalidStack);
-    }
-    *stack_depth += 1;
-    Ok(())
-}
+use super::{
+    S7Address, S7AddressIndex, S7AddressType, S7AnyPointer, S7Block, S7BlockKind, S7Call,
+    S7Constant, S7DataType, S7Instruction, S7Lir, S7Network, S7Operand, S7Param,
+};
 
-fn pop_stack(stack_depth: &mut usize) -> Result<()> {
-    if *stack_depth == 0 {
-        return Err(Error::InvalidStack);
-    }
-    *stack_depth -= 1;
-    Ok(())
-}
+/// Nesting stack of the S7-300 and S7-400 CPUs
+const STACK_DEPTH: usize = 7;
 
-fn transform_address(mir: &Mir, addr: MirAddress) -> S7Address {
+/// S7 operand of a MIR address, allocated memory is part of the `M` area
+pub fn transform_address(addr: MirAddress) -> S7Address {
     let r#type = match addr.r#type {
         MirAddressType::PhysicalInput1 => S7AddressType::Input1,
+        MirAddressType::PhysicalInput8 => S7AddressType::Input8,
+        MirAddressType::PhysicalInput16 => S7AddressType::Input16,
+        MirAddressType::PhysicalInput32 => S7AddressType::Input32,
         MirAddressType::PhysicalOutput1 => S7AddressType::Output1,
+        MirAddressType::PhysicalOutput8 => S7AddressType::Output8,
+        MirAddressType::PhysicalOutput16 => S7AddressType::Output16,
+        MirAddressType::PhysicalOutput32 => S7AddressType::Output32,
+        MirAddressType::PhysicalMemory1 | MirAddressType::Memory1 => S7AddressType::Memory1,
+        MirAddressType::PhysicalMemory8 | MirAddressType::Memory8 => S7AddressType::Memory8,
+        MirAddressType::PhysicalMemory16 | MirAddressType::Memory16 => S7AddressType::Memory16,
+        MirAddressType::PhysicalMemory32 | MirAddressType::Memory32 => S7AddressType::Memory32,
+        MirAddressType::PeripheralInput8 => S7AddressType::PeripheralInput8,
+        MirAddressType::PeripheralInput16 => S7AddressType::PeripheralInput16,
+        MirAddressType::PeripheralInput32 => S7AddressType::PeripheralInput32,
+        MirAddressType::PeripheralOutput8 => S7AddressType::PeripheralOutput8,
+        MirAddressType::PeripheralOutput16 => S7AddressType::PeripheralOutput16,
+        MirAddressType::PeripheralOutput32 => S7AddressType::PeripheralOutput32,
+        MirAddressType::PhysicalTimer => S7AddressType::Timer,
         MirAddressType::PhysicalCounter => S7AddressType::Counter,
-        MirAddressType::Memory1 => S7AddressType::Memory1,
-        MirAddressType::Memory8 => S7AddressType::Memory8,
-        MirAddressType::Memory16 => S7AddressType::Memory16,
-        MirAddressType::Memory32 => S7AddressType::Memory32,
+        MirAddressType::DataBlock1 => S7AddressType::DataBlock1,
+        MirAddressType::DataBlock8 => S7AddressType::DataBlock8,
+        MirAddressType::DataBlock16 => S7AddressType::DataBlock16,
+        MirAddressType::DataBlock32 => S7AddressType::DataBlock32,
+        MirAddressType::Temp1 => S7AddressType::Temp1,
+        MirAddressType::Temp8 => S7AddressType::Temp8,
+        MirAddressType::Temp16 => S7AddressType::Temp16,
+        MirAddressType::Temp32 => S7AddressType::Temp32,
     };
-    let ptr = if addr.is_virtual() {
-        mir.allocator.byte_offset() as u16 + addr.ptr
-    } else {
-        addr.ptr
+    let index = match addr.index {
+        MirAddressIndex::Direct => S7AddressIndex::Direct,
+        MirAddressIndex::Memory { ptr } => S7AddressIndex::Memory { ptr },
+        MirAddressIndex::Register => S7AddressIndex::Register,
     };
+    let db = addr.db;
+    let ptr = addr.ptr;
     let bit = addr.bit;
-    S7Address { r#type, ptr, bit }
+    S7Address {
+        r#type,
+        db,
+        ptr,
+        bit,
+        index,
+    }
 }
 
-fn assert_bit(mir: &Mir, addr: MirAddress) -> Result<S7Address> {
+fn assert_bit(addr: MirAddress) -> Result<S7Address> {
     if !addr.is_bit_address() {
         return Err(Error::NonBitAddress);
     }
-    Ok(transform_address(mir, addr))
+    Ok(transform_address(addr))
 }
 
-fn assert_any_byte(mir: &Mir, addr: MirAddress) -> Result<S7Address> {
-    if !addr.is_any_byte_address() {
+fn assert_bit_readable(addr: MirAddress) -> Result<S7Address> {
+    if !addr.is_bit_readable() {
         return Err(Error::NonBitAddress);
     }
-    Ok(transform_address(mir, addr))
+    Ok(transform_address(addr))
+}
+
+fn assert_any_byte(addr: MirAddress) -> Result<S7Address> {
+    if !addr.is_any_byte_address() {
+        return Err(Error::NonByteAddress);
+    }
+    Ok(transform_address(addr))
 }
 
-fn assert_counter(mir: &Mir, addr: MirAddress) -> Result<S7Address> {
+fn assert_counter(addr: MirAddress) -> Result<S7Address> {
     if addr.r#type != MirAddressType::PhysicalCounter {
         return Err(Error::NonCounterAddress);
     }
-    Ok(transform_address(mir, addr))
+    Ok(transform_address(addr))
+}
+
+pub(crate) fn transform_constant(value: u32, r#type: MirType) -> Result<S7Constant> {
+    match r#type {
+        MirType::Bool => Ok(S7Constant::Bool(value != 0)),
+        MirType::Byte => Ok(S7Constant::Byte(value as u8)),
+        MirType::Word => Ok(S7Constant::Word(value as u16)),
+        MirType::DWord => Ok(S7Constant::DWord(value)),
+        MirType::Int => Ok(S7Constant::Int(value as i16)),
+        MirType::DInt => Ok(S7Constant::DInt(value as i32)),
+        MirType::Real => Ok(S7Constant::Real(value as f32)),
+        MirType::Time => Ok(S7Constant::Time(value as i32)),
+        MirType::Pointer => Ok(S7Constant::Pointer(value)),
+    }
+}
+
+/// Open the data block of an indirect operand before the instruction at `index`
+fn open_data_block(dst: &mut Vec<S7Instruction>, index: usize) {
+    let Some(addr) = dst.get(index).and_then(|it| it.addr()) else {
+        return;
+    };
+    if addr.db != 0 && addr.is_indirect() {
+        dst.insert(index, S7Instruction::OpenDataBlock { db: addr.db });
+    }
 }
 
-fn transform_instructions(
-    mir: &Mir,
-    src: &[MirInstruction],
-    dst: &mut Vec<S7Instruction>,
-) -> Result<()> {
+fn transform_instructions(src: &[MirInstruction], dst: &mut Vec<S7Instruction>) -> Result<()> {
     let mut stack_depth = 0;
     for &instruction in src {
+        let index = dst.len();
         match instruction {
             MirInstruction::Dummy => {}
             MirInstruction::And { addr } => {
-                let addr = assert_bit(mir, addr)?;
+                let addr = assert_bit_readable(addr)?;
                 dst.push(S7Instruction::And { addr });
             }
             MirInstruction::AndNot { addr } => {
-                let addr = assert_bit(mir, addr)?;
+                let addr = assert_bit_readable(addr)?;
                 dst.push(S7Instruction::AndNot { addr });
             }
             MirInstruction::Or { addr } => {
-                let addr = assert_bit(mir, addr)?;
+                let addr = assert_bit_readable(addr)?;
                 dst.push(S7Instruction::Or { addr });
             }
             MirInstruction::OrNot { addr } => {
-                let addr = assert_bit(mir, addr)?;
+                let addr = assert_bit_readable(addr)?;
                 dst.push(S7Instruction::OrNot { addr });
             }
             MirInstruction::Xor { addr } => {
-                let addr = assert_bit(mir, addr)?;
+                let addr = assert_bit_readable(addr)?;
                 dst.push(S7Instruction::Xor { addr });
             }
             MirInstruction::XorNot { addr } => {
-                let addr = assert_bit(mir, addr)?;
+                let addr = assert_bit_readable(addr)?;
                 dst.push(S7Instruction::XorNot { addr });
             }
             MirInstruction::AndStart => {
-                push_stack(&mut stack_depth)?;
+                push_stack(&mut stack_depth, STACK_DEPTH)?;
                 dst.push(S7Instruction::AndStart);
             }
             MirInstruction::AndNotStart => {
-                push_stack(&mut stack_depth)?;
+                push_stack(&mut stack_depth, STACK_DEPTH)?;
                 dst.push(S7Instruction::AndNotStart);
             }
             MirInstruction::OrStart => {
-                push_stack(&mut stack_depth)?;
+                push_stack(&mut stack_depth, STACK_DEPTH)?;
                 dst.push(S7Instruction::OrStart);
             }
             MirInstruction::OrNotStart => {
-                push_stack(&mut stack_depth)?;
+                push_stack(&mut stack_depth, STACK_DEPTH)?;
                 dst.push(S7Instruction::OrNotStart);
             }
             MirInstruction::XorStart => {
-                push_stack(&mut stack_depth)?;
+                push_stack(&mut stack_depth, STACK_DEPTH)?;
                 dst.push(S7Instruction::XorStart);
             }
             MirInstruction::XorNotStart => {
-                push_stack(&mut stack_depth)?;
+                push_stack(&mut stack_depth, STACK_DEPTH)?;
                 dst.push(S7Instruction::XorNotStart);
             }
             MirInstruction::End => {
@@ -126,15 +180,15 @@ fn transform_instructions(
                 dst.push(S7Instruction::End);
             }
             MirInstruction::AssignBit { addr } => {
-                let addr = assert_bit(mir, addr)?;
+                let addr = assert_bit(addr)?;
                 dst.push(S7Instruction::AssignBit { addr });
             }
             MirInstruction::ResetBit { addr } => {
-                let addr = assert_bit(mir, addr)?;
+                let addr = assert_bit(addr)?;
                 dst.push(S7Instruction::ResetBit { addr });
             }
             MirInstruction::SetBit { addr } => {
-                let addr = assert_bit(mir, addr)?;
+                let addr = assert_bit(addr)?;
                 dst.push(S7Instruction::SetBit { addr });
             }
             MirInstruction::Not => dst.push(S7Instruction::Not),
@@ -142,34 +196,82 @@ fn transform_instructions(
             MirInstruction::Clear => dst.push(S7Instruction::Clear),
             MirInstruction::Save => dst.push(S7Instruction::Save),
             MirInstruction::CounterLoadInt { addr } => {
-                let addr = assert_counter(mir, addr)?;
+                let addr = assert_counter(addr)?;
                 dst.push(S7Instruction::CounterLoadInt { addr });
             }
             MirInstruction::CounterLoadBcd { addr } => {
-                let addr = assert_counter(mir, addr)?;
+                let addr = assert_counter(addr)?;
                 dst.push(S7Instruction::CounterLoadBcd { addr });
             }
             MirInstruction::CounterReset { addr } => {
-                let addr = assert_counter(mir, addr)?;
+                let addr = assert_counter(addr)?;
                 dst.push(S7Instruction::CounterReset { addr });
             }
             MirInstruction::CounterSet { addr } => {
-                let addr = assert_counter(mir, addr)?;
+                let addr = assert_counter(addr)?;
                 dst.push(S7Instruction::CounterSet { addr });
             }
             MirInstruction::CounterForward { addr } => {
-                let addr = assert_counter(mir, addr)?;
+                let addr = assert_counter(addr)?;
                 dst.push(S7Instruction::CounterForward { addr });
             }
             MirInstruction::CounterBackward { addr } => {
-                let addr = assert_counter(mir, addr)?;
+                let addr = assert_counter(addr)?;
                 dst.push(S7Instruction::CounterBackward { addr });
             }
+            MirInstruction::Load { addr } => {
+                let addr = assert_any_byte(addr)?;
+                dst.push(S7Instruction::Load { addr });
+            }
+            MirInstruction::LoadConst { value, r#type } => {
+                let value = transform_constant(value, r#type)?;
+                dst.push(S7Instruction::LoadConst { value });
+            }
             MirInstruction::Transfer { addr } => {
-                let addr = assert_any_byte(mir, addr)?;
+                let addr = assert_any_byte(addr)?;
                 dst.push(S7Instruction::Transfer { addr });
             }
+            MirInstruction::AndWord => dst.push(S7Instruction::AndWord),
+            MirInstruction::OrWord => dst.push(S7Instruction::OrWord),
+            MirInstruction::XorWord => dst.push(S7Instruction::XorWord),
+            MirInstruction::AndDWord => dst.push(S7Instruction::AndDWord),
+            MirInstruction::OrDWord => dst.push(S7Instruction::OrDWord),
+            MirInstruction::XorDWord => dst.push(S7Instruction::XorDWord),
+            MirInstruction::InvertInt => dst.push(S7Instruction::InvertInt),
+            MirInstruction::InvertDInt => dst.push(S7Instruction::InvertDInt),
+            MirInstruction::ShiftLeftWord { amount } => {
+                dst.push(S7Instruction::ShiftLeftWord { amount })
+            }
+            MirInstruction::ShiftRightWord { amount } => {
+                dst.push(S7Instruction::ShiftRightWord { amount })
+            }
+            MirInstruction::ShiftLeftDWord { amount } => {
+                dst.push(S7Instruction::ShiftLeftDWord { amount })
+            }
+            MirInstruction::ShiftRightDWord { amount } => {
+                dst.push(S7Instruction::ShiftRightDWord { amount })
+            }
+            MirInstruction::RotateLeftDWord { amount } => {
+                dst.push(S7Instruction::RotateLeftDWord { amount })
+            }
+            MirInstruction::RotateRightDWord { amount } => {
+                dst.push(S7Instruction::RotateRightDWord { amount })
+            }
+            MirInstruction::BcdToInt => dst.push(S7Instruction::BcdToInt),
+            MirInstruction::IntToBcd => dst.push(S7Instruction::IntToBcd),
+            MirInstruction::IntToDInt => dst.push(S7Instruction::IntToDInt),
+            MirInstruction::DIntToReal => dst.push(S7Instruction::DIntToReal),
+            MirInstruction::Round => dst.push(S7Instruction::Round),
+            MirInstruction::Trunc => dst.push(S7Instruction::Trunc),
+            MirInstruction::LoadAddressRegister => dst.push(S7Instruction::LoadAddressRegister),
+            MirInstruction::TransferAddressRegister => {
+                dst.push(S7Instruction::TransferAddressRegister)
+            }
+            MirInstruction::AddAddressRegister { offset } => {
+                dst.push(S7Instruction::AddAddressRegister { offset })
+            }
         }
+        open_data_block(dst, index);
     }
     if stack_depth != 0 {
         return Err(Error::InvalidStack);
@@ -177,18 +279,76 @@ fn transform_instructions(
     Ok(())
 }
 
-fn transform_action(lir: &mut S7Lir, mir: &Mir, action: &MirAction) -> Result<()> {
-    let network = &mut lir.networks[0];
-    // Clear `/ER`
-    network.instructions.push(S7Instruction::Clear);
+pub fn transform_data_type(r#type: MirType) -> Result<S7DataType> {
+    match r#type {
+        MirType::Bool => Ok(S7DataType::Bool),
+        MirType::Byte => Ok(S7DataType::Byte),
+        MirType::Word => Ok(S7DataType::Word),
+        MirType::DWord => Ok(S7DataType::DWord),
+        MirType::Int => Ok(S7DataType::Int),
+        MirType::DInt => Ok(S7DataType::DInt),
+        MirType::Real => Ok(S7DataType::Real),
+        MirType::Time => Ok(S7DataType::Time),
+        MirType::Pointer => Err(Error::NonByteAddress),
+    }
+}
+
+pub(crate) fn transform_operand(operand: MirOperand) -> Result<S7Operand> {
+    match operand {
+        MirOperand::Address(addr) => Ok(S7Operand::Address(transform_address(addr))),
+        MirOperand::Any(any) => Ok(S7Operand::Any(S7AnyPointer {
+            addr: assert_bit(any.addr)?,
+            r#type: transform_data_type(any.r#type)?,
+            count: any.count,
+        })),
+        MirOperand::Constant { value, r#type } => {
+            Ok(S7Operand::Constant(transform_constant(value, r#type)?))
+        }
+    }
+}
+
+fn transform_call(call: &MirCallAction) -> Result<S7Call> {
+    let kind = match call.block.kind {
+        MirBlockKind::Function => S7BlockKind::Function,
+        MirBlockKind::FunctionBlock => S7BlockKind::FunctionBlock,
+        MirBlockKind::SystemFunction => S7BlockKind::SystemFunction,
+        MirBlockKind::SystemFunctionBlock => S7BlockKind::SystemFunctionBlock,
+    };
+    let mut params = Vec::with_capacity(call.params.len());
+    for param in &call.params {
+        params.push(S7Param {
+            name: param.name.clone(),
+            value: transform_operand(param.value)?,
+            output: param.output,
+        });
+    }
+    Ok(S7Call {
+        block: S7Block {
+            kind,
+            number: call.block.number,
+        },
+        instance: call.instance,
+        params,
+    })
+}
+
+fn transform_action(network: &mut S7Network, action: &MirAction) -> Result<()> {
     match action {
         MirAction::Raw(raw) => {
-            transform_instructions(mir, &raw.instructions, &mut network.instructions)?;
+            transform_instructions(&raw.instructions, &mut network.instructions)?;
         }
         MirAction::Output(output) => {
-            let addr = assert_bit(mir, output.address)?;
-            transform_instructions(mir, &output.instructions, &mut network.instructions)?;
+            let addr = assert_bit(output.address)?;
+            transform_instructions(&output.instructions, &mut network.instructions)?;
+            let index = network.instructions.len();
             network.instructions.push(S7Instruction::AssignBit { addr });
+            open_data_block(&mut network.instructions, index);
+        }
+        MirAction::Call(call) => {
+            let call = transform_call(call)?;
+            network
+                .instructions
+                .push(S7Instruction::Call(Rc::new(call)));
         }
     }
     Ok(())
@@ -196,9 +356,20 @@ fn transform_action(lir: &mut S7Lir, mir: &Mir, action: &MirAction) -> Result<()
 
 pub fn transform(mir: &Mir) -> Result<S7Lir> {
     let mut lir = S7Lir::default();
-    lir.networks.push(S7Network::default());
-    for action in &mir.actions {
-        transform_action(&mut lir, mir, action)?;
+    for network in &mir.networks {
+        let mut s7_network = S7Network {
+            title: network.title.clone(),
+            comments: network.comments.clone(),
+            instructions: Vec::new(),
+        };
+        for action in &mir.actions[network.actions.clone()] {
+            transform_action(&mut s7_network, action)?;
+        }
+        lir.networks.push(s7_network);
+    }
+    for &(addr, r#type) in &mir.allocator.temps {
+        lir.temps
+            .push((transform_address(addr), transform_data_type(r#type)?));
     }
     Ok(lir)
 }
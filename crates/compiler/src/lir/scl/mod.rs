@@ -0,0 +1,445 @@
+//! Structured Control Language for CPUs without STL support (S7-1200/1500)
+
+use std::{io::Write, rc::Rc};
+
+use super::s7::{
+    S7Address, S7AddressIndex, S7AddressType, S7AnyPointer, S7Constant, S7DataType, S7Mnemonics,
+    WriteAwl,
+};
+
+pub mod transformer;
+
+/// Thing that can be written to a [Write] in SCL-form
+pub trait WriteScl {
+    fn write_scl(&self, out: &mut impl Write, mnemonics: S7Mnemonics) -> std::io::Result<()>;
+}
+
+#[derive(Debug, Default)]
+pub struct SclLir {
+    /// Static instances of timers, counters and function blocks
+    pub instances: Vec<SclInstance>,
+    /// Block temporaries (`#T0`) declared in `VAR_TEMP`
+    pub temps: Vec<(S7Address, S7DataType)>,
+    pub networks: Vec<SclNetwork>,
+}
+
+/// `Counter1 : CTU;`
+#[derive(Debug)]
+pub struct SclInstance {
+    pub name: Rc<str>,
+    pub r#type: SclBlock,
+}
+
+/// Called or instantiated block
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SclBlock {
+    /// Block of the system library (`BLKMOV`, `TON`, `CTU`)
+    System(&'static str),
+    /// Block declared by the user, written as quoted symbol (`"Motor"`)
+    Declared(Rc<str>),
+}
+
+impl WriteScl for SclBlock {
+    fn write_scl(&self, out: &mut impl Write, _: S7Mnemonics) -> std::io::Result<()> {
+        match self {
+            Self::System(name) => write!(out, "{name}"),
+            Self::Declared(name) => write!(out, "\"{name}\""),
+        }
+    }
+}
+
+/// Statements of a single F++ statement, written as `REGION`
+#[derive(Debug, Default)]
+pub struct SclNetwork {
+    pub title: Rc<str>,
+    pub comments: Vec<Rc<str>>,
+    pub statements: Vec<SclStatement>,
+}
+
+#[derive(Debug)]
+pub enum SclStatement {
+    /// `target := value;`
+    Assign { target: SclExpr, value: SclExpr },
+    /// `IF cond THEN .. END_IF;`
+    If {
+        cond: SclExpr,
+        then: Vec<SclStatement>,
+    },
+    /// `ret := name(..);` or `name(..);`
+    Call {
+        ret_val: Option<SclExpr>,
+        call: SclCall,
+    },
+}
+
+impl SclStatement {
+    fn write_scl_indented(
+        &self,
+        out: &mut impl Write,
+        mnemonics: S7Mnemonics,
+        indent: usize,
+    ) -> std::io::Result<()> {
+        write!(out, "{:indent$}", "")?;
+        match self {
+            Self::Assign { target, value } => {
+                target.write_scl(out, mnemonics)?;
+                write!(out, " := ")?;
+                value.write_scl(out, mnemonics)?;
+                writeln!(out, ";")
+            }
+            Self::If { cond, then } => {
+                write!(out, "IF ")?;
+                cond.write_scl(out, mnemonics)?;
+                writeln!(out, " THEN")?;
+                for statement in then {
+                    statement.write_scl_indented(out, mnemonics, indent + 4)?;
+                }
+                writeln!(out, "{:indent$}END_IF;", "")
+            }
+            Self::Call { ret_val, call } => {
+                if let Some(ret_val) = ret_val {
+                    ret_val.write_scl(out, mnemonics)?;
+                    write!(out, " := ")?;
+                }
+                call.write_scl(out, mnemonics)?;
+                writeln!(out, ";")
+            }
+        }
+    }
+}
+
+/// `SHL(IN := %MW10, N := 3)`, parameters without name are positional
+#[derive(Debug, Clone)]
+pub struct SclCall {
+    pub target: SclCallTarget,
+    pub params: Vec<SclParam>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SclCallTarget {
+    Function(SclFunction),
+    Block(SclBlock),
+    /// `#TON_DB100`, instance declared in [SclLir::instances]
+    Instance(Rc<str>),
+}
+
+impl WriteScl for SclCall {
+    fn write_scl(&self, out: &mut impl Write, mnemonics: S7Mnemonics) -> std::io::Result<()> {
+        match &self.target {
+            SclCallTarget::Function(function) => write!(out, "{}", function.scl_name())?,
+            SclCallTarget::Block(block) => block.write_scl(out, mnemonics)?,
+            SclCallTarget::Instance(instance) => write!(out, "#{instance}")?,
+        }
+        write!(out, "(")?;
+        for (i, param) in self.params.iter().enumerate() {
+            if i != 0 {
+                write!(out, ", ")?;
+            }
+            if let Some(name) = &param.name {
+                let assign = if param.output { "=>" } else { ":=" };
+                write!(out, "{name} {assign} ")?;
+            }
+            param.value.write_scl(out, mnemonics)?;
+        }
+        write!(out, ")")
+    }
+}
+
+/// Built-in function replacing an accumulator instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SclFunction {
+    Shl,
+    Shr,
+    Rol,
+    Ror,
+    BcdToInt,
+    IntToBcd,
+    IntToDInt,
+    DIntToReal,
+    Round,
+    Trunc,
+}
+
+impl SclFunction {
+    pub fn scl_name(self) -> &'static str {
+        match self {
+            Self::Shl => "SHL",
+            Self::Shr => "SHR",
+            Self::Rol => "ROL",
+            Self::Ror => "ROR",
+            Self::BcdToInt => "BCD16_TO_INT",
+            Self::IntToBcd => "INT_TO_BCD16",
+            Self::IntToDInt => "INT_TO_DINT",
+            Self::DIntToReal => "DINT_TO_REAL",
+            Self::Round => "ROUND",
+            Self::Trunc => "TRUNC",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SclParam {
+    pub name: Option<Rc<str>>,
+    pub value: SclExpr,
+    /// Written with `=>`
+    pub output: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum SclExpr {
+    Bool(bool),
+    Constant(S7Constant),
+    /// `%I0.0`
+    Address(S7Address),
+    /// `%M4.0`, memory reserved by the allocator
+    Allocated(S7Address),
+    /// `P#DB10.DBX0.0 BYTE 20`
+    Any(S7AnyPointer),
+    /// `#Counter1.CV`
+    Member {
+        instance: Rc<str>,
+        member: &'static str,
+    },
+    Not(Box<SclExpr>),
+    Binary(Box<SclBinary>),
+    Call(SclCall),
+}
+
+impl SclExpr {
+    pub fn binary(op: SclOp, left: SclExpr, right: SclExpr) -> Self {
+        Self::Binary(Box::new(SclBinary { op, left, right }))
+    }
+
+    /// Binding strength, operands with a lower precedence than their parent need parentheses
+    pub(crate) fn precedence(&self) -> u8 {
+        match self {
+            Self::Binary(binary) => binary.op.precedence(),
+            Self::Not(_) => 6,
+            _ => 7,
+        }
+    }
+
+    fn write_operand(
+        &self,
+        out: &mut impl Write,
+        mnemonics: S7Mnemonics,
+        precedence: u8,
+    ) -> std::io::Result<()> {
+        if self.precedence() < precedence {
+            write!(out, "(")?;
+            self.write_scl(out, mnemonics)?;
+            return write!(out, ")");
+        }
+        self.write_scl(out, mnemonics)
+    }
+}
+
+impl WriteScl for SclExpr {
+    fn write_scl(&self, out: &mut impl Write, mnemonics: S7Mnemonics) -> std::io::Result<()> {
+        match self {
+            Self::Bool(value) => write!(out, "{}", if *value { "TRUE" } else { "FALSE" }),
+            Self::Constant(constant) => constant.write_scl(out, mnemonics),
+            Self::Address(addr) | Self::Allocated(addr) => addr.write_scl(out, mnemonics),
+            Self::Any(any) => any.write_scl(out, mnemonics),
+            Self::Member { instance, member } => write!(out, "#{instance}.{member}"),
+            Self::Not(value) => {
+                write!(out, "NOT ")?;
+                value.write_operand(out, mnemonics, self.precedence())
+            }
+            Self::Binary(binary) => {
+                let precedence = binary.op.precedence();
+                binary.left.write_operand(out, mnemonics, precedence)?;
+                write!(out, " {} ", binary.op.symbol())?;
+                // Comparisons are not associative, equal precedence on the right needs parentheses
+                let right = match binary.op {
+                    SclOp::NotEqual => precedence + 1,
+                    _ => precedence,
+                };
+                binary.right.write_operand(out, mnemonics, right)
+            }
+            Self::Call(call) => call.write_scl(out, mnemonics),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SclBinary {
+    pub op: SclOp,
+    pub left: SclExpr,
+    pub right: SclExpr,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SclOp {
+    And,
+    Or,
+    Xor,
+    NotEqual,
+}
+
+impl SclOp {
+    pub(crate) fn precedence(self) -> u8 {
+        match self {
+            Self::Or => 1,
+            Self::Xor => 2,
+            Self::And => 3,
+            Self::NotEqual => 4,
+        }
+    }
+
+    pub(crate) fn symbol(self) -> &'static str {
+        match self {
+            Self::And => "AND",
+            Self::Or => "OR",
+            Self::Xor => "XOR",
+            Self::NotEqual => "<>",
+        }
+    }
+}
+
+impl WriteScl for S7Constant {
+    fn write_scl(&self, out: &mut impl Write, mnemonics: S7Mnemonics) -> std::io::Result<()> {
+        match self {
+            Self::Byte(value) => write!(out, "BYTE#16#{value:02X}"),
+            Self::Word(value) => write!(out, "WORD#16#{value:04X}"),
+            Self::DWord(value) => write!(out, "DWORD#16#{value:08X}"),
+            Self::DInt(value) => write!(out, "DINT#{value}"),
+            Self::Real(value) => write!(out, "{value:?}"),
+            Self::Bool(value) => write!(out, "{}", if *value { "TRUE" } else { "FALSE" }),
+            Self::Int(_) | Self::Time(_) | Self::Pointer(_) => self.write_awl(out, mnemonics),
+        }
+    }
+}
+
+impl WriteScl for S7AnyPointer {
+    fn write_scl(&self, out: &mut impl Write, mnemonics: S7Mnemonics) -> std::io::Result<()> {
+        write!(out, "P#")?;
+        if self.addr.db != 0 {
+            write!(out, "DB{}.", self.addr.db)?;
+        }
+        self.addr.r#type.write_awl(out, mnemonics)?;
+        write!(out, "{}.{} ", self.addr.ptr, self.addr.bit)?;
+        self.r#type.write_awl(out, mnemonics)?;
+        write!(out, " {}", self.count)
+    }
+}
+
+impl WriteScl for S7Address {
+    fn write_scl(&self, out: &mut impl Write, mnemonics: S7Mnemonics) -> std::io::Result<()> {
+        assert_eq!(self.index, S7AddressIndex::Direct, "Indirect SCL operand");
+        if self.is_temp() {
+            return write!(out, "#T{}", self.ptr);
+        }
+        // Peripheral operands are written as their process image operand with `:P`
+        let (r#type, peripheral) = match self.r#type {
+            S7AddressType::PeripheralInput8 => (S7AddressType::Input8, true),
+            S7AddressType::PeripheralInput16 => (S7AddressType::Input16, true),
+            S7AddressType::PeripheralInput32 => (S7AddressType::Input32, true),
+            S7AddressType::PeripheralOutput8 => (S7AddressType::Output8, true),
+            S7AddressType::PeripheralOutput16 => (S7AddressType::Output16, true),
+            S7AddressType::PeripheralOutput32 => (S7AddressType::Output32, true),
+            r#type => (r#type, false),
+        };
+        write!(out, "%")?;
+        if self.db != 0 {
+            write!(out, "DB{}.", self.db)?;
+        }
+        r#type.write_awl(out, mnemonics)?;
+        if self.is_bit_address() {
+            write!(out, "{}.{}", self.ptr, self.bit)?;
+        } else {
+            write!(out, "{}", self.ptr)?;
+        }
+        if peripheral {
+            write!(out, ":P")?;
+        }
+        Ok(())
+    }
+}
+
+/// Kind of the generated code block
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SclSourceKind {
+    /// `FUNCTION_BLOCK`, required for static instances
+    FunctionBlock,
+    /// `FUNCTION "Main" : Void`
+    Function,
+}
+
+/// External source file (`*.scl`) with a single code block
+#[derive(Debug)]
+pub struct SclSource<'a> {
+    pub name: String,
+    /// `VERSION : 0.1`
+    pub version: String,
+    pub lir: &'a SclLir,
+}
+
+impl<'a> SclSource<'a> {
+    pub fn new(name: impl Into<String>, lir: &'a SclLir) -> Self {
+        Self {
+            name: name.into(),
+            version: "0.1".into(),
+            lir,
+        }
+    }
+
+    /// Functions have no static memory, instances need a function block
+    pub fn kind(&self) -> SclSourceKind {
+        if self.lir.instances.is_empty() {
+            SclSourceKind::Function
+        } else {
+            SclSourceKind::FunctionBlock
+        }
+    }
+}
+
+impl WriteScl for SclSource<'_> {
+    fn write_scl(&self, out: &mut impl Write, mnemonics: S7Mnemonics) -> std::io::Result<()> {
+        let end = match self.kind() {
+            SclSourceKind::FunctionBlock => {
+                writeln!(out, "FUNCTION_BLOCK \"{}\"", self.name)?;
+                "END_FUNCTION_BLOCK"
+            }
+            SclSourceKind::Function => {
+                writeln!(out, "FUNCTION \"{}\" : Void", self.name)?;
+                "END_FUNCTION"
+            }
+        };
+        // Absolute operands and `ANY` pointers need standard access
+        writeln!(out, "{{ S7_Optimized_Access := 'FALSE' }}")?;
+        writeln!(out, "VERSION : {}", self.version)?;
+        if !self.lir.instances.is_empty() {
+            writeln!(out, "   VAR")?;
+            for instance in &self.lir.instances {
+                write!(out, "      {} : ", instance.name)?;
+                instance.r#type.write_scl(out, mnemonics)?;
+                writeln!(out, ";")?;
+            }
+            writeln!(out, "   END_VAR")?;
+        }
+        if !self.lir.temps.is_empty() {
+            writeln!(out, "   VAR_TEMP")?;
+            for (addr, r#type) in &self.lir.temps {
+                write!(out, "      T{} : ", addr.ptr)?;
+                r#type.write_awl(out, mnemonics)?;
+                writeln!(out, ";")?;
+            }
+            writeln!(out, "   END_VAR")?;
+        }
+        writeln!(out)?;
+        writeln!(out, "BEGIN")?;
+        for network in &self.lir.networks {
+            writeln!(out, "    REGION {}", network.title)?;
+            for comment in &network.comments {
+                writeln!(out, "        //{comment}")?;
+            }
+            for statement in &network.statements {
+                statement.write_scl_indented(out, mnemonics, 8)?;
+            }
+            writeln!(out, "    END_REGION")?;
+            writeln!(out)?;
+        }
+        writeln!(out, "{end}")
+    }
+}
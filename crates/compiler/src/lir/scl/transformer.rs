@@ -0,0 +1,637 @@
+//! Decompiles the instruction lists of a [Mir] into SCL statements
+
+use std::rc::Rc;
+
+use crate::{
+    lir::{
+        error::{Error, Result},
+        s7::{
+            transformer::{
+                transform_address, transform_constant, transform_data_type, transform_operand,
+            },
+            S7Address, S7AddressIndex, S7AddressType, S7Constant, S7Operand,
+        },
+    },
+    mir::{
+        builtin::system::system_block_name,
+        value::{MirAddress, MirAddressType},
+        Mir, MirAction, MirBlockKind, MirCallAction, MirInstruction, MirOperand,
+    },
+};
+
+use super::{
+    SclBlock, SclCall, SclCallTarget, SclExpr, SclFunction, SclInstance, SclLir, SclNetwork, SclOp,
+    SclParam, SclStatement,
+};
+
+/// IEC counter replacing a S5 counter, chosen by the instructions using it
+#[derive(Debug)]
+struct Counter {
+    number: u16,
+    up: bool,
+    down: bool,
+    set: bool,
+    reset: bool,
+}
+
+impl Counter {
+    /// `CTU` counts up and resets, `CTD` counts down and loads, everything else needs `CTUD`
+    fn r#type(&self) -> &'static str {
+        if !self.down && !self.set {
+            "CTU"
+        } else if !self.up && !self.reset {
+            "CTD"
+        } else {
+            "CTUD"
+        }
+    }
+
+    fn name(number: u16) -> Rc<str> {
+        format!("Counter{number}").into()
+    }
+}
+
+/// Find all counters and the instructions applied to them
+fn scan_counters(mir: &Mir) -> Vec<Counter> {
+    let mut counters: Vec<Counter> = Vec::new();
+    let instructions = mir.actions.iter().flat_map(|action| match action {
+        MirAction::Raw(raw) => raw.instructions.as_slice(),
+        MirAction::Output(output) => output.instructions.as_slice(),
+        MirAction::Call(_) => &[],
+    });
+    for instruction in instructions {
+        let addr = match instruction {
+            MirInstruction::And { addr }
+            | MirInstruction::AndNot { addr }
+            | MirInstruction::Or { addr }
+            | MirInstruction::OrNot { addr }
+            | MirInstruction::Xor { addr }
+            | MirInstruction::XorNot { addr }
+            | MirInstruction::CounterLoadInt { addr }
+            | MirInstruction::CounterLoadBcd { addr }
+            | MirInstruction::CounterReset { addr }
+            | MirInstruction::CounterSet { addr }
+            | MirInstruction::CounterForward { addr }
+            | MirInstruction::CounterBackward { addr } => *addr,
+            _ => continue,
+        };
+        if addr.r#type != MirAddressType::PhysicalCounter {
+            continue;
+        }
+        let index = match counters.iter().position(|it| it.number == addr.ptr) {
+            Some(index) => index,
+            None => {
+                counters.push(Counter {
+                    number: addr.ptr,
+                    up: false,
+                    down: false,
+                    set: false,
+                    reset: false,
+                });
+                counters.len() - 1
+            }
+        };
+        let counter = &mut counters[index];
+        match instruction {
+            MirInstruction::CounterForward { .. } => counter.up = true,
+            MirInstruction::CounterBackward { .. } => counter.down = true,
+            MirInstruction::CounterSet { .. } => counter.set = true,
+            MirInstruction::CounterReset { .. } => counter.reset = true,
+            _ => {}
+        }
+    }
+    counters
+}
+
+/// Logic string of one bracket level, `AND` binds stronger than `OR`
+#[derive(Debug, Default)]
+struct Logic {
+    /// Completed `AND` groups, combined with `OR`
+    or_terms: Vec<SclExpr>,
+    and: Option<SclExpr>,
+    /// The next operation starts a new logic string
+    first: bool,
+}
+
+impl Logic {
+    fn new() -> Self {
+        Self {
+            first: true,
+            ..Default::default()
+        }
+    }
+
+    fn rlo(&self) -> Result<SclExpr> {
+        let mut terms = self.or_terms.iter().chain(&self.and).cloned();
+        let first = terms.next().ok_or(Error::InvalidStack)?;
+        Ok(terms.fold(first, |left, right| SclExpr::binary(SclOp::Or, left, right)))
+    }
+
+    fn set_rlo(&mut self, value: SclExpr) {
+        self.or_terms.clear();
+        self.and = Some(value);
+    }
+
+    fn and(&mut self, value: SclExpr) {
+        if self.first {
+            self.first = false;
+            return self.set_rlo(value);
+        }
+        self.and = Some(match self.and.take() {
+            Some(and) => SclExpr::binary(SclOp::And, and, value),
+            None => value,
+        });
+    }
+
+    fn or(&mut self, value: SclExpr) {
+        if self.first {
+            self.first = false;
+            return self.set_rlo(value);
+        }
+        if let Some(and) = self.and.take() {
+            self.or_terms.push(and);
+        }
+        self.and = Some(value);
+    }
+
+    fn xor(&mut self, value: SclExpr) -> Result<()> {
+        if self.first {
+            self.first = false;
+            self.set_rlo(value);
+            return Ok(());
+        }
+        let rlo = self.rlo()?;
+        self.set_rlo(SclExpr::binary(SclOp::Xor, rlo, value));
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Bracket {
+    And,
+    AndNot,
+    Or,
+    OrNot,
+    Xor,
+    XorNot,
+}
+
+#[derive(Debug)]
+struct Decompiler<'a> {
+    mir: &'a Mir,
+    counters: &'a [Counter],
+    logic: Logic,
+    brackets: Vec<(Bracket, Logic)>,
+    accu1: Option<SclExpr>,
+    accu2: Option<SclExpr>,
+    statements: Vec<SclStatement>,
+}
+
+impl<'a> Decompiler<'a> {
+    fn new(mir: &'a Mir, counters: &'a [Counter]) -> Self {
+        Self {
+            mir,
+            counters,
+            logic: Logic::new(),
+            brackets: Vec::new(),
+            accu1: None,
+            accu2: None,
+            statements: Vec::new(),
+        }
+    }
+
+    fn counter(&self, addr: MirAddress) -> Result<Rc<str>> {
+        if addr.r#type != MirAddressType::PhysicalCounter {
+            return Err(Error::NonCounterAddress);
+        }
+        let counter = self
+            .counters
+            .iter()
+            .find(|it| it.number == addr.ptr)
+            .expect("Scanned counter");
+        Ok(Counter::name(counter.number))
+    }
+
+    fn counter_value(&self, addr: MirAddress) -> Result<SclExpr> {
+        let instance = self.counter(addr)?;
+        Ok(SclExpr::Member {
+            instance,
+            member: "CV",
+        })
+    }
+
+    /// Bit operand of a logic operation, counters are set while their value is not zero
+    fn bit(&self, addr: MirAddress) -> Result<SclExpr> {
+        match addr.r#type {
+            MirAddressType::PhysicalCounter => Ok(SclExpr::binary(
+                SclOp::NotEqual,
+                self.counter_value(addr)?,
+                SclExpr::Constant(S7Constant::Int(0)),
+            )),
+            MirAddressType::PhysicalTimer => Err(Error::Unsupported(
+                "S5 timers have no IEC equivalent, use TON instead",
+            )),
+            _ if addr.is_bit_readable() => operand(addr),
+            _ => Err(Error::NonBitAddress),
+        }
+    }
+
+    fn accu1(&self) -> Result<SclExpr> {
+        self.accu1.clone().ok_or(Error::InvalidStack)
+    }
+
+    fn load(&mut self, value: SclExpr) {
+        self.accu2 = self.accu1.replace(value);
+    }
+
+    /// Combine `ACCU2` and `ACCU1` into `ACCU1`
+    fn word_op(&mut self, op: SclOp) -> Result<()> {
+        let left = self.accu2.clone().ok_or(Error::InvalidStack)?;
+        let right = self.accu1()?;
+        self.accu1 = Some(SclExpr::binary(op, left, right));
+        Ok(())
+    }
+
+    /// Apply a conversion or shift to `ACCU1`, the shift amount is taken from `ACCU2`
+    fn function(&mut self, function: SclFunction, amount: Option<Option<u8>>) -> Result<()> {
+        let value = self.accu1()?;
+        let mut params = Vec::with_capacity(2);
+        let Some(amount) = amount else {
+            params.push(positional(value));
+            self.accu1 = Some(SclExpr::Call(SclCall {
+                target: SclCallTarget::Function(function),
+                params,
+            }));
+            return Ok(());
+        };
+        let amount = match amount {
+            Some(amount) => SclExpr::Constant(S7Constant::Int(amount as i16)),
+            None => self.accu2.clone().ok_or(Error::InvalidStack)?,
+        };
+        params.push(named("IN", value));
+        params.push(named("N", amount));
+        self.accu1 = Some(SclExpr::Call(SclCall {
+            target: SclCallTarget::Function(function),
+            params,
+        }));
+        Ok(())
+    }
+
+    fn start(&mut self, bracket: Bracket) {
+        let logic = std::mem::replace(&mut self.logic, Logic::new());
+        self.brackets.push((bracket, logic));
+    }
+
+    fn end(&mut self) -> Result<()> {
+        let value = self.logic.rlo()?;
+        let (bracket, logic) = self.brackets.pop().ok_or(Error::InvalidStack)?;
+        self.logic = logic;
+        match bracket {
+            Bracket::And => self.logic.and(value),
+            Bracket::AndNot => self.logic.and(not(value)),
+            Bracket::Or => self.logic.or(value),
+            Bracket::OrNot => self.logic.or(not(value)),
+            Bracket::Xor => self.logic.xor(value)?,
+            Bracket::XorNot => self.logic.xor(not(value))?,
+        }
+        Ok(())
+    }
+
+    /// `= addr`, the assigned operand holds the result of logic operation afterwards
+    fn assign(&mut self, addr: MirAddress) -> Result<()> {
+        let target = operand(addr)?;
+        let value = self.logic.rlo()?;
+        self.statements.push(SclStatement::Assign {
+            target: target.clone(),
+            value,
+        });
+        self.logic.set_rlo(target);
+        self.logic.first = true;
+        Ok(())
+    }
+
+    /// `S addr` and `R addr`
+    fn set_reset(&mut self, addr: MirAddress, value: bool) -> Result<()> {
+        let target = operand(addr)?;
+        let cond = self.logic.rlo()?;
+        if reads(&cond, &transform_address(addr)) {
+            return Err(Error::Unsupported(
+                "a set or reset operand is part of its own condition",
+            ));
+        }
+        self.statements.push(SclStatement::If {
+            cond,
+            then: vec![SclStatement::Assign {
+                target,
+                value: SclExpr::Bool(value),
+            }],
+        });
+        self.logic.first = true;
+        Ok(())
+    }
+
+    /// Call a counter instance with the result of logic operation on `input`
+    fn count(&mut self, addr: MirAddress, input: &'static str) -> Result<()> {
+        let instance = self.counter(addr)?;
+        let mut params = vec![named(input, self.logic.rlo()?)];
+        if input == "LD" {
+            params.push(named("PV", self.accu1()?));
+        }
+        self.statements.push(SclStatement::Call {
+            ret_val: None,
+            call: SclCall {
+                target: SclCallTarget::Instance(instance),
+                params,
+            },
+        });
+        self.logic.first = true;
+        Ok(())
+    }
+
+    fn instruction(&mut self, instruction: MirInstruction) -> Result<()> {
+        match instruction {
+            MirInstruction::Dummy => {}
+            MirInstruction::And { addr } => self.logic.and(self.bit(addr)?),
+            MirInstruction::AndNot { addr } => self.logic.and(not(self.bit(addr)?)),
+            MirInstruction::Or { addr } => self.logic.or(self.bit(addr)?),
+            MirInstruction::OrNot { addr } => self.logic.or(not(self.bit(addr)?)),
+            MirInstruction::Xor { addr } => self.logic.xor(self.bit(addr)?)?,
+            MirInstruction::XorNot { addr } => self.logic.xor(not(self.bit(addr)?))?,
+            MirInstruction::AndStart => self.start(Bracket::And),
+            MirInstruction::AndNotStart => self.start(Bracket::AndNot),
+            MirInstruction::OrStart => self.start(Bracket::Or),
+            MirInstruction::OrNotStart => self.start(Bracket::OrNot),
+            MirInstruction::XorStart => self.start(Bracket::Xor),
+            MirInstruction::XorNotStart => self.start(Bracket::XorNot),
+            MirInstruction::End => self.end()?,
+            MirInstruction::AssignBit { addr } => self.assign(addr)?,
+            MirInstruction::SetBit { addr } => self.set_reset(addr, true)?,
+            MirInstruction::ResetBit { addr } => self.set_reset(addr, false)?,
+            MirInstruction::Not => {
+                let rlo = self.logic.rlo()?;
+                self.logic.set_rlo(not(rlo));
+            }
+            MirInstruction::Set | MirInstruction::Clear => {
+                let value = matches!(instruction, MirInstruction::Set);
+                self.logic.set_rlo(SclExpr::Bool(value));
+                self.logic.first = true;
+            }
+            MirInstruction::Save => {
+                return Err(Error::Unsupported(
+                    "the binary result bit is specific to STEP 7",
+                ));
+            }
+            MirInstruction::CounterLoadInt { addr } => {
+                let value = self.counter_value(addr)?;
+                self.load(value);
+            }
+            MirInstruction::CounterLoadBcd { addr } => {
+                let value = self.counter_value(addr)?;
+                self.load(SclExpr::Call(SclCall {
+                    target: SclCallTarget::Function(SclFunction::IntToBcd),
+                    params: vec![positional(value)],
+                }));
+            }
+            MirInstruction::CounterForward { addr } => self.count(addr, "CU")?,
+            MirInstruction::CounterBackward { addr } => self.count(addr, "CD")?,
+            MirInstruction::CounterSet { addr } => self.count(addr, "LD")?,
+            MirInstruction::CounterReset { addr } => self.count(addr, "R")?,
+            MirInstruction::Load { addr } => {
+                let value = operand(addr)?;
+                self.load(value);
+            }
+            MirInstruction::LoadConst { value, r#type } => {
+                self.load(SclExpr::Constant(transform_constant(value, r#type)?));
+            }
+            MirInstruction::Transfer { addr } => {
+                let target = operand(addr)?;
+                self.statements.push(SclStatement::Assign {
+                    target: target.clone(),
+                    value: self.accu1()?,
+                });
+                self.accu1 = Some(target);
+            }
+            MirInstruction::AndWord | MirInstruction::AndDWord => self.word_op(SclOp::And)?,
+            MirInstruction::OrWord | MirInstruction::OrDWord => self.word_op(SclOp::Or)?,
+            MirInstruction::XorWord | MirInstruction::XorDWord => self.word_op(SclOp::Xor)?,
+            MirInstruction::InvertInt | MirInstruction::InvertDInt => {
+                self.accu1 = Some(not(self.accu1()?));
+            }
+            MirInstruction::ShiftLeftWord { amount }
+            | MirInstruction::ShiftLeftDWord { amount } => {
+                self.function(SclFunction::Shl, Some(amount))?
+            }
+            MirInstruction::ShiftRightWord { amount }
+            | MirInstruction::ShiftRightDWord { amount } => {
+                self.function(SclFunction::Shr, Some(amount))?
+            }
+            MirInstruction::RotateLeftDWord { amount } => {
+                self.function(SclFunction::Rol, Some(amount))?
+            }
+            MirInstruction::RotateRightDWord { amount } => {
+                self.function(SclFunction::Ror, Some(amount))?
+            }
+            MirInstruction::BcdToInt => self.function(SclFunction::BcdToInt, None)?,
+            MirInstruction::IntToBcd => self.function(SclFunction::IntToBcd, None)?,
+            MirInstruction::IntToDInt => self.function(SclFunction::IntToDInt, None)?,
+            MirInstruction::DIntToReal => self.function(SclFunction::DIntToReal, None)?,
+            MirInstruction::Round => self.function(SclFunction::Round, None)?,
+            MirInstruction::Trunc => self.function(SclFunction::Trunc, None)?,
+            MirInstruction::LoadAddressRegister
+            | MirInstruction::TransferAddressRegister
+            | MirInstruction::AddAddressRegister { .. } => {
+                return Err(Error::Unsupported(
+                    "the address register is specific to STEP 7",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn instructions(&mut self, instructions: &[MirInstruction]) -> Result<()> {
+        for &instruction in instructions {
+            self.instruction(instruction)?;
+        }
+        if !self.brackets.is_empty() {
+            return Err(Error::InvalidStack);
+        }
+        Ok(())
+    }
+
+    fn call(&mut self, lir: &mut SclLir, call: &MirCallAction) -> Result<()> {
+        let block = called_block(self.mir, call);
+        let target = match call.instance {
+            Some(db) => {
+                let name = match &block {
+                    SclBlock::System(name) => name,
+                    SclBlock::Declared(name) => &**name,
+                };
+                let instance: Rc<str> = format!("{name}_DB{db}").into();
+                lir.instances.push(SclInstance {
+                    name: instance.clone(),
+                    r#type: block,
+                });
+                SclCallTarget::Instance(instance)
+            }
+            None => SclCallTarget::Block(block),
+        };
+        let mut ret_val = None;
+        let mut params = Vec::with_capacity(call.params.len());
+        for param in &call.params {
+            let value = match param.value {
+                MirOperand::Address(addr) => operand(addr)?,
+                operand => match transform_operand(operand)? {
+                    S7Operand::Any(any) => SclExpr::Any(any),
+                    S7Operand::Constant(constant) => SclExpr::Constant(constant),
+                    S7Operand::Address(_) => unreachable!("Address operand"),
+                },
+            };
+            if &*param.name == "RET_VAL" {
+                ret_val = Some(value);
+                continue;
+            }
+            params.push(SclParam {
+                name: Some(param.name.clone()),
+                value,
+                output: param.output,
+            });
+        }
+        self.statements.push(SclStatement::Call {
+            ret_val,
+            call: SclCall { target, params },
+        });
+        Ok(())
+    }
+
+    fn action(&mut self, lir: &mut SclLir, action: &MirAction) -> Result<()> {
+        match action {
+            MirAction::Raw(raw) => self.instructions(&raw.instructions)?,
+            MirAction::Output(output) => {
+                self.instructions(&output.instructions)?;
+                self.assign(output.address)?;
+            }
+            MirAction::Call(call) => self.call(lir, call)?,
+        }
+        Ok(())
+    }
+}
+
+/// Symbol of the called block, external blocks use their declared name
+pub(crate) fn called_block(mir: &Mir, call: &MirCallAction) -> SclBlock {
+    let declared = mir
+        .externs
+        .iter()
+        .find(|it| it.signature.block == call.block);
+    if let Some(declared) = declared {
+        return SclBlock::Declared(mir.source.code[&declared.name].into());
+    }
+    if let Some(name) = system_block_name(call.block) {
+        return SclBlock::System(name);
+    }
+    let prefix = match call.block.kind {
+        MirBlockKind::Function => "FC",
+        MirBlockKind::FunctionBlock => "FB",
+        MirBlockKind::SystemFunction => "SFC",
+        MirBlockKind::SystemFunctionBlock => "SFB",
+    };
+    SclBlock::Declared(format!("{prefix}{}", call.block.number).into())
+}
+
+fn assert_direct(addr: S7Address) -> Result<()> {
+    if addr.index != S7AddressIndex::Direct {
+        return Err(Error::Unsupported(
+            "indirect addressing is specific to STEP 7",
+        ));
+    }
+    if matches!(addr.r#type, S7AddressType::Timer | S7AddressType::Counter) {
+        return Err(Error::Unsupported(
+            "S5 timers and counters can not be passed to blocks",
+        ));
+    }
+    Ok(())
+}
+
+fn operand(addr: MirAddress) -> Result<SclExpr> {
+    let allocated = matches!(
+        addr.r#type,
+        MirAddressType::Memory1
+            | MirAddressType::Memory8
+            | MirAddressType::Memory16
+            | MirAddressType::Memory32
+    );
+    let addr = transform_address(addr);
+    assert_direct(addr)?;
+    match allocated {
+        true => Ok(SclExpr::Allocated(addr)),
+        false => Ok(SclExpr::Address(addr)),
+    }
+}
+
+fn not(value: SclExpr) -> SclExpr {
+    match value {
+        SclExpr::Not(value) => *value,
+        SclExpr::Bool(value) => SclExpr::Bool(!value),
+        value => SclExpr::Not(Box::new(value)),
+    }
+}
+
+fn named(name: &str, value: SclExpr) -> SclParam {
+    SclParam {
+        name: Some(name.into()),
+        value,
+        output: false,
+    }
+}
+
+fn positional(value: SclExpr) -> SclParam {
+    SclParam {
+        name: None,
+        value,
+        output: false,
+    }
+}
+
+/// Checks if the expression reads the bit operand
+fn reads(expr: &SclExpr, target: &S7Address) -> bool {
+    match expr {
+        SclExpr::Address(addr) | SclExpr::Allocated(addr) => {
+            addr.r#type == target.r#type
+                && addr.db == target.db
+                && addr.ptr == target.ptr
+                && addr.bit == target.bit
+        }
+        SclExpr::Not(value) => reads(value, target),
+        SclExpr::Binary(binary) => reads(&binary.left, target) || reads(&binary.right, target),
+        _ => false,
+    }
+}
+
+/// Decompile the networks, errors point at the statement that has no structured equivalent
+pub fn transform(mir: &Mir) -> crate::error::Result<SclLir> {
+    let counters = scan_counters(mir);
+    let mut lir = SclLir::default();
+    for counter in &counters {
+        lir.instances.push(SclInstance {
+            name: Counter::name(counter.number),
+            r#type: SclBlock::System(counter.r#type()),
+        });
+    }
+    for network in &mir.networks {
+        let mut decompiler = Decompiler::new(mir, &counters);
+        for action in &mir.actions[network.actions.clone()] {
+            decompiler
+                .action(&mut lir, action)
+                .map_err(|err| err.at(mir.source.clone(), network.quote.clone()))?;
+        }
+        lir.networks.push(SclNetwork {
+            title: network.title.clone(),
+            comments: network.comments.clone(),
+            statements: decompiler.statements,
+        });
+    }
+    for &(addr, r#type) in &mir.allocator.temps {
+        let r#type = transform_data_type(r#type).expect("Temporaries have elementary types");
+        lir.temps.push((transform_address(addr), r#type));
+    }
+    Ok(lir)
+}
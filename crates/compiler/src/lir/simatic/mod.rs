@@ -0,0 +1,584 @@
+//! TIA Portal Openness documents (SimaticML) with STL networks and a PLC tag table
+
+use std::io::Write;
+
+use crate::mir::{builtin::system::system_block_name, MirBlock, MirBlockKind};
+
+use super::{
+    error::{Error, Result},
+    plcopen::escape,
+    s7::{
+        source::S7SourceKind, S7Address, S7AddressType, S7BlockKind, S7Call, S7Constant,
+        S7DataType, S7Instruction, S7Lir, S7Mnemonics, S7Operand, WriteAwl,
+    },
+};
+
+const INTERFACE: &str = "http://www.siemens.com/automation/Openness/SW/Interface/v5";
+const STATEMENT_LIST: &str =
+    "http://www.siemens.com/automation/Openness/SW/NetworkSource/StatementList/v4";
+
+/// PLC tag of an input, output, memory, timer or counter operand
+#[derive(Debug, PartialEq, Eq)]
+pub struct SimaticTag {
+    /// `I0_0`, `MW10`
+    pub name: String,
+    /// `%I0.0`, `%MW10`
+    pub address: String,
+    pub r#type: &'static str,
+}
+
+/// Instance data block of a called function block
+#[derive(Debug, PartialEq, Eq)]
+pub struct SimaticInstance {
+    /// `TON_DB100`
+    pub name: String,
+    pub number: u16,
+    /// Name of the function block
+    pub block: String,
+}
+
+/// Code block with one `CompileUnit` per network, imported by TIA Portal via Openness
+#[derive(Debug)]
+pub struct SimaticBlock<'a> {
+    pub kind: S7SourceKind,
+    pub number: u16,
+    pub name: String,
+    /// `Engineering version` of the documents
+    pub engineering: String,
+    pub lir: &'a S7Lir,
+    pub tags: Vec<SimaticTag>,
+    pub instances: Vec<SimaticInstance>,
+}
+
+impl<'a> SimaticBlock<'a> {
+    pub fn new(kind: S7SourceKind, number: u16, name: String, lir: &'a S7Lir) -> Result<Self> {
+        let mut tags = Vec::new();
+        let mut instances = Vec::new();
+        for instruction in lir.networks.iter().flat_map(|it| &it.instructions) {
+            if let S7Instruction::OpenDataBlock { .. } = instruction {
+                return Err(Error::Unsupported(
+                    "SimaticML networks have no indirect addressing",
+                ));
+            }
+            if let Some(addr) = instruction.addr() {
+                declare_tag(&mut tags, addr)?;
+            }
+            let S7Instruction::Call(call) = instruction else {
+                continue;
+            };
+            for param in &call.params {
+                match param.value {
+                    S7Operand::Address(addr) => declare_tag(&mut tags, addr)?,
+                    S7Operand::Any(any) => declare_tag(&mut tags, any.addr)?,
+                    S7Operand::Constant(_) => {}
+                }
+            }
+            if let Some(number) = call.instance {
+                if instances
+                    .iter()
+                    .all(|it: &SimaticInstance| it.number != number)
+                {
+                    let block = block_name(call);
+                    instances.push(SimaticInstance {
+                        name: instance_name(&block, number),
+                        number,
+                        block,
+                    });
+                }
+            }
+        }
+        Ok(Self {
+            kind,
+            number,
+            name,
+            engineering: "V17".into(),
+            lir,
+            tags,
+            instances,
+        })
+    }
+
+    /// Write the document of the code block
+    pub fn write_block(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let mut ids = 0..;
+        let element = match self.kind {
+            S7SourceKind::OrganizationBlock => "SW.Blocks.OB",
+            S7SourceKind::Function => "SW.Blocks.FC",
+        };
+        self.write_header(out)?;
+        writeln!(out, "  <{element} ID=\"{}\">", ids.next().unwrap())?;
+        writeln!(out, "    <AttributeList>")?;
+        self.write_interface(out)?;
+        writeln!(out, "      <MemoryLayout>Standard</MemoryLayout>")?;
+        writeln!(out, "      <Name>{}</Name>", escape(&self.name))?;
+        writeln!(out, "      <Number>{}</Number>", self.number)?;
+        writeln!(out, "      <ProgrammingLanguage>STL</ProgrammingLanguage>")?;
+        if self.kind == S7SourceKind::OrganizationBlock && self.number == 1 {
+            writeln!(out, "      <SecondaryType>ProgramCycle</SecondaryType>")?;
+        }
+        writeln!(out, "    </AttributeList>")?;
+        writeln!(out, "    <ObjectList>")?;
+        for network in &self.lir.networks {
+            let id = ids.next().unwrap();
+            writeln!(
+                out,
+                "      <SW.Blocks.CompileUnit ID=\"{id}\" CompositionName=\"CompileUnits\">"
+            )?;
+            writeln!(out, "        <AttributeList>")?;
+            writeln!(out, "          <NetworkSource>")?;
+            writeln!(
+                out,
+                "            <StatementList xmlns=\"{STATEMENT_LIST}\">"
+            )?;
+            for instruction in &network.instructions {
+                self.write_statement(out, instruction)?;
+            }
+            writeln!(out, "            </StatementList>")?;
+            writeln!(out, "          </NetworkSource>")?;
+            writeln!(
+                out,
+                "          <ProgrammingLanguage>STL</ProgrammingLanguage>"
+            )?;
+            writeln!(out, "        </AttributeList>")?;
+            writeln!(out, "        <ObjectList>")?;
+            let comment = network.comments.join("\n");
+            write_text(out, &mut ids, "Comment", &comment)?;
+            write_text(out, &mut ids, "Title", &network.title)?;
+            writeln!(out, "        </ObjectList>")?;
+            writeln!(out, "      </SW.Blocks.CompileUnit>")?;
+        }
+        writeln!(out, "    </ObjectList>")?;
+        writeln!(out, "  </{element}>")?;
+        writeln!(out, "</Document>")
+    }
+
+    /// Write the document of an instance data block
+    pub fn write_instance(
+        &self,
+        out: &mut impl Write,
+        instance: &SimaticInstance,
+    ) -> std::io::Result<()> {
+        self.write_header(out)?;
+        writeln!(out, "  <SW.Blocks.InstanceDB ID=\"0\">")?;
+        writeln!(out, "    <AttributeList>")?;
+        writeln!(
+            out,
+            "      <InstanceOfName>{}</InstanceOfName>",
+            escape(&instance.block)
+        )?;
+        writeln!(out, "      <InstanceOfType>FB</InstanceOfType>")?;
+        writeln!(out, "      <MemoryLayout>Standard</MemoryLayout>")?;
+        writeln!(out, "      <Name>{}</Name>", escape(&instance.name))?;
+        writeln!(out, "      <Number>{}</Number>", instance.number)?;
+        writeln!(out, "      <ProgrammingLanguage>DB</ProgrammingLanguage>")?;
+        writeln!(out, "    </AttributeList>")?;
+        writeln!(out, "  </SW.Blocks.InstanceDB>")?;
+        writeln!(out, "</Document>")
+    }
+
+    /// Write the tag table declaring every input, output, memory, timer and counter operand
+    pub fn write_tags(&self, out: &mut impl Write, table: &str) -> std::io::Result<()> {
+        self.write_header(out)?;
+        writeln!(out, "  <SW.Tags.PlcTagTable ID=\"0\">")?;
+        writeln!(out, "    <AttributeList>")?;
+        writeln!(out, "      <Name>{}</Name>", escape(table))?;
+        writeln!(out, "    </AttributeList>")?;
+        writeln!(out, "    <ObjectList>")?;
+        for (i, tag) in self.tags.iter().enumerate() {
+            writeln!(
+                out,
+                "      <SW.Tags.PlcTag ID=\"{}\" CompositionName=\"Tags\">",
+                i + 1
+            )?;
+            writeln!(out, "        <AttributeList>")?;
+            writeln!(out, "          <DataTypeName>{}</DataTypeName>", tag.r#type)?;
+            writeln!(
+                out,
+                "          <LogicalAddress>{}</LogicalAddress>",
+                tag.address
+            )?;
+            writeln!(out, "          <Name>{}</Name>", tag.name)?;
+            writeln!(out, "        </AttributeList>")?;
+            writeln!(out, "      </SW.Tags.PlcTag>")?;
+        }
+        writeln!(out, "    </ObjectList>")?;
+        writeln!(out, "  </SW.Tags.PlcTagTable>")?;
+        writeln!(out, "</Document>")
+    }
+
+    fn write_header(&self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(out, "<?xml version=\"1.0\" encoding=\"utf-8\"?>")?;
+        writeln!(out, "<Document>")?;
+        writeln!(out, "  <Engineering version=\"{}\" />", self.engineering)
+    }
+
+    /// Interface with the temporaries, organization blocks list their start information
+    fn write_interface(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let sections: &[&str] = match self.kind {
+            S7SourceKind::OrganizationBlock => &["Input", "Temp", "Constant"],
+            S7SourceKind::Function => &["Input", "Output", "InOut", "Temp", "Constant", "Return"],
+        };
+        writeln!(out, "      <Interface>")?;
+        writeln!(out, "        <Sections xmlns=\"{INTERFACE}\">")?;
+        for &section in sections {
+            writeln!(out, "          <Section Name=\"{section}\">")?;
+            match (self.kind, section) {
+                (S7SourceKind::OrganizationBlock, "Input") if self.number == 1 => {
+                    writeln!(
+                        out,
+                        "            <Member Name=\"Initial_Call\" Datatype=\"Bool\" Accessibility=\"Public\" Informative=\"true\" />"
+                    )?;
+                    writeln!(
+                        out,
+                        "            <Member Name=\"Remanence\" Datatype=\"Bool\" Accessibility=\"Public\" Informative=\"true\" />"
+                    )?;
+                }
+                (S7SourceKind::Function, "Return") => writeln!(
+                    out,
+                    "            <Member Name=\"Ret_Val\" Datatype=\"Void\" Accessibility=\"Public\" />"
+                )?,
+                (_, "Temp") => {
+                    for &(addr, r#type) in &self.lir.temps {
+                        writeln!(
+                            out,
+                            "            <Member Name=\"T{}\" Datatype=\"{}\" />",
+                            addr.ptr,
+                            temp_type(r#type)
+                        )?;
+                    }
+                }
+                _ => {}
+            }
+            writeln!(out, "          </Section>")?;
+        }
+        writeln!(out, "        </Sections>")?;
+        writeln!(out, "      </Interface>")
+    }
+
+    /// `StlStatement` with the mnemonic token and its operand
+    fn write_statement(
+        &self,
+        out: &mut impl Write,
+        instruction: &S7Instruction,
+    ) -> std::io::Result<()> {
+        let indent = "              ";
+        writeln!(out, "{indent}<StlStatement>")?;
+        writeln!(
+            out,
+            "{indent}  <StlToken Text=\"{}\" />",
+            stl_token(instruction)
+        )?;
+        if let Some(addr) = instruction.addr() {
+            write_access(out, addr, 16)?;
+        }
+        match instruction {
+            S7Instruction::LoadConst { value } => write_constant(out, *value, 16)?,
+            S7Instruction::AddAddressRegister {
+                offset: Some(offset),
+            } => write_constant(out, S7Constant::Pointer(*offset), 16)?,
+            S7Instruction::Call(call) => self.write_call(out, call)?,
+            _ => {}
+        }
+        if let Some(amount) = instruction.amount() {
+            write_constant(out, S7Constant::Int(i16::from(amount)), 16)?;
+        }
+        writeln!(out, "{indent}</StlStatement>")
+    }
+
+    fn write_call(&self, out: &mut impl Write, call: &S7Call) -> std::io::Result<()> {
+        let indent = "                ";
+        let block = block_name(call);
+        let r#type = match call.block.kind {
+            S7BlockKind::Function | S7BlockKind::SystemFunction => "FC",
+            S7BlockKind::FunctionBlock | S7BlockKind::SystemFunctionBlock => "FB",
+        };
+        writeln!(out, "{indent}<Access Scope=\"Call\">")?;
+        writeln!(
+            out,
+            "{indent}  <CallInfo Name=\"{}\" BlockType=\"{type}\">",
+            escape(&block)
+        )?;
+        if let Some(number) = call.instance {
+            writeln!(out, "{indent}    <Instance Scope=\"GlobalVariable\">")?;
+            writeln!(
+                out,
+                "{indent}      <Component Name=\"{}\" />",
+                escape(&instance_name(&block, number))
+            )?;
+            writeln!(out, "{indent}    </Instance>")?;
+        }
+        for param in &call.params {
+            let section = match (&*param.name, param.output) {
+                ("RET_VAL", _) => "Return",
+                (_, true) => "Output",
+                (_, false) => "Input",
+            };
+            let r#type = match param.value {
+                S7Operand::Address(addr) if addr.is_temp() => self
+                    .lir
+                    .temps
+                    .iter()
+                    .find(|(temp, _)| temp.ptr == addr.ptr)
+                    .map_or(data_type(addr.r#type), |&(_, r#type)| temp_type(r#type)),
+                S7Operand::Address(addr) => data_type(addr.r#type),
+                S7Operand::Any(_) => "Any",
+                S7Operand::Constant(value) => constant_type(value),
+            };
+            writeln!(
+                out,
+                "{indent}    <Parameter Name=\"{}\" Section=\"{section}\" Type=\"{type}\">",
+                param.name
+            )?;
+            match param.value {
+                S7Operand::Address(addr) => write_access(out, addr, 22)?,
+                S7Operand::Any(any) => {
+                    let mut text = Vec::new();
+                    any.write_awl(&mut text, S7Mnemonics::English)?;
+                    write_literal(out, "Any", &String::from_utf8_lossy(&text), 22)?;
+                }
+                S7Operand::Constant(value) => write_constant(out, value, 22)?,
+            }
+            writeln!(out, "{indent}    </Parameter>")?;
+        }
+        writeln!(out, "{indent}  </CallInfo>")?;
+        writeln!(out, "{indent}</Access>")
+    }
+}
+
+/// Token of the mnemonic, bracket and symbol instructions are spelled out
+fn stl_token(instruction: &S7Instruction) -> &'static str {
+    match instruction.awl_mnemonic(S7Mnemonics::English) {
+        "A(" => "A_BRACK",
+        "AN(" => "AN_BRACK",
+        "O(" => "O_BRACK",
+        "ON(" => "ON_BRACK",
+        "X(" => "X_BRACK",
+        "XN(" => "XN_BRACK",
+        ")" => "BRACKET",
+        "=" => "ASSIGN",
+        "+AR1" => "PAR1",
+        mnemonic => mnemonic,
+    }
+}
+
+/// Direct operands, data block and peripheral operands are accessed by their absolute address
+fn write_access(out: &mut impl Write, addr: S7Address, indent: usize) -> std::io::Result<()> {
+    let indent = " ".repeat(indent);
+    if addr.is_temp() {
+        writeln!(out, "{indent}<Access Scope=\"LocalVariable\">")?;
+        writeln!(out, "{indent}  <Symbol>")?;
+        writeln!(out, "{indent}    <Component Name=\"T{}\" />", addr.ptr)?;
+        writeln!(out, "{indent}  </Symbol>")?;
+        return writeln!(out, "{indent}</Access>");
+    }
+    if let Some(tag) = tag(addr) {
+        writeln!(out, "{indent}<Access Scope=\"GlobalVariable\">")?;
+        writeln!(out, "{indent}  <Symbol>")?;
+        writeln!(out, "{indent}    <Component Name=\"{}\" />", tag.name)?;
+        writeln!(out, "{indent}  </Symbol>")?;
+        return writeln!(out, "{indent}</Access>");
+    }
+    let area = match addr.r#type {
+        S7AddressType::PeripheralInput8
+        | S7AddressType::PeripheralInput16
+        | S7AddressType::PeripheralInput32 => "PeripheralInput",
+        S7AddressType::PeripheralOutput8
+        | S7AddressType::PeripheralOutput16
+        | S7AddressType::PeripheralOutput32 => "PeripheralOutput",
+        _ => "DB",
+    };
+    let offset = u32::from(addr.ptr) * 8 + u32::from(addr.bit);
+    writeln!(out, "{indent}<Access Scope=\"Address\">")?;
+    write!(
+        out,
+        "{indent}  <Address Area=\"{area}\" Type=\"{}\"",
+        data_type(addr.r#type)
+    )?;
+    if area == "DB" {
+        write!(out, " BlockNumber=\"{}\"", addr.db)?;
+    }
+    writeln!(out, " BitOffset=\"{offset}\" />")?;
+    writeln!(out, "{indent}</Access>")
+}
+
+fn write_constant(out: &mut impl Write, value: S7Constant, indent: usize) -> std::io::Result<()> {
+    let mut text = Vec::new();
+    value.write_awl(&mut text, S7Mnemonics::English)?;
+    write_literal(
+        out,
+        constant_type(value),
+        &String::from_utf8_lossy(&text),
+        indent,
+    )
+}
+
+fn write_literal(
+    out: &mut impl Write,
+    r#type: &str,
+    value: &str,
+    indent: usize,
+) -> std::io::Result<()> {
+    let indent = " ".repeat(indent);
+    writeln!(out, "{indent}<Access Scope=\"LiteralConstant\">")?;
+    writeln!(out, "{indent}  <Constant>")?;
+    writeln!(out, "{indent}    <ConstantType>{type}</ConstantType>")?;
+    writeln!(
+        out,
+        "{indent}    <ConstantValue>{}</ConstantValue>",
+        escape(value)
+    )?;
+    writeln!(out, "{indent}  </Constant>")?;
+    writeln!(out, "{indent}</Access>")
+}
+
+/// English `MultilingualText` of a compile unit
+fn write_text(
+    out: &mut impl Write,
+    ids: &mut impl Iterator<Item = u32>,
+    composition: &str,
+    text: &str,
+) -> std::io::Result<()> {
+    let indent = "          ";
+    writeln!(
+        out,
+        "{indent}<MultilingualText ID=\"{}\" CompositionName=\"{composition}\">",
+        ids.next().unwrap()
+    )?;
+    writeln!(out, "{indent}  <ObjectList>")?;
+    writeln!(
+        out,
+        "{indent}    <MultilingualTextItem ID=\"{}\" CompositionName=\"Items\">",
+        ids.next().unwrap()
+    )?;
+    writeln!(out, "{indent}      <AttributeList>")?;
+    writeln!(out, "{indent}        <Culture>en-US</Culture>")?;
+    writeln!(out, "{indent}        <Text>{}</Text>", escape(text))?;
+    writeln!(out, "{indent}      </AttributeList>")?;
+    writeln!(out, "{indent}    </MultilingualTextItem>")?;
+    writeln!(out, "{indent}  </ObjectList>")?;
+    writeln!(out, "{indent}</MultilingualText>")
+}
+
+fn declare_tag(tags: &mut Vec<SimaticTag>, addr: S7Address) -> Result<()> {
+    if addr.is_indirect() {
+        return Err(Error::Unsupported(
+            "SimaticML networks have no indirect addressing",
+        ));
+    }
+    if let Some(tag) = tag(addr) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    Ok(())
+}
+
+/// Tag of an input, output, memory, timer or counter operand
+fn tag(addr: S7Address) -> Option<SimaticTag> {
+    let mut text = Vec::new();
+    addr.write_awl(&mut text, S7Mnemonics::English)
+        .expect("Write to vector");
+    let text = String::from_utf8(text).expect("ASCII operand");
+    match addr.r#type {
+        S7AddressType::Input1
+        | S7AddressType::Input8
+        | S7AddressType::Input16
+        | S7AddressType::Input32
+        | S7AddressType::Output1
+        | S7AddressType::Output8
+        | S7AddressType::Output16
+        | S7AddressType::Output32
+        | S7AddressType::Memory1
+        | S7AddressType::Memory8
+        | S7AddressType::Memory16
+        | S7AddressType::Memory32
+        | S7AddressType::Timer
+        | S7AddressType::Counter => Some(SimaticTag {
+            name: text.replace('.', "_"),
+            address: format!("%{text}"),
+            r#type: data_type(addr.r#type),
+        }),
+        _ => None,
+    }
+}
+
+fn data_type(r#type: S7AddressType) -> &'static str {
+    match r#type {
+        S7AddressType::Input1
+        | S7AddressType::Output1
+        | S7AddressType::Memory1
+        | S7AddressType::DataBlock1
+        | S7AddressType::Temp1 => "Bool",
+        S7AddressType::Input8
+        | S7AddressType::Output8
+        | S7AddressType::Memory8
+        | S7AddressType::PeripheralInput8
+        | S7AddressType::PeripheralOutput8
+        | S7AddressType::DataBlock8
+        | S7AddressType::Temp8 => "Byte",
+        S7AddressType::Input16
+        | S7AddressType::Output16
+        | S7AddressType::Memory16
+        | S7AddressType::PeripheralInput16
+        | S7AddressType::PeripheralOutput16
+        | S7AddressType::DataBlock16
+        | S7AddressType::Temp16 => "Word",
+        S7AddressType::Input32
+        | S7AddressType::Output32
+        | S7AddressType::Memory32
+        | S7AddressType::PeripheralInput32
+        | S7AddressType::PeripheralOutput32
+        | S7AddressType::DataBlock32
+        | S7AddressType::Temp32 => "DWord",
+        S7AddressType::Timer => "Timer",
+        S7AddressType::Counter => "Counter",
+    }
+}
+
+/// Data type of a temporary in the block interface
+fn temp_type(r#type: S7DataType) -> &'static str {
+    match r#type {
+        S7DataType::Bool => "Bool",
+        S7DataType::Byte => "Byte",
+        S7DataType::Word => "Word",
+        S7DataType::DWord => "DWord",
+        S7DataType::Int => "Int",
+        S7DataType::DInt => "DInt",
+        S7DataType::Real => "Real",
+        S7DataType::Time => "Time",
+    }
+}
+
+fn constant_type(value: S7Constant) -> &'static str {
+    match value {
+        S7Constant::Byte(_) => "Byte",
+        S7Constant::Word(_) => "Word",
+        S7Constant::DWord(_) => "DWord",
+        S7Constant::Int(_) => "Int",
+        S7Constant::DInt(_) => "DInt",
+        S7Constant::Real(_) => "Real",
+        S7Constant::Pointer(_) => "Pointer",
+        S7Constant::Bool(_) => "Bool",
+        S7Constant::Time(_) => "Time",
+    }
+}
+
+/// Symbolic name of the called block, user blocks keep their absolute name
+fn block_name(call: &S7Call) -> String {
+    let (kind, prefix) = match call.block.kind {
+        S7BlockKind::Function => (MirBlockKind::Function, "FC"),
+        S7BlockKind::FunctionBlock => (MirBlockKind::FunctionBlock, "FB"),
+        S7BlockKind::SystemFunction => (MirBlockKind::SystemFunction, "SFC"),
+        S7BlockKind::SystemFunctionBlock => (MirBlockKind::SystemFunctionBlock, "SFB"),
+    };
+    let number = call.block.number;
+    match system_block_name(MirBlock { kind, number }) {
+        Some(name) => name.into(),
+        None => format!("{prefix}{number}"),
+    }
+}
+
+/// `TON_DB100`, matching the instances of the SCL backend
+fn instance_name(block: &str, number: u16) -> String {
+    format!("{block}_DB{number}")
+}
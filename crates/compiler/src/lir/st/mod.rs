@@ -0,0 +1,443 @@
+//! Vendor-neutral IEC 61131-3 Structured Text (CODESYS, OpenPLC)
+//!
+//! Uses the statements decompiled for SCL, operands become variables of the program.
+
+use std::io::Write;
+
+use super::{
+    error::{Error, Result},
+    s7::{S7Address, S7AddressType, S7Constant, S7DataType, S7Mnemonics, WriteAwl},
+    scl::{SclBlock, SclCall, SclCallTarget, SclExpr, SclFunction, SclLir, SclOp, SclStatement},
+};
+
+/// Thing that can be written to a [Write] in ST-form
+pub trait WriteSt {
+    fn write_st(&self, out: &mut impl Write) -> std::io::Result<()>;
+}
+
+/// Declaration of memory reserved by the allocator, memory operands of the source are located
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StMemory {
+    /// `M10_0 AT %MX10.0 : BOOL;`, words must be aligned to their size
+    Located,
+    /// `M10_0 : BOOL;`, overlapping operands no longer share their bits
+    Variables,
+}
+
+/// `I0_0 AT %IX0.0 : BOOL;`
+#[derive(Debug)]
+pub struct StVariable {
+    pub name: String,
+    /// `%IX0.0`
+    pub location: Option<String>,
+    pub r#type: &'static str,
+}
+
+/// `PROGRAM` with its variables
+#[derive(Debug)]
+pub struct StSource<'a> {
+    pub name: String,
+    pub variables: Vec<StVariable>,
+    pub lir: &'a SclLir,
+}
+
+impl<'a> StSource<'a> {
+    /// Declare every operand of the statements, fails on STEP 7 specific operands and blocks
+    pub fn new(name: impl Into<String>, lir: &'a SclLir, memory: StMemory) -> Result<Self> {
+        let mut source = Self {
+            name: name.into(),
+            variables: Vec::new(),
+            lir,
+        };
+        for network in &lir.networks {
+            for statement in &network.statements {
+                source.declare_statement(statement, memory)?;
+            }
+        }
+        type_temps(&mut source.variables, &lir.temps);
+        Ok(source)
+    }
+
+    fn declare_statement(&mut self, statement: &SclStatement, memory: StMemory) -> Result<()> {
+        match statement {
+            SclStatement::Assign { target, value } => {
+                self.declare_expr(target, memory)?;
+                self.declare_expr(value, memory)
+            }
+            SclStatement::If { cond, then } => {
+                self.declare_expr(cond, memory)?;
+                for statement in then {
+                    self.declare_statement(statement, memory)?;
+                }
+                Ok(())
+            }
+            SclStatement::Call { ret_val, call } => {
+                if let Some(ret_val) = ret_val {
+                    self.declare_expr(ret_val, memory)?;
+                }
+                self.declare_call(call, memory)
+            }
+        }
+    }
+
+    fn declare_call(&mut self, call: &SclCall, memory: StMemory) -> Result<()> {
+        if let SclCallTarget::Block(SclBlock::System(_)) = call.target {
+            return Err(Error::Unsupported(
+                "system functions are specific to STEP 7",
+            ));
+        }
+        for param in &call.params {
+            self.declare_expr(&param.value, memory)?;
+        }
+        Ok(())
+    }
+
+    fn declare_expr(&mut self, expr: &SclExpr, memory: StMemory) -> Result<()> {
+        match expr {
+            SclExpr::Bool(_) | SclExpr::Member { .. } => Ok(()),
+            SclExpr::Constant(S7Constant::Pointer(_)) => {
+                Err(Error::Unsupported("pointers are specific to STEP 7"))
+            }
+            SclExpr::Constant(_) => Ok(()),
+            SclExpr::Any(_) => Err(Error::Unsupported("ANY pointers are specific to STEP 7")),
+            SclExpr::Address(addr) => declare_variable(&mut self.variables, *addr, None),
+            SclExpr::Allocated(addr) => declare_variable(&mut self.variables, *addr, Some(memory)),
+            SclExpr::Not(value) => self.declare_expr(value, memory),
+            SclExpr::Binary(binary) => {
+                self.declare_expr(&binary.left, memory)?;
+                self.declare_expr(&binary.right, memory)
+            }
+            SclExpr::Call(call) => self.declare_call(call, memory),
+        }
+    }
+}
+
+impl StSource<'_> {
+    /// Statements of the program without its declarations
+    pub fn write_body(&self, out: &mut impl Write) -> std::io::Result<()> {
+        for (i, network) in self.lir.networks.iter().enumerate() {
+            if i != 0 {
+                writeln!(out)?;
+            }
+            write_comment(out, &network.title)?;
+            for comment in &network.comments {
+                write_comment(out, comment)?;
+            }
+            for statement in &network.statements {
+                statement.write_st_indented(out, 0)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl WriteSt for StSource<'_> {
+    fn write_st(&self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(out, "PROGRAM {}", self.name)?;
+        writeln!(out, "VAR")?;
+        for variable in &self.variables {
+            write!(out, "    {}", variable.name)?;
+            if let Some(location) = &variable.location {
+                write!(out, " AT {location}")?;
+            }
+            writeln!(out, " : {};", variable.r#type)?;
+        }
+        for instance in &self.lir.instances {
+            write!(out, "    {} : ", instance.name)?;
+            instance.r#type.write_st(out)?;
+            writeln!(out, ";")?;
+        }
+        writeln!(out, "END_VAR")?;
+        writeln!(out)?;
+        self.write_body(out)?;
+        if !self.lir.networks.is_empty() {
+            writeln!(out)?;
+        }
+        writeln!(out, "END_PROGRAM")
+    }
+}
+
+/// `(* text *)`, the comment is closed by the first `*)`
+pub(crate) fn write_comment(out: &mut impl Write, text: &str) -> std::io::Result<()> {
+    writeln!(out, "(* {} *)", text.trim().replace("*)", "* )"))
+}
+
+impl SclStatement {
+    fn write_st_indented(&self, out: &mut impl Write, indent: usize) -> std::io::Result<()> {
+        write!(out, "{:indent$}", "")?;
+        match self {
+            Self::Assign { target, value } => {
+                target.write_st(out)?;
+                write!(out, " := ")?;
+                value.write_st(out)?;
+                writeln!(out, ";")
+            }
+            Self::If { cond, then } => {
+                write!(out, "IF ")?;
+                cond.write_st(out)?;
+                writeln!(out, " THEN")?;
+                for statement in then {
+                    statement.write_st_indented(out, indent + 4)?;
+                }
+                writeln!(out, "{:indent$}END_IF;", "")
+            }
+            Self::Call { ret_val, call } => {
+                if let Some(ret_val) = ret_val {
+                    ret_val.write_st(out)?;
+                    write!(out, " := ")?;
+                }
+                call.write_st(out)?;
+                writeln!(out, ";")
+            }
+        }
+    }
+}
+
+impl WriteSt for SclBlock {
+    fn write_st(&self, out: &mut impl Write) -> std::io::Result<()> {
+        match self {
+            Self::System(name) => write!(out, "{name}"),
+            Self::Declared(name) => write!(out, "{name}"),
+        }
+    }
+}
+
+impl WriteSt for SclCall {
+    fn write_st(&self, out: &mut impl Write) -> std::io::Result<()> {
+        match &self.target {
+            SclCallTarget::Function(function) => write!(out, "{}", function.st_name())?,
+            SclCallTarget::Block(block) => block.write_st(out)?,
+            SclCallTarget::Instance(instance) => write!(out, "{instance}")?,
+        }
+        write!(out, "(")?;
+        for (i, param) in self.params.iter().enumerate() {
+            if i != 0 {
+                write!(out, ", ")?;
+            }
+            if let Some(name) = &param.name {
+                let assign = if param.output { "=>" } else { ":=" };
+                write!(out, "{name} {assign} ")?;
+            }
+            param.value.write_st(out)?;
+        }
+        write!(out, ")")
+    }
+}
+
+impl SclFunction {
+    pub fn st_name(self) -> &'static str {
+        match self {
+            Self::BcdToInt => "BCD_TO_INT",
+            Self::IntToBcd => "INT_TO_BCD",
+            // Conversions from `REAL` round to the nearest integer
+            Self::Round => "REAL_TO_DINT",
+            _ => self.scl_name(),
+        }
+    }
+}
+
+impl SclExpr {
+    fn write_st_operand(&self, out: &mut impl Write, precedence: u8) -> std::io::Result<()> {
+        if self.precedence() < precedence {
+            write!(out, "(")?;
+            self.write_st(out)?;
+            return write!(out, ")");
+        }
+        self.write_st(out)
+    }
+}
+
+impl WriteSt for SclExpr {
+    fn write_st(&self, out: &mut impl Write) -> std::io::Result<()> {
+        match self {
+            Self::Bool(value) => write!(out, "{}", if *value { "TRUE" } else { "FALSE" }),
+            Self::Constant(constant) => constant.write_st(out),
+            Self::Address(addr) | Self::Allocated(addr) => {
+                write!(out, "{}", variable_name(*addr))
+            }
+            Self::Any(_) => unreachable!("ANY pointer in ST"),
+            Self::Member { instance, member } => write!(out, "{instance}.{member}"),
+            Self::Not(value) => {
+                write!(out, "NOT ")?;
+                value.write_st_operand(out, self.precedence())
+            }
+            Self::Binary(binary) => {
+                let precedence = binary.op.precedence();
+                binary.left.write_st_operand(out, precedence)?;
+                write!(out, " {} ", binary.op.symbol())?;
+                let right = match binary.op {
+                    SclOp::NotEqual => precedence + 1,
+                    _ => precedence,
+                };
+                binary.right.write_st_operand(out, right)
+            }
+            Self::Call(call) => call.write_st(out),
+        }
+    }
+}
+
+impl WriteSt for S7Constant {
+    fn write_st(&self, out: &mut impl Write) -> std::io::Result<()> {
+        match self {
+            Self::Byte(value) => write!(out, "BYTE#16#{value:02X}"),
+            Self::Word(value) => write!(out, "WORD#16#{value:04X}"),
+            Self::DWord(value) => write!(out, "DWORD#16#{value:08X}"),
+            Self::Int(value) => write!(out, "{value}"),
+            Self::DInt(value) => write!(out, "DINT#{value}"),
+            Self::Real(value) => write!(out, "{value:?}"),
+            Self::Bool(value) => write!(out, "{}", if *value { "TRUE" } else { "FALSE" }),
+            Self::Time(_) => self.write_awl(out, S7Mnemonics::English),
+            Self::Pointer(_) => unreachable!("Pointer in ST"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Area {
+    Input,
+    Output,
+    Memory,
+    DataBlock,
+    Temp,
+}
+
+pub(crate) fn area(r#type: S7AddressType) -> Area {
+    match r#type {
+        S7AddressType::Input1
+        | S7AddressType::Input8
+        | S7AddressType::Input16
+        | S7AddressType::Input32
+        | S7AddressType::PeripheralInput8
+        | S7AddressType::PeripheralInput16
+        | S7AddressType::PeripheralInput32 => Area::Input,
+        S7AddressType::Output1
+        | S7AddressType::Output8
+        | S7AddressType::Output16
+        | S7AddressType::Output32
+        | S7AddressType::PeripheralOutput8
+        | S7AddressType::PeripheralOutput16
+        | S7AddressType::PeripheralOutput32 => Area::Output,
+        S7AddressType::Memory1
+        | S7AddressType::Memory8
+        | S7AddressType::Memory16
+        | S7AddressType::Memory32
+        | S7AddressType::Timer
+        | S7AddressType::Counter => Area::Memory,
+        S7AddressType::DataBlock1
+        | S7AddressType::DataBlock8
+        | S7AddressType::DataBlock16
+        | S7AddressType::DataBlock32 => Area::DataBlock,
+        S7AddressType::Temp1
+        | S7AddressType::Temp8
+        | S7AddressType::Temp16
+        | S7AddressType::Temp32 => Area::Temp,
+    }
+}
+
+/// Width of the operand in bits
+pub(crate) fn width(r#type: S7AddressType) -> u16 {
+    match r#type {
+        S7AddressType::Input1
+        | S7AddressType::Output1
+        | S7AddressType::Memory1
+        | S7AddressType::DataBlock1
+        | S7AddressType::Temp1
+        | S7AddressType::Timer
+        | S7AddressType::Counter => 1,
+        S7AddressType::Input8
+        | S7AddressType::Output8
+        | S7AddressType::Memory8
+        | S7AddressType::PeripheralInput8
+        | S7AddressType::PeripheralOutput8
+        | S7AddressType::DataBlock8
+        | S7AddressType::Temp8 => 8,
+        S7AddressType::Input16
+        | S7AddressType::Output16
+        | S7AddressType::Memory16
+        | S7AddressType::PeripheralInput16
+        | S7AddressType::PeripheralOutput16
+        | S7AddressType::DataBlock16
+        | S7AddressType::Temp16 => 16,
+        S7AddressType::Input32
+        | S7AddressType::Output32
+        | S7AddressType::Memory32
+        | S7AddressType::PeripheralInput32
+        | S7AddressType::PeripheralOutput32
+        | S7AddressType::DataBlock32
+        | S7AddressType::Temp32 => 32,
+    }
+}
+
+fn data_type(r#type: S7AddressType) -> &'static str {
+    match width(r#type) {
+        1 => "BOOL",
+        8 => "BYTE",
+        16 => "WORD",
+        _ => "DWORD",
+    }
+}
+
+/// Declare the variable of an operand once, `memory` is set for memory reserved by the allocator
+pub(crate) fn declare_variable(
+    variables: &mut Vec<StVariable>,
+    addr: S7Address,
+    memory: Option<StMemory>,
+) -> Result<()> {
+    let name = variable_name(addr);
+    if variables.iter().any(|it| it.name == name) {
+        return Ok(());
+    }
+    let location = match (area(addr.r#type), memory) {
+        (Area::DataBlock | Area::Temp, _) | (_, Some(StMemory::Variables)) => None,
+        _ => Some(location(addr)?),
+    };
+    variables.push(StVariable {
+        name,
+        location,
+        r#type: data_type(addr.r#type),
+    });
+    Ok(())
+}
+
+/// Block temporaries keep the type they were reserved with instead of the one of their width
+pub(crate) fn type_temps(variables: &mut [StVariable], temps: &[(S7Address, S7DataType)]) {
+    for &(addr, r#type) in temps {
+        let name = variable_name(addr);
+        if let Some(variable) = variables.iter_mut().find(|it| it.name == name) {
+            variable.r#type = r#type.name();
+        }
+    }
+}
+
+/// Name of the variable holding the operand (`I0_0`, `MW10`, `DB5_DBX2_1`, `T4`)
+pub(crate) fn variable_name(addr: S7Address) -> String {
+    let mut text = Vec::new();
+    addr.write_awl(&mut text, S7Mnemonics::English)
+        .expect("Write to vector");
+    String::from_utf8(text)
+        .expect("ASCII operand")
+        .trim_start_matches('#')
+        .replace('.', "_")
+}
+
+/// IEC address of the operand, words and double words are numbered in their own size
+fn location(addr: S7Address) -> Result<String> {
+    let prefix = match area(addr.r#type) {
+        Area::Input => "I",
+        Area::Output => "Q",
+        Area::Memory => "M",
+        Area::DataBlock | Area::Temp => unreachable!("Operand has no location"),
+    };
+    let location = match width(addr.r#type) {
+        1 => format!("%{prefix}X{}.{}", addr.ptr, addr.bit),
+        8 => format!("%{prefix}B{}", addr.ptr),
+        16 if addr.ptr.is_multiple_of(2) => format!("%{prefix}W{}", addr.ptr / 2),
+        32 if addr.ptr.is_multiple_of(4) => format!("%{prefix}D{}", addr.ptr / 4),
+        _ => {
+            return Err(Error::Unsupported(
+                "IEC addresses of words and double words must be aligned to their size",
+            ))
+        }
+    };
+    Ok(location)
+}
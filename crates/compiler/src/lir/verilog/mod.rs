@@ -0,0 +1,405 @@
+//! Synthesizable Verilog for FPGAs and CPLDs, with a testbench driving the generated module
+//!
+//! Uses the statements decompiled for SCL. Every operand holds its value between scans and
+//! becomes a register updated when `scan` is set at the rising edge of `clk`, so flip-flops
+//! and edge memories are clocked registers. Inputs and outputs are ports of the module.
+
+use std::io::Write;
+
+use super::{
+    error::{Error, Result},
+    s7::{S7Address, S7Constant},
+    scl::{SclCallTarget, SclExpr, SclFunction, SclLir, SclOp, SclStatement},
+    st::{area, variable_name, width, Area},
+};
+
+/// Thing that can be written to a [Write] in Verilog-form
+pub trait WriteVerilog {
+    fn write_verilog(&self, out: &mut impl Write) -> std::io::Result<()>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerilogDirection {
+    Input,
+    Output,
+    /// Register of the module
+    Internal,
+}
+
+/// `input wire [15:0] IW2`
+#[derive(Debug, PartialEq, Eq)]
+pub struct VerilogSignal {
+    pub name: String,
+    pub width: u16,
+    pub direction: VerilogDirection,
+}
+
+impl VerilogSignal {
+    /// `[15:0] `, empty for single bits
+    fn range(&self) -> String {
+        match self.width {
+            1 => String::new(),
+            width => format!("[{}:0] ", width - 1),
+        }
+    }
+}
+
+/// `module` executing every statement of the program once per scan
+#[derive(Debug)]
+pub struct VerilogModule<'a> {
+    pub name: String,
+    pub signals: Vec<VerilogSignal>,
+    /// Operand of each signal
+    addresses: Vec<S7Address>,
+    pub lir: &'a SclLir,
+}
+
+impl<'a> VerilogModule<'a> {
+    /// Declare every operand of the statements, fails on blocks, timers and counters
+    pub fn new(name: impl Into<String>, lir: &'a SclLir) -> Result<Self> {
+        if !lir.instances.is_empty() {
+            return Err(Error::Unsupported(
+                "timers, counters and function blocks have no hardware equivalent",
+            ));
+        }
+        let mut module = Self {
+            name: name.into(),
+            signals: Vec::new(),
+            addresses: Vec::new(),
+            lir,
+        };
+        for network in &lir.networks {
+            for statement in &network.statements {
+                module.declare_statement(statement)?;
+            }
+        }
+        Ok(module)
+    }
+
+    fn declare_statement(&mut self, statement: &SclStatement) -> Result<()> {
+        match statement {
+            SclStatement::Assign { target, value } => {
+                self.declare_expr(target)?;
+                self.declare_expr(value)
+            }
+            SclStatement::If { cond, then } => {
+                self.declare_expr(cond)?;
+                for statement in then {
+                    self.declare_statement(statement)?;
+                }
+                Ok(())
+            }
+            SclStatement::Call { .. } => Err(Error::Unsupported(
+                "block calls have no hardware equivalent",
+            )),
+        }
+    }
+
+    fn declare_expr(&mut self, expr: &SclExpr) -> Result<()> {
+        match expr {
+            SclExpr::Bool(_) => Ok(()),
+            SclExpr::Constant(constant) => constant_width(*constant).map(|_| ()),
+            SclExpr::Address(addr) | SclExpr::Allocated(addr) => self.declare_address(*addr),
+            SclExpr::Not(value) => self.declare_expr(value),
+            SclExpr::Binary(binary) => {
+                self.declare_expr(&binary.left)?;
+                self.declare_expr(&binary.right)
+            }
+            SclExpr::Call(call) => {
+                let SclCallTarget::Function(SclFunction::Shl | SclFunction::Shr) = call.target
+                else {
+                    return Err(Error::Unsupported(
+                        "only shifts and logic operations have a hardware equivalent",
+                    ));
+                };
+                for param in &call.params {
+                    self.declare_expr(&param.value)?;
+                }
+                Ok(())
+            }
+            SclExpr::Any(_) | SclExpr::Member { .. } => Err(Error::Unsupported(
+                "timers, counters and function blocks have no hardware equivalent",
+            )),
+        }
+    }
+
+    /// Operands sharing bytes would be separate registers, so they are rejected
+    fn declare_address(&mut self, addr: S7Address) -> Result<()> {
+        let name = variable_name(addr);
+        if self.signals.iter().any(|it| it.name == name) {
+            return Ok(());
+        }
+        let overlaps = self.addresses.iter().any(|it| {
+            area(it.r#type) == area(addr.r#type)
+                && it.db == addr.db
+                && bytes(*it).start < bytes(addr).end
+                && bytes(addr).start < bytes(*it).end
+                && !(width(it.r#type) == 1 && width(addr.r#type) == 1 && it.bit != addr.bit)
+        });
+        if overlaps {
+            return Err(Error::Unsupported(
+                "operands overlapping in memory have no hardware equivalent",
+            ));
+        }
+        let direction = match area(addr.r#type) {
+            Area::Input => VerilogDirection::Input,
+            Area::Output => VerilogDirection::Output,
+            Area::Memory | Area::DataBlock | Area::Temp => VerilogDirection::Internal,
+        };
+        self.addresses.push(addr);
+        self.signals.push(VerilogSignal {
+            name,
+            width: width(addr.r#type),
+            direction,
+        });
+        Ok(())
+    }
+
+    fn ports(&self) -> impl Iterator<Item = &VerilogSignal> {
+        self.signals
+            .iter()
+            .filter(|it| it.direction != VerilogDirection::Internal)
+    }
+
+    fn inputs(&self) -> impl Iterator<Item = &VerilogSignal> {
+        self.signals
+            .iter()
+            .filter(|it| it.direction == VerilogDirection::Input)
+    }
+
+    fn registers(&self) -> impl Iterator<Item = &VerilogSignal> {
+        self.signals
+            .iter()
+            .filter(|it| it.direction != VerilogDirection::Input)
+    }
+
+    /// Write a testbench applying one input combination per scan, every combination is
+    /// applied if there are up to 12 input bits and `scans` random ones otherwise
+    pub fn write_testbench(&self, out: &mut impl Write, scans: u32) -> std::io::Result<()> {
+        let name = &self.name;
+        let bits: u32 = self.inputs().map(|it| u32::from(it.width)).sum();
+        let (scans, random) = match bits {
+            0..=12 => (1 << bits, false),
+            _ => (scans, true),
+        };
+        writeln!(out, "`timescale 1ns / 1ps")?;
+        writeln!(out)?;
+        writeln!(out, "module {name}_tb;")?;
+        writeln!(out, "    reg clk = 1'b0;")?;
+        writeln!(out, "    reg rst = 1'b1;")?;
+        writeln!(out, "    reg scan = 1'b0;")?;
+        for signal in self.ports() {
+            match signal.direction {
+                VerilogDirection::Input => writeln!(
+                    out,
+                    "    reg {}{} = {}'d0;",
+                    signal.range(),
+                    signal.name,
+                    signal.width
+                )?,
+                _ => writeln!(out, "    wire {}{};", signal.range(), signal.name)?,
+            }
+        }
+        writeln!(out, "    integer i;")?;
+        writeln!(out, "    integer seed = 1;")?;
+        writeln!(out)?;
+        write!(out, "    {name} dut (.clk(clk), .rst(rst), .scan(scan)")?;
+        for signal in self.ports() {
+            write!(out, ", .{0}({0})", signal.name)?;
+        }
+        writeln!(out, ");")?;
+        writeln!(out)?;
+        writeln!(out, "    always #5 clk = ~clk;")?;
+        writeln!(out)?;
+        writeln!(out, "    initial begin")?;
+        writeln!(out, "        $dumpfile(\"{name}_tb.vcd\");")?;
+        writeln!(out, "        $dumpvars(0, {name}_tb);")?;
+        writeln!(out, "        @(negedge clk) rst = 1'b0;")?;
+        writeln!(out, "        for (i = 0; i < {scans}; i = i + 1) begin")?;
+        if bits > 0 {
+            let inputs: Vec<_> = self.inputs().map(|it| it.name.as_str()).collect();
+            let value = if random {
+                let words = vec!["$random(seed)"; bits.div_ceil(32) as usize];
+                format!("{{{}}}", words.join(", "))
+            } else {
+                "i".into()
+            };
+            writeln!(out, "            {{{}}} = {value};", inputs.join(", "))?;
+        }
+        writeln!(out, "            scan = 1'b1;")?;
+        writeln!(out, "            @(negedge clk) scan = 1'b0;")?;
+        write!(out, "            $display(\"scan %0d:")?;
+        for signal in self.ports() {
+            let format = if signal.width == 1 { "%b" } else { "%h" };
+            write!(out, " {}={format}", signal.name)?;
+        }
+        write!(out, "\", i")?;
+        for signal in self.ports() {
+            write!(out, ", {}", signal.name)?;
+        }
+        writeln!(out, ");")?;
+        writeln!(out, "        end")?;
+        writeln!(out, "        $finish;")?;
+        writeln!(out, "    end")?;
+        writeln!(out, "endmodule")
+    }
+}
+
+impl WriteVerilog for VerilogModule<'_> {
+    fn write_verilog(&self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(out, "module {} (", self.name)?;
+        writeln!(out, "    input wire clk,")?;
+        writeln!(out, "    input wire rst,")?;
+        write!(out, "    input wire scan")?;
+        for signal in self.ports() {
+            let kind = match signal.direction {
+                VerilogDirection::Input => "input wire",
+                _ => "output reg",
+            };
+            write!(out, ",\n    {kind} {}{}", signal.range(), signal.name)?;
+        }
+        writeln!(out)?;
+        writeln!(out, ");")?;
+        for signal in self.registers() {
+            if signal.direction == VerilogDirection::Internal {
+                writeln!(out, "    reg {}{};", signal.range(), signal.name)?;
+            }
+        }
+        writeln!(out)?;
+        writeln!(out, "    always @(posedge clk) begin")?;
+        writeln!(out, "        if (rst) begin")?;
+        for signal in self.registers() {
+            writeln!(out, "            {} = {}'d0;", signal.name, signal.width)?;
+        }
+        writeln!(out, "        end else if (scan) begin")?;
+        // Blocking assignments keep the order of the statements within a scan
+        for network in &self.lir.networks {
+            write_comment(out, &network.title)?;
+            for comment in &network.comments {
+                write_comment(out, comment)?;
+            }
+            for statement in &network.statements {
+                statement.write_verilog_indented(out, 12)?;
+            }
+        }
+        writeln!(out, "        end")?;
+        writeln!(out, "    end")?;
+        writeln!(out, "endmodule")
+    }
+}
+
+impl SclStatement {
+    fn write_verilog_indented(&self, out: &mut impl Write, indent: usize) -> std::io::Result<()> {
+        write!(out, "{:indent$}", "")?;
+        match self {
+            Self::Assign { target, value } => {
+                target.write_verilog(out)?;
+                write!(out, " = ")?;
+                value.write_verilog(out)?;
+                writeln!(out, ";")
+            }
+            Self::If { cond, then } => {
+                write!(out, "if (")?;
+                cond.write_verilog(out)?;
+                writeln!(out, ") begin")?;
+                for statement in then {
+                    statement.write_verilog_indented(out, indent + 4)?;
+                }
+                writeln!(out, "{:indent$}end", "")
+            }
+            Self::Call { .. } => unreachable!("Block call in Verilog"),
+        }
+    }
+}
+
+impl SclExpr {
+    fn write_verilog_operand(&self, out: &mut impl Write, precedence: u8) -> std::io::Result<()> {
+        if self.precedence() < precedence {
+            write!(out, "(")?;
+            self.write_verilog(out)?;
+            return write!(out, ")");
+        }
+        self.write_verilog(out)
+    }
+}
+
+impl WriteVerilog for SclExpr {
+    fn write_verilog(&self, out: &mut impl Write) -> std::io::Result<()> {
+        match self {
+            Self::Bool(value) => write!(out, "1'b{}", u8::from(*value)),
+            Self::Constant(constant) => {
+                let width = constant_width(*constant).expect("Declared constant");
+                let value = match *constant {
+                    S7Constant::Byte(value) => u32::from(value),
+                    S7Constant::Word(value) => u32::from(value),
+                    S7Constant::Int(value) => u32::from(value as u16),
+                    S7Constant::DWord(value) => value,
+                    S7Constant::DInt(value) => value as u32,
+                    S7Constant::Bool(value) => u32::from(value),
+                    _ => unreachable!("Declared constant"),
+                };
+                write!(out, "{width}'h{value:X}")
+            }
+            Self::Address(addr) | Self::Allocated(addr) => {
+                write!(out, "{}", variable_name(*addr))
+            }
+            Self::Not(value) => {
+                write!(out, "~")?;
+                value.write_verilog_operand(out, self.precedence())
+            }
+            Self::Binary(binary) => {
+                let precedence = binary.op.precedence();
+                binary.left.write_verilog_operand(out, precedence)?;
+                let symbol = match binary.op {
+                    SclOp::And => "&",
+                    SclOp::Or => "|",
+                    SclOp::Xor => "^",
+                    SclOp::NotEqual => "!=",
+                };
+                write!(out, " {symbol} ")?;
+                let right = match binary.op {
+                    SclOp::NotEqual => precedence + 1,
+                    _ => precedence,
+                };
+                binary.right.write_verilog_operand(out, right)
+            }
+            Self::Call(call) => {
+                let symbol = match call.target {
+                    SclCallTarget::Function(SclFunction::Shl) => "<<",
+                    _ => ">>",
+                };
+                write!(out, "(")?;
+                call.params[0].value.write_verilog_operand(out, 7)?;
+                write!(out, " {symbol} ")?;
+                call.params[1].value.write_verilog_operand(out, 7)?;
+                write!(out, ")")
+            }
+            Self::Any(_) | Self::Member { .. } => unreachable!("Block operand in Verilog"),
+        }
+    }
+}
+
+fn write_comment(out: &mut impl Write, text: &str) -> std::io::Result<()> {
+    for line in text.trim().lines() {
+        writeln!(out, "            // {}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+/// Width of a constant in bits, fails on constants without hardware equivalent
+fn constant_width(constant: S7Constant) -> Result<u16> {
+    match constant {
+        S7Constant::Bool(_) => Ok(1),
+        S7Constant::Byte(_) => Ok(8),
+        S7Constant::Word(_) | S7Constant::Int(_) => Ok(16),
+        S7Constant::DWord(_) | S7Constant::DInt(_) => Ok(32),
+        S7Constant::Real(_) | S7Constant::Time(_) | S7Constant::Pointer(_) => Err(
+            Error::Unsupported("only integer and bit string constants have a hardware equivalent"),
+        ),
+    }
+}
+
+/// Byte range of the operand within its memory area
+fn bytes(addr: S7Address) -> std::ops::Range<u16> {
+    addr.ptr..addr.ptr + width(addr.r#type).div_ceil(8)
+}
@@ -0,0 +1,90 @@
+use crate::{
+    error::{Error, Reason, Result},
+    mir::{
+        builtin::{
+            assertions::assert_args_len,
+            system::{BLKMOV, FILL},
+        },
+        call::{any_constant, call_block},
+        value::{MirAddressType, MirAnyPointer, MirValue},
+        Mir,
+    },
+    util::Quote,
+};
+
+/// Resolve an argument into an `ANY` pointer covering whole bytes
+fn any_pointer(mir: &mut Mir, quote: &Quote, value: &MirValue) -> Result<MirAnyPointer> {
+    let any = match value.resolve(mir) {
+        MirValue::Any(any) => {
+            if any.addr.r#type == MirAddressType::PhysicalMemory1 {
+                mir.constant_pointers.push((quote.clone(), any.addr));
+            }
+            Some(any)
+        }
+        MirValue::Address(addr) => MirAnyPointer::from_address(addr),
+        _ => None,
+    };
+    let Some(any) = any.filter(|it| it.bits().is_multiple_of(8)) else {
+        return Err(Error::new(
+            mir.source.clone(),
+            quote.clone(),
+            Reason::InvalidArgType,
+        ));
+    };
+    Ok(any)
+}
+
+/// Resolve the destination of a block operation, which must be writable
+fn any_pointer_mut(mir: &mut Mir, quote: &Quote, value: &MirValue) -> Result<MirAnyPointer> {
+    let any = any_pointer(mir, quote, value)?;
+    if !any.addr.is_writable() {
+        return Err(Error::new(
+            mir.source.clone(),
+            quote.clone(),
+            Reason::NoWriteHandler,
+        ));
+    }
+    Ok(any)
+}
+
+/// # Block move (`SFC 20 BLKMOV`)
+///
+/// **Signature:**
+/// ```rs
+/// fn(src: ANY, dst: ANY) -> INT
+/// ```
+pub fn builtin_copy(mir: &mut Mir, quote: Quote, args: &[MirValue]) -> Result<MirValue> {
+    assert_args_len(mir, &quote, args, 2)?;
+    let src = any_pointer(mir, &quote, &args[0])?;
+    let dst = any_pointer_mut(mir, &quote, &args[1])?;
+    if src.bits() != dst.bits() {
+        return Err(Error::new(mir.source.clone(), quote, Reason::SizeMismatch));
+    }
+    if src.overlaps(dst) {
+        return Err(Error::new(mir.source.clone(), quote, Reason::BlockOverlap));
+    }
+    let args = [MirValue::Any(src), MirValue::Any(dst)];
+    call_block(mir, quote, &BLKMOV, &args, &[])
+}
+
+/// # Fill memory area (`SFC 21 FILL`)
+///
+/// The value is repeated until the destination is full, constants are stored in memory first.
+///
+/// **Signature:**
+/// ```rs
+/// fn(dst: ANY, value: ANY) -> INT
+/// ```
+pub fn builtin_fill(mir: &mut Mir, quote: Quote, args: &[MirValue]) -> Result<MirValue> {
+    assert_args_len(mir, &quote, args, 2)?;
+    let dst = any_pointer_mut(mir, &quote, &args[0])?;
+    let value = match args[1].resolve(mir) {
+        MirValue::Number(number) => any_constant(mir, number.value, number.r#type),
+        value => any_pointer(mir, &quote, &value)?,
+    };
+    if value.bits() > dst.bits() {
+        return Err(Error::new(mir.source.clone(), quote, Reason::SizeMismatch));
+    }
+    let args = [MirValue::Any(value), MirValue::Any(dst)];
+    call_block(mir, quote, &FILL, &args, &[])
+}
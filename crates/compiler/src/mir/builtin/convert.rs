@@ -0,0 +1,96 @@
+use std::rc::Rc;
+
+use crate::{
+    error::Result,
+    mir::{
+        builtin::assertions::assert_args_len,
+        types::{assert_assignable, MirType},
+        value::{MirConvert, MirValue},
+        Mir, MirInstruction,
+    },
+    util::Quote,
+};
+
+/// Convert the single argument from `from` to `to` with `instruction`
+fn convert(
+    mir: &mut Mir,
+    quote: Quote,
+    args: &[MirValue],
+    (from, to): (MirType, MirType),
+    instruction: MirInstruction,
+) -> Result<MirValue> {
+    assert_args_len(mir, &quote, args, 1)?;
+    assert_assignable(mir, &quote, &args[0], from)?;
+    Ok(MirValue::Convert(Rc::new(MirConvert {
+        value: args[0].clone(),
+        from,
+        to,
+        instruction,
+    })))
+}
+
+/// # BCD to INT
+///
+/// **Signature:**
+/// ```rs
+/// fn(bcd: WORD) -> INT
+/// ```
+pub fn builtin_bcd_to_int(mir: &mut Mir, quote: Quote, args: &[MirValue]) -> Result<MirValue> {
+    let types = (MirType::Word, MirType::Int);
+    convert(mir, quote, args, types, MirInstruction::BcdToInt)
+}
+
+/// # INT to BCD
+///
+/// **Signature:**
+/// ```rs
+/// fn(value: INT) -> WORD
+/// ```
+pub fn builtin_int_to_bcd(mir: &mut Mir, quote: Quote, args: &[MirValue]) -> Result<MirValue> {
+    let types = (MirType::Int, MirType::Word);
+    convert(mir, quote, args, types, MirInstruction::IntToBcd)
+}
+
+/// # INT to DINT
+///
+/// **Signature:**
+/// ```rs
+/// fn(value: INT) -> DINT
+/// ```
+pub fn builtin_int_to_dint(mir: &mut Mir, quote: Quote, args: &[MirValue]) -> Result<MirValue> {
+    let types = (MirType::Int, MirType::DInt);
+    convert(mir, quote, args, types, MirInstruction::IntToDInt)
+}
+
+/// # DINT to REAL
+///
+/// **Signature:**
+/// ```rs
+/// fn(value: DINT) -> REAL
+/// ```
+pub fn builtin_dint_to_real(mir: &mut Mir, quote: Quote, args: &[MirValue]) -> Result<MirValue> {
+    let types = (MirType::DInt, MirType::Real);
+    convert(mir, quote, args, types, MirInstruction::DIntToReal)
+}
+
+/// # Round REAL to nearest DINT
+///
+/// **Signature:**
+/// ```rs
+/// fn(value: REAL) -> DINT
+/// ```
+pub fn builtin_round(mir: &mut Mir, quote: Quote, args: &[MirValue]) -> Result<MirValue> {
+    let types = (MirType::Real, MirType::DInt);
+    convert(mir, quote, args, types, MirInstruction::Round)
+}
+
+/// # Truncate REAL to DINT
+///
+/// **Signature:**
+/// ```rs
+/// fn(value: REAL) -> DINT
+/// ```
+pub fn builtin_trunc(mir: &mut Mir, quote: Quote, args: &[MirValue]) -> Result<MirValue> {
+    let types = (MirType::Real, MirType::DInt);
+    convert(mir, quote, args, types, MirInstruction::Trunc)
+}
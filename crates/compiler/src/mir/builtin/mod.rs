@@ -1,4 +1,7 @@
 pub mod assertions;
+pub mod block;
+pub mod convert;
 pub mod counter;
 pub mod flipflop;
 pub mod memory;
+pub mod system;
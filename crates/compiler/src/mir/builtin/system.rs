@@ -0,0 +1,94 @@
+use phf::{phf_map, Map};
+
+use std::borrow::Cow;
+
+use crate::mir::{
+    call::{MirBlockSignature, MirParamSignature, MirParamType},
+    types::MirType,
+    MirBlock, MirBlockKind,
+};
+
+/// System functions and function blocks callable by their name or number
+pub const SYSTEM_BLOCKS: Map<&str, MirBlockSignature> = phf_map! {
+    "READ_CLK" => READ_CLK,
+    "SFC1" => READ_CLK,
+    "BLKMOV" => BLKMOV,
+    "SFC20" => BLKMOV,
+    "FILL" => FILL,
+    "SFC21" => FILL,
+    "TIME_TCK" => TIME_TCK,
+    "SFC64" => TIME_TCK,
+    "TON" => TON,
+    "SFB4" => TON,
+};
+
+/// `SFC 1`: read the CPU clock
+const READ_CLK: MirBlockSignature = MirBlockSignature {
+    block: sfc(1),
+    inputs: Cow::Borrowed(&[]),
+    ret_val: Some(MirParamType::Value(MirType::Int)),
+    outputs: Cow::Borrowed(&[param("CDT", MirParamType::DateAndTime)]),
+};
+
+/// `SFC 20`: copy a memory area
+pub const BLKMOV: MirBlockSignature = MirBlockSignature {
+    block: sfc(20),
+    inputs: Cow::Borrowed(&[param("SRCBLK", MirParamType::Any)]),
+    ret_val: Some(MirParamType::Value(MirType::Int)),
+    outputs: Cow::Borrowed(&[param("DSTBLK", MirParamType::Any)]),
+};
+
+/// `SFC 21`: fill a memory area with a repeated value
+pub const FILL: MirBlockSignature = MirBlockSignature {
+    block: sfc(21),
+    inputs: Cow::Borrowed(&[param("BVAL", MirParamType::Any)]),
+    ret_val: Some(MirParamType::Value(MirType::Int)),
+    outputs: Cow::Borrowed(&[param("BLK", MirParamType::Any)]),
+};
+
+/// `SFC 64`: milliseconds since the CPU started
+const TIME_TCK: MirBlockSignature = MirBlockSignature {
+    block: sfc(64),
+    inputs: Cow::Borrowed(&[]),
+    ret_val: Some(MirParamType::Value(MirType::Time)),
+    outputs: Cow::Borrowed(&[]),
+};
+
+/// `SFB 4`: IEC on-delay timer
+const TON: MirBlockSignature = MirBlockSignature {
+    block: MirBlock {
+        kind: MirBlockKind::SystemFunctionBlock,
+        number: 4,
+    },
+    inputs: Cow::Borrowed(&[
+        param("IN", MirParamType::Value(MirType::Bool)),
+        param("PT", MirParamType::Value(MirType::Time)),
+    ]),
+    ret_val: None,
+    outputs: Cow::Borrowed(&[
+        param("Q", MirParamType::Value(MirType::Bool)),
+        param("ET", MirParamType::Value(MirType::Time)),
+    ]),
+};
+
+const fn sfc(number: u16) -> MirBlock {
+    MirBlock {
+        kind: MirBlockKind::SystemFunction,
+        number,
+    }
+}
+
+const fn param(name: &'static str, r#type: MirParamType) -> MirParamSignature {
+    MirParamSignature {
+        name: Cow::Borrowed(name),
+        r#type,
+    }
+}
+
+/// Symbolic name of a system block (`BLKMOV` for `SFC 20`)
+pub fn system_block_name(block: MirBlock) -> Option<&'static str> {
+    SYSTEM_BLOCKS
+        .entries()
+        .find(|(name, it)| it.block == block && !name.starts_with("SF"))
+        .map(|(name, _)| *name)
+}
@@ -0,0 +1,311 @@
+//! Calls of blocks with a declared signature
+
+use std::borrow::Cow;
+
+use crate::{
+    error::{Error, Reason, Result},
+    mir::{
+        types::{check_type, MirType},
+        value::{MirAddress, MirAddressIndex, MirAnyPointer, MirValue},
+        Mir, MirAction, MirBlock, MirBlockKind, MirCallAction, MirInstruction, MirOperand,
+        MirParam, MirRawAction,
+    },
+    util::Quote,
+};
+
+/// Signature of a called function or function block
+#[derive(Clone, Debug)]
+pub struct MirBlockSignature {
+    pub block: MirBlock,
+    pub inputs: Cow<'static, [MirParamSignature]>,
+    /// Type of `RET_VAL`, only used by functions
+    pub ret_val: Option<MirParamType>,
+    pub outputs: Cow<'static, [MirParamSignature]>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MirParamSignature {
+    pub name: Cow<'static, str>,
+    pub r#type: MirParamType,
+}
+
+/// Type of a block parameter
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MirParamType {
+    Value(MirType),
+    /// `DATE_AND_TIME`, passed as 8 bytes
+    DateAndTime,
+    Any,
+}
+
+/// Call a block, the arguments are its inputs followed by optional outputs
+///
+/// Returns `RET_VAL` or the first output, outputs without an argument are written to block
+/// temporaries. Function blocks get a new instance data block for every call.
+///
+/// - `quotes`: quotes of the arguments, errors of arguments without one point at the call
+pub fn call_block(
+    mir: &mut Mir,
+    quote: Quote,
+    signature: &MirBlockSignature,
+    args: &[MirValue],
+    quotes: &[Quote],
+) -> Result<MirValue> {
+    let inputs = signature.inputs.len();
+    if args.len() < inputs || args.len() > inputs + signature.outputs.len() {
+        return Err(Error::new(
+            mir.source.clone(),
+            quote,
+            Reason::InvalidArgsCount,
+        ));
+    }
+    let arg_quote = |i: usize| quotes.get(i).unwrap_or(&quote).clone();
+    let mut params = Vec::with_capacity(inputs + signature.outputs.len() + 1);
+    for (i, (param, arg)) in signature.inputs.iter().zip(args).enumerate() {
+        params.push(MirParam {
+            name: param.name.as_ref().into(),
+            value: input_operand(mir, &arg_quote(i), param, arg)?,
+            output: false,
+        });
+    }
+    let mut result = None;
+    if let Some(r#type) = signature.ret_val {
+        let value = output_operand(mir, &quote, "RET_VAL", r#type, None)?;
+        result = Some(value);
+        params.push(MirParam {
+            name: "RET_VAL".into(),
+            value,
+            output: true,
+        });
+    }
+    for (i, param) in signature.outputs.iter().enumerate() {
+        let arg = args.get(inputs + i);
+        let arg_quote = arg.map_or(quote.clone(), |_| arg_quote(inputs + i));
+        let value = output_operand(mir, &arg_quote, &param.name, param.r#type, arg)?;
+        result = result.or(Some(value));
+        params.push(MirParam {
+            name: param.name.as_ref().into(),
+            value,
+            output: true,
+        });
+    }
+    let instance = match signature.block.kind {
+        MirBlockKind::FunctionBlock | MirBlockKind::SystemFunctionBlock => {
+            Some(mir.allocator.alloc_instance().expect("Allocate instance"))
+        }
+        MirBlockKind::Function | MirBlockKind::SystemFunction => None,
+    };
+    mir.actions.push(MirAction::Call(MirCallAction {
+        block: signature.block,
+        instance,
+        params,
+    }));
+    Ok(match result {
+        Some(MirOperand::Address(addr)) => MirValue::Address(addr),
+        Some(MirOperand::Any(any)) => MirValue::Any(any),
+        Some(MirOperand::Constant { .. }) | None => MirValue::Unit,
+    })
+}
+
+/// Checks if the address can be passed directly as block parameter
+fn is_direct(addr: MirAddress) -> bool {
+    addr.index == MirAddressIndex::Direct
+}
+
+/// Store a constant in allocated memory and point to it
+pub fn any_constant(mir: &mut Mir, value: usize, r#type: Option<MirType>) -> MirAnyPointer {
+    let r#type = r#type.unwrap_or(match value {
+        0..=0xFF => MirType::Byte,
+        0x100..=0xFFFF => MirType::Word,
+        _ => MirType::DWord,
+    });
+    let addr = alloc(mir, r#type);
+    let instructions = vec![
+        MirInstruction::LoadConst {
+            value: value as u32,
+            r#type,
+        },
+        MirInstruction::Transfer { addr },
+    ];
+    mir.actions
+        .push(MirAction::Raw(MirRawAction { instructions }));
+    MirAnyPointer::from_address(addr).expect("Memory is addressable")
+}
+
+/// Allocate a block temporary for an argument or result of the type
+fn alloc_temp(mir: &mut Mir, r#type: MirType) -> MirAddress {
+    mir.allocator
+        .alloc_temp(r#type)
+        .expect("Allocate temporary")
+}
+
+/// Allocate memory for a value of the type
+fn alloc(mir: &mut Mir, r#type: MirType) -> MirAddress {
+    match r#type.width() {
+        1 => mir.allocator.alloc1().expect("Allocate u1"),
+        8 => mir.allocator.alloc8().expect("Allocate u8"),
+        16 => mir.allocator.alloc16().expect("Allocate u16"),
+        _ => mir.allocator.alloc32().expect("Allocate u32"),
+    }
+}
+
+/// Create an error for an argument that does not match the type of its parameter
+fn invalid_arg(
+    mir: &Mir,
+    quote: &Quote,
+    name: &str,
+    r#type: MirParamType,
+    value: &MirValue,
+) -> Error {
+    let expected = match r#type {
+        MirParamType::Value(r#type) => r#type.name(),
+        MirParamType::DateAndTime => "DATE_AND_TIME",
+        MirParamType::Any => "ANY",
+    };
+    let actual = match value.resolve(mir) {
+        MirValue::Any(any) if any.bits() == 64 => "is a pointer to 8 bytes".to_string(),
+        MirValue::Any(any) => format!("is a pointer to {} bits", any.bits()),
+        MirValue::Number(number) if number.r#type.is_none() => "is an untyped number".to_string(),
+        value => match value.r#type(mir) {
+            Some(r#type) => format!("is {}", r#type.name()),
+            None => "has no value".to_string(),
+        },
+    };
+    Error::new(mir.source.clone(), quote.clone(), Reason::ArgTypeMismatch).with_note(format!(
+        "parameter `{name}` expects {expected}, the argument {actual}"
+    ))
+}
+
+/// Create an error for an output argument that can not be written
+fn read_only_arg(mir: &Mir, quote: &Quote, name: &str) -> Error {
+    Error::new(mir.source.clone(), quote.clone(), Reason::NoWriteHandler)
+        .with_note(format!("parameter `{name}` is an output of the block"))
+}
+
+/// Resolve an `ANY` argument of the given size in bits
+fn any_operand(mir: &mut Mir, value: &MirValue, bits: Option<usize>) -> Option<MirAnyPointer> {
+    let any = match value.resolve(mir) {
+        MirValue::Any(any) => Some(any),
+        MirValue::Address(addr) => MirAnyPointer::from_address(addr),
+        MirValue::Number(number) if bits.is_none() => {
+            Some(any_constant(mir, number.value, number.r#type))
+        }
+        _ => None,
+    };
+    any.filter(|any| bits.is_none_or(|bits| any.bits() == bits))
+}
+
+/// Pass an input argument, values that are not operands are evaluated into a block temporary
+fn input_operand(
+    mir: &mut Mir,
+    quote: &Quote,
+    param: &MirParamSignature,
+    value: &MirValue,
+) -> Result<MirOperand> {
+    let bits = match param.r#type {
+        MirParamType::Any => None,
+        MirParamType::DateAndTime => Some(64),
+        MirParamType::Value(r#type) => return value_operand(mir, quote, param, r#type, value),
+    };
+    match any_operand(mir, value, bits) {
+        Some(any) => Ok(MirOperand::Any(any)),
+        None => Err(invalid_arg(mir, quote, &param.name, param.r#type, value)),
+    }
+}
+
+/// Pass an input argument of an elementary type
+fn value_operand(
+    mir: &mut Mir,
+    quote: &Quote,
+    param: &MirParamSignature,
+    r#type: MirType,
+    value: &MirValue,
+) -> Result<MirOperand> {
+    let matching = if r#type == MirType::Bool {
+        value.r#type(mir) == Some(MirType::Bool)
+    } else {
+        check_type(mir, value, r#type)
+    };
+    if !matching {
+        return Err(invalid_arg(mir, quote, &param.name, param.r#type, value));
+    }
+    match value.resolve(mir) {
+        MirValue::Bool(bool) if r#type == MirType::Bool => Ok(MirOperand::Constant {
+            value: bool.value as u32,
+            r#type,
+        }),
+        MirValue::Number(number) if r#type != MirType::Bool => Ok(MirOperand::Constant {
+            value: number.value as u32,
+            r#type,
+        }),
+        MirValue::Address(addr)
+            if is_direct(addr) && addr.is_bit_address() == (r#type == MirType::Bool) =>
+        {
+            Ok(MirOperand::Address(addr))
+        }
+        value => {
+            let addr = alloc_temp(mir, r#type);
+            addr.write(mir, quote.clone(), quote.clone(), value)?;
+            Ok(MirOperand::Address(addr))
+        }
+    }
+}
+
+/// Bind an output to the argument, or to a block temporary without argument
+///
+/// - `quote`: quote of the argument, or of the call without argument
+fn output_operand(
+    mir: &mut Mir,
+    quote: &Quote,
+    name: &str,
+    r#type: MirParamType,
+    value: Option<&MirValue>,
+) -> Result<MirOperand> {
+    let bits = match r#type {
+        MirParamType::Any => None,
+        MirParamType::DateAndTime if value.is_none() => {
+            let addr = mir.allocator.alloc_block(8).expect("Allocate block");
+            let any = MirAnyPointer::from_address(addr).expect("Memory is addressable");
+            return Ok(MirOperand::Any(MirAnyPointer { count: 8, ..any }));
+        }
+        MirParamType::DateAndTime => Some(64),
+        MirParamType::Value(r#type) => {
+            let Some(value) = value else {
+                return Ok(MirOperand::Address(alloc_temp(mir, r#type)));
+            };
+            return match value.resolve(mir) {
+                MirValue::Address(addr)
+                    if is_direct(addr)
+                        && (addr.is_bit_address() == (r#type == MirType::Bool))
+                        && MirType::from_width(addr.r#type.width()).accepts(r#type) =>
+                {
+                    if !addr.is_writable() {
+                        return Err(read_only_arg(mir, quote, name));
+                    }
+                    Ok(MirOperand::Address(addr))
+                }
+                _ => Err(invalid_arg(
+                    mir,
+                    quote,
+                    name,
+                    MirParamType::Value(r#type),
+                    value,
+                )),
+            };
+        }
+    };
+    let Some(value) = value else {
+        return Err(Error::new(
+            mir.source.clone(),
+            quote.clone(),
+            Reason::InvalidArgsCount,
+        ));
+    };
+    let Some(any) = any_operand(mir, value, bits) else {
+        return Err(invalid_arg(mir, quote, name, r#type, value));
+    };
+    if !any.addr.is_writable() {
+        return Err(read_only_arg(mir, quote, name));
+    }
+    Ok(MirOperand::Any(any))
+}
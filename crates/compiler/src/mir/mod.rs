@@ -1,4 +1,4 @@
-use std::{fmt::Debug, rc::Rc};
+use std::{fmt::Debug, ops::Range, rc::Rc};
 
 use phf::{phf_map, Map};
 
@@ -9,16 +9,26 @@ use crate::{
 
 use self::{
     builtin::{
+        block::{builtin_copy, builtin_fill},
+        convert::{
+            builtin_bcd_to_int, builtin_dint_to_real, builtin_int_to_bcd, builtin_int_to_dint,
+            builtin_round, builtin_trunc,
+        },
         counter::builtin_counter,
         flipflop::{builtin_rs, builtin_sr},
         memory::{builtin_alloc1, builtin_alloc16, builtin_alloc32, builtin_alloc8},
     },
-    value::{MirAddress, MirAddressType, MirValue},
+    call::MirBlockSignature,
+    types::MirType,
+    value::{MirAddress, MirAddressIndex, MirAddressType, MirAnyPointer, MirValue},
 };
 
 pub mod builtin;
+pub mod call;
 pub mod ops;
+pub mod test;
 pub mod transformer;
+pub mod types;
 pub mod value;
 pub mod writer;
 
@@ -32,11 +42,25 @@ pub const BUILTIN_FUNCTIONS: Map<&str, MirFunction> = phf_map! {
     "MD" => builtin_alloc32,
     "alloc32" => builtin_alloc32,
     "Z" => builtin_counter,
+    "C" => builtin_counter,
     "counter" => builtin_counter,
     "rs" => builtin_rs,
     "sr" => builtin_sr,
+    "bcd_to_int" => builtin_bcd_to_int,
+    "int_to_bcd" => builtin_int_to_bcd,
+    "int_to_dint" => builtin_int_to_dint,
+    "dint_to_real" => builtin_dint_to_real,
+    "round" => builtin_round,
+    "trunc" => builtin_trunc,
+    "copy" => builtin_copy,
+    "fill" => builtin_fill,
 };
 
+/// Number of the first instance data block allocated for function block calls
+///
+/// Programs using data blocks from this number on get their instances after the highest one.
+pub const INSTANCE_DB_BASE: u16 = 100;
+
 /// MIR Function
 ///
 /// - `&mut Mir`: reference to the MIR
@@ -50,6 +74,14 @@ pub struct Mir {
     pub allocator: MirAllocator,
     pub variables: Vec<MirVariable>,
     pub actions: Vec<MirAction>,
+    /// Targets of constant pointers into the memory area, checked against the allocator
+    pub constant_pointers: Vec<(Quote, MirAddress)>,
+    /// Pointers copied into the memory area before each statement accessing through them
+    pub pointer_copies: Vec<MirPointerCopy>,
+    /// Declared external blocks (`extern fc FC10(..);`)
+    pub externs: Vec<MirExtern>,
+    /// Actions grouped by the statement that created them
+    pub networks: Vec<MirNetwork>,
 }
 
 impl Mir {
@@ -59,93 +91,168 @@ impl Mir {
             allocator: MirAllocator::default(),
             variables: Vec::new(),
             actions: Vec::new(),
+            constant_pointers: Vec::new(),
+            pointer_copies: Vec::new(),
+            externs: Vec::new(),
+            networks: Vec::new(),
         }
     }
 
+    /// Index of the variable named `name`, a later `let` shadows earlier ones
     pub fn find_var(&self, name: &str) -> Option<usize> {
-        for (i, var) in self.variables.iter().enumerate() {
-            if name == &self.source.code[&var.name] {
-                return Some(i);
-            }
-        }
-        None
+        self.variables
+            .iter()
+            .rposition(|it| name == &self.source.code[&it.name])
+    }
+
+    pub fn find_extern(&self, name: &str) -> Option<usize> {
+        self.externs
+            .iter()
+            .position(|it| name == &self.source.code[&it.name])
     }
 }
 
+/// Allocator for virtual memory, bits are packed into bytes taken from the byte area
+///
+/// Bytes of the memory area used directly by the program are reserved and skipped.
 #[derive(Debug, Default)]
 pub struct MirAllocator {
     pub allocated_bits: usize,
+    /// Byte holding the most recently allocated bits
+    pub bit_byte: u16,
     pub allocated_bytes: usize,
     pub allocated_counters: u16,
+    pub allocated_instances: u16,
+    /// Highest data block number used by operands, instances are numbered above it
+    pub used_db: u16,
+    /// Temporaries for intermediate accumulator results, shared by all statements
+    pub scratch: Vec<MirAddress>,
+    /// Byte ranges of the memory area used by operands (`MW10`, `M3.1`)
+    pub reserved: Vec<Range<usize>>,
+    /// Block temporaries with their declared type, call results only live for a scan
+    pub temps: Vec<(MirAddress, MirType)>,
+    pub allocated_temp_bytes: usize,
 }
 
 impl MirAllocator {
-    pub fn byte_offset(&self) -> usize {
-        if self.allocated_bits % 8 != 0 {
-            self.allocated_bits / 8 + 1
-        } else {
-            self.allocated_bits / 8
-        }
+    pub fn usage(&self) -> usize {
+        self.allocated_bytes
     }
 
-    pub fn usage(&self) -> usize {
-        self.byte_offset() + self.allocated_bytes
+    /// Checks if the byte has been handed out, rather than skipped as reserved
+    pub fn is_allocated(&self, ptr: usize) -> bool {
+        ptr < self.allocated_bytes && !self.reserved.iter().any(|it| it.contains(&ptr))
     }
 
-    pub fn can_alloc_bit(&self) -> bool {
-        self.allocated_bits % 8 != 0 || self.usage() < 65535
+    /// Keep the allocator from handing out the given bytes
+    pub fn reserve(&mut self, bytes: Range<usize>) {
+        self.reserved.push(bytes);
     }
 
-    pub fn alloc1(&mut self) -> Option<MirAddress> {
-        if !self.can_alloc_bit() {
+    /// Keep instance data blocks from taking the number of a data block used by the program
+    pub fn reserve_db(&mut self, db: u16) {
+        self.used_db = self.used_db.max(db);
+    }
+
+    /// Take the next `bytes` bytes that are not reserved
+    fn take(&mut self, bytes: usize) -> Option<u16> {
+        let mut ptr = self.allocated_bytes;
+        while let Some(reserved) = self
+            .reserved
+            .iter()
+            .find(|it| it.start < ptr + bytes && ptr < it.end)
+        {
+            ptr = reserved.end;
+        }
+        if ptr + bytes > 65536 {
             return None;
         }
-        let ptr = self.allocated_bits;
+        self.allocated_bytes = ptr + bytes;
+        Some(ptr as u16)
+    }
+
+    pub fn alloc1(&mut self) -> Option<MirAddress> {
+        if self.allocated_bits.is_multiple_of(8) {
+            self.bit_byte = self.take(1)?;
+        }
+        let bit = self.allocated_bits % 8;
         self.allocated_bits += 1;
         Some(MirAddress {
             r#type: value::MirAddressType::Memory1,
-            ptr: (ptr / 8) as u16,
-            bit: (ptr % 8) as u8,
+            db: 0,
+            ptr: self.bit_byte,
+            bit: bit as u8,
+            index: MirAddressIndex::Direct,
         })
     }
 
     pub fn alloc8(&mut self) -> Option<MirAddress> {
-        if self.usage() >= 65535 {
-            return None;
-        }
-        let ptr = self.allocated_bytes;
-        self.allocated_bytes += 1;
-        Some(MirAddress {
-            r#type: value::MirAddressType::Memory8,
-            ptr: ptr as u16,
-            bit: 0,
-        })
+        self.alloc(MirAddressType::Memory8, 1)
     }
 
     pub fn alloc16(&mut self) -> Option<MirAddress> {
-        if self.usage() >= 65534 {
-            return None;
-        }
-        let ptr = self.allocated_bytes;
-        self.allocated_bytes += 2;
+        self.alloc(MirAddressType::Memory16, 2)
+    }
+
+    pub fn alloc32(&mut self) -> Option<MirAddress> {
+        self.alloc(MirAddressType::Memory32, 4)
+    }
+
+    fn alloc(&mut self, r#type: MirAddressType, bytes: usize) -> Option<MirAddress> {
+        let ptr = self.take(bytes)?;
         Some(MirAddress {
-            r#type: value::MirAddressType::Memory16,
-            ptr: ptr as u16,
+            r#type,
+            db: 0,
+            ptr,
             bit: 0,
+            index: MirAddressIndex::Direct,
         })
     }
 
-    pub fn alloc32(&mut self) -> Option<MirAddress> {
-        if self.usage() >= 65532 {
+    /// Allocate a contiguous block of bytes, used for `DATE_AND_TIME` and other compound values
+    pub fn alloc_block(&mut self, bytes: usize) -> Option<MirAddress> {
+        self.alloc(MirAddressType::Memory8, bytes)
+    }
+
+    /// Allocate a block temporary of the type, every temporary is declared on its own
+    pub fn alloc_temp(&mut self, r#type: MirType) -> Option<MirAddress> {
+        let (addr_type, bytes) = match r#type.width() {
+            1 => (MirAddressType::Temp1, 1),
+            8 => (MirAddressType::Temp8, 1),
+            16 => (MirAddressType::Temp16, 2),
+            _ => (MirAddressType::Temp32, 4),
+        };
+        let ptr = self.allocated_temp_bytes;
+        if ptr + bytes > 65536 {
             return None;
         }
-        let ptr = self.allocated_bytes;
-        self.allocated_bytes += 4;
-        Some(MirAddress {
-            r#type: value::MirAddressType::Memory32,
+        self.allocated_temp_bytes += bytes;
+        let addr = MirAddress {
+            r#type: addr_type,
+            db: 0,
             ptr: ptr as u16,
             bit: 0,
-        })
+            index: MirAddressIndex::Direct,
+        };
+        self.temps.push((addr, r#type));
+        Some(addr)
+    }
+
+    /// Allocate the number of an instance data block
+    pub fn alloc_instance(&mut self) -> Option<u16> {
+        let base = INSTANCE_DB_BASE.max(self.used_db.checked_add(1)?);
+        let db = base.checked_add(self.allocated_instances)?;
+        self.allocated_instances += 1;
+        Some(db)
+    }
+
+    /// Get the `DWORD` temporary for the given nesting depth
+    pub fn scratch(&mut self, depth: usize) -> Option<MirAddress> {
+        while self.scratch.len() <= depth {
+            let addr = self.alloc32()?;
+            self.scratch.push(addr);
+        }
+        Some(self.scratch[depth])
     }
 
     pub fn alloc_counter(&mut self) -> Option<MirAddress> {
@@ -156,8 +263,10 @@ impl MirAllocator {
         self.allocated_counters += 1;
         Some(MirAddress {
             r#type: MirAddressType::PhysicalCounter,
+            db: 0,
             ptr,
             bit: 0,
+            index: MirAddressIndex::Direct,
         })
     }
 }
@@ -168,10 +277,30 @@ pub struct MirVariable {
     pub value: MirValue,
 }
 
+/// Actions of a single statement
+#[derive(Debug)]
+pub struct MirNetwork {
+    /// Statement the actions were created by
+    pub quote: Quote,
+    /// Doc comment or source text of the statement
+    pub title: Rc<str>,
+    /// `//` comments preceding the statement
+    pub comments: Vec<Rc<str>>,
+    pub actions: Range<usize>,
+}
+
+/// External block declared by `extern fc FC10(..);`
+#[derive(Debug)]
+pub struct MirExtern {
+    pub name: Quote,
+    pub signature: MirBlockSignature,
+}
+
 #[derive(Debug)]
 pub enum MirAction {
     Raw(MirRawAction),
     Output(MirOutputAction),
+    Call(MirCallAction),
 }
 
 #[derive(Debug)]
@@ -183,6 +312,62 @@ pub struct MirRawAction {
 pub struct MirOutputAction {
     pub address: MirAddress,
     pub instructions: Vec<MirInstruction>,
+    /// Value the instructions were written from, compared with them by the verifier
+    pub value: MirValue,
+}
+
+/// `CALL SFC 20`, parameters are passed as operands
+#[derive(Debug)]
+pub struct MirCallAction {
+    pub block: MirBlock,
+    /// Instance data block of function blocks
+    pub instance: Option<u16>,
+    pub params: Vec<MirParam>,
+}
+
+/// Called code block
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MirBlock {
+    pub kind: MirBlockKind,
+    pub number: u16,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MirBlockKind {
+    /// `FC`
+    Function,
+    /// `FB`
+    FunctionBlock,
+    /// `SFC`
+    SystemFunction,
+    /// `SFB`
+    SystemFunctionBlock,
+}
+
+/// `NAME := value` of a [MirCallAction]
+#[derive(Debug)]
+pub struct MirParam {
+    pub name: Rc<str>,
+    pub value: MirOperand,
+    /// Written by the block, `RET_VAL` and outputs
+    pub output: bool,
+}
+
+/// Operand passed to a block parameter
+#[derive(Clone, Copy, Debug)]
+pub enum MirOperand {
+    Address(MirAddress),
+    Any(MirAnyPointer),
+    Constant { value: u32, r#type: MirType },
+}
+
+/// Pointer of an indirect address that is not stored in the memory area (`M[DB5.DBD0]`)
+#[derive(Debug)]
+pub struct MirPointerCopy {
+    /// `MD ptr` the pointer is copied to, `MW ptr` for timers and counters
+    pub location: u16,
+    /// Load the pointer and transfer it to the location
+    pub instructions: Vec<MirInstruction>,
 }
 
 #[derive(Clone, Copy)]
@@ -241,8 +426,58 @@ pub enum MirInstruction {
     CounterForward { addr: MirAddress },
     /// `ZR addr`
     CounterBackward { addr: MirAddress },
+    /// `L addr`
+    Load { addr: MirAddress },
+    /// `L value`
+    LoadConst { value: u32, r#type: MirType },
     /// `T addr`
     Transfer { addr: MirAddress },
+    /// `UW`
+    AndWord,
+    /// `OW`
+    OrWord,
+    /// `XOW`
+    XorWord,
+    /// `UD`
+    AndDWord,
+    /// `OD`
+    OrDWord,
+    /// `XOD`
+    XorDWord,
+    /// `INVI`
+    InvertInt,
+    /// `INVD`
+    InvertDInt,
+    /// `SLW n`, `SLW` shifts by `ACCU2-L-L`
+    ShiftLeftWord { amount: Option<u8> },
+    /// `SRW n`
+    ShiftRightWord { amount: Option<u8> },
+    /// `SLD n`
+    ShiftLeftDWord { amount: Option<u8> },
+    /// `SRD n`
+    ShiftRightDWord { amount: Option<u8> },
+    /// `RLD n`
+    RotateLeftDWord { amount: Option<u8> },
+    /// `RRD n`
+    RotateRightDWord { amount: Option<u8> },
+    /// `BTI`
+    BcdToInt,
+    /// `ITB`
+    IntToBcd,
+    /// `ITD`
+    IntToDInt,
+    /// `DTR`
+    DIntToReal,
+    /// `RND`
+    Round,
+    /// `TRUNC`
+    Trunc,
+    /// `LAR1`
+    LoadAddressRegister,
+    /// `TAR1`
+    TransferAddressRegister,
+    /// `+AR1 P#n.m`, `+AR1` adds `ACCU1-L`
+    AddAddressRegister { offset: Option<u32> },
 }
 
 impl MirInstruction {
@@ -279,6 +514,11 @@ impl MirInstruction {
     }
 
     pub fn addr(&self) -> MirAddress {
+        self.operand().expect("Invalid instruction")
+    }
+
+    /// Address of the operand, `None` for instructions without one
+    pub fn operand(&self) -> Option<MirAddress> {
         match self {
             Self::And { addr }
             | Self::AndNot { addr }
@@ -295,8 +535,9 @@ impl MirInstruction {
             | Self::CounterSet { addr }
             | Self::CounterForward { addr }
             | Self::CounterBackward { addr }
-            | Self::Transfer { addr } => *addr,
-            _ => panic!("Invalid instruction"),
+            | Self::Load { addr }
+            | Self::Transfer { addr } => Some(*addr),
+            _ => None,
         }
     }
 }
@@ -331,7 +572,46 @@ impl Debug for MirInstruction {
             Self::CounterSet { addr } => write!(f, "S {addr:?}"),
             Self::CounterForward { addr } => write!(f, "ZV {addr:?}"),
             Self::CounterBackward { addr } => write!(f, "ZR {addr:?}"),
+            Self::Load { addr } => write!(f, "L {addr:?}"),
+            Self::LoadConst { value, r#type } => write!(f, "L {}#{value:X}", r#type.name()),
             Self::Transfer { addr } => write!(f, "T {addr:?}"),
+            Self::AndWord => write!(f, "UW"),
+            Self::OrWord => write!(f, "OW"),
+            Self::XorWord => write!(f, "XOW"),
+            Self::AndDWord => write!(f, "UD"),
+            Self::OrDWord => write!(f, "OD"),
+            Self::XorDWord => write!(f, "XOD"),
+            Self::InvertInt => write!(f, "INVI"),
+            Self::InvertDInt => write!(f, "INVD"),
+            Self::ShiftLeftWord { amount } => write_amount(f, "SLW", *amount),
+            Self::ShiftRightWord { amount } => write_amount(f, "SRW", *amount),
+            Self::ShiftLeftDWord { amount } => write_amount(f, "SLD", *amount),
+            Self::ShiftRightDWord { amount } => write_amount(f, "SRD", *amount),
+            Self::RotateLeftDWord { amount } => write_amount(f, "RLD", *amount),
+            Self::RotateRightDWord { amount } => write_amount(f, "RRD", *amount),
+            Self::BcdToInt => write!(f, "BTI"),
+            Self::IntToBcd => write!(f, "ITB"),
+            Self::IntToDInt => write!(f, "ITD"),
+            Self::DIntToReal => write!(f, "DTR"),
+            Self::Round => write!(f, "RND"),
+            Self::Trunc => write!(f, "TRUNC"),
+            Self::LoadAddressRegister => write!(f, "LAR1"),
+            Self::TransferAddressRegister => write!(f, "TAR1"),
+            Self::AddAddressRegister { offset: None } => write!(f, "+AR1"),
+            Self::AddAddressRegister {
+                offset: Some(offset),
+            } => write!(f, "+AR1 P#{}.{}", offset / 8, offset % 8),
         }
     }
 }
+
+fn write_amount(
+    f: &mut std::fmt::Formatter<'_>,
+    mnemonic: &str,
+    amount: Option<u8>,
+) -> std::fmt::Result {
+    match amount {
+        Some(amount) => write!(f, "{mnemonic} {amount}"),
+        None => write!(f, "{mnemonic}"),
+    }
+}
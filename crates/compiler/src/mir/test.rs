@@ -0,0 +1,51 @@
+//! Tests resolved against the variables of a program, executed by a simulator.
+
+use crate::util::Quote;
+
+use super::value::MirAddress;
+
+/// `test "motor stops on fault" { .. }`
+#[derive(Debug)]
+pub struct MirTest {
+    pub quote: Quote,
+    /// Text between the quotes
+    pub name: Quote,
+    pub steps: Vec<MirTestStep>,
+}
+
+#[derive(Debug)]
+pub enum MirTestStep {
+    /// Write the operand before the next scan, inputs are set on their module
+    Set {
+        quote: Quote,
+        addr: MirAddress,
+        value: MirTestValue,
+    },
+    Scan {
+        quote: Quote,
+        length: MirScanLength,
+    },
+    /// Compare the operand with the value after the last scan
+    Assert {
+        quote: Quote,
+        addr: MirAddress,
+        value: MirTestValue,
+        equal: bool,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MirScanLength {
+    Scans(usize),
+    /// Milliseconds, rounded up to whole scans
+    Duration(usize),
+}
+
+/// Constant set or expected by a test
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MirTestValue {
+    /// Value of a bit, timer or counter status
+    Bool(bool),
+    /// Bits of a `BYTE`, `WORD` or `DWORD`
+    Bits(u32),
+}
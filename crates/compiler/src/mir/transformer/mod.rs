@@ -1,20 +1,38 @@
 //! This module is responsible for compiling a [Hir] into an [Mir].
 
+pub mod reserve;
+pub mod test;
 pub mod value;
 
+use std::{borrow::Cow, rc::Rc};
+
 use crate::{
     error::{Error, Reason, Result},
-    hir::{Hir, HirCallStatement, HirLetStatement, HirStatement, HirWriteStatement},
-    mir::{transformer::value::transform_values, Mir, MirVariable, BUILTIN_FUNCTIONS},
+    hir::{
+        Hir, HirAddStatement, HirBlockKind, HirCallStatement, HirComment, HirExternStatement,
+        HirLetStatement, HirParamDirection, HirParamType, HirStatement, HirWriteStatement,
+    },
+    mir::{
+        call::{MirBlockSignature, MirParamSignature, MirParamType},
+        transformer::value::call_function,
+        value::{MirAddress, MirAddressIndex, MirValue},
+        Mir, MirBlock, MirBlockKind, MirExtern, MirInstruction, MirNetwork, MirOperand,
+        MirVariable,
+    },
+    parser::lexer::continues_literal,
+    util::Quote,
 };
 
-use self::value::transform_value;
+use self::value::{data_type, transform_value};
 
 use super::{
     writer::{optimizer::optimize, MirInstructionWriter},
     MirAction, MirRawAction,
 };
 
+/// Maximum length of a network title accepted by STEP 7
+const MAX_TITLE_LEN: usize = 64;
+
 fn transform_let(
     mir: &mut Mir,
     HirLetStatement { name, value, .. }: HirLetStatement,
@@ -29,38 +47,91 @@ fn transform_let(
 
 fn transform_write(
     mir: &mut Mir,
-    HirWriteStatement { quote, name, value }: HirWriteStatement,
+    HirWriteStatement { target, value, .. }: HirWriteStatement,
 ) -> Result<()> {
-    let write_name = &mir.source.code[&name];
-    let Some(index) = mir.find_var(write_name) else {
-        return Err(Error::new(
-            mir.source.clone(),
-            quote,
-            Reason::UnknownVariable,
-        ));
-    };
-    let var_value = mir.variables[index].value.clone();
+    let target_quote = target.quote.clone();
+    let target = transform_value(mir, target)?;
     let value_quote = value.quote.clone();
     let mir_value = transform_value(mir, value)?;
-    var_value.write(mir, name, value_quote, mir_value)?;
-    mir.variables[index].value = var_value;
+    target.write(mir, target_quote, value_quote, mir_value)
+}
+
+fn transform_add(
+    mir: &mut Mir,
+    HirAddStatement { target, value, .. }: HirAddStatement,
+) -> Result<()> {
+    let target_quote = target.quote.clone();
+    let target = transform_value(mir, target)?;
+    let value_quote = value.quote.clone();
+    let mir_value = transform_value(mir, value)?;
+    target.add(mir, target_quote, value_quote, mir_value)
+}
+
+fn transform_extern(
+    mir: &mut Mir,
+    HirExternStatement {
+        kind,
+        name,
+        number,
+        params,
+        ..
+    }: HirExternStatement,
+) -> Result<()> {
+    if mir.find_extern(&mir.source.code[&name]).is_some() {
+        return Err(Error::new(mir.source.clone(), name, Reason::DuplicateBlock));
+    }
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    for (i, param) in params.iter().enumerate() {
+        let param_name = &mir.source.code[&param.name];
+        if params[..i]
+            .iter()
+            .any(|it| &mir.source.code[&it.name] == param_name)
+        {
+            return Err(Error::new(
+                mir.source.clone(),
+                param.name.clone(),
+                Reason::DuplicateParam,
+            ));
+        }
+        let r#type = match param.r#type {
+            HirParamType::Data(r#type) => MirParamType::Value(data_type(r#type)),
+            HirParamType::DateAndTime => MirParamType::DateAndTime,
+            HirParamType::Any => MirParamType::Any,
+        };
+        let signature = MirParamSignature {
+            name: Cow::Owned(param_name.to_owned()),
+            r#type,
+        };
+        match param.direction {
+            HirParamDirection::Input => inputs.push(signature),
+            HirParamDirection::Output => outputs.push(signature),
+        }
+    }
+    let kind = match kind {
+        HirBlockKind::Function => MirBlockKind::Function,
+        HirBlockKind::FunctionBlock => MirBlockKind::FunctionBlock,
+    };
+    mir.externs.push(MirExtern {
+        name,
+        signature: MirBlockSignature {
+            block: MirBlock { kind, number },
+            inputs: Cow::Owned(inputs),
+            ret_val: None,
+            outputs: Cow::Owned(outputs),
+        },
+    });
     Ok(())
 }
 
 fn transform_call(mir: &mut Mir, call: HirCallStatement) -> Result<()> {
-    let function_name = &mir.source.code[&call.name];
-    let mut value = None;
-    if let Some(func) = BUILTIN_FUNCTIONS.get(function_name) {
-        let args = transform_values(mir, call.args)?;
-        value = Some(func(mir, call.quote.clone(), &args)?);
-    };
-    let Some(value) = value else {
-        return Err(Error::new(
-            mir.source.clone(),
-            call.name,
-            Reason::UnknownFunction,
-        ));
-    };
+    let actions = mir.actions.len();
+    let value = call_function(mir, call.quote.clone(), call.name, call.args)?;
+    // Calls with side effects may discard their result, outputs are already written
+    let discard = !value.is_bit_readable(mir) || matches!(value, MirValue::Address(_));
+    if discard && mir.actions.len() > actions {
+        return Ok(());
+    }
     if !value.is_bit_readable(mir) {
         return Err(Error::new(
             mir.source.clone(),
@@ -77,14 +148,174 @@ fn transform_call(mir: &mut Mir, call: HirCallStatement) -> Result<()> {
     Ok(())
 }
 
+/// Add the locations of the copied pointers the address is accessed through, the pointers they
+/// are copied from come first
+fn collect_copies(mir: &Mir, addr: MirAddress, copies: &mut Vec<usize>) {
+    let MirAddressIndex::Memory { ptr } = addr.index else {
+        return;
+    };
+    let Some(index) = mir.pointer_copies.iter().position(|it| it.location == ptr) else {
+        return;
+    };
+    if copies.contains(&index) {
+        return;
+    }
+    for instruction in &mir.pointer_copies[index].instructions {
+        if let Some(addr) = instruction.operand() {
+            collect_copies(mir, addr, copies);
+        }
+    }
+    copies.push(index);
+}
+
+/// Copy the pointers accessed through by the actions in front of them
+fn insert_pointer_copies(mir: &mut Mir, actions: usize) {
+    let mut copies = Vec::new();
+    let collect = |instructions: &[MirInstruction], copies: &mut Vec<usize>| {
+        for instruction in instructions {
+            if let Some(addr) = instruction.operand() {
+                collect_copies(mir, addr, copies);
+            }
+        }
+    };
+    for action in &mir.actions[actions..] {
+        match action {
+            MirAction::Raw(raw) => collect(&raw.instructions, &mut copies),
+            MirAction::Output(output) => {
+                collect(&output.instructions, &mut copies);
+                collect_copies(mir, output.address, &mut copies);
+            }
+            MirAction::Call(call) => {
+                for param in &call.params {
+                    match param.value {
+                        MirOperand::Address(addr) => collect_copies(mir, addr, &mut copies),
+                        MirOperand::Any(any) => collect_copies(mir, any.addr, &mut copies),
+                        MirOperand::Constant { .. } => {}
+                    }
+                }
+            }
+        }
+    }
+    if copies.is_empty() {
+        return;
+    }
+    let instructions = copies
+        .into_iter()
+        .flat_map(|index| mir.pointer_copies[index].instructions.clone())
+        .collect();
+    mir.actions
+        .insert(actions, MirAction::Raw(MirRawAction { instructions }));
+}
+
+/// Title of a network, taken from the doc comments or the statement on one line
+///
+/// Comments inside the statement are part of the network comments and cut from the title.
+fn network_title(mir: &Mir, docs: &[Rc<str>], quote: &Quote, comments: &[HirComment]) -> Rc<str> {
+    let title = if docs.is_empty() {
+        let code = &mir.source.code;
+        let mut text = String::new();
+        let mut index = quote.start;
+        for comment in comments.iter().filter(|it| it.quote.end <= quote.end) {
+            // The quote of a comment does not cover its `//` or `/* */`
+            text.push_str(match comment.block {
+                true => &code[index..comment.quote.start - 2],
+                false => code[index..comment.quote.start].trim_end_matches('/'),
+            });
+            index = comment.quote.end + if comment.block { 2 } else { 0 };
+        }
+        text.push_str(&code[index..quote.end]);
+        let words: Vec<_> = text
+            .lines()
+            .flat_map(|line| {
+                let end = line
+                    .match_indices('#')
+                    .map(|(i, _)| i)
+                    .find(|&i| !continues_literal(line, i))
+                    .unwrap_or(line.len());
+                line[..end].split_whitespace()
+            })
+            .collect();
+        words.join(" ").trim_end_matches(';').to_owned()
+    } else {
+        docs.join(" ")
+    };
+    title.chars().take(MAX_TITLE_LEN).collect::<String>().into()
+}
+
+/// Lines of a comment, lines of block comments are trimmed and lose their leading `*`
+fn comment_lines(text: &str, block: bool) -> Vec<Rc<str>> {
+    if !block {
+        return vec![text.trim_end().into()];
+    }
+    text.lines()
+        .map(|line| {
+            let line = line.trim();
+            line.strip_prefix('*').unwrap_or(line).trim()
+        })
+        .filter(|line| !line.is_empty())
+        .map(|line| format!(" {line}").into())
+        .collect()
+}
+
 pub fn transform(hir: Hir) -> Result<Mir> {
     let mut mir = Mir::new(hir.source.clone());
+    reserve::reserve_operands(&mut mir, &hir.statements);
+    let mut comments = hir.comments.into_iter().peekable();
+    let mut pending = Vec::new();
     for statement in hir.statements {
+        let quote = statement.quote().clone();
+        let mut docs = Vec::new();
+        while let Some(comment) = comments.next_if(|it| it.quote.start < quote.start) {
+            let text = &mir.source.code[&comment.quote];
+            match comment.doc {
+                true => docs.push(text.trim().into()),
+                false => pending.extend(comment_lines(text, comment.block)),
+            }
+        }
+        // Comments inside the statement and behind it on its last line belong to it as well
+        let code = &mir.source.code;
+        let line_end = code[quote.end..]
+            .find(['\n', '\r'])
+            .map_or(code.len(), |it| quote.end + it);
+        let mut inner = Vec::new();
+        while let Some(comment) = comments.next_if(|it| it.quote.start < line_end) {
+            let text = &mir.source.code[&comment.quote];
+            pending.extend(comment_lines(text, comment.block));
+            inner.push(comment);
+        }
+        let actions = mir.actions.len();
         match statement {
             HirStatement::Let(stmt) => transform_let(&mut mir, stmt)?,
             HirStatement::Write(write) => transform_write(&mut mir, write)?,
+            HirStatement::Add(add) => transform_add(&mut mir, add)?,
             HirStatement::Call(call) => transform_call(&mut mir, call)?,
+            HirStatement::Extern(stmt) => transform_extern(&mut mir, stmt)?,
+        }
+        insert_pointer_copies(&mut mir, actions);
+        // Statements without actions pass their comments on to the next network
+        if mir.actions.len() > actions {
+            mir.networks.push(MirNetwork {
+                title: network_title(&mir, &docs, &quote, &inner),
+                quote,
+                comments: std::mem::take(&mut pending),
+                actions: actions..mir.actions.len(),
+            });
         }
     }
+    check_constant_pointers(&mir)?;
     Ok(mir)
 }
+
+/// Checks that no constant pointer targets memory used by the allocator
+fn check_constant_pointers(mir: &Mir) -> Result<()> {
+    for (quote, addr) in &mir.constant_pointers {
+        if mir.allocator.is_allocated(addr.ptr as usize) {
+            return Err(Error::new(
+                mir.source.clone(),
+                quote.clone(),
+                Reason::PointerIntoAllocated,
+            ));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,71 @@
+//! Reservation of the memory and data blocks used directly by the program, before anything is
+//! allocated
+
+use crate::{
+    hir::{
+        value::{HirAddressArea, HirAddressIndex, HirValue, HirValueType},
+        HirStatement,
+    },
+    mir::Mir,
+};
+
+/// Reserve the bytes of every memory operand and the number of every data block of the statements
+/// in the allocator
+pub fn reserve_operands(mir: &mut Mir, statements: &[HirStatement]) {
+    for statement in statements {
+        match statement {
+            HirStatement::Let(stmt) => reserve_value(mir, &stmt.value),
+            HirStatement::Write(stmt) => {
+                reserve_value(mir, &stmt.target);
+                reserve_value(mir, &stmt.value);
+            }
+            HirStatement::Add(stmt) => {
+                reserve_value(mir, &stmt.target);
+                reserve_value(mir, &stmt.value);
+            }
+            HirStatement::Call(stmt) => stmt.args.iter().for_each(|it| reserve_value(mir, it)),
+            HirStatement::Extern(_) => {}
+        }
+    }
+}
+
+fn reserve_value(mir: &mut Mir, value: &HirValue) {
+    match &value.r#type {
+        HirValueType::BitAddress(addr) if addr.area == HirAddressArea::Memory => {
+            let ptr = addr.ptr as usize;
+            mir.allocator.reserve(ptr..ptr + addr.width.bytes());
+        }
+        HirValueType::BitAddress(addr) => mir.allocator.reserve_db(addr.db),
+        HirValueType::AnyPointer(any) if any.area == HirAddressArea::Memory => {
+            let start = any.ptr as usize * 8 + any.bit as usize;
+            let end = start + any.r#type.bits() * any.count as usize;
+            mir.allocator.reserve(start / 8..end.div_ceil(8));
+        }
+        HirValueType::AnyPointer(any) => mir.allocator.reserve_db(any.db),
+        HirValueType::IndirectAddress(addr) => {
+            mir.allocator.reserve_db(addr.db);
+            if let HirAddressIndex::Memory(index) = &addr.index {
+                reserve_value(mir, index);
+            }
+        }
+        HirValueType::BitAccess(access) => reserve_value(mir, &access.value),
+        HirValueType::Not(not) => reserve_value(mir, &not.value),
+        HirValueType::And(op) => reserve_pair(mir, &op.left, &op.right),
+        HirValueType::Or(op) => reserve_pair(mir, &op.left, &op.right),
+        HirValueType::Xor(op) => reserve_pair(mir, &op.left, &op.right),
+        HirValueType::Shl(op) => reserve_pair(mir, &op.left, &op.right),
+        HirValueType::Shr(op) => reserve_pair(mir, &op.left, &op.right),
+        HirValueType::Rotl(op) => reserve_pair(mir, &op.left, &op.right),
+        HirValueType::Rotr(op) => reserve_pair(mir, &op.left, &op.right),
+        HirValueType::Call(call) => call.args.iter().for_each(|it| reserve_value(mir, it)),
+        HirValueType::Number(_)
+        | HirValueType::Bool(_)
+        | HirValueType::AddressRegister
+        | HirValueType::VarRef(_) => {}
+    }
+}
+
+fn reserve_pair(mir: &mut Mir, left: &HirValue, right: &HirValue) {
+    reserve_value(mir, left);
+    reserve_value(mir, right);
+}
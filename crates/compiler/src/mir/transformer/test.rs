@@ -0,0 +1,127 @@
+//! This module is responsible for resolving the tests of a [Hir](crate::hir::Hir) against the
+//! variables of its [Mir].
+
+use crate::{
+    error::{Error, Reason, Result},
+    hir::{
+        value::{HirNot, HirValue, HirValueType},
+        HirScanLength, HirTest, HirTestStep,
+    },
+    mir::{
+        test::{MirScanLength, MirTest, MirTestStep, MirTestValue},
+        types::{assert_assignable, MirType},
+        value::{MirAddress, MirAddressIndex, MirValue},
+        Mir,
+    },
+};
+
+use super::value::transform_value;
+
+/// Resolve the operand of a `set` or `assert`, only direct operands can be accessed
+fn transform_operand(mir: &mut Mir, value: HirValue) -> Result<MirAddress> {
+    let quote = value.quote.clone();
+    let operand = matches!(
+        value.r#type,
+        HirValueType::BitAddress(_) | HirValueType::VarRef(_) | HirValueType::BitAccess(_)
+    );
+    if operand {
+        if let MirValue::Address(addr) = transform_value(mir, value)?.resolve(mir) {
+            if addr.index == MirAddressIndex::Direct {
+                return Ok(addr);
+            }
+        }
+    }
+    Err(Error::new(mir.source.clone(), quote, Reason::NotTestable))
+}
+
+/// Resolve a constant and check it against the type of the operand
+fn transform_constant(mir: &mut Mir, addr: MirAddress, value: HirValue) -> Result<MirTestValue> {
+    let quote = value.quote.clone();
+    if !matches!(
+        value.r#type,
+        HirValueType::Bool(_) | HirValueType::Number(_)
+    ) {
+        return Err(Error::new(mir.source.clone(), quote, Reason::NotConstant));
+    }
+    let value = transform_value(mir, value)?;
+    let r#type = MirValue::Address(addr)
+        .r#type(mir)
+        .expect("Type of an address");
+    assert_assignable(mir, &quote, &value, r#type)?;
+    Ok(match value {
+        MirValue::Bool(bool) => MirTestValue::Bool(bool.value),
+        MirValue::Number(number) if r#type == MirType::Bool => {
+            MirTestValue::Bool(number.value != 0)
+        }
+        MirValue::Number(number) => MirTestValue::Bits(number.value as u32),
+        _ => unreachable!("Constant value"),
+    })
+}
+
+fn transform_step(mir: &mut Mir, step: HirTestStep) -> Result<MirTestStep> {
+    Ok(match step {
+        HirTestStep::Set(set) => {
+            let addr = transform_operand(mir, set.target)?;
+            let value = transform_constant(mir, addr, set.value)?;
+            MirTestStep::Set {
+                quote: set.quote,
+                addr,
+                value,
+            }
+        }
+        HirTestStep::Scan(scan) => MirTestStep::Scan {
+            quote: scan.quote,
+            length: match scan.length {
+                HirScanLength::Scans(scans) => MirScanLength::Scans(scans),
+                HirScanLength::Duration(millis) => MirScanLength::Duration(millis),
+            },
+        },
+        HirTestStep::Assert(assert) => {
+            let (addr, value) = match assert.expected {
+                Some(expected) => {
+                    let addr = transform_operand(mir, assert.value)?;
+                    (addr, transform_constant(mir, addr, expected)?)
+                }
+                // `assert value;` and `assert not value;` check a bit
+                None => {
+                    let (value, expected) = match assert.value.r#type {
+                        HirValueType::Not(not) => {
+                            let HirNot { value } = *not;
+                            (value, false)
+                        }
+                        _ => (assert.value, true),
+                    };
+                    let quote = value.quote.clone();
+                    let addr = transform_operand(mir, value)?;
+                    if !addr.is_bit_readable() {
+                        return Err(Error::new(mir.source.clone(), quote, Reason::TypeMismatch));
+                    }
+                    (addr, MirTestValue::Bool(expected))
+                }
+            };
+            MirTestStep::Assert {
+                quote: assert.quote,
+                addr,
+                value,
+                equal: assert.equal,
+            }
+        }
+    })
+}
+
+/// Resolve the tests after the program has been transformed, the program is not changed
+pub fn transform_tests(mir: &mut Mir, tests: Vec<HirTest>) -> Result<Vec<MirTest>> {
+    let mut mir_tests = Vec::with_capacity(tests.len());
+    for test in tests {
+        let mut steps = Vec::with_capacity(test.steps.len());
+        for step in test.steps {
+            steps.push(transform_step(mir, step)?);
+        }
+        mir_tests.push(MirTest {
+            quote: test.quote,
+            name: test.name,
+            steps,
+        });
+    }
+    Ok(mir_tests)
+}
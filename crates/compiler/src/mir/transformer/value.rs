@@ -3,15 +3,20 @@ use std::rc::Rc;
 use crate::{
     error::{Error, Reason, Result},
     hir::value::{
-        HirAnd, HirBitAddress, HirBool, HirCall, HirNot, HirNumber, HirOr, HirValue, HirValueType,
-        HirXor,
+        HirAddressArea, HirAddressIndex, HirAddressWidth, HirAnd, HirAnyPointer, HirBitAccess,
+        HirBitAddress, HirBool, HirCall, HirDataType, HirIndirectAddress, HirNot, HirNumber,
+        HirNumberType, HirOr, HirRotl, HirRotr, HirShl, HirShr, HirValue, HirValueType, HirXor,
     },
     mir::{
+        builtin::system::SYSTEM_BLOCKS,
+        call::call_block,
+        types::{assert_assignable, assert_same_type, assert_shift, MirType},
         value::{
-            MirAddress, MirAddressType, MirAnd, MirBool, MirNot, MirNumber, MirOr, MirValue,
-            MirVarRef, MirXor,
+            MirAddress, MirAddressIndex, MirAddressType, MirAnd, MirAnyPointer, MirBool, MirNot,
+            MirNumber, MirOr, MirRotl, MirRotr, MirShl, MirShr, MirValue, MirVarRef, MirXor,
         },
-        Mir, BUILTIN_FUNCTIONS,
+        writer::MirInstructionWriter,
+        Mir, MirInstruction, MirPointerCopy, BUILTIN_FUNCTIONS,
     },
     util::Quote,
 };
@@ -25,8 +30,18 @@ pub fn transform_values(mir: &mut Mir, hir_values: Vec<HirValue>) -> Result<Vec<
 }
 
 fn transform_number(number: HirNumber) -> Result<MirValue> {
+    let r#type = match number.r#type {
+        HirNumberType::Untyped => None,
+        HirNumberType::Byte => Some(MirType::Byte),
+        HirNumberType::Word => Some(MirType::Word),
+        HirNumberType::DWord => Some(MirType::DWord),
+        HirNumberType::DInt => Some(MirType::DInt),
+        HirNumberType::Pointer => Some(MirType::Pointer),
+        HirNumberType::Time => Some(MirType::Time),
+    };
     Ok(MirValue::Number(MirNumber {
         value: number.value,
+        r#type,
     }))
 }
 
@@ -34,28 +49,271 @@ fn transform_bool(bool: HirBool) -> Result<MirValue> {
     Ok(MirValue::Bool(MirBool { value: bool.value }))
 }
 
+/// Map an operand area and width to its [MirAddressType]
+pub fn address_type(
+    mir: &Mir,
+    quote: &Quote,
+    area: HirAddressArea,
+    width: HirAddressWidth,
+) -> Result<MirAddressType> {
+    use HirAddressArea as A;
+    use HirAddressWidth as W;
+    use MirAddressType as T;
+    Ok(match (area, width) {
+        (A::Input, W::Bit) => T::PhysicalInput1,
+        (A::Input, W::Byte) => T::PhysicalInput8,
+        (A::Input, W::Word) => T::PhysicalInput16,
+        (A::Input, W::DWord) => T::PhysicalInput32,
+        (A::Output, W::Bit) => T::PhysicalOutput1,
+        (A::Output, W::Byte) => T::PhysicalOutput8,
+        (A::Output, W::Word) => T::PhysicalOutput16,
+        (A::Output, W::DWord) => T::PhysicalOutput32,
+        (A::Memory, W::Bit) => T::PhysicalMemory1,
+        (A::Memory, W::Byte) => T::PhysicalMemory8,
+        (A::Memory, W::Word) => T::PhysicalMemory16,
+        (A::Memory, W::DWord) => T::PhysicalMemory32,
+        (A::PeripheralInput, W::Byte) => T::PeripheralInput8,
+        (A::PeripheralInput, W::Word) => T::PeripheralInput16,
+        (A::PeripheralInput, W::DWord) => T::PeripheralInput32,
+        (A::PeripheralOutput, W::Byte) => T::PeripheralOutput8,
+        (A::PeripheralOutput, W::Word) => T::PeripheralOutput16,
+        (A::PeripheralOutput, W::DWord) => T::PeripheralOutput32,
+        (A::Timer, W::Bit) => T::PhysicalTimer,
+        (A::Counter, W::Bit) => T::PhysicalCounter,
+        (A::DataBlock, W::Bit) => T::DataBlock1,
+        (A::DataBlock, W::Byte) => T::DataBlock8,
+        (A::DataBlock, W::Word) => T::DataBlock16,
+        (A::DataBlock, W::DWord) => T::DataBlock32,
+        (A::PeripheralInput | A::PeripheralOutput, W::Bit)
+        | (A::Timer | A::Counter, W::Byte | W::Word | W::DWord) => {
+            return Err(Error::new(
+                mir.source.clone(),
+                quote.clone(),
+                Reason::InvalidAddressWidth,
+            ))
+        }
+        (A::Raw, _) => {
+            return Err(Error::new(
+                mir.source.clone(),
+                quote.clone(),
+                Reason::UnknownBitAddressType,
+            ))
+        }
+    })
+}
+
 fn transform_address(
     mir: &mut Mir,
     quote: Quote,
-    HirBitAddress { char, ptr, bit }: HirBitAddress,
+    HirBitAddress {
+        area,
+        width,
+        db,
+        ptr,
+        bit,
+    }: HirBitAddress,
+) -> Result<MirValue> {
+    let r#type = address_type(mir, &quote, area, width)?;
+    Ok(MirValue::Address(MirAddress {
+        r#type,
+        db,
+        ptr,
+        bit,
+        index: MirAddressIndex::Direct,
+    }))
+}
+
+pub(super) fn data_type(r#type: HirDataType) -> MirType {
+    match r#type {
+        HirDataType::Bool => MirType::Bool,
+        HirDataType::Byte => MirType::Byte,
+        HirDataType::Word => MirType::Word,
+        HirDataType::DWord => MirType::DWord,
+        HirDataType::Int => MirType::Int,
+        HirDataType::DInt => MirType::DInt,
+        HirDataType::Real => MirType::Real,
+        HirDataType::Time => MirType::Time,
+    }
+}
+
+fn transform_any_pointer(
+    mir: &mut Mir,
+    quote: Quote,
+    HirAnyPointer {
+        area,
+        db,
+        ptr,
+        bit,
+        r#type,
+        count,
+    }: HirAnyPointer,
 ) -> Result<MirValue> {
-    match char {
-        b'I' | b'E' => Ok(MirValue::Address(MirAddress {
-            r#type: MirAddressType::PhysicalInput1,
+    let r#type = data_type(r#type);
+    let addr = MirAddress {
+        r#type: address_type(mir, &quote, area, HirAddressWidth::Bit)?,
+        db,
+        ptr,
+        bit,
+        index: MirAddressIndex::Direct,
+    };
+    Ok(MirValue::Any(MirAnyPointer {
+        addr,
+        r#type,
+        count,
+    }))
+}
+
+fn transform_indirect_address(
+    mir: &mut Mir,
+    quote: Quote,
+    HirIndirectAddress {
+        area,
+        width,
+        db,
+        index,
+    }: HirIndirectAddress,
+) -> Result<MirValue> {
+    let r#type = address_type(mir, &quote, area, width)?;
+    let (ptr, bit, index) = match index {
+        HirAddressIndex::Register { ptr, bit } => (ptr, bit, MirAddressIndex::Register),
+        HirAddressIndex::Memory(value) => {
+            let value_quote = value.quote.clone();
+            let value = transform_value(mir, value)?;
+            let ptr = transform_pointer(mir, &value_quote, r#type, db, value)?;
+            match ptr {
+                MirPointer::Constant(addr) => return Ok(MirValue::Address(addr)),
+                MirPointer::Memory(ptr) => (0, 0, MirAddressIndex::Memory { ptr }),
+            }
+        }
+    };
+    Ok(MirValue::Address(MirAddress {
+        r#type,
+        db,
+        ptr,
+        bit,
+        index,
+    }))
+}
+
+/// Location of the pointer of an indirect address
+enum MirPointer {
+    /// The pointer is known, the address is accessed directly
+    Constant(MirAddress),
+    /// The pointer is read from `MD ptr`, `MW ptr` for timers and counters
+    Memory(u16),
+}
+
+/// Resolve the pointer of a memory-indirect address
+///
+/// Pointers not stored in the memory area are copied into a newly allocated location by every
+/// statement accessing through them, so they follow changes of their source.
+fn transform_pointer(
+    mir: &mut Mir,
+    quote: &Quote,
+    r#type: MirAddressType,
+    db: u16,
+    value: MirValue,
+) -> Result<MirPointer> {
+    use MirAddressType as T;
+    let numbered = matches!(r#type, T::PhysicalTimer | T::PhysicalCounter);
+    let pointer_type = if numbered {
+        MirType::Word
+    } else {
+        MirType::Pointer
+    };
+    // Pointers in a `WORD` (`E[MW20]`) are zero-extended into the memory area
+    let load_type = match value.r#type(mir) {
+        Some(MirType::Word) => MirType::Word,
+        _ => pointer_type,
+    };
+    assert_assignable(mir, quote, &value, load_type)?;
+    match value.resolve(mir) {
+        MirValue::Number(number) => {
+            let (ptr, bit) = if numbered {
+                (number.value, 0)
+            } else {
+                (number.value / 8, number.value % 8)
+            };
+            if r#type.width() != 1 && bit != 0 {
+                return Err(Error::new(
+                    mir.source.clone(),
+                    quote.clone(),
+                    Reason::InvalidAddressWidth,
+                ));
+            }
+            if ptr + (r#type.width() as usize).div_ceil(8) > u16::MAX as usize + 1 {
+                return Err(Error::new(
+                    mir.source.clone(),
+                    quote.clone(),
+                    Reason::PointerOutOfRange,
+                ));
+            }
+            let addr = MirAddress {
+                r#type,
+                db,
+                ptr: ptr as u16,
+                bit: bit as u8,
+                index: MirAddressIndex::Direct,
+            };
+            if matches!(
+                r#type,
+                T::PhysicalMemory1 | T::PhysicalMemory8 | T::PhysicalMemory16 | T::PhysicalMemory32
+            ) {
+                mir.constant_pointers.push((quote.clone(), addr));
+            }
+            return Ok(MirPointer::Constant(addr));
+        }
+        MirValue::Address(MirAddress {
+            r#type: T::PhysicalMemory32 | T::Memory32,
             ptr,
-            bit,
-        })),
-        b'Q' | b'A' => Ok(MirValue::Address(MirAddress {
-            r#type: MirAddressType::PhysicalOutput1,
+            index: MirAddressIndex::Direct,
+            ..
+        }) if !numbered => return Ok(MirPointer::Memory(ptr)),
+        MirValue::Address(MirAddress {
+            r#type: T::PhysicalMemory16 | T::Memory16,
             ptr,
-            bit,
-        })),
-        _ => Err(Error::new(
-            mir.source.clone(),
-            quote,
-            Reason::UnknownBitAddressType,
-        )),
+            index: MirAddressIndex::Direct,
+            ..
+        }) if numbered => return Ok(MirPointer::Memory(ptr)),
+        _ => {}
     }
+    let location = if numbered {
+        mir.allocator.alloc16().expect("Allocate u16")
+    } else {
+        mir.allocator.alloc32().expect("Allocate u32")
+    };
+    let mut writer = MirInstructionWriter::default();
+    writer.write_load(mir, &value, pointer_type)?;
+    writer
+        .instructions
+        .push(MirInstruction::Transfer { addr: location });
+    mir.pointer_copies.push(MirPointerCopy {
+        location: location.ptr,
+        instructions: writer.instructions,
+    });
+    Ok(MirPointer::Memory(location.ptr))
+}
+
+fn transform_bit_access(mir: &mut Mir, quote: Quote, access: HirBitAccess) -> Result<MirValue> {
+    let value = transform_value(mir, access.value)?.resolve(mir);
+    let addr = match value {
+        MirValue::Address(addr)
+            if addr.r#type.bit_type().is_some() && addr.index == MirAddressIndex::Direct =>
+        {
+            addr
+        }
+        _ => {
+            return Err(Error::new(
+                mir.source.clone(),
+                quote,
+                Reason::ValueNotBitAccessible,
+            ))
+        }
+    };
+    if access.bit >= addr.r#type.width() {
+        return Err(Error::new(mir.source.clone(), quote, Reason::BitOutOfRange));
+    }
+    Ok(MirValue::Address(addr.bit_address(access.bit)))
 }
 
 fn transform_not(mir: &mut Mir, not: HirNot) -> Result<MirValue> {
@@ -64,25 +322,53 @@ fn transform_not(mir: &mut Mir, not: HirNot) -> Result<MirValue> {
     })))
 }
 
-fn transform_and(mir: &mut Mir, and: HirAnd) -> Result<MirValue> {
-    Ok(MirValue::And(Rc::new(MirAnd {
-        left: transform_value(mir, and.left)?,
-        right: transform_value(mir, and.right)?,
-    })))
+fn transform_and(mir: &mut Mir, quote: Quote, and: HirAnd) -> Result<MirValue> {
+    let left = transform_value(mir, and.left)?;
+    let right = transform_value(mir, and.right)?;
+    assert_same_type(mir, &quote, &left, &right)?;
+    Ok(MirValue::And(Rc::new(MirAnd { left, right })))
 }
 
-fn transform_or(mir: &mut Mir, or: HirOr) -> Result<MirValue> {
-    Ok(MirValue::Or(Rc::new(MirOr {
-        left: transform_value(mir, or.left)?,
-        right: transform_value(mir, or.right)?,
-    })))
+fn transform_or(mir: &mut Mir, quote: Quote, or: HirOr) -> Result<MirValue> {
+    let left = transform_value(mir, or.left)?;
+    let right = transform_value(mir, or.right)?;
+    assert_same_type(mir, &quote, &left, &right)?;
+    Ok(MirValue::Or(Rc::new(MirOr { left, right })))
 }
 
-fn transform_xor(mir: &mut Mir, xor: HirXor) -> Result<MirValue> {
-    Ok(MirValue::Xor(Rc::new(MirXor {
-        left: transform_value(mir, xor.left)?,
-        right: transform_value(mir, xor.right)?,
-    })))
+fn transform_xor(mir: &mut Mir, quote: Quote, xor: HirXor) -> Result<MirValue> {
+    let left = transform_value(mir, xor.left)?;
+    let right = transform_value(mir, xor.right)?;
+    assert_same_type(mir, &quote, &left, &right)?;
+    Ok(MirValue::Xor(Rc::new(MirXor { left, right })))
+}
+
+fn transform_shl(mir: &mut Mir, quote: Quote, shl: HirShl) -> Result<MirValue> {
+    let left = transform_value(mir, shl.left)?;
+    let right = transform_value(mir, shl.right)?;
+    assert_shift(mir, &quote, &left, &right, false)?;
+    Ok(MirValue::Shl(Rc::new(MirShl { left, right })))
+}
+
+fn transform_shr(mir: &mut Mir, quote: Quote, shr: HirShr) -> Result<MirValue> {
+    let left = transform_value(mir, shr.left)?;
+    let right = transform_value(mir, shr.right)?;
+    assert_shift(mir, &quote, &left, &right, false)?;
+    Ok(MirValue::Shr(Rc::new(MirShr { left, right })))
+}
+
+fn transform_rotl(mir: &mut Mir, quote: Quote, rotl: HirRotl) -> Result<MirValue> {
+    let left = transform_value(mir, rotl.left)?;
+    let right = transform_value(mir, rotl.right)?;
+    assert_shift(mir, &quote, &left, &right, true)?;
+    Ok(MirValue::Rotl(Rc::new(MirRotl { left, right })))
+}
+
+fn transform_rotr(mir: &mut Mir, quote: Quote, rotr: HirRotr) -> Result<MirValue> {
+    let left = transform_value(mir, rotr.left)?;
+    let right = transform_value(mir, rotr.right)?;
+    assert_shift(mir, &quote, &left, &right, true)?;
+    Ok(MirValue::Rotr(Rc::new(MirRotr { left, right })))
 }
 
 fn transform_var_ref(mir: &mut Mir, quote: Quote) -> Result<MirValue> {
@@ -98,14 +384,35 @@ fn transform_var_ref(mir: &mut Mir, quote: Quote) -> Result<MirValue> {
 }
 
 fn transform_call(mir: &mut Mir, quote: Quote, call: HirCall) -> Result<MirValue> {
-    let function_name = &mir.source.code[&call.name];
+    call_function(mir, quote, call.name, call.args)
+}
+
+/// Call a builtin function, external block or system block by name
+pub(super) fn call_function(
+    mir: &mut Mir,
+    quote: Quote,
+    name: Quote,
+    args: Vec<HirValue>,
+) -> Result<MirValue> {
+    let function_name = &mir.source.code[&name];
     if let Some(func) = BUILTIN_FUNCTIONS.get(function_name) {
-        let args = transform_values(mir, call.args)?;
+        let args = transform_values(mir, args)?;
         return func(mir, quote, &args);
     };
+    if let Some(index) = mir.find_extern(function_name) {
+        let quotes: Vec<_> = args.iter().map(|it| it.quote.clone()).collect();
+        let args = transform_values(mir, args)?;
+        let signature = mir.externs[index].signature.clone();
+        return call_block(mir, quote, &signature, &args, &quotes);
+    };
+    if let Some(signature) = SYSTEM_BLOCKS.get(function_name) {
+        let quotes: Vec<_> = args.iter().map(|it| it.quote.clone()).collect();
+        let args = transform_values(mir, args)?;
+        return call_block(mir, quote, signature, &args, &quotes);
+    };
     Err(Error::new(
         mir.source.clone(),
-        call.name,
+        name,
         Reason::UnknownFunction,
     ))
 }
@@ -115,10 +422,20 @@ pub(super) fn transform_value(mir: &mut Mir, value: HirValue) -> Result<MirValue
         HirValueType::Number(number) => transform_number(number),
         HirValueType::Bool(bool) => transform_bool(bool),
         HirValueType::BitAddress(address) => transform_address(mir, value.quote, address),
+        HirValueType::IndirectAddress(address) => {
+            transform_indirect_address(mir, value.quote, *address)
+        }
+        HirValueType::AddressRegister => Ok(MirValue::AddressRegister),
+        HirValueType::AnyPointer(any) => transform_any_pointer(mir, value.quote, any),
+        HirValueType::BitAccess(access) => transform_bit_access(mir, value.quote, *access),
         HirValueType::Not(not) => transform_not(mir, *not),
-        HirValueType::And(and) => transform_and(mir, *and),
-        HirValueType::Or(or) => transform_or(mir, *or),
-        HirValueType::Xor(xor) => transform_xor(mir, *xor),
+        HirValueType::And(and) => transform_and(mir, value.quote, *and),
+        HirValueType::Or(or) => transform_or(mir, value.quote, *or),
+        HirValueType::Xor(xor) => transform_xor(mir, value.quote, *xor),
+        HirValueType::Shl(shl) => transform_shl(mir, value.quote, *shl),
+        HirValueType::Shr(shr) => transform_shr(mir, value.quote, *shr),
+        HirValueType::Rotl(rotl) => transform_rotl(mir, value.quote, *rotl),
+        HirValueType::Rotr(rotr) => transform_rotr(mir, value.quote, *rotr),
         HirValueType::VarRef(_) => transform_var_ref(mir, value.quote),
         HirValueType::Call(call) => transform_call(mir, value.quote, call),
     }
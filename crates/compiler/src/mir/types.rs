@@ -0,0 +1,216 @@
+//! Data types of [MirValue]s
+
+use std::cmp::Ordering;
+
+use crate::{
+    error::{Error, Reason, Result},
+    util::Quote,
+};
+
+use super::{value::MirValue, Mir};
+
+/// Data type of a [MirValue]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MirType {
+    Bool,
+    Byte,
+    Word,
+    DWord,
+    Int,
+    DInt,
+    Real,
+    /// Area-internal pointer (`P#2.0`)
+    Pointer,
+    /// Duration in milliseconds (`T#5s`)
+    Time,
+}
+
+impl MirType {
+    /// Width of the type in bits
+    pub fn width(self) -> u8 {
+        match self {
+            Self::Bool => 1,
+            Self::Byte => 8,
+            Self::Word | Self::Int => 16,
+            Self::DWord | Self::DInt | Self::Real | Self::Pointer | Self::Time => 32,
+        }
+    }
+
+    /// Untyped bit pattern of the given width in bits
+    pub fn from_width(width: u8) -> Self {
+        match width {
+            1 => Self::Bool,
+            8 => Self::Byte,
+            16 => Self::Word,
+            _ => Self::DWord,
+        }
+    }
+
+    /// Name of the type as used in STEP 7
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Bool => "BOOL",
+            Self::Byte => "BYTE",
+            Self::Word => "WORD",
+            Self::DWord => "DWORD",
+            Self::Int => "INT",
+            Self::DInt => "DINT",
+            Self::Real => "REAL",
+            Self::Pointer => "POINTER",
+            Self::Time => "TIME",
+        }
+    }
+
+    /// Checks if the type is an untyped bit pattern (`BYTE`, `WORD`, `DWORD`)
+    pub fn is_pattern(self) -> bool {
+        matches!(self, Self::Byte | Self::Word | Self::DWord)
+    }
+
+    /// Checks if a value of type `other` can be used as this type without conversion
+    ///
+    /// Bit patterns are compatible with numbers of the same width.
+    pub fn accepts(self, other: MirType) -> bool {
+        self == other
+            || (self.width() == other.width() && (self.is_pattern() || other.is_pattern()))
+    }
+
+    /// Checks if an untyped constant fits into the type
+    pub fn fits(self, value: usize) -> bool {
+        match self {
+            Self::Int => value <= i16::MAX as usize,
+            Self::DInt | Self::Time => value <= i32::MAX as usize,
+            Self::Real => true,
+            _ => value >> self.width() == 0,
+        }
+    }
+}
+
+/// Checks if the value can be used as the given type, untyped numbers adapt to it
+pub fn check_type(mir: &Mir, value: &MirValue, r#type: MirType) -> bool {
+    match value {
+        MirValue::Number(number) => match number.r#type {
+            Some(number_type) => r#type.accepts(number_type),
+            None => r#type.fits(number.value),
+        },
+        MirValue::Convert(convert) => r#type.accepts(convert.to),
+        MirValue::VarRef(var) => check_type(mir, &mir.variables[var.index].value, r#type),
+        MirValue::Not(not) => check_type(mir, &not.value, r#type),
+        MirValue::And(and) => {
+            check_type(mir, &and.left, r#type) && check_type(mir, &and.right, r#type)
+        }
+        MirValue::Or(or) => check_type(mir, &or.left, r#type) && check_type(mir, &or.right, r#type),
+        MirValue::Xor(xor) => {
+            check_type(mir, &xor.left, r#type) && check_type(mir, &xor.right, r#type)
+        }
+        MirValue::Shl(shl) => r#type != MirType::Bool && check_type(mir, &shl.left, r#type),
+        MirValue::Shr(shr) => r#type != MirType::Bool && check_type(mir, &shr.left, r#type),
+        MirValue::Rotl(rotl) => r#type == MirType::DWord && check_type(mir, &rotl.left, r#type),
+        MirValue::Rotr(rotr) => r#type == MirType::DWord && check_type(mir, &rotr.left, r#type),
+        value => value.r#type(mir).is_some_and(|it| r#type.accepts(it)),
+    }
+}
+
+/// Asserts that both operands of a logic operation have the same type
+pub fn assert_same_type(mir: &Mir, quote: &Quote, left: &MirValue, right: &MirValue) -> Result<()> {
+    let r#type = left.r#type(mir).or(right.r#type(mir));
+    let matching = match r#type {
+        Some(MirType::Bool) => {
+            left.r#type(mir) == Some(MirType::Bool) && right.r#type(mir) == Some(MirType::Bool)
+        }
+        Some(r#type) => check_type(mir, left, r#type) && check_type(mir, right, r#type),
+        None => true,
+    };
+    if !matching {
+        return Err(Error::new(
+            mir.source.clone(),
+            quote.clone(),
+            Reason::TypeMismatch,
+        ));
+    }
+    Ok(())
+}
+
+/// Asserts that the value and amount of a shift or rotation are valid
+///
+/// Rotations are only available for `DWORD`, constant amounts must not exceed the width.
+pub fn assert_shift(
+    mir: &Mir,
+    quote: &Quote,
+    value: &MirValue,
+    amount: &MirValue,
+    rotate: bool,
+) -> Result<()> {
+    let r#type = value.r#type(mir);
+    let valid_type = match r#type {
+        Some(MirType::Bool | MirType::Real | MirType::Pointer | MirType::Time) => false,
+        Some(r#type) => !rotate || r#type.width() == 32,
+        None => true,
+    };
+    let valid_amount = match amount.resolve(mir) {
+        MirValue::Number(_) => true,
+        amount => matches!(amount.r#type(mir), Some(MirType::Byte | MirType::Word)),
+    };
+    if !valid_type || !valid_amount {
+        return Err(Error::new(
+            mir.source.clone(),
+            quote.clone(),
+            Reason::InvalidOperandType,
+        ));
+    }
+    if let MirValue::Number(number) = amount.resolve(mir) {
+        let width = r#type.map_or(32, |it| it.width().max(16)) as usize;
+        if number.value >= width {
+            return Err(Error::new(
+                mir.source.clone(),
+                quote.clone(),
+                Reason::ShiftOutOfRange,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Asserts that the value can be assigned to a target of the given type
+pub fn assert_assignable(
+    mir: &Mir,
+    quote: &Quote,
+    value: &MirValue,
+    r#type: MirType,
+) -> Result<()> {
+    if !check_type(mir, value, r#type) {
+        return Err(type_error(mir, quote, value, r#type));
+    }
+    Ok(())
+}
+
+/// Create an error for a value that cannot be used as the given type
+fn type_error(mir: &Mir, quote: &Quote, value: &MirValue, r#type: MirType) -> Error {
+    let from = match value.r#type(mir) {
+        Some(from) if from != MirType::Bool && r#type != MirType::Bool => from,
+        _ => {
+            return Error::new(mir.source.clone(), quote.clone(), Reason::TypeMismatch);
+        }
+    };
+    let reason = match from.width().cmp(&r#type.width()) {
+        Ordering::Less => Reason::ImplicitWidening,
+        Ordering::Greater => Reason::ImplicitNarrowing,
+        Ordering::Equal => Reason::ImplicitConversion,
+    };
+    let error = Error::new(mir.source.clone(), quote.clone(), reason);
+    let conversion = match (from, r#type) {
+        (MirType::Int, MirType::DInt | MirType::DWord) => "int_to_dint(..)",
+        (MirType::Int, MirType::Real) => "dint_to_real(int_to_dint(..))",
+        (MirType::DInt, MirType::Real) => "dint_to_real(..)",
+        (MirType::Real, MirType::DInt) => "round(..)` or `trunc(..)",
+        _ => {
+            return error.with_note(format!(
+                "store the value in a target of type {}",
+                from.name()
+            ));
+        }
+    };
+    error.with_note(format!(
+        "convert the {} explicitly with `{conversion}`",
+        from.name()
+    ))
+}
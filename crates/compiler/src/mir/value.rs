@@ -7,8 +7,9 @@ use crate::{
 
 use super::{
     ops::MirOp,
+    types::{assert_assignable, MirType},
     writer::{optimizer::optimize, MirInstructionWriter},
-    Mir, MirAction, MirOutputAction,
+    Mir, MirAction, MirInstruction, MirOutputAction, MirRawAction,
 };
 
 #[derive(Clone, Debug)]
@@ -17,6 +18,9 @@ pub enum MirValue {
     Bool(MirBool),
     Number(MirNumber),
     Address(MirAddress),
+    /// `AR1`
+    AddressRegister,
+    Any(MirAnyPointer),
     VarRef(MirVarRef),
     /// A series of operations expected to return a bit-value
     Ops(Rc<MirOps>),
@@ -25,6 +29,11 @@ pub enum MirValue {
     And(Rc<MirAnd>),
     Or(Rc<MirOr>),
     Xor(Rc<MirXor>),
+    Shl(Rc<MirShl>),
+    Shr(Rc<MirShr>),
+    Rotl(Rc<MirRotl>),
+    Rotr(Rc<MirRotr>),
+    Convert(Rc<MirConvert>),
 }
 
 impl MirValue {
@@ -50,6 +59,7 @@ impl MirValue {
                 Ok(())
             }
             Self::Object(object) => object.write(mir, name_quote, value_quote, value),
+            Self::AddressRegister => write_address_register(mir, value_quote, value),
             _ => Err(Error::new(
                 mir.source.clone(),
                 name_quote,
@@ -58,22 +68,126 @@ impl MirValue {
         }
     }
 
+    /// Handler for an addition to a variable (`target += value`)
+    pub fn add(
+        &self,
+        mir: &mut Mir,
+        name_quote: Quote,
+        value_quote: Quote,
+        value: MirValue,
+    ) -> Result<()> {
+        match self {
+            Self::VarRef(var) => {
+                let var_value = mir.variables[var.index].value.clone();
+                var_value.add(mir, name_quote, value_quote, value)
+            }
+            Self::AddressRegister => add_address_register(mir, value_quote, value),
+            _ => Err(Error::new(
+                mir.source.clone(),
+                name_quote,
+                Reason::NoAddHandler,
+            )),
+        }
+    }
+
     pub fn is_bit_readable(&self, mir: &Mir) -> bool {
         match self {
             Self::Ops(_) => true,
-            Self::Address(addr) => addr.is_bit_address(),
-            Self::Unit | Self::Bool(_) | Self::Number(_) | Self::Object(_) => false,
+            Self::Address(addr) => addr.is_bit_readable(),
+            Self::Unit
+            | Self::Bool(_)
+            | Self::Number(_)
+            | Self::Object(_)
+            | Self::AddressRegister
+            | Self::Any(_) => false,
             Self::VarRef(var) => mir.variables[var.index].value.is_bit_readable(mir),
             Self::Not(not) => not.value.is_bit_readable(mir),
             Self::And(and) => and.left.is_bit_readable(mir) && and.right.is_bit_readable(mir),
             Self::Or(or) => or.left.is_bit_readable(mir) && or.right.is_bit_readable(mir),
             Self::Xor(xor) => xor.left.is_bit_readable(mir) && xor.right.is_bit_readable(mir),
+            Self::Shl(_) | Self::Shr(_) | Self::Rotl(_) | Self::Rotr(_) | Self::Convert(_) => false,
         }
     }
 
     pub fn is_unit(&self) -> bool {
         matches!(self, Self::Unit)
     }
+
+    /// Data type of the value, `None` for untyped numbers and values without a type
+    pub fn r#type(&self, mir: &Mir) -> Option<MirType> {
+        match self {
+            Self::Unit | Self::Object(_) | Self::Any(_) => None,
+            Self::Number(number) => number.r#type,
+            Self::Address(addr) if addr.is_bit_readable() => Some(MirType::Bool),
+            Self::Address(addr) => Some(MirType::from_width(addr.r#type.width())),
+            Self::AddressRegister => Some(MirType::Pointer),
+            Self::VarRef(var) => mir.variables[var.index].value.r#type(mir),
+            Self::Bool(_) | Self::Ops(_) => Some(MirType::Bool),
+            Self::Not(not) => not.value.r#type(mir),
+            Self::And(and) => and.left.r#type(mir).or(and.right.r#type(mir)),
+            Self::Or(or) => or.left.r#type(mir).or(or.right.r#type(mir)),
+            Self::Xor(xor) => xor.left.r#type(mir).or(xor.right.r#type(mir)),
+            Self::Shl(shl) => shl.left.r#type(mir),
+            Self::Shr(shr) => shr.left.r#type(mir),
+            Self::Rotl(rotl) => rotl.left.r#type(mir),
+            Self::Rotr(rotr) => rotr.left.r#type(mir),
+            Self::Convert(convert) => Some(convert.to),
+        }
+    }
+
+    /// Follow variable references to the referenced value
+    pub fn resolve(&self, mir: &Mir) -> MirValue {
+        match self {
+            Self::VarRef(var) => mir.variables[var.index].value.resolve(mir),
+            _ => self.clone(),
+        }
+    }
+}
+
+/// Load a pointer into `AR1` using `LAR1`
+fn write_address_register(mir: &mut Mir, value_quote: Quote, value: MirValue) -> Result<()> {
+    assert_assignable(mir, &value_quote, &value, MirType::Pointer)?;
+    let mut writer = MirInstructionWriter::default();
+    writer.write_load(mir, &value, MirType::Pointer)?;
+    writer
+        .instructions
+        .push(MirInstruction::LoadAddressRegister);
+    mir.actions.push(MirAction::Raw(MirRawAction {
+        instructions: writer.instructions,
+    }));
+    Ok(())
+}
+
+/// Add an offset to `AR1` using `+AR1`, constant offsets up to `P#4095.7` are encoded directly
+fn add_address_register(mir: &mut Mir, value_quote: Quote, value: MirValue) -> Result<()> {
+    let mut writer = MirInstructionWriter::default();
+    match value.resolve(mir) {
+        MirValue::Number(number) if number.r#type.is_none_or(|it| it == MirType::Pointer) => {
+            if number.value >= 4096 * 8 {
+                return Err(Error::new(
+                    mir.source.clone(),
+                    value_quote,
+                    Reason::PointerOutOfRange,
+                ));
+            }
+            writer
+                .instructions
+                .push(MirInstruction::AddAddressRegister {
+                    offset: Some(number.value as u32),
+                });
+        }
+        _ => {
+            assert_assignable(mir, &value_quote, &value, MirType::Int)?;
+            writer.write_load(mir, &value, MirType::Int)?;
+            writer
+                .instructions
+                .push(MirInstruction::AddAddressRegister { offset: None });
+        }
+    }
+    mir.actions.push(MirAction::Raw(MirRawAction {
+        instructions: writer.instructions,
+    }));
+    Ok(())
 }
 
 pub trait MirObject: Debug {
@@ -106,25 +220,125 @@ pub struct MirBool {
 #[derive(Clone, Copy, Debug)]
 pub struct MirNumber {
     pub value: usize,
+    /// Type of the number, untyped numbers adapt to their target
+    pub r#type: Option<MirType>,
 }
 
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MirAddressType {
     PhysicalInput1,
+    PhysicalInput8,
+    PhysicalInput16,
+    PhysicalInput32,
     PhysicalOutput1,
+    PhysicalOutput8,
+    PhysicalOutput16,
+    PhysicalOutput32,
+    PhysicalMemory1,
+    PhysicalMemory8,
+    PhysicalMemory16,
+    PhysicalMemory32,
+    PeripheralInput8,
+    PeripheralInput16,
+    PeripheralInput32,
+    PeripheralOutput8,
+    PeripheralOutput16,
+    PeripheralOutput32,
+    PhysicalTimer,
     PhysicalCounter,
+    DataBlock1,
+    DataBlock8,
+    DataBlock16,
+    DataBlock32,
     Memory1,
     Memory8,
     Memory16,
     Memory32,
+    /// Block temporary (`VAR_TEMP`) holding a call result, `ptr` is its byte offset
+    Temp1,
+    Temp8,
+    Temp16,
+    Temp32,
+}
+
+impl MirAddressType {
+    /// Width of the operand in bits, timers and counters are read as a status bit
+    pub fn width(self) -> u8 {
+        match self {
+            Self::PhysicalInput1
+            | Self::PhysicalOutput1
+            | Self::PhysicalMemory1
+            | Self::PhysicalTimer
+            | Self::PhysicalCounter
+            | Self::DataBlock1
+            | Self::Memory1
+            | Self::Temp1 => 1,
+            Self::PhysicalInput8
+            | Self::PhysicalOutput8
+            | Self::PhysicalMemory8
+            | Self::PeripheralInput8
+            | Self::PeripheralOutput8
+            | Self::DataBlock8
+            | Self::Memory8
+            | Self::Temp8 => 8,
+            Self::PhysicalInput16
+            | Self::PhysicalOutput16
+            | Self::PhysicalMemory16
+            | Self::PeripheralInput16
+            | Self::PeripheralOutput16
+            | Self::DataBlock16
+            | Self::Memory16
+            | Self::Temp16 => 16,
+            Self::PhysicalInput32
+            | Self::PhysicalOutput32
+            | Self::PhysicalMemory32
+            | Self::PeripheralInput32
+            | Self::PeripheralOutput32
+            | Self::DataBlock32
+            | Self::Memory32
+            | Self::Temp32 => 32,
+        }
+    }
+
+    /// Type of a single bit inside a `BYTE`, `WORD` or `DWORD` of this type
+    pub fn bit_type(self) -> Option<MirAddressType> {
+        match self {
+            Self::PhysicalInput8 | Self::PhysicalInput16 | Self::PhysicalInput32 => {
+                Some(Self::PhysicalInput1)
+            }
+            Self::PhysicalOutput8 | Self::PhysicalOutput16 | Self::PhysicalOutput32 => {
+                Some(Self::PhysicalOutput1)
+            }
+            Self::PhysicalMemory8 | Self::PhysicalMemory16 | Self::PhysicalMemory32 => {
+                Some(Self::PhysicalMemory1)
+            }
+            Self::DataBlock8 | Self::DataBlock16 | Self::DataBlock32 => Some(Self::DataBlock1),
+            Self::Memory8 | Self::Memory16 | Self::Memory32 => Some(Self::Memory1),
+            _ => None,
+        }
+    }
+}
+
+/// Indexing of a [MirAddress]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MirAddressIndex {
+    /// `MW10`
+    Direct,
+    /// `MW [MD10]`, the pointer is read from `MD ptr`, timers and counters use `MW ptr`
+    Memory { ptr: u16 },
+    /// `MW [AR1, P#2.0]`, `ptr` and `bit` of the address are the offset to `AR1`
+    Register,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct MirAddress {
     pub r#type: MirAddressType,
+    /// Number of the data block, only used by `DataBlock` types
+    pub db: u16,
     pub ptr: u16,
     pub bit: u8,
+    pub index: MirAddressIndex,
 }
 
 impl MirAddress {
@@ -135,13 +349,16 @@ impl MirAddress {
         value_quote: Quote,
         value: MirValue,
     ) -> Result<()> {
-        if self.r#type != MirAddressType::PhysicalOutput1 {
+        if !self.is_writable() {
             return Err(Error::new(
                 mir.source.clone(),
                 name_quote,
                 Reason::NoWriteHandler,
             ));
         }
+        if self.is_any_byte_address() {
+            return self.write_transfer(mir, value_quote, value);
+        }
         if !value.is_bit_readable(mir) {
             return Err(Error::new(
                 mir.source.clone(),
@@ -155,16 +372,44 @@ impl MirAddress {
         mir.actions.push(MirAction::Output(MirOutputAction {
             address: *self,
             instructions: writer.instructions,
+            value,
+        }));
+        Ok(())
+    }
+
+    /// Write a `BYTE`, `WORD` or `DWORD` using `L value` and `T addr`
+    fn write_transfer(&self, mir: &mut Mir, value_quote: Quote, value: MirValue) -> Result<()> {
+        let r#type = MirType::from_width(self.r#type.width());
+        assert_assignable(mir, &value_quote, &value, r#type)?;
+        let mut writer = MirInstructionWriter::default();
+        writer.write_load(mir, &value, r#type)?;
+        writer
+            .instructions
+            .push(MirInstruction::Transfer { addr: *self });
+        mir.actions.push(MirAction::Raw(MirRawAction {
+            instructions: writer.instructions,
         }));
         Ok(())
     }
 
     pub fn is_physical(self) -> bool {
+        !matches!(
+            self.r#type,
+            MirAddressType::Memory1
+                | MirAddressType::Memory8
+                | MirAddressType::Memory16
+                | MirAddressType::Memory32
+        ) && !self.is_temp()
+    }
+
+    /// Checks if the address is a block temporary
+    pub fn is_temp(self) -> bool {
         matches!(
             self.r#type,
-            MirAddressType::PhysicalInput1
-                | MirAddressType::PhysicalOutput1
-                | MirAddressType::PhysicalCounter
+            MirAddressType::Temp1
+                | MirAddressType::Temp8
+                | MirAddressType::Temp16
+                | MirAddressType::Temp32
         )
     }
 
@@ -172,36 +417,111 @@ impl MirAddress {
         !self.is_physical()
     }
 
+    /// Address of a single bit inside a `BYTE`, `WORD` or `DWORD`
+    ///
+    /// S7 stores the most significant byte first, so bit 0 of `MW10` is `M11.0`.
+    pub fn bit_address(self, bit: u8) -> MirAddress {
+        let bytes = self.r#type.width() / 8;
+        MirAddress {
+            r#type: self.r#type.bit_type().expect("Bit accessible address"),
+            db: self.db,
+            ptr: self.ptr + (bytes - 1 - bit / 8) as u16,
+            bit: bit % 8,
+            index: MirAddressIndex::Direct,
+        }
+    }
+
     /// Checks if the address points to a `BIT`
     pub fn is_bit_address(self) -> bool {
         matches!(
             self.r#type,
             MirAddressType::PhysicalInput1
                 | MirAddressType::PhysicalOutput1
+                | MirAddressType::PhysicalMemory1
+                | MirAddressType::DataBlock1
                 | MirAddressType::Memory1
+                | MirAddressType::Temp1
         )
     }
 
-    /// Checks if the address points to a `BYTE`, `WORD` or `DWORD`
-    pub fn is_any_byte_address(self) -> bool {
-        matches!(
+    /// Checks if the address can be read into the RLO, including timer and counter status
+    pub fn is_bit_readable(self) -> bool {
+        self.is_bit_address()
+            || matches!(
+                self.r#type,
+                MirAddressType::PhysicalTimer | MirAddressType::PhysicalCounter
+            )
+    }
+
+    /// Checks if the address can be written to
+    pub fn is_writable(self) -> bool {
+        !matches!(
             self.r#type,
-            MirAddressType::Memory8 | MirAddressType::Memory16 | MirAddressType::Memory32
+            MirAddressType::PhysicalInput1
+                | MirAddressType::PhysicalInput8
+                | MirAddressType::PhysicalInput16
+                | MirAddressType::PhysicalInput32
+                | MirAddressType::PeripheralInput8
+                | MirAddressType::PeripheralInput16
+                | MirAddressType::PeripheralInput32
+                | MirAddressType::PhysicalTimer
+                | MirAddressType::PhysicalCounter
         )
     }
+
+    /// Checks if the address points to a `BYTE`, `WORD` or `DWORD`
+    pub fn is_any_byte_address(self) -> bool {
+        matches!(self.r#type.width(), 8 | 16 | 32)
+    }
 }
 
 impl Debug for MirAddress {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let prefix = match self.r#type {
             MirAddressType::PhysicalInput1 => "I",
+            MirAddressType::PhysicalInput8 => "IB",
+            MirAddressType::PhysicalInput16 => "IW",
+            MirAddressType::PhysicalInput32 => "ID",
             MirAddressType::PhysicalOutput1 => "Q",
+            MirAddressType::PhysicalOutput8 => "QB",
+            MirAddressType::PhysicalOutput16 => "QW",
+            MirAddressType::PhysicalOutput32 => "QD",
+            MirAddressType::PhysicalMemory1 | MirAddressType::Memory1 => "M",
+            MirAddressType::PhysicalMemory8 | MirAddressType::Memory8 => "MB",
+            MirAddressType::PhysicalMemory16 | MirAddressType::Memory16 => "MW",
+            MirAddressType::PhysicalMemory32 | MirAddressType::Memory32 => "MD",
+            MirAddressType::PeripheralInput8 => "PIB",
+            MirAddressType::PeripheralInput16 => "PIW",
+            MirAddressType::PeripheralInput32 => "PID",
+            MirAddressType::PeripheralOutput8 => "PQB",
+            MirAddressType::PeripheralOutput16 => "PQW",
+            MirAddressType::PeripheralOutput32 => "PQD",
+            MirAddressType::PhysicalTimer => "T",
             MirAddressType::PhysicalCounter => "C",
-            MirAddressType::Memory1 => "M",
-            MirAddressType::Memory8 => "MB",
-            MirAddressType::Memory16 => "MW",
-            MirAddressType::Memory32 => "MD",
+            MirAddressType::DataBlock1 => "DBX",
+            MirAddressType::DataBlock8 => "DBB",
+            MirAddressType::DataBlock16 => "DBW",
+            MirAddressType::DataBlock32 => "DBD",
+            MirAddressType::Temp1
+            | MirAddressType::Temp8
+            | MirAddressType::Temp16
+            | MirAddressType::Temp32 => return write!(f, "#T{}", self.ptr),
         };
+        if self.db != 0 {
+            write!(f, "DB{}.", self.db)?;
+        }
+        match self.index {
+            MirAddressIndex::Direct => {}
+            MirAddressIndex::Memory { ptr }
+                if self.r#type.width() == 1 && !self.is_bit_address() =>
+            {
+                return write!(f, "{prefix} [MW{ptr}]");
+            }
+            MirAddressIndex::Memory { ptr } => return write!(f, "{prefix} [MD{ptr}]"),
+            MirAddressIndex::Register => {
+                return write!(f, "{prefix} [AR1, P#{}.{}]", self.ptr, self.bit);
+            }
+        }
         if self.is_bit_address() && self.is_physical() {
             write!(f, "{prefix}{}.{}", self.ptr, self.bit)
         } else {
@@ -210,6 +530,65 @@ impl Debug for MirAddress {
     }
 }
 
+/// `P#DB10.DBX0.0 BYTE 20`, `addr` is the first bit of the area
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MirAnyPointer {
+    pub addr: MirAddress,
+    pub r#type: MirType,
+    pub count: u16,
+}
+
+impl MirAnyPointer {
+    /// `ANY` pointer to a single operand, `None` for areas an `ANY` can not point to
+    pub fn from_address(addr: MirAddress) -> Option<Self> {
+        use MirAddressType as T;
+        let valid = matches!(
+            addr.r#type.bit_type().unwrap_or(addr.r#type),
+            T::PhysicalInput1
+                | T::PhysicalOutput1
+                | T::PhysicalMemory1
+                | T::DataBlock1
+                | T::Memory1
+        );
+        if !valid || addr.index != MirAddressIndex::Direct {
+            return None;
+        }
+        Some(Self {
+            addr: MirAddress {
+                r#type: addr.r#type.bit_type().unwrap_or(addr.r#type),
+                ..addr
+            },
+            r#type: MirType::from_width(addr.r#type.width()),
+            count: 1,
+        })
+    }
+
+    /// Size of the area in bits
+    pub fn bits(self) -> usize {
+        self.r#type.width() as usize * self.count as usize
+    }
+
+    /// First and last byte of the area
+    fn byte_range(self) -> (usize, usize) {
+        let start = self.addr.ptr as usize * 8 + self.addr.bit as usize;
+        (start / 8, (start + self.bits()).div_ceil(8) - 1)
+    }
+
+    /// Checks if both pointers share at least one byte
+    pub fn overlaps(self, other: MirAnyPointer) -> bool {
+        let area = |it: MirAddressType| match it {
+            MirAddressType::PhysicalMemory1 => MirAddressType::Memory1,
+            it => it,
+        };
+        if area(self.addr.r#type) != area(other.addr.r#type) || self.addr.db != other.addr.db {
+            return false;
+        }
+        let (start, end) = self.byte_range();
+        let (other_start, other_end) = other.byte_range();
+        start <= other_end && other_start <= end
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct MirVarRef {
     pub index: usize,
@@ -242,3 +621,36 @@ pub struct MirXor {
     pub left: MirValue,
     pub right: MirValue,
 }
+
+#[derive(Debug)]
+pub struct MirShl {
+    pub left: MirValue,
+    pub right: MirValue,
+}
+
+#[derive(Debug)]
+pub struct MirShr {
+    pub left: MirValue,
+    pub right: MirValue,
+}
+
+#[derive(Debug)]
+pub struct MirRotl {
+    pub left: MirValue,
+    pub right: MirValue,
+}
+
+#[derive(Debug)]
+pub struct MirRotr {
+    pub left: MirValue,
+    pub right: MirValue,
+}
+
+/// Conversion of a value in `ACCU1` with a single instruction
+#[derive(Debug)]
+pub struct MirConvert {
+    pub value: MirValue,
+    pub from: MirType,
+    pub to: MirType,
+    pub instruction: MirInstruction,
+}
@@ -5,6 +5,7 @@ use std::rc::Rc;
 use crate::{error::Result, mir::ops::MirOp};
 
 use super::{
+    types::MirType,
     value::{MirAddress, MirAnd, MirNot, MirOps, MirOr, MirValue, MirVarRef, MirXor},
     Mir, MirInstruction,
 };
@@ -12,11 +13,13 @@ use super::{
 #[derive(Default)]
 pub struct MirInstructionWriter {
     pub instructions: Vec<MirInstruction>,
+    /// Depth of the next free scratch temporary
+    scratch_depth: usize,
 }
 
 impl MirInstructionWriter {
     fn write_addr(&mut self, addr: MirAddress) -> Result<()> {
-        assert!(addr.is_bit_address());
+        assert!(addr.is_bit_readable());
         self.instructions.push(MirInstruction::And { addr });
         Ok(())
     }
@@ -51,8 +54,21 @@ impl MirInstructionWriter {
         Ok(())
     }
 
+    /// Write the left operand of a logic operation, bracketed if it would leave an open chain
+    ///
+    /// `AND` binds tighter than `OR`, so `U a; O b; U c` computes `a or (b and c)`.
+    fn write_left(&mut self, mir: &mut Mir, value: &MirValue, xor: bool) -> Result<()> {
+        if !leaves_open_chain(mir, value, xor) {
+            return self.write_value(mir, value);
+        }
+        self.instructions.push(MirInstruction::AndStart);
+        self.write_value(mir, value)?;
+        self.instructions.push(MirInstruction::End);
+        Ok(())
+    }
+
     fn write_and(&mut self, mir: &mut Mir, and: &Rc<MirAnd>) -> Result<()> {
-        self.write_value(mir, &and.left)?;
+        self.write_left(mir, &and.left, true)?;
         self.instructions.push(MirInstruction::AndStart);
         self.write_value(mir, &and.right)?;
         self.instructions.push(MirInstruction::End);
@@ -68,13 +84,164 @@ impl MirInstructionWriter {
     }
 
     fn write_xor(&mut self, mir: &mut Mir, xor: &Rc<MirXor>) -> Result<()> {
-        self.write_value(mir, &xor.left)?;
+        self.write_left(mir, &xor.left, false)?;
         self.instructions.push(MirInstruction::XorStart);
         self.write_value(mir, &xor.right)?;
         self.instructions.push(MirInstruction::End);
         Ok(())
     }
 
+    /// Load two values so that `ACCU2` holds `first` and `ACCU1` holds `second`
+    ///
+    /// If `second` needs more than one instruction, `first` is computed in between and `second`
+    /// is kept in a scratch temporary.
+    fn write_load_pair(
+        &mut self,
+        mir: &mut Mir,
+        (first, first_type): (&MirValue, MirType),
+        (second, second_type): (&MirValue, MirType),
+    ) -> Result<()> {
+        if is_leaf(mir, second) {
+            self.write_load(mir, first, first_type)?;
+            return self.write_load(mir, second, second_type);
+        }
+        self.write_load(mir, second, second_type)?;
+        let addr = mir
+            .allocator
+            .scratch(self.scratch_depth)
+            .expect("Allocate scratch");
+        self.instructions.push(MirInstruction::Transfer { addr });
+        self.scratch_depth += 1;
+        self.write_load(mir, first, first_type)?;
+        self.scratch_depth -= 1;
+        self.instructions.push(MirInstruction::Load { addr });
+        Ok(())
+    }
+
+    /// Write a commutative word operation, loading a single-instruction operand last
+    fn write_load_logic(
+        &mut self,
+        mir: &mut Mir,
+        (left, right): (&MirValue, &MirValue),
+        r#type: MirType,
+        instruction: MirInstruction,
+    ) -> Result<()> {
+        if !is_leaf(mir, right) && is_leaf(mir, left) {
+            self.write_load_pair(mir, (right, r#type), (left, r#type))?;
+        } else {
+            self.write_load_pair(mir, (left, r#type), (right, r#type))?;
+        }
+        self.instructions.push(instruction);
+        Ok(())
+    }
+
+    /// Write a shift or rotation, constant amounts are encoded into the instruction
+    fn write_load_shift(
+        &mut self,
+        mir: &mut Mir,
+        (value, amount): (&MirValue, &MirValue),
+        r#type: MirType,
+        instruction: fn(Option<u8>) -> MirInstruction,
+    ) -> Result<()> {
+        if let MirValue::Number(number) = amount.resolve(mir) {
+            self.write_load(mir, value, r#type)?;
+            self.instructions
+                .push(instruction(Some(number.value as u8)));
+            return Ok(());
+        }
+        let amount_type = amount.r#type(mir).unwrap_or(MirType::Byte);
+        self.write_load_pair(mir, (amount, amount_type), (value, r#type))?;
+        self.instructions.push(instruction(None));
+        Ok(())
+    }
+
+    /// Write a `BYTE`, `WORD` or `DWORD` value into `ACCU1`
+    ///
+    /// Bytes and words use the word instructions, the upper bits are dropped by `T`.
+    pub fn write_load(&mut self, mir: &mut Mir, value: &MirValue, r#type: MirType) -> Result<()> {
+        let dword = r#type.width() == 32;
+        match value {
+            // Pointer literals stay `P#x.y` in a `DWORD`
+            MirValue::Number(number) => self.instructions.push(MirInstruction::LoadConst {
+                value: number.value as u32,
+                r#type: match number.r#type {
+                    Some(MirType::Pointer) => MirType::Pointer,
+                    _ => r#type,
+                },
+            }),
+            MirValue::Address(addr) => self.instructions.push(MirInstruction::Load { addr: *addr }),
+            MirValue::AddressRegister => self
+                .instructions
+                .push(MirInstruction::TransferAddressRegister),
+            MirValue::VarRef(var_ref) => {
+                let value = mir.variables[var_ref.index].value.clone();
+                self.write_load(mir, &value, r#type)?;
+            }
+            MirValue::Not(not) => {
+                self.write_load(mir, &not.value, r#type)?;
+                self.instructions.push(if dword {
+                    MirInstruction::InvertDInt
+                } else {
+                    MirInstruction::InvertInt
+                });
+            }
+            MirValue::And(and) => {
+                let instruction = if dword {
+                    MirInstruction::AndDWord
+                } else {
+                    MirInstruction::AndWord
+                };
+                self.write_load_logic(mir, (&and.left, &and.right), r#type, instruction)?;
+            }
+            MirValue::Or(or) => {
+                let instruction = if dword {
+                    MirInstruction::OrDWord
+                } else {
+                    MirInstruction::OrWord
+                };
+                self.write_load_logic(mir, (&or.left, &or.right), r#type, instruction)?;
+            }
+            MirValue::Xor(xor) => {
+                let instruction = if dword {
+                    MirInstruction::XorDWord
+                } else {
+                    MirInstruction::XorWord
+                };
+                self.write_load_logic(mir, (&xor.left, &xor.right), r#type, instruction)?;
+            }
+            MirValue::Shl(shl) => {
+                let instruction = if dword {
+                    |amount| MirInstruction::ShiftLeftDWord { amount }
+                } else {
+                    |amount| MirInstruction::ShiftLeftWord { amount }
+                };
+                self.write_load_shift(mir, (&shl.left, &shl.right), r#type, instruction)?;
+            }
+            MirValue::Shr(shr) => {
+                let instruction = if dword {
+                    |amount| MirInstruction::ShiftRightDWord { amount }
+                } else {
+                    |amount| MirInstruction::ShiftRightWord { amount }
+                };
+                self.write_load_shift(mir, (&shr.left, &shr.right), r#type, instruction)?;
+            }
+            MirValue::Rotl(rotl) => {
+                let instruction = |amount| MirInstruction::RotateLeftDWord { amount };
+                self.write_load_shift(mir, (&rotl.left, &rotl.right), r#type, instruction)?;
+            }
+            MirValue::Rotr(rotr) => {
+                let instruction = |amount| MirInstruction::RotateRightDWord { amount };
+                self.write_load_shift(mir, (&rotr.left, &rotr.right), r#type, instruction)?;
+            }
+            MirValue::Convert(convert) => {
+                self.write_load(mir, &convert.value, convert.from)?;
+                self.instructions.push(convert.instruction);
+            }
+            _ => unreachable!("Value is not loadable"),
+        }
+        Ok(())
+    }
+
     pub fn write_value(&mut self, mir: &mut Mir, value: &MirValue) -> Result<()> {
         match value {
             MirValue::Unit => todo!(),
@@ -88,6 +255,34 @@ impl MirInstructionWriter {
             MirValue::And(and) => self.write_and(mir, and),
             MirValue::Or(or) => self.write_or(mir, or),
             MirValue::Xor(xor) => self.write_xor(mir, xor),
+            MirValue::AddressRegister
+            | MirValue::Any(_)
+            | MirValue::Shl(_)
+            | MirValue::Shr(_)
+            | MirValue::Rotl(_)
+            | MirValue::Rotr(_)
+            | MirValue::Convert(_) => {
+                unreachable!("Value is not bit-readable")
+            }
         }
     }
 }
+
+/// Checks if the bit value ends in an `O` chain, or an `X` chain if `xor` is set, that a
+/// following logic instruction would bind to
+fn leaves_open_chain(mir: &Mir, value: &MirValue, xor: bool) -> bool {
+    match value.resolve(mir) {
+        MirValue::Or(_) => true,
+        MirValue::Xor(_) => xor,
+        MirValue::Not(not) => leaves_open_chain(mir, &not.value, xor),
+        _ => false,
+    }
+}
+
+/// Checks if the value can be loaded with a single `L`
+fn is_leaf(mir: &Mir, value: &MirValue) -> bool {
+    matches!(
+        value.resolve(mir),
+        MirValue::Number(_) | MirValue::Address(_)
+    )
+}
@@ -47,6 +47,7 @@ fn optimize_brackets(slice: &mut [MirInstruction], mut index: usize, bracket_ter
         }
         let inner = *get_non_dummy(term, 0);
         if !matches!(inner, MirInstruction::And { .. }) {
+            index = term_end + 1;
             continue;
         }
         fill_dummy(term);
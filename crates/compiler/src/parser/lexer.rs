@@ -4,15 +4,31 @@ use std::rc::Rc;
 
 use crate::{
     error::{Error, Reason, Result},
+    hir::HirComment,
     parser::symbol::{Symbol, KEYWORDS},
     util::{Quote, Source, Q},
 };
 
 pub const NULL: char = '\0';
 
+/// Prefixes that start a literal when directly followed by `#` (`W#16#FF`, `16#FF`),
+/// a `#` after anything else starts a comment
+const LITERAL_PREFIXES: &[&str] = &["B", "W", "DW", "C", "L", "P", "T", "TIME", "2", "8", "16"];
+
+/// Checks if the `#` at `index` continues a literal (`W#16#FF`) instead of starting a comment,
+/// the bit number of an operand (`E0.2#`) is never a literal prefix
+pub fn continues_literal(code: &str, index: usize) -> bool {
+    let start = code[..index]
+        .trim_end_matches(|c: char| c == '_' || c.is_ascii_alphanumeric())
+        .len();
+    !code[..start].ends_with('.') && LITERAL_PREFIXES.contains(&&code[start..index])
+}
+
 pub struct Lexer {
     source: Rc<Source>,
     index: usize,
+    /// Comments read so far
+    pub comments: Vec<HirComment>,
 }
 
 /// General lexer functions
@@ -22,7 +38,11 @@ impl Lexer {
             !source.code.contains(NULL),
             "Source must not contain a null-character."
         );
-        Self { source, index: 0 }
+        Self {
+            source,
+            index: 0,
+            comments: Vec::new(),
+        }
     }
 
     /// Get the current char
@@ -87,17 +107,102 @@ impl Lexer {
         }
     }
 
+    /// Read a `//` or `///` comment, the slashes have not been consumed yet
+    fn read_comment(&mut self) {
+        self.advance();
+        self.advance();
+        // `////` is a separator line, not a doc comment
+        let doc = self.get() == '/' && self.peek() != '/';
+        if doc {
+            self.advance();
+        }
+        let start = self.index;
+        while !matches!(self.get(), '\n' | '\r' | NULL) {
+            self.advance();
+        }
+        self.comments.push(HirComment {
+            quote: Quote::new(start, self.index),
+            doc,
+            block: false,
+        });
+    }
+
+    /// Read a `/* */` comment, the opening has not been consumed yet
+    fn read_block_comment(&mut self) -> Result<()> {
+        let start = self.index;
+        self.advance();
+        self.advance();
+        loop {
+            match self.get() {
+                NULL => {
+                    return Err(Error::new(
+                        self.source.clone(),
+                        Quote::new(start, start + 2),
+                        Reason::UnterminatedComment,
+                    ))
+                }
+                '*' if self.peek() == '/' => {
+                    self.comments.push(HirComment {
+                        quote: Quote::new(start + 2, self.index),
+                        doc: false,
+                        block: true,
+                    });
+                    self.advance();
+                    self.advance();
+                    return Ok(());
+                }
+                _ => self.advance(),
+            }
+        }
+    }
+
     fn skip_whitespace(&mut self) {
         while self.get().is_ascii_whitespace() {
             self.advance();
         }
     }
 
+    /// Checks if a `#` follows a literal prefix
+    fn at_literal_body(&self) -> bool {
+        self.get() == '#' && continues_literal(&self.source.code, self.index)
+    }
+
+    /// Read the body of a literal (`16#FF`, `(1,2)`, `2.0`, `DB10.DBX0.0`)
+    fn read_literal_body(&mut self) {
+        if self.get() == '(' {
+            while !matches!(self.get(), ')' | NULL) {
+                self.advance();
+            }
+            if self.get() == ')' {
+                self.advance();
+            }
+            return;
+        }
+        loop {
+            match self.get() {
+                '_' | '#' | 'a'..='z' | 'A'..='Z' | '0'..='9' => self.advance(),
+                '.' if self.peek().is_ascii_alphanumeric() => self.advance(),
+                _ => break,
+            }
+        }
+    }
+
     fn read_number(&mut self) -> Result<Q<Symbol>> {
         let start_index = self.index;
+        if self.get() == '0' && matches!(self.peek(), 'x' | 'b') {
+            self.advance();
+            self.advance();
+            self.read_literal_body();
+            return self.quote(Symbol::Literal, start_index);
+        }
         while self.get().is_ascii_digit() {
             self.advance();
         }
+        if self.at_literal_body() {
+            self.advance();
+            self.read_literal_body();
+            return self.quote(Symbol::Literal, start_index);
+        }
         let slice = &self.source.code[start_index..self.index];
         if slice.parse::<usize>().is_err() {
             return self.error(Reason::InvalidNumber, start_index);
@@ -105,11 +210,35 @@ impl Lexer {
         self.quote(Symbol::Number, start_index)
     }
 
+    /// Read a `"string"`, it must not span multiple lines
+    fn read_string(&mut self) -> Result<Q<Symbol>> {
+        let start_index = self.index;
+        self.advance();
+        loop {
+            match self.get() {
+                '"' => return self.quote_next(Symbol::String, start_index),
+                '\n' | '\r' | NULL => {
+                    return Err(Error::new(
+                        self.source.clone(),
+                        Quote::new(start_index, start_index + 1),
+                        Reason::UnterminatedString,
+                    ))
+                }
+                _ => self.advance(),
+            }
+        }
+    }
+
     fn read_identifier(&mut self) -> Result<Q<Symbol>> {
         let start_index = self.index;
         while matches!(self.get(), '_' | 'a'..='z' | 'A'..='Z' | '0'..='9') {
             self.advance();
         }
+        if self.at_literal_body() {
+            self.advance();
+            self.read_literal_body();
+            return self.quote(Symbol::Literal, start_index);
+        }
         let slice = &self.source.code[start_index..self.index];
         if let Some(symbol) = KEYWORDS.get(slice) {
             return self.quote(*symbol, start_index);
@@ -124,15 +253,41 @@ impl Lexer {
             return match self.get() {
                 NULL => Ok(Q::new(Symbol::Null, start_index, self.index)),
                 ';' => self.quote_next(Symbol::Semicolon, start_index),
+                '=' if self.peek() == '=' => {
+                    self.advance();
+                    self.quote_next(Symbol::EqualEqual, start_index)
+                }
                 '=' => self.quote_next(Symbol::Equal, start_index),
                 '.' => self.quote_next(Symbol::Punct, start_index),
                 ',' => self.quote_next(Symbol::Comma, start_index),
+                ':' => self.quote_next(Symbol::Colon, start_index),
                 '(' => self.quote_next(Symbol::LeftParen, start_index),
                 ')' => self.quote_next(Symbol::RightParen, start_index),
+                '[' => self.quote_next(Symbol::LeftBracket, start_index),
+                ']' => self.quote_next(Symbol::RightBracket, start_index),
+                '{' => self.quote_next(Symbol::LeftBrace, start_index),
+                '}' => self.quote_next(Symbol::RightBrace, start_index),
+                '+' if self.peek() == '=' => {
+                    self.advance();
+                    self.quote_next(Symbol::PlusEqual, start_index)
+                }
+                '!' if self.peek() == '=' => {
+                    self.advance();
+                    self.quote_next(Symbol::NotEqual, start_index)
+                }
                 '!' => self.quote_next(Symbol::Not, start_index),
                 '&' => self.quote_next(Symbol::And, start_index),
                 '|' => self.quote_next(Symbol::Or, start_index),
                 '^' => self.quote_next(Symbol::Xor, start_index),
+                '<' if self.peek() == '<' => {
+                    self.advance();
+                    self.quote_next(Symbol::Shl, start_index)
+                }
+                '>' if self.peek() == '>' => {
+                    self.advance();
+                    self.quote_next(Symbol::Shr, start_index)
+                }
+                '"' => self.read_string(),
                 '0'..='9' => self.read_number(),
                 '_' | 'a'..='z' | 'A'..='Z' => self.read_identifier(),
                 '#' => {
@@ -140,10 +295,11 @@ impl Lexer {
                     continue;
                 }
                 '/' => {
-                    if self.peek() != '/' {
-                        return self.error_next(Reason::UnexpectedCharacter, start_index);
+                    match self.peek() {
+                        '/' => self.read_comment(),
+                        '*' => self.read_block_comment()?,
+                        _ => return self.error_next(Reason::UnexpectedCharacter, start_index),
                     }
-                    self.skip_line();
                     continue;
                 }
                 _ => self.error_next(Reason::UnexpectedCharacter, start_index),
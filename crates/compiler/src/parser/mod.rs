@@ -9,17 +9,21 @@ use crate::{
     error::{Error, Reason, Result},
     hir::{
         value::{
-            HirAnd, HirBitAddress, HirBool, HirCall, HirNot, HirNumber, HirValue, HirValueType,
-            HirVarRef,
+            HirAddressArea, HirAddressIndex, HirAddressWidth, HirAnd, HirAnyPointer, HirBitAccess,
+            HirBitAddress, HirBool, HirCall, HirDataType, HirIndirectAddress, HirNot, HirNumber,
+            HirNumberType, HirOr, HirRotl, HirRotr, HirShl, HirShr, HirValue, HirValueType,
+            HirVarRef, HirXor,
         },
-        Hir, HirCallStatement, HirLetStatement, HirStatement, HirWriteStatement,
+        Hir, HirAddStatement, HirAssertStep, HirBlockKind, HirCallStatement, HirExternStatement,
+        HirLetStatement, HirParam, HirParamDirection, HirScanLength, HirScanStep, HirSetStep,
+        HirStatement, HirTest, HirTestStep, HirWriteStatement,
     },
     util::{parse_number, Quote, Source, Q},
 };
 
 use self::{
-    lexer::{Lexer, NULL},
-    symbol::Symbol,
+    lexer::Lexer,
+    symbol::{Symbol, ADDRESS_PREFIXES, DATA_TYPES, PARAM_TYPES},
 };
 
 pub struct Parser {
@@ -77,19 +81,51 @@ impl Parser {
 
 /// Parsing specific functions
 impl Parser {
-    fn parse_address_prefix(&self, prefix: &str, start: usize, end: usize) -> Result<(u8, usize)> {
-        if prefix.len() < 2 {
-            return self.error(Reason::InvalidBitAddressSymbol, start, end);
-        }
-        let char = prefix.chars().next();
-        let num = prefix.get(1..);
-        if char.is_none() || num.is_none() {
-            return self.error(Reason::InvalidBitAddressSymbol, start, end);
-        }
-        let Ok(x) = num.unwrap().parse() else {
-            return self.error(Reason::InvalidBitAddressSymbol, start, end);
+    /// Checks if the identifier is an operand (`E0`, `MW10`, `DB5`)
+    fn is_address(&self, ident: &Quote) -> bool {
+        let Some((prefix, _)) = split_operand(&self.source.code[ident]) else {
+            return false;
+        };
+        prefix == "DB" || ADDRESS_PREFIXES.contains_key(prefix)
+    }
+
+    /// Checks if the identifier is an operand prefix followed by an index (`M[`, `DBX[`)
+    fn is_indirect_address(&self, ident: &Quote) -> bool {
+        self.buffer.value == Symbol::LeftBracket
+            && ident.adjacent(&self.buffer.quote)
+            && ADDRESS_PREFIXES.contains_key(&self.source.code[ident])
+    }
+
+    /// Parse an operand (`MW10`) into its area, width and number
+    fn parse_address_operand(
+        &self,
+        operand: &Quote,
+    ) -> Result<(HirAddressArea, HirAddressWidth, usize)> {
+        let Some((prefix, num)) = split_operand(&self.source.code[operand]) else {
+            return self.error(Reason::InvalidBitAddressSymbol, operand.start, operand.end);
+        };
+        let Some(&(area, width)) = ADDRESS_PREFIXES.get(prefix) else {
+            return self.error(Reason::UnknownBitAddressType, operand.start, operand.end);
+        };
+        let Ok(ptr) = num.parse() else {
+            return self.error(Reason::InvalidBitAddressSymbol, operand.start, operand.end);
         };
-        Ok((char.unwrap() as u8, x))
+        Ok((area, width, ptr))
+    }
+
+    /// Read data block prefix (`DB5.`) and return the number with the following operand
+    fn read_data_block(&mut self, db: Quote) -> Result<(u16, Quote)> {
+        let Some((_, num)) = split_operand(&self.source.code[&db]) else {
+            return self.error(Reason::InvalidBitAddressSymbol, db.start, db.end);
+        };
+        let num = num.parse::<usize>().unwrap_or(0);
+        let punct = self.expect(Symbol::Punct)?;
+        let operand = self.expect(Symbol::Identifier)?;
+        if !db.adjacent(&punct) || !punct.adjacent(&operand) || num == 0 || num > u16::MAX as usize
+        {
+            return self.error(Reason::InvalidBitAddressSymbol, db.start, operand.end);
+        }
+        Ok((num as u16, operand))
     }
 
     /// Read raw address (`0.0`)
@@ -109,33 +145,159 @@ impl Parser {
         Ok(HirValue::new(
             quote,
             HirValueType::BitAddress(HirBitAddress {
-                char: NULL as u8,
+                area: HirAddressArea::Raw,
+                width: HirAddressWidth::Bit,
+                db: 0,
                 ptr: ptr as u16,
                 bit: bit as u8,
             }),
         ))
     }
 
-    /// Read prefixed address (`E0.0`)
-    fn read_prefixed_address(&mut self, prefix: Quote) -> Result<HirValue> {
-        let start = prefix.start;
+    /// Read prefixed address (`E0.0`, `MW10`, `T5`, `DB5.DBX2.1`)
+    fn read_prefixed_address(&mut self, operand: Quote) -> Result<HirValue> {
+        let start = operand.start;
+        let (db, operand) = match split_operand(&self.source.code[&operand]) {
+            Some(("DB", _)) => self.read_data_block(operand)?,
+            _ => (0, operand),
+        };
+        if self.is_indirect_address(&operand) {
+            return self.read_indirect_address(start, db, operand);
+        }
+        let (area, width, ptr) = self.parse_address_operand(&operand)?;
+        if (area == HirAddressArea::DataBlock) != (db != 0) {
+            return self.error(Reason::InvalidBitAddressSymbol, start, operand.end);
+        }
+        let mut end = operand.end;
+        let mut bit = 0;
+        let has_bit = width == HirAddressWidth::Bit
+            && !matches!(area, HirAddressArea::Timer | HirAddressArea::Counter);
+        let punct = self.buffer.quote.clone();
+        let has_punct = self.buffer.value == Symbol::Punct && operand.adjacent(&punct);
+        if has_bit != has_punct {
+            return self.error(Reason::InvalidAddressWidth, start, end);
+        }
+        if has_bit {
+            self.advance()?;
+            let q_bit = self.expect(Symbol::Number)?;
+            let value = parse_number(&self.source, &q_bit)?;
+            end = q_bit.end;
+            if !punct.adjacent(&q_bit) || value > 7 {
+                return self.error(Reason::InvalidBitAddressSymbol, start, end);
+            }
+            bit = value as u8;
+        }
+        if ptr + width.bytes() > u16::MAX as usize + 1 {
+            return self.error(Reason::InvalidAddressWidth, start, end);
+        }
+        let quote = Quote { start, end };
+        Ok(HirValue::new(
+            quote,
+            HirValueType::BitAddress(HirBitAddress {
+                area,
+                width,
+                db,
+                ptr: ptr as u16,
+                bit,
+            }),
+        ))
+    }
+
+    /// Read indirect address (`M[MD10]`, `DBX[AR1, P#0.0]`)
+    fn read_indirect_address(&mut self, start: usize, db: u16, operand: Quote) -> Result<HirValue> {
+        let Some(&(area, width)) = ADDRESS_PREFIXES.get(&self.source.code[&operand]) else {
+            return self.error(Reason::UnknownBitAddressType, operand.start, operand.end);
+        };
+        if db != 0 && area != HirAddressArea::DataBlock {
+            return self.error(Reason::InvalidBitAddressSymbol, start, operand.end);
+        }
+        self.expect(Symbol::LeftBracket)?;
+        let index = if self.buffer.value == Symbol::AddressRegister {
+            let register = self.buffer.quote.clone();
+            self.advance()?;
+            self.expect(Symbol::Comma)?;
+            let offset = self.expect(Symbol::Literal)?;
+            let Some((value, HirNumberType::Pointer)) = parse_literal(&self.source.code[&offset])
+            else {
+                return self.error(Reason::InvalidNumber, offset.start, offset.end);
+            };
+            if matches!(area, HirAddressArea::Timer | HirAddressArea::Counter) {
+                return self.error(Reason::InvalidAddressIndex, register.start, offset.end);
+            }
+            if width != HirAddressWidth::Bit && value % 8 != 0 {
+                return self.error(Reason::InvalidAddressWidth, offset.start, offset.end);
+            }
+            HirAddressIndex::Register {
+                ptr: (value / 8) as u16,
+                bit: (value % 8) as u8,
+            }
+        } else {
+            HirAddressIndex::Memory(self.read_value()?)
+        };
+        let end = self.expect(Symbol::RightBracket)?.end;
+        let quote = Quote { start, end };
+        Ok(HirValue::new(
+            quote,
+            HirValueType::IndirectAddress(Box::new(HirIndirectAddress {
+                area,
+                width,
+                db,
+                index,
+            })),
+        ))
+    }
+
+    /// Read `ANY` pointer (`P#DB10.DBX0.0 BYTE 20`) following its address literal
+    fn read_any_pointer(&mut self, literal: Quote) -> Result<HirValue> {
+        let start = literal.start;
+        let q_type = self.expect(Symbol::Identifier)?;
+        let q_count = self.expect(Symbol::Number)?;
+        let end = q_count.end;
+        let address = parse_any_address(&self.source.code[&literal]);
+        let r#type = DATA_TYPES.get(&self.source.code[&q_type]).copied();
+        let count = parse_number(&self.source, &q_count)?;
+        let (Some((area, db, ptr, bit)), Some(r#type)) = (address, r#type) else {
+            return self.error(Reason::InvalidAnyPointer, start, end);
+        };
+        let bits = r#type.bits() * count;
+        if count == 0
+            || count > u16::MAX as usize
+            || (r#type != HirDataType::Bool && bit != 0)
+            || (ptr as usize * 8 + bit as usize + bits).div_ceil(8) > u16::MAX as usize + 1
+        {
+            return self.error(Reason::InvalidAnyPointer, start, end);
+        }
+        let quote = Quote { start, end };
+        Ok(HirValue::new(
+            quote,
+            HirValueType::AnyPointer(HirAnyPointer {
+                area,
+                db,
+                ptr,
+                bit,
+                r#type,
+                count: count as u16,
+            }),
+        ))
+    }
+
+    /// Read bit access (`status.3`)
+    fn read_bit_access(&mut self, value: HirValue) -> Result<HirValue> {
+        let start = value.quote.start;
         let punct = self.expect(Symbol::Punct)?;
         let q_bit = self.expect(Symbol::Number)?;
         let bit = parse_number(&self.source, &q_bit)?;
         let end = q_bit.end;
-        if !prefix.adjacent(&punct) || !punct.adjacent(&q_bit) || bit > 7 {
+        if !value.quote.adjacent(&punct) || !punct.adjacent(&q_bit) || bit > 31 {
             return self.error(Reason::InvalidBitAddressSymbol, start, end);
         }
-        let (char, ptr) = self.parse_address_prefix(&self.source.code[&prefix], start, end)?;
-        if ptr > u16::MAX as usize {
-            return self.error(Reason::InvalidBitAddressSymbol, start, end);
-        }
-        let ptr = ptr as u16;
-        let bit = bit as u8;
         let quote = Quote { start, end };
         Ok(HirValue::new(
             quote,
-            HirValueType::BitAddress(HirBitAddress { char, ptr, bit }),
+            HirValueType::BitAccess(Box::new(HirBitAccess {
+                value,
+                bit: bit as u8,
+            })),
         ))
     }
 
@@ -193,15 +355,43 @@ impl Parser {
                 let value = parse_number(&self.source, &symbol.quote)?;
                 Ok(HirValue::new(
                     symbol.quote,
-                    HirValueType::Number(HirNumber { value }),
+                    HirValueType::Number(HirNumber {
+                        value,
+                        r#type: HirNumberType::Untyped,
+                    }),
                 ))
             }
+            Symbol::Literal => {
+                self.advance()?;
+                if is_any_address(&self.source.code[&symbol.quote]) {
+                    return self.read_any_pointer(symbol.quote);
+                }
+                let Some((value, r#type)) = parse_literal(&self.source.code[&symbol.quote]) else {
+                    return self.error(Reason::InvalidNumber, symbol.quote.start, symbol.quote.end);
+                };
+                Ok(HirValue::new(
+                    symbol.quote,
+                    HirValueType::Number(HirNumber { value, r#type }),
+                ))
+            }
+            Symbol::AddressRegister => {
+                self.advance()?;
+                Ok(HirValue::new(symbol.quote, HirValueType::AddressRegister))
+            }
             Symbol::Identifier => {
                 self.advance()?;
+                if self.is_address(&symbol.quote) {
+                    return self.read_prefixed_address(symbol.quote);
+                }
+                if self.is_indirect_address(&symbol.quote) {
+                    let start = symbol.quote.start;
+                    return self.read_indirect_address(start, 0, symbol.quote);
+                }
+                let var = HirValue::new(symbol.quote, HirValueType::VarRef(HirVarRef));
                 match self.buffer.value {
-                    Symbol::Punct => self.read_prefixed_address(symbol.quote),
-                    Symbol::LeftParen => self.read_call(symbol.quote),
-                    _ => Ok(HirValue::new(symbol.quote, HirValueType::VarRef(HirVarRef))),
+                    Symbol::LeftParen => self.read_call(var.quote),
+                    Symbol::Punct => self.read_bit_access(var),
+                    _ => Ok(var),
                 }
             }
             _ => self.error_buffer(Reason::InvalidUnaryOperation),
@@ -235,6 +425,9 @@ impl Parser {
     fn read_let(&mut self) -> Result<HirStatement> {
         let start = self.expect(Symbol::Let)?.start;
         let name = self.expect(Symbol::Identifier)?;
+        if self.is_address(&name) {
+            return self.error(Reason::OperandName, name.start, name.end);
+        }
         self.expect(Symbol::Equal)?;
         let value = self.read_value()?;
         let end = self.expect(Symbol::Semicolon)?.end;
@@ -243,24 +436,124 @@ impl Parser {
     }
 
     /// Read a [HirWrite]
-    fn read_write(&mut self, name: Quote) -> Result<HirStatement> {
-        let start = name.start;
+    fn read_write(&mut self, target: HirValue) -> Result<HirStatement> {
+        let start = target.quote.start;
         self.expect(Symbol::Equal)?;
         let value = self.read_value()?;
         let end = self.expect(Symbol::Semicolon)?.end;
         let quote = Quote { start, end };
         Ok(HirStatement::Write(HirWriteStatement {
             quote,
-            name,
+            target,
             value,
         }))
     }
 
-    /// Read a statement starting with an [Symbol::Identifier]
-    fn read_ident_statement(&mut self) -> Result<HirStatement> {
-        let ident = self.expect(Symbol::Identifier)?;
+    /// Read a [HirAdd]
+    fn read_add(&mut self, target: HirValue) -> Result<HirStatement> {
+        let start = target.quote.start;
+        self.expect(Symbol::PlusEqual)?;
+        let value = self.read_value()?;
+        let end = self.expect(Symbol::Semicolon)?.end;
+        let quote = Quote { start, end };
+        Ok(HirStatement::Add(HirAddStatement {
+            quote,
+            target,
+            value,
+        }))
+    }
+
+    /// Read a [HirWrite] or [HirAdd] to the target
+    fn read_assignment(&mut self, target: HirValue) -> Result<HirStatement> {
+        match self.buffer.value {
+            Symbol::PlusEqual => self.read_add(target),
+            _ => self.read_write(target),
+        }
+    }
+
+    /// Read a block parameter (`in start: bool`)
+    fn read_param(&mut self) -> Result<HirParam> {
+        let q_direction = self.expect(Symbol::Identifier)?;
+        let direction = match &self.source.code[&q_direction] {
+            "in" => HirParamDirection::Input,
+            "out" => HirParamDirection::Output,
+            _ => return self.error(Reason::UnexpectedSymbol, q_direction.start, q_direction.end),
+        };
+        let name = self.expect(Symbol::Identifier)?;
+        self.expect(Symbol::Colon)?;
+        let q_type = self.expect(Symbol::Identifier)?;
+        let type_name = self.source.code[&q_type].to_ascii_uppercase();
+        let Some(&r#type) = PARAM_TYPES.get(&type_name) else {
+            return self.error(Reason::UnknownDataType, q_type.start, q_type.end);
+        };
+        Ok(HirParam {
+            direction,
+            name,
+            r#type,
+        })
+    }
+
+    /// Read a [HirExternStatement]
+    fn read_extern(&mut self) -> Result<HirStatement> {
+        let start = self.expect(Symbol::Extern)?.start;
+        let q_kind = self.expect(Symbol::Identifier)?;
+        let (kind, prefix) = match &self.source.code[&q_kind] {
+            "fc" => (HirBlockKind::Function, "FC"),
+            "fb" => (HirBlockKind::FunctionBlock, "FB"),
+            _ => return self.error(Reason::UnexpectedSymbol, q_kind.start, q_kind.end),
+        };
+        let name = self.expect(Symbol::Identifier)?;
+        let number = match split_operand(&self.source.code[&name]) {
+            Some((it, num)) if it == prefix => num.parse::<u16>().ok().filter(|it| *it != 0),
+            _ => None,
+        };
+        let Some(number) = number else {
+            return self.error(Reason::InvalidBlockName, name.start, name.end);
+        };
+        self.expect(Symbol::LeftParen)?;
+        let mut params = Vec::new();
+        while self.buffer.value != Symbol::RightParen {
+            params.push(self.read_param()?);
+            if self.buffer.value != Symbol::Comma {
+                break;
+            }
+            self.advance()?;
+        }
+        self.expect(Symbol::RightParen)?;
+        let end = self.expect(Symbol::Semicolon)?.end;
+        let quote = Quote { start, end };
+        Ok(HirStatement::Extern(HirExternStatement {
+            quote,
+            kind,
+            name,
+            number,
+            params,
+        }))
+    }
+
+    /// Read a statement starting with `AR1`
+    fn read_register_statement(&mut self) -> Result<HirStatement> {
+        let register = self.expect(Symbol::AddressRegister)?;
+        self.read_assignment(HirValue::new(register, HirValueType::AddressRegister))
+    }
+
+    /// Read a statement starting with the [Symbol::Identifier]
+    fn read_ident_statement(&mut self, ident: Quote) -> Result<HirStatement> {
+        if self.is_address(&ident) {
+            let target = self.read_prefixed_address(ident)?;
+            return self.read_assignment(target);
+        }
+        if self.is_indirect_address(&ident) {
+            let target = self.read_indirect_address(ident.start, 0, ident)?;
+            return self.read_assignment(target);
+        }
         match self.buffer.value {
-            Symbol::Equal => self.read_write(ident),
+            Symbol::Equal => self.read_write(HirValue::new(ident, HirValueType::VarRef(HirVarRef))),
+            Symbol::Punct => {
+                let var = HirValue::new(ident, HirValueType::VarRef(HirVarRef));
+                let target = self.read_bit_access(var)?;
+                self.read_write(target)
+            }
             Symbol::LeftParen => {
                 let (HirCall { name, args }, quote) = self.read_call_raw(ident)?;
                 self.expect(Symbol::Semicolon)?;
@@ -270,20 +563,287 @@ impl Parser {
         }
     }
 
+    /// Read a [HirSetStep] following `set`
+    fn read_set_step(&mut self, start: usize) -> Result<HirTestStep> {
+        let target = self.read_value()?;
+        self.expect(Symbol::Equal)?;
+        let value = self.read_value()?;
+        let end = self.expect(Symbol::Semicolon)?.end;
+        let quote = Quote { start, end };
+        Ok(HirTestStep::Set(HirSetStep {
+            quote,
+            target,
+            value,
+        }))
+    }
+
+    /// Read a [HirScanStep] following `scan`
+    fn read_scan_step(&mut self, start: usize) -> Result<HirTestStep> {
+        let symbol = self.get();
+        let length = match symbol.value {
+            Symbol::Semicolon => HirScanLength::Scans(1),
+            Symbol::Number => {
+                self.advance()?;
+                let value = parse_number(&self.source, &symbol.quote)?;
+                // `10ms` is read as a number followed by its unit
+                let unit = self.get();
+                if unit.value != Symbol::Identifier || !symbol.quote.adjacent(&unit.quote) {
+                    HirScanLength::Scans(value)
+                } else {
+                    let text = format!("{value}{}", &self.source.code[&unit.quote]);
+                    let Some(value) = parse_time(&text) else {
+                        return self.error(
+                            Reason::InvalidNumber,
+                            symbol.quote.start,
+                            unit.quote.end,
+                        );
+                    };
+                    self.advance()?;
+                    HirScanLength::Duration(value)
+                }
+            }
+            Symbol::Literal => {
+                self.advance()?;
+                let Some((value, HirNumberType::Time)) =
+                    parse_literal(&self.source.code[&symbol.quote])
+                else {
+                    return self.error(Reason::InvalidNumber, symbol.quote.start, symbol.quote.end);
+                };
+                HirScanLength::Duration(value)
+            }
+            _ => return self.error_buffer(Reason::UnexpectedSymbol),
+        };
+        let end = self.expect(Symbol::Semicolon)?.end;
+        let quote = Quote { start, end };
+        Ok(HirTestStep::Scan(HirScanStep { quote, length }))
+    }
+
+    /// Read a [HirAssertStep] following `assert`
+    fn read_assert_step(&mut self, start: usize) -> Result<HirTestStep> {
+        let value = self.read_value()?;
+        let (expected, equal) = match self.buffer.value {
+            Symbol::EqualEqual | Symbol::NotEqual => {
+                let equal = self.buffer.value == Symbol::EqualEqual;
+                self.advance()?;
+                (Some(self.read_value()?), equal)
+            }
+            _ => (None, true),
+        };
+        let end = self.expect(Symbol::Semicolon)?.end;
+        let quote = Quote { start, end };
+        Ok(HirTestStep::Assert(HirAssertStep {
+            quote,
+            value,
+            expected,
+            equal,
+        }))
+    }
+
+    /// Read a [HirTest] following `test`
+    fn read_test(&mut self, start: usize) -> Result<HirTest> {
+        let name = self.expect(Symbol::String)?;
+        let name = Quote::new(name.start + 1, name.end - 1);
+        self.expect(Symbol::LeftBrace)?;
+        let mut steps = Vec::new();
+        while self.buffer.value != Symbol::RightBrace {
+            let step = self.expect(Symbol::Identifier)?;
+            steps.push(match &self.source.code[&step] {
+                "set" => self.read_set_step(step.start)?,
+                "scan" => self.read_scan_step(step.start)?,
+                "assert" => self.read_assert_step(step.start)?,
+                _ => return self.error(Reason::UnexpectedSymbol, step.start, step.end),
+            });
+        }
+        let end = self.expect(Symbol::RightBrace)?.end;
+        let quote = Quote { start, end };
+        Ok(HirTest { quote, name, steps })
+    }
+
     pub fn parse(&mut self) -> Result<Hir> {
         let mut hir = Hir::new(self.source.clone());
         self.advance()?;
+        // End of the last statement or test
+        let mut end = 0;
         while self.buffer.value != Symbol::Null {
-            hir.statements.push(match self.buffer.value {
+            let statement = match self.buffer.value {
                 Symbol::Let => self.read_let()?,
-                Symbol::Identifier => self.read_ident_statement()?,
+                Symbol::Extern => self.read_extern()?,
+                Symbol::Identifier => {
+                    let ident = self.expect(Symbol::Identifier)?;
+                    // `test` is only a keyword in front of a name
+                    if &self.source.code[&ident] == "test" && self.buffer.value == Symbol::String {
+                        let test = self.read_test(ident.start)?;
+                        // Comments of a test must not end up in the program
+                        self.lexer
+                            .comments
+                            .retain(|it| it.quote.start < end || it.quote.start > test.quote.end);
+                        end = test.quote.end;
+                        hir.tests.push(test);
+                        continue;
+                    }
+                    self.read_ident_statement(ident)?
+                }
+                Symbol::AddressRegister => self.read_register_statement()?,
                 _ => self.error_buffer(Reason::UnexpectedSymbol)?,
-            });
+            };
+            end = statement.quote().end;
+            hir.statements.push(statement);
         }
+        hir.comments = std::mem::take(&mut self.lexer.comments);
         Ok(hir)
     }
 }
 
+/// Split an operand (`MW10`) into its prefix (`MW`) and number (`10`)
+fn split_operand(text: &str) -> Option<(&str, &str)> {
+    let index = text.find(|c: char| c.is_ascii_digit())?;
+    let (prefix, num) = text.split_at(index);
+    if prefix.is_empty() || !num.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((prefix, num))
+}
+
+/// Parse the digits of a literal, ignoring `_` separators
+fn parse_digits(digits: &str, radix: u32) -> Option<usize> {
+    let digits = digits.replace('_', "");
+    if digits.is_empty() {
+        return None;
+    }
+    usize::from_str_radix(&digits, radix).ok()
+}
+
+/// Parse a radix literal (`16#FF`, `2#1010`)
+fn parse_radix(text: &str) -> Option<usize> {
+    let (radix, digits) = text.split_once('#')?;
+    match radix {
+        "2" => parse_digits(digits, 2),
+        "8" => parse_digits(digits, 8),
+        "16" => parse_digits(digits, 16),
+        _ => None,
+    }
+}
+
+/// Parse a BCD-encoded literal (`C#123`)
+fn parse_bcd(digits: &str) -> Option<usize> {
+    if digits.len() > 3 || !digits.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    parse_digits(digits, 16)
+}
+
+/// Parse a byte pair (`B#(1,2)`)
+fn parse_byte_pair(body: &str) -> Option<usize> {
+    let (high, low) = body.strip_prefix('(')?.strip_suffix(')')?.split_once(',')?;
+    let high = parse_digits(high.trim(), 10).filter(|&it| it <= 0xFF)?;
+    let low = parse_digits(low.trim(), 10).filter(|&it| it <= 0xFF)?;
+    Some(high << 8 | low)
+}
+
+/// Parse an area-internal pointer (`2.0`) into `byte * 8 + bit`
+fn parse_pointer(body: &str) -> Option<usize> {
+    let (byte, bit) = body.split_once('.')?;
+    let byte = parse_digits(byte, 10).filter(|&it| it <= u16::MAX as usize)?;
+    let bit = parse_digits(bit, 10).filter(|&it| it <= 7)?;
+    Some(byte * 8 + bit)
+}
+
+/// Parse a duration (`1m30s`, `500ms`) into milliseconds
+fn parse_time(body: &str) -> Option<usize> {
+    let body = body.to_ascii_lowercase().replace('_', "");
+    let mut rest = body.as_str();
+    let mut total = 0usize;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let value: usize = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let (factor, unit) = [
+            ("ms", 1),
+            ("d", 86_400_000),
+            ("h", 3_600_000),
+            ("m", 60_000),
+            ("s", 1000),
+        ]
+        .into_iter()
+        .find(|(unit, _)| rest.starts_with(unit))
+        .map(|(unit, factor)| (factor, unit.len()))?;
+        rest = &rest[unit..];
+        total = total.checked_add(value.checked_mul(factor)?)?;
+    }
+    (!body.is_empty()).then_some(total)
+}
+
+/// Checks if the literal is the address of an `ANY` pointer (`P#M0.0`)
+fn is_any_address(text: &str) -> bool {
+    text.strip_prefix("P#")
+        .is_some_and(|it| it.starts_with(|c: char| c.is_ascii_alphabetic()))
+}
+
+/// Parse the address of an `ANY` pointer (`P#DB10.DBX0.0`) into area, data block, byte and bit
+fn parse_any_address(text: &str) -> Option<(HirAddressArea, u16, u16, u8)> {
+    let body = text.strip_prefix("P#")?;
+    let (db, operand) = match body.strip_prefix("DB") {
+        Some(rest) if rest.starts_with(|c: char| c.is_ascii_digit()) => {
+            let (db, operand) = rest.split_once('.')?;
+            let db = parse_digits(db, 10).filter(|&it| it != 0 && it <= u16::MAX as usize)?;
+            (db as u16, operand)
+        }
+        _ => (0, body),
+    };
+    let (operand, bit) = operand.rsplit_once('.')?;
+    let (prefix, ptr) = split_operand(operand)?;
+    let &(area, width) = ADDRESS_PREFIXES.get(prefix)?;
+    let valid_area = matches!(
+        area,
+        HirAddressArea::Input
+            | HirAddressArea::Output
+            | HirAddressArea::Memory
+            | HirAddressArea::DataBlock
+    );
+    if !valid_area
+        || width != HirAddressWidth::Bit
+        || (area == HirAddressArea::DataBlock) != (db != 0)
+    {
+        return None;
+    }
+    let ptr = parse_digits(ptr, 10).filter(|&it| it <= u16::MAX as usize)?;
+    let bit = parse_digits(bit, 10).filter(|&it| it <= 7)?;
+    Some((area, db, ptr as u16, bit as u8))
+}
+
+/// Parse a [Symbol::Literal] into its value and type
+fn parse_literal(text: &str) -> Option<(usize, HirNumberType)> {
+    let (value, r#type) = if let Some(digits) = text.strip_prefix("0x") {
+        (parse_digits(digits, 16)?, HirNumberType::Untyped)
+    } else if let Some(digits) = text.strip_prefix("0b") {
+        (parse_digits(digits, 2)?, HirNumberType::Untyped)
+    } else if let Some(body) = text.strip_prefix("B#") {
+        if body.starts_with('(') {
+            (parse_byte_pair(body)?, HirNumberType::Word)
+        } else {
+            (parse_radix(body)?, HirNumberType::Byte)
+        }
+    } else {
+        let (prefix, body) = text.split_once('#')?;
+        match prefix {
+            "W" => (parse_radix(body)?, HirNumberType::Word),
+            "DW" => (parse_radix(body)?, HirNumberType::DWord),
+            "C" => (parse_bcd(body)?, HirNumberType::Word),
+            "L" => (parse_digits(body, 10)?, HirNumberType::DInt),
+            "P" => (parse_pointer(body)?, HirNumberType::Pointer),
+            "T" | "TIME" => (parse_time(body)?, HirNumberType::Time),
+            _ => (parse_radix(text)?, HirNumberType::Untyped),
+        }
+    };
+    let max = match r#type {
+        HirNumberType::Byte => 0xFF,
+        HirNumberType::Word => 0xFFFF,
+        HirNumberType::DInt | HirNumberType::Time => i32::MAX as usize,
+        HirNumberType::Untyped | HirNumberType::DWord | HirNumberType::Pointer => 0xFFFF_FFFF,
+    };
+    (value <= max).then_some((value, r#type))
+}
+
 fn apply_unary(op: Q<Symbol>, value: HirValue) -> HirValue {
     let quote = Quote::new(op.quote.start, value.quote.end);
     match op.value {
@@ -296,8 +856,12 @@ fn apply_binary(op: Q<Symbol>, left: HirValue, right: HirValue) -> HirValue {
     let quote = Quote::new(left.quote.start, right.quote.end);
     match op.value {
         Symbol::And => HirValue::new(quote, HirValueType::And(Box::new(HirAnd { left, right }))),
-        Symbol::Or => HirValue::new(quote, HirValueType::And(Box::new(HirAnd { left, right }))),
-        Symbol::Xor => HirValue::new(quote, HirValueType::And(Box::new(HirAnd { left, right }))),
+        Symbol::Or => HirValue::new(quote, HirValueType::Or(Box::new(HirOr { left, right }))),
+        Symbol::Xor => HirValue::new(quote, HirValueType::Xor(Box::new(HirXor { left, right }))),
+        Symbol::Shl => HirValue::new(quote, HirValueType::Shl(Box::new(HirShl { left, right }))),
+        Symbol::Shr => HirValue::new(quote, HirValueType::Shr(Box::new(HirShr { left, right }))),
+        Symbol::Rotl => HirValue::new(quote, HirValueType::Rotl(Box::new(HirRotl { left, right }))),
+        Symbol::Rotr => HirValue::new(quote, HirValueType::Rotr(Box::new(HirRotr { left, right }))),
         _ => panic!("Invalid binary op"),
     }
 }
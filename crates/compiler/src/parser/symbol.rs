@@ -1,13 +1,96 @@
 use phf::{phf_map, Map};
 
+use crate::hir::{
+    value::{
+        HirAddressArea::{self, *},
+        HirAddressWidth::{self, *},
+        HirDataType,
+    },
+    HirParamType,
+};
+
 pub const KEYWORDS: Map<&str, Symbol> = phf_map! {
     "let" => Symbol::Let,
+    "extern" => Symbol::Extern,
     "not" => Symbol::Not,
     "and" => Symbol::And,
     "or" => Symbol::Or,
     "xor" => Symbol::Xor,
+    "rotl" => Symbol::Rotl,
+    "rotr" => Symbol::Rotr,
     "true" => Symbol::True,
     "false" => Symbol::False,
+    "AR1" => Symbol::AddressRegister,
+};
+
+/// Operand prefixes in German and international mnemonics
+pub const ADDRESS_PREFIXES: Map<&str, (HirAddressArea, HirAddressWidth)> = phf_map! {
+    "E" => (Input, Bit),
+    "I" => (Input, Bit),
+    "EB" => (Input, Byte),
+    "IB" => (Input, Byte),
+    "EW" => (Input, Word),
+    "IW" => (Input, Word),
+    "ED" => (Input, DWord),
+    "ID" => (Input, DWord),
+    "A" => (Output, Bit),
+    "Q" => (Output, Bit),
+    "AB" => (Output, Byte),
+    "QB" => (Output, Byte),
+    "AW" => (Output, Word),
+    "QW" => (Output, Word),
+    "AD" => (Output, DWord),
+    "QD" => (Output, DWord),
+    "M" => (Memory, Bit),
+    "MB" => (Memory, Byte),
+    "MW" => (Memory, Word),
+    "MD" => (Memory, DWord),
+    "PEB" => (PeripheralInput, Byte),
+    "PIB" => (PeripheralInput, Byte),
+    "PEW" => (PeripheralInput, Word),
+    "PIW" => (PeripheralInput, Word),
+    "PED" => (PeripheralInput, DWord),
+    "PID" => (PeripheralInput, DWord),
+    "PAB" => (PeripheralOutput, Byte),
+    "PQB" => (PeripheralOutput, Byte),
+    "PAW" => (PeripheralOutput, Word),
+    "PQW" => (PeripheralOutput, Word),
+    "PAD" => (PeripheralOutput, DWord),
+    "PQD" => (PeripheralOutput, DWord),
+    "T" => (Timer, Bit),
+    "Z" => (Counter, Bit),
+    "C" => (Counter, Bit),
+    "DBX" => (DataBlock, Bit),
+    "DBB" => (DataBlock, Byte),
+    "DBW" => (DataBlock, Word),
+    "DBD" => (DataBlock, DWord),
+};
+
+/// Data types of `ANY` pointers (`P#M0.0 BYTE 10`)
+pub const DATA_TYPES: Map<&str, HirDataType> = phf_map! {
+    "BOOL" => HirDataType::Bool,
+    "BYTE" => HirDataType::Byte,
+    "WORD" => HirDataType::Word,
+    "DWORD" => HirDataType::DWord,
+    "INT" => HirDataType::Int,
+    "DINT" => HirDataType::DInt,
+    "REAL" => HirDataType::Real,
+    "TIME" => HirDataType::Time,
+};
+
+/// Data types of block parameters (`in speed: int`), matched case-insensitively
+pub const PARAM_TYPES: Map<&str, HirParamType> = phf_map! {
+    "BOOL" => HirParamType::Data(HirDataType::Bool),
+    "BYTE" => HirParamType::Data(HirDataType::Byte),
+    "WORD" => HirParamType::Data(HirDataType::Word),
+    "DWORD" => HirParamType::Data(HirDataType::DWord),
+    "INT" => HirParamType::Data(HirDataType::Int),
+    "DINT" => HirParamType::Data(HirDataType::DInt),
+    "REAL" => HirParamType::Data(HirDataType::Real),
+    "TIME" => HirParamType::Data(HirDataType::Time),
+    "DATE_AND_TIME" => HirParamType::DateAndTime,
+    "DT" => HirParamType::DateAndTime,
+    "ANY" => HirParamType::Any,
 };
 
 /// A part of the parsed source
@@ -18,17 +101,35 @@ pub enum Symbol {
     Semicolon,
     /// `=`
     Equal,
+    /// `==`
+    EqualEqual,
+    /// `!=`
+    NotEqual,
+    /// `+=`
+    PlusEqual,
     /// `.`
     Punct,
     /// `,`
     Comma,
+    /// `:`
+    Colon,
     /// `(`
     LeftParen,
     /// `)`
     RightParen,
+    /// `[`
+    LeftBracket,
+    /// `]`
+    RightBracket,
+    /// `{`
+    LeftBrace,
+    /// `}`
+    RightBrace,
     // --- Keywords ---
     /// `let`
     Let,
+    /// `extern`
+    Extern,
     /// `not`, `!`
     Not,
     /// `and`, `&`
@@ -37,13 +138,27 @@ pub enum Symbol {
     Or,
     /// `xor`, `^`
     Xor,
+    /// `<<`
+    Shl,
+    /// `>>`
+    Shr,
+    /// `rotl`
+    Rotl,
+    /// `rotr`
+    Rotr,
     /// `true`
     True,
     /// `false`
     False,
+    /// `AR1`
+    AddressRegister,
     // --- Literals ---
     Number,
+    /// `16#FF`, `2#1010`, `0b1010`, `W#16#FF`, `B#(1,2)`, `C#123`, `P#2.0`, `P#M0.0`
+    Literal,
     Identifier,
+    /// `"motor stops on fault"`
+    String,
     // --- Special ---
     Null,
 }
@@ -54,11 +169,21 @@ impl Symbol {
     }
 
     pub fn is_binary_op(self) -> bool {
-        matches!(self, Symbol::And | Symbol::Or | Symbol::Xor)
+        matches!(
+            self,
+            Symbol::And
+                | Symbol::Or
+                | Symbol::Xor
+                | Symbol::Shl
+                | Symbol::Shr
+                | Symbol::Rotl
+                | Symbol::Rotr
+        )
     }
 
     pub fn precedence(self) -> usize {
         match self {
+            Symbol::Shl | Symbol::Shr | Symbol::Rotl | Symbol::Rotr => 4,
             Symbol::And => 3,
             Symbol::Xor => 2,
             Symbol::Or => 1,
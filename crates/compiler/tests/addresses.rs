@@ -0,0 +1,34 @@
+mod common;
+
+use common::awl;
+
+#[test]
+fn timer_is_read_as_bit() {
+    let networks = awl("let m = M0.0; m = T5;");
+    assert_eq!(networks[0], ["U T5;", "= M0.0;"]);
+}
+
+#[test]
+fn counter_is_read_as_bit() {
+    let networks = awl("let m = M0.1; m = Z3;");
+    assert_eq!(networks[0], ["U Z3;", "= M0.1;"]);
+}
+
+#[test]
+fn data_block_operands_are_qualified() {
+    let networks = awl("let q = A0.0; q = DB5.DBX0.1;");
+    assert_eq!(networks[0], ["U DB5.DBX0.1;", "= A0.0;"]);
+}
+
+#[test]
+fn word_inputs_are_transferred() {
+    let networks = awl("MW10 = EW2;");
+    assert_eq!(networks[0], ["L EW2;", "T MW10;"]);
+}
+
+#[test]
+fn peripheral_operands_are_transferred() {
+    let networks = awl("AB0 = PEB256; PAW258 = MW12;");
+    assert_eq!(networks[0], ["L PEB256;", "T AB0;"]);
+    assert_eq!(networks[1], ["L MW12;", "T PAW258;"]);
+}
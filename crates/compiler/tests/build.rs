@@ -0,0 +1,46 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use fpp_compiler::build::compile;
+
+/// Write a program into the test directory and return its path
+fn program(name: &str, code: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("fpp_build");
+    fs::create_dir_all(&dir).expect("Create test directory");
+    let path = dir.join(name);
+    fs::write(&path, code).expect("Write program");
+    path
+}
+
+// `OUT_DIR` is process wide, so all cases run in one test
+#[test]
+fn build_script_writes_struct_into_out_dir() {
+    let missing = program("missing.fpp", "").with_file_name("does_not_exist.fpp");
+    let Err(err) = compile(&missing, "Missing") else {
+        panic!("A missing file compiled");
+    };
+    assert!(err.starts_with(&missing.display().to_string()), "{err}");
+
+    let invalid = program("invalid.fpp", "A0.0 = start;\n");
+    let Err(err) = compile(&invalid, "Invalid") else {
+        panic!("An invalid program compiled");
+    };
+    assert!(err.contains("this variable does not exist"), "{err}");
+
+    let plant = program("plant.fpp", "A0.0 = E0.0 and E0.1;\n");
+    env::remove_var("OUT_DIR");
+    assert_eq!(
+        compile(&plant, "Plant"),
+        Err("`OUT_DIR` is only set for build scripts".to_string())
+    );
+
+    let out_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("fpp_build_out");
+    fs::create_dir_all(&out_dir).expect("Create out directory");
+    env::set_var("OUT_DIR", &out_dir);
+    let path = compile(&plant, "Plant").expect("Compile plant");
+    assert_eq!(path, out_dir.join("plant.rs"));
+    let code = fs::read_to_string(path).expect("Read generated struct");
+    assert!(code.contains("pub struct Plant"), "{code}");
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use common::compile;
+use fpp_compiler::{
+    lir::c::{self, CSource, WriteC},
+    mir::{
+        value::{MirAddress, MirAddressIndex, MirAddressType},
+        Mir, MirAction, MirInstruction,
+    },
+};
+
+/// Transform a program into C, the statements of each network without the title
+fn c(mir: &Mir) -> Vec<Vec<String>> {
+    let Ok(lir) = c::transformer::transform(mir) else {
+        panic!("The program has no C-LIR");
+    };
+    lir.networks
+        .iter()
+        .map(|network| {
+            let mut out = Vec::new();
+            network.write_c(&mut out).expect("Write to memory");
+            String::from_utf8(out)
+                .expect("ASCII statements")
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.starts_with("/*"))
+                .map(str::to_string)
+                .collect()
+        })
+        .collect()
+}
+
+fn input(bit: u8) -> MirAddress {
+    MirAddress {
+        r#type: MirAddressType::PhysicalInput1,
+        db: 0,
+        ptr: 0,
+        bit,
+        index: MirAddressIndex::Direct,
+    }
+}
+
+fn counter(ptr: u16) -> MirAddress {
+    MirAddress {
+        r#type: MirAddressType::PhysicalCounter,
+        db: 0,
+        ptr,
+        bit: 0,
+        index: MirAddressIndex::Direct,
+    }
+}
+
+#[test]
+fn or_before_and_is_nested() {
+    let Ok(mir) = compile("A0.0 = (E0.0 or E0.1) and E0.2;") else {
+        panic!("The program does not compile");
+    };
+    assert_eq!(
+        c(&mir)[0],
+        [
+            "rlo = get_bit(inputs, SCAN_INPUT_BYTES, P(0, 0));",
+            "rlo = rlo || get_bit(inputs, SCAN_INPUT_BYTES, P(0, 1));",
+            "rlo = rlo && get_bit(inputs, SCAN_INPUT_BYTES, P(0, 2));",
+            "set_bit(outputs, SCAN_OUTPUT_BYTES, P(0, 0), rlo);",
+        ]
+    );
+}
+
+#[test]
+fn and_after_or_restores_saved_rlo() {
+    let Ok(mir) = compile("A0.0 = E0.0 or E0.1 and E0.2;") else {
+        panic!("The program does not compile");
+    };
+    assert_eq!(
+        c(&mir)[0],
+        [
+            "rlo = get_bit(inputs, SCAN_INPUT_BYTES, P(0, 0));",
+            "rlo_0 = rlo;",
+            "rlo = get_bit(inputs, SCAN_INPUT_BYTES, P(0, 1));",
+            "rlo = rlo && get_bit(inputs, SCAN_INPUT_BYTES, P(0, 2));",
+            "nested = rlo;",
+            "rlo = rlo_0;",
+            "rlo = rlo || nested;",
+            "set_bit(outputs, SCAN_OUTPUT_BYTES, P(0, 0), rlo);",
+        ]
+    );
+}
+
+#[test]
+fn not_keeps_or_bit() {
+    let Ok(mut mir) = compile("A0.0 = E0.0;") else {
+        panic!("The program does not compile");
+    };
+    let MirAction::Output(output) = &mut mir.actions[0] else {
+        panic!("The assignment is an output");
+    };
+    output.instructions = vec![
+        MirInstruction::And { addr: input(0) },
+        MirInstruction::Or { addr: input(1) },
+        MirInstruction::Not,
+        MirInstruction::And { addr: input(2) },
+    ];
+    let networks = c(&mir);
+    assert_eq!(networks[0][3], "rlo = !rlo;");
+    assert_eq!(
+        networks[0][4],
+        "rlo = or_bit || (rlo && get_bit(inputs, SCAN_INPUT_BYTES, P(0, 2)));"
+    );
+}
+
+#[test]
+fn flip_flop_input_written_last_wins() {
+    let Ok(mir) = compile("A0.0 = sr(E0.1, E0.2);") else {
+        panic!("The program does not compile");
+    };
+    assert_eq!(
+        c(&mir)[0],
+        [
+            "rlo = get_bit(inputs, SCAN_INPUT_BYTES, P(0, 1));",
+            "if (rlo) {",
+            "set_bit(state->memory, sizeof state->memory, P(0, 0), true);",
+            "}",
+            "rlo = get_bit(inputs, SCAN_INPUT_BYTES, P(0, 2));",
+            "if (rlo) {",
+            "set_bit(state->memory, sizeof state->memory, P(0, 0), false);",
+            "}",
+            "rlo = get_bit(state->memory, sizeof state->memory, P(0, 0));",
+            "set_bit(outputs, SCAN_OUTPUT_BYTES, P(0, 0), rlo);",
+        ]
+    );
+}
+
+#[test]
+fn counter_counts_on_rising_edges() {
+    let Ok(mut mir) = compile("A0.0 = E0.0;") else {
+        panic!("The program does not compile");
+    };
+    let MirAction::Output(output) = &mut mir.actions[0] else {
+        panic!("The assignment is an output");
+    };
+    output.instructions = vec![
+        MirInstruction::And { addr: input(0) },
+        MirInstruction::CounterForward { addr: counter(2) },
+        MirInstruction::And { addr: counter(2) },
+    ];
+    assert_eq!(
+        c(&mir)[0][..3],
+        [
+            "rlo = get_bit(inputs, SCAN_INPUT_BYTES, P(0, 0));",
+            "count_up(&state->counters[2], rlo);",
+            "rlo = state->counters[2].value != 0u;",
+        ]
+    );
+    let Ok(lir) = c::transformer::transform(&mir) else {
+        panic!("The program has no C-LIR");
+    };
+    let mut out = Vec::new();
+    CSource::new("plc", &lir)
+        .write_source(&mut out)
+        .expect("Write to memory");
+    let text = String::from_utf8(out).expect("ASCII source");
+    // The count input is only counted when it was not set in the previous scan
+    assert!(text.contains(
+        "    if (rlo && !counter->up && counter->value < 999u)\n        \
+         counter->value++;\n    counter->up = rlo;\n"
+    ));
+}
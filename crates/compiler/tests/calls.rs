@@ -0,0 +1,259 @@
+mod common;
+
+use common::{awl, error, source};
+use fpp_compiler::lir::{
+    s7::{self, S7DataType, S7Mnemonics},
+    scl::{self, SclSource, WriteScl},
+};
+
+#[test]
+fn copy_calls_blkmov_with_any_pointers() {
+    let networks = awl("copy(P#DB10.DBX0.0 BYTE 20, P#DB11.DBX0.0 BYTE 20);");
+    assert_eq!(
+        networks[0],
+        [
+            "CALL SFC 20 (",
+            "SRCBLK := P#DB10.DBX 0.0 BYTE 20,",
+            "RET_VAL := #T0,",
+            "DSTBLK := P#DB11.DBX 0.0 BYTE 20);",
+        ]
+    );
+}
+
+#[test]
+fn fill_passes_value_as_any_pointer() {
+    let networks = awl("MW10 = fill(P#M0.0 BYTE 4, MB4);");
+    assert_eq!(
+        networks[0],
+        [
+            "CALL SFC 21 (",
+            "BVAL := P#M 4.0 BYTE 1,",
+            "RET_VAL := #T0,",
+            "BLK := P#M 0.0 BYTE 4);",
+            "L #T0;",
+            "T MW10;",
+        ]
+    );
+}
+
+#[test]
+fn copy_of_different_sizes_is_rejected() {
+    let (message, _) = error("copy(P#M0.0 BYTE 4, P#M8.0 BYTE 2);\n");
+    assert_eq!(message, "the sizes of these memory areas do not match");
+}
+
+#[test]
+fn system_function_is_callable_by_name_and_number() {
+    let expected = [
+        "CALL SFC 1 (",
+        "RET_VAL := #T0,",
+        "CDT := P#M 10.0 BYTE 8);",
+        "L #T0;",
+        "T MW0;",
+    ];
+    assert_eq!(awl("MW0 = READ_CLK(P#M10.0 BYTE 8);")[0], expected);
+    assert_eq!(awl("MW0 = SFC1(P#M10.0 BYTE 8);")[0], expected);
+}
+
+#[test]
+fn return_value_is_read_after_call() {
+    let networks = awl("MD4 = TIME_TCK();");
+    assert_eq!(
+        networks[0],
+        ["CALL SFC 64 (", "RET_VAL := #T0);", "L #T0;", "T MD4;"]
+    );
+}
+
+#[test]
+fn system_function_block_gets_instance() {
+    let networks = awl("M0.0 = TON(E0.0, T#5s);");
+    assert_eq!(
+        networks[0],
+        [
+            "CALL SFB 4, DB 100 (",
+            "IN := E0.0,",
+            "PT := T#5S,",
+            "Q := #T0,",
+            "ET := #T1);",
+            "U #T0;",
+            "= M0.0;",
+        ]
+    );
+}
+
+#[test]
+fn outputs_bind_to_arguments() {
+    let networks = awl("M0.0 = TON(E0.0, TIME#1m5s, M1.0);");
+    assert!(networks[0].contains(&"PT := T#1M5S,".to_string()));
+    assert!(networks[0].contains(&"Q := M1.0,".to_string()));
+}
+
+#[test]
+fn output_argument_must_be_writable() {
+    let (message, quote) = error("M0.0 = TON(E0.0, T#5s, E1.0);");
+    assert_eq!(
+        message,
+        "there is no write handler available for this variable"
+    );
+    assert_eq!(quote, "E1.0");
+}
+
+#[test]
+fn argument_type_error_points_at_argument() {
+    let (message, quote) = error("M0.0 = TON(E0.0, MW4);");
+    assert_eq!(
+        message,
+        "this argument does not match the type of its parameter"
+    );
+    assert_eq!(quote, "MW4");
+}
+
+#[test]
+fn argument_type_error_names_types() {
+    let Err(err) = common::compile("M0.0 = TON(MW2, T#5s);") else {
+        panic!("The program compiles");
+    };
+    assert_eq!(
+        err.note(),
+        Some("parameter `IN` expects BOOL, the argument is WORD")
+    );
+}
+
+#[test]
+fn extern_argument_type_error_names_parameter() {
+    let code = "extern fc FC10(in start: bool, in speed: int, out running: bool);\n\
+                FC10(E0.0, MD4);";
+    let Err(err) = common::compile(code) else {
+        panic!("The program compiles");
+    };
+    assert_eq!(&code[err.quote().start..err.quote().end], "MD4");
+    assert_eq!(
+        err.note(),
+        Some("parameter `speed` expects INT, the argument is DWORD")
+    );
+}
+
+#[test]
+fn temporaries_are_listed_with_their_type() {
+    let Ok(mir) = common::compile("M0.0 = TON(E0.0, T#5s);") else {
+        panic!("The program does not compile");
+    };
+    let Ok(lir) = s7::transformer::transform(&mir) else {
+        panic!("The program has no S7-LIR");
+    };
+    let types: Vec<_> = lir.temps.iter().map(|(_, r#type)| *r#type).collect();
+    assert_eq!(types, [S7DataType::Bool, S7DataType::Time]);
+}
+
+#[test]
+fn instance_skips_used_data_blocks() {
+    let networks = awl("DB100.DBX0.0 = TON(E0.0, T#5s); M0.0 = TON(E0.1, T#1s);");
+    assert!(networks[0].contains(&"CALL SFB 4, DB 101 (".to_string()));
+    assert!(networks[1].contains(&"CALL SFB 4, DB 102 (".to_string()));
+}
+
+#[test]
+fn instance_skips_data_blocks_of_pointers() {
+    let networks = awl("MW0 = DB120.DBW[AR1, P#0.0]; M2.0 = TON(E0.0, T#5s);");
+    assert!(networks[1].contains(&"CALL SFB 4, DB 121 (".to_string()));
+}
+
+#[test]
+fn extern_function_is_called_with_parameter_names() {
+    let networks = awl(
+        "extern fc FC10(in start: bool, in speed: int, out running: bool);
+         FC10(E0.0, MW2, A0.0);",
+    );
+    assert_eq!(
+        networks[0],
+        [
+            "CALL FC 10 (",
+            "start := E0.0,",
+            "speed := MW2,",
+            "running := A0.0);",
+        ]
+    );
+}
+
+#[test]
+fn extern_function_block_output_is_bindable() {
+    let networks = awl("extern fb FB5(in start: bool, out running: bool);
+         let r = FB5(E0.0); A0.1 = r;");
+    assert_eq!(
+        networks[0],
+        ["CALL FB 5, DB 100 (", "start := E0.0,", "running := #T0);"]
+    );
+    assert_eq!(networks[1], ["U #T0;", "= A0.1;"]);
+}
+
+#[test]
+fn extern_argument_is_type_checked() {
+    let (message, quote) = error("extern fc FC10(in speed: int);\nFC10(MD2);");
+    assert_eq!(
+        message,
+        "this argument does not match the type of its parameter"
+    );
+    assert_eq!(quote, "MD2");
+}
+
+#[test]
+fn extern_declarations_are_checked() {
+    let cases = [
+        (
+            "extern fc FC10();\nextern fc FC10();",
+            "this block has already been declared",
+            "FC10",
+        ),
+        (
+            "extern fc FC10(in x: bool, in x: int);",
+            "this parameter has already been declared",
+            "x",
+        ),
+        (
+            "extern fc FC10(in x: foo);",
+            "this data type does not exist",
+            "foo",
+        ),
+        (
+            "extern fc XY10(in x: bool);",
+            "this is not a valid block name",
+            "XY10",
+        ),
+    ];
+    for (code, expected_message, expected_quote) in cases {
+        let (message, quote) = error(code);
+        assert_eq!(message, expected_message);
+        assert_eq!(quote, expected_quote);
+    }
+}
+
+#[test]
+fn temporaries_are_declared() {
+    let text = source("M0.0 = TON(E0.0, T#5s);");
+    assert!(text.contains("  T0 : BOOL;\n  T1 : TIME;\nEND_VAR\nBEGIN"));
+    assert!(text.contains("Q := #T0,"));
+    assert!(!text.contains(":= M"));
+}
+
+#[test]
+fn parameters_are_indented_below_call() {
+    let text = source("M0.0 = TON(E0.0, T#5s);");
+    assert!(text.contains("      CALL SFB 4, DB 100 (\n        IN := E0.0,\n"));
+}
+
+#[test]
+fn temporaries_are_declared_in_scl() {
+    let Ok(mir) = common::compile("M0.0 = TON(E0.0, T#5s);") else {
+        panic!("The program does not compile");
+    };
+    let Ok(lir) = scl::transformer::transform(&mir) else {
+        panic!("The program has no SCL-LIR");
+    };
+    let mut out = Vec::new();
+    SclSource::new("Main", &lir)
+        .write_scl(&mut out, S7Mnemonics::English)
+        .expect("Write to memory");
+    let text = String::from_utf8(out).expect("ASCII source");
+    assert!(text.contains("   VAR_TEMP\n      T0 : BOOL;\n      T1 : TIME;\n   END_VAR\n"));
+    assert!(text.contains("%M0.0 := #T0;"));
+}
@@ -0,0 +1,79 @@
+//! Helpers shared by the integration tests
+
+#![allow(dead_code)]
+
+use std::rc::Rc;
+
+use fpp_compiler::{
+    error::Error,
+    lir::s7::{
+        self,
+        source::{S7Source, S7SourceKind},
+        S7Mnemonics,
+    },
+    mir::{self, Mir},
+    parser::Parser,
+    util::Source,
+};
+
+/// Parse and transform a program
+pub fn compile(code: &str) -> Result<Mir, Error> {
+    let source = Rc::new(Source::new("test.fpp", code));
+    let hir = Parser::new(source).parse()?;
+    mir::transformer::transform(hir)
+}
+
+/// Compile a program into the AWL source of `OB 1`, with `\n` line endings
+pub fn source(code: &str) -> String {
+    source_in(code, S7Mnemonics::German)
+}
+
+/// Compile a program into the AWL source of `OB 1` with the mnemonic set
+pub fn source_in(code: &str, mnemonics: S7Mnemonics) -> String {
+    let Ok(mir) = compile(code) else {
+        panic!("The program does not compile");
+    };
+    let Ok(lir) = s7::transformer::transform(&mir) else {
+        panic!("The program has no S7-LIR");
+    };
+    let mut out = Vec::new();
+    S7Source::new(S7SourceKind::OrganizationBlock, 1, &lir)
+        .write_source(&mut out, mnemonics)
+        .expect("Write to memory");
+    let text = String::from_utf8(out).expect("ASCII source");
+    text.replace("\r\n", "\n")
+}
+
+/// Compile a program into `OB 1`, the instructions of each network without indentation
+pub fn awl(code: &str) -> Vec<Vec<String>> {
+    awl_in(code, S7Mnemonics::German)
+}
+
+/// Compile a program into `OB 1` with the mnemonic set, the instructions of each network
+pub fn awl_in(code: &str, mnemonics: S7Mnemonics) -> Vec<Vec<String>> {
+    source_in(code, mnemonics)
+        .split("NETWORK\n")
+        .skip(1)
+        .map(|network| {
+            network
+                .lines()
+                .skip(1)
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with("END_"))
+                .map(str::to_string)
+                .collect()
+        })
+        .collect()
+}
+
+/// Compile a program that is expected to fail, the message and the quoted source
+pub fn error(code: &str) -> (String, String) {
+    let Err(err) = compile(code) else {
+        panic!("The program compiles");
+    };
+    let quote = err.quote();
+    (
+        err.message().to_string(),
+        code[quote.start..quote.end].to_string(),
+    )
+}
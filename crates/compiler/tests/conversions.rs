@@ -0,0 +1,43 @@
+mod common;
+
+use common::{awl, error};
+
+#[test]
+fn bcd_conversions() {
+    let networks = awl("MW30 = bcd_to_int(EW0); MW16 = int_to_bcd(MW18);");
+    assert_eq!(networks[0], ["L EW0;", "BTI;", "T MW30;"]);
+    assert_eq!(networks[1], ["L MW18;", "ITB;", "T MW16;"]);
+}
+
+#[test]
+fn nested_conversions_widen_to_real() {
+    let networks = awl("MD24 = dint_to_real(int_to_dint(MW18));");
+    assert_eq!(networks[0], ["L MW18;", "ITD;", "DTR;", "T MD24;"]);
+}
+
+#[test]
+fn real_to_dint_rounds_or_truncates() {
+    let networks = awl("MD32 = round(MD36); MD40 = trunc(MD36);");
+    assert_eq!(networks[0], ["L MD36;", "RND;", "T MD32;"]);
+    assert_eq!(networks[1], ["L MD36;", "TRUNC;", "T MD40;"]);
+}
+
+#[test]
+fn implicit_narrowing_is_reported() {
+    let (message, quote) = error("MW0 = MD4;\n");
+    assert_eq!(message, "this value would be implicitly narrowed");
+    assert_eq!(quote, "MD4");
+}
+
+#[test]
+fn implicit_widening_is_reported() {
+    let (message, quote) = error("MD0 = MW4;\n");
+    assert_eq!(message, "this value would be implicitly widened");
+    assert_eq!(quote, "MW4");
+}
+
+#[test]
+fn dint_literal_is_typed() {
+    let networks = awl("MD4 = dint_to_real(L#100000);");
+    assert_eq!(networks[0], ["L L#100000;", "DTR;", "T MD4;"]);
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::compile;
+use fpp_compiler::lir::{
+    il::{self, IlSource, WriteIl},
+    st::StMemory,
+};
+
+/// Transform a program into IL, the instructions of each network
+fn il(code: &str, max_depth: usize) -> Vec<Vec<String>> {
+    let Ok(mir) = compile(code) else {
+        panic!("The program does not compile");
+    };
+    let Ok(lir) = il::transformer::transform(&mir, max_depth) else {
+        panic!("The program has no IL-LIR");
+    };
+    lir.networks
+        .iter()
+        .map(|network| {
+            network
+                .instructions
+                .iter()
+                .map(|instruction| {
+                    let mut out = Vec::new();
+                    instruction.write_il(&mut out).expect("Write to memory");
+                    String::from_utf8(out).expect("ASCII instruction")
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn or_before_and_is_bracketed() {
+    let networks = il("A0.0 = (E0.0 or E0.1) and E0.2;", 8);
+    assert_eq!(networks[0], ["LD I0_0", "OR I0_1", "AND I0_2", "ST Q0_0"]);
+}
+
+#[test]
+fn and_after_or_is_bracketed() {
+    let networks = il("A0.0 = E0.0 or E0.1 and !E0.2;", 8);
+    assert_eq!(
+        networks[0],
+        ["LD I0_0", "OR(", "LD I0_1", "ANDN I0_2", ")", "ST Q0_0"]
+    );
+}
+
+#[test]
+fn negated_bracket_is_inverted() {
+    let networks = il("A0.0 = E0.0 and !(E0.1 or E0.2);", 8);
+    assert_eq!(
+        networks[0],
+        ["LD I0_0", "ANDN(", "LD I0_1", "OR I0_2", ")", "ST Q0_0"]
+    );
+}
+
+#[test]
+fn set_and_reset_are_conditional() {
+    let networks = il("let s = sr(E0.0, E0.1); A0.0 = s;", 8);
+    assert_eq!(
+        networks[0],
+        ["LD I0_0", "S M0_0", "LD I0_1", "R M0_0", "LD M0_0", "ST Q0_0"]
+    );
+}
+
+#[test]
+fn error_points_at_statement() {
+    let code = "A0.0 = E0.0 and (E0.1 or E0.2 and (E0.3 or E0.4));";
+    let Ok(mir) = compile(code) else {
+        panic!("The program does not compile");
+    };
+    let Err(err) = il::transformer::transform(&mir, 1) else {
+        panic!("The brackets fit");
+    };
+    assert_eq!(
+        err.message(),
+        "the nesting of the logic operations is invalid"
+    );
+    assert_eq!(&code[err.quote().start..err.quote().end], code);
+}
+
+#[test]
+fn temporaries_keep_their_type() {
+    let Ok(mir) = compile("M0.0 = TON(E0.4, T#5s);") else {
+        panic!("The program does not compile");
+    };
+    let Ok(lir) = il::transformer::transform(&mir, 8) else {
+        panic!("The program has no IL-LIR");
+    };
+    let Ok(source) = IlSource::new("Main", &lir, StMemory::Variables) else {
+        panic!("The program has no IL");
+    };
+    let mut out = Vec::new();
+    source.write_il(&mut out).expect("Write to memory");
+    let text = String::from_utf8(out).expect("ASCII source");
+    assert!(text.contains("    T0 : BOOL;\n    T1 : TIME;\n"));
+    assert!(text.contains("    TON_DB100 : TON;\n"));
+}
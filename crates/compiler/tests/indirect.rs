@@ -0,0 +1,45 @@
+mod common;
+
+use common::awl;
+
+#[test]
+fn pointer_literal_is_loaded_as_pointer() {
+    let networks = awl("MD20 = P#4.1;");
+    assert_eq!(networks[0], ["L P#4.1;", "T MD20;"]);
+}
+
+#[test]
+fn constant_pointer_is_direct() {
+    let networks = awl("A0.0 = M[P#2.1];");
+    assert_eq!(networks[0], ["U M2.1;", "= A0.0;"]);
+}
+
+#[test]
+fn pointer_in_memory_is_not_copied() {
+    let networks = awl("A0.0 = M[MD20];");
+    assert_eq!(networks[0], ["U M [MD20];", "= A0.0;"]);
+}
+
+#[test]
+fn pointer_is_copied_at_each_use() {
+    let networks = awl("let p = M[DB5.DBD0]; A0.0 = p; A0.1 = p;");
+    assert_eq!(networks.len(), 2);
+    for network in &networks {
+        assert_eq!(network[..2], ["L DB5.DBD0;", "T MD0;"]);
+    }
+    assert_eq!(networks[1][2..], ["U M [MD0];", "= A0.1;"]);
+}
+
+#[test]
+fn constant_pointer_into_program_memory_is_allowed() {
+    let networks = awl("let a = alloc8(); a = MB0; A0.0 = M[P#0.1];");
+    assert_eq!(networks[0], ["L MB0;", "T MB1;"]);
+    assert_eq!(networks[1], ["U M0.1;", "= A0.0;"]);
+}
+
+#[test]
+fn pointer_is_copied_before_call() {
+    let networks = awl("extern fc FC10(in x: bool);
+         FC10(M[DB5.DBD0]);");
+    assert_eq!(networks[0][..2], ["L DB5.DBD0;", "T MD0;"]);
+}
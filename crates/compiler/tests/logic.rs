@@ -0,0 +1,54 @@
+mod common;
+
+use common::awl;
+
+#[test]
+fn or_before_and_is_bracketed() {
+    let networks = awl("A0.0 = (E0.0 or E0.1) and E0.2;");
+    assert_eq!(
+        networks[0],
+        ["U(;", "U E0.0;", "O E0.1;", ");", "U E0.2;", "= A0.0;"]
+    );
+}
+
+#[test]
+fn or_after_and_is_bracketed() {
+    let networks = awl("A0.0 = E0.0 and (E0.1 or E0.2);");
+    assert_eq!(
+        networks[0],
+        ["U E0.0;", "U(;", "U E0.1;", "O E0.2;", ");", "= A0.0;"]
+    );
+}
+
+#[test]
+fn xor_before_or_is_not_bracketed() {
+    let networks = awl("A0.0 = (E0.0 xor E0.1) or E0.2;");
+    assert_eq!(networks[0], ["U E0.0;", "X E0.1;", "O E0.2;", "= A0.0;"]);
+}
+
+#[test]
+fn xor_before_and_is_bracketed() {
+    let networks = awl("A0.0 = (E0.0 xor E0.1) and E0.2;");
+    assert_eq!(
+        networks[0],
+        ["U(;", "U E0.0;", "X E0.1;", ");", "U E0.2;", "= A0.0;"]
+    );
+}
+
+#[test]
+fn negated_or_before_and_is_bracketed() {
+    let networks = awl("A0.0 = !(E0.0 or E0.1) and E0.2;");
+    assert_eq!(
+        networks[0],
+        ["U(;", "U E0.0;", "O E0.1;", "NOT;", ");", "U E0.2;", "= A0.0;"]
+    );
+}
+
+#[test]
+fn variable_holding_or_is_bracketed() {
+    let networks = awl("let a = E0.0 or E0.1; A0.0 = a and E0.2;");
+    assert_eq!(
+        networks[0],
+        ["U(;", "U E0.0;", "O E0.1;", ");", "U E0.2;", "= A0.0;"]
+    );
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::compile;
+use fpp_compiler::lir::{
+    logix::{LogixRoutine, LogixTag},
+    scl,
+};
+
+/// Convert a program into the ladder routine `MainRoutine`
+fn routine(code: &str) -> LogixRoutine {
+    let Ok(mir) = compile(code) else {
+        panic!("The program does not compile");
+    };
+    let Ok(lir) = scl::transformer::transform(&mir) else {
+        panic!("The program has no SCL-LIR");
+    };
+    let Ok(routine) = LogixRoutine::new("MainRoutine", &lir) else {
+        panic!("The program has no Logix routine");
+    };
+    routine
+}
+
+fn rungs(code: &str) -> Vec<String> {
+    routine(code).rungs.into_iter().map(|it| it.text).collect()
+}
+
+#[test]
+fn or_becomes_branch() {
+    assert_eq!(
+        rungs("A0.0 = E0.0 and not E0.1 or E0.2;"),
+        ["[XIC(I0_0)XIO(I0_1) ,XIC(I0_2) ]OTE(Q0_0);"]
+    );
+}
+
+#[test]
+fn flip_flop_latches_and_unlatches() {
+    assert_eq!(
+        rungs("A0.1 = rs(E0.3, E0.4);"),
+        [
+            "XIC(I0_3)OTU(M0_0);",
+            "XIC(I0_4)OTL(M0_0);",
+            "XIC(M0_0)OTE(Q0_1);",
+        ]
+    );
+}
+
+#[test]
+fn timer_is_timer_tag() {
+    let routine = routine("M0.0 = TON(E0.4, T#5s);");
+    assert_eq!(
+        routine.tags[0],
+        LogixTag {
+            name: "TON_DB100".into(),
+            r#type: "TIMER",
+        }
+    );
+    let rungs: Vec<_> = routine.rungs.iter().map(|it| it.text.as_str()).collect();
+    assert_eq!(
+        rungs,
+        [
+            "XIC(I0_4)TON(TON_DB100,5000,0);",
+            "XIC(TON_DB100.DN)OTE(T0);",
+            "MOV(TON_DB100.ACC,T1);",
+            "XIC(T0)OTE(M0_0);",
+        ]
+    );
+}
+
+#[test]
+fn words_are_dint_tags() {
+    let routine = routine("MW2 = MW4;");
+    assert_eq!(routine.rungs[0].text, "MOV(MW4,MW2);");
+    assert!(routine.tags.iter().all(|it| it.r#type == "DINT"));
+}
+
+#[test]
+fn l5x_wraps_routine_with_comments() {
+    let routine = routine("// Pump\nA0.0 = E0.0;\n");
+    let mut out = Vec::new();
+    routine.write_l5x(&mut out).expect("Write to memory");
+    let text = String::from_utf8(out).expect("UTF-8 routine");
+    assert!(text.contains("TargetName=\"MainRoutine\" TargetType=\"Routine\""));
+    assert!(text.contains("<Tag Name=\"Q0_0\" TagType=\"Base\" DataType=\"BOOL\""));
+    assert!(text.contains("<![CDATA[A0.0 = E0.0\n Pump]]>"));
+    assert!(text.contains("<![CDATA[XIC(I0_0)OTE(Q0_0);]]>"));
+}
@@ -0,0 +1,29 @@
+mod common;
+
+use common::awl;
+
+#[test]
+fn allocation_skips_memory_operands() {
+    let networks = awl("let m = M0.0; let a = alloc1(); m = E0.0; a = E0.1;");
+    assert_eq!(networks[0], ["U E0.0;", "= M0.0;"]);
+    assert_eq!(networks[1], ["U E0.1;", "= M1.0;"]);
+}
+
+#[test]
+fn allocation_skips_operands_used_later() {
+    let networks = awl("let a = alloc16(); let b = a.0; b = E0.0; let m = M1.0; m = E0.1;");
+    assert_eq!(networks[0], ["U E0.0;", "= M3.0;"]);
+}
+
+#[test]
+fn allocation_skips_words_read_bitwise() {
+    let networks = awl("let w = MW0; let a = alloc1(); a = w.9;");
+    assert_eq!(networks[0], ["U M0.1;", "= M2.0;"]);
+}
+
+#[test]
+fn allocation_skips_any_pointers() {
+    let networks = awl("let r = fill(P#M0.0 BYTE 4, MW4); let b = alloc1(); b = E0.0;");
+    assert!(networks[0].contains(&"RET_VAL := #T0,".to_string()));
+    assert_eq!(networks[1], ["U E0.0;", "= M6.0;"]);
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use common::{awl, awl_in};
+use fpp_compiler::lir::s7::S7Mnemonics;
+
+#[test]
+fn english_logic_and_operands() {
+    let networks = awl_in(
+        "Q0.0 = I0.0 and I0.1 or not I0.2; MW4 = PIW256;",
+        S7Mnemonics::English,
+    );
+    assert_eq!(
+        networks[0],
+        ["A I0.0;", "A I0.1;", "O(;", "A I0.2;", "NOT;", ");", "= Q0.0;"]
+    );
+    assert_eq!(networks[1], ["L PIW256;", "T MW4;"]);
+}
+
+#[test]
+fn english_flip_flop() {
+    let networks = awl_in("M1.0 = rs(I0.5, I0.6);", S7Mnemonics::English);
+    assert_eq!(
+        networks[0],
+        ["A I0.5;", "R M0.0;", "A I0.6;", "S M0.0;", "A M0.0;", "= M1.0;"]
+    );
+}
+
+#[test]
+fn english_operands_are_read_in_german_output() {
+    let networks = awl("Q0.0 = I0.0; MW4 = PIW256;");
+    assert_eq!(networks[0], ["U E0.0;", "= A0.0;"]);
+    assert_eq!(networks[1], ["L PEW256;", "T MW4;"]);
+}
+
+#[test]
+fn operands_of_both_languages_can_be_mixed() {
+    let networks = awl_in("A0.0 = E0.0 and I0.1;", S7Mnemonics::English);
+    assert_eq!(networks[0], ["A I0.0;", "A I0.1;", "= Q0.0;"]);
+}
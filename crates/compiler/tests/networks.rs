@@ -0,0 +1,80 @@
+mod common;
+
+use common::source;
+
+/// Title and comment lines of each network
+fn headers(code: &str) -> Vec<Vec<String>> {
+    source(code)
+        .split("NETWORK\n")
+        .skip(1)
+        .map(|network| {
+            network
+                .lines()
+                .take_while(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn statement_is_title() {
+    let networks = headers("A0.0 = E0.0;\nA0.1 = E0.1;");
+    assert_eq!(networks, [["TITLE = A0.0 = E0.0"], ["TITLE = A0.1 = E0.1"]]);
+}
+
+#[test]
+fn doc_comment_is_title() {
+    let networks = headers("/// Conveyor start\nA0.0 = E0.0;");
+    assert_eq!(networks, [["TITLE = Conveyor start"]]);
+}
+
+#[test]
+fn line_comments_precede_network() {
+    let networks = headers("// Start\n// of the belt\nA0.0 = E0.0;");
+    assert_eq!(networks[0][1..], ["// Start", "// of the belt"]);
+}
+
+#[test]
+fn block_comments_are_split_into_lines() {
+    let networks = headers("/* Start\n * of the belt\n */\nA0.0 = E0.0;");
+    assert_eq!(networks[0][1..], ["// Start", "// of the belt"]);
+}
+
+#[test]
+fn trailing_comment_belongs_to_statement() {
+    let networks = headers("A0.0 = E0.0; // first\nA0.1 = E0.1; /* second */");
+    assert_eq!(networks[0][1..], ["// first"]);
+    assert_eq!(networks[1][1..], ["// second"]);
+}
+
+#[test]
+fn comment_inside_statement_is_kept() {
+    let networks = headers("A0.0 = E0.0 // first input\n    and E0.1;\nA0.1 = E0.2;");
+    assert_eq!(
+        networks[0],
+        ["TITLE = A0.0 = E0.0 and E0.1", "// first input"]
+    );
+    assert_eq!(networks[1].len(), 1);
+}
+
+#[test]
+fn statement_over_several_lines_is_one_title() {
+    let networks = headers("A0.0 = E0.0\n    and E0.1\n    and E0.2;");
+    assert_eq!(networks[0], ["TITLE = A0.0 = E0.0 and E0.1 and E0.2"]);
+}
+
+#[test]
+fn comments_are_cut_from_title() {
+    let networks = headers("A0.0 = E0.0 # first input\n    and /* second */ E0.1;");
+    assert_eq!(networks[0][0], "TITLE = A0.0 = E0.0 and E0.1");
+    let networks = headers("MW0 = W#16#FF; # mask");
+    assert_eq!(networks[0], ["TITLE = MW0 = W#16#FF"]);
+}
+
+#[test]
+fn long_title_is_truncated() {
+    let code = format!("A0.0 = E0.0{};", " and E0.1".repeat(10));
+    let networks = headers(&code);
+    assert_eq!(networks[0][0].len(), "TITLE = ".len() + 64);
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use common::compile;
+use fpp_compiler::lir::{
+    plcopen::{PlcopenBody, PlcopenProject},
+    scl,
+    st::{StMemory, StSource},
+};
+
+/// Compile a program into a PLCopen project with the body language
+fn plcopen(code: &str, body: PlcopenBody) -> String {
+    let Ok(mir) = compile(code) else {
+        panic!("The program does not compile");
+    };
+    let Ok(lir) = scl::transformer::transform(&mir) else {
+        panic!("The program has no SCL-LIR");
+    };
+    let Ok(source) = StSource::new("Main", &lir, StMemory::Variables) else {
+        panic!("The program has no ST");
+    };
+    let Ok(project) = PlcopenProject::new(&source, body) else {
+        panic!("The program has no PLCopen project");
+    };
+    let mut out = Vec::new();
+    project.write_xml(&mut out).expect("Write to memory");
+    String::from_utf8(out).expect("UTF-8 project")
+}
+
+const PROGRAM: &str = "A0.0 = E0.0 and not E0.1; M0.0 = TON(E0.4, T#5s);";
+
+#[test]
+fn variables_are_declared_in_interface() {
+    let text = plcopen(PROGRAM, PlcopenBody::St);
+    assert!(text.contains("<pou name=\"Main\" pouType=\"program\">"));
+    assert!(text.contains(
+        "<variable name=\"I0_1\" address=\"%IX0.1\">\n              <type><BOOL/></type>"
+    ));
+    assert!(text.contains("<variable name=\"T1\">\n              <type><TIME/></type>"));
+    assert!(text.contains(
+        "<variable name=\"TON_DB100\">\n              <type><derived name=\"TON\"/></type>"
+    ));
+}
+
+#[test]
+fn st_body_is_escaped() {
+    let text = plcopen(PROGRAM, PlcopenBody::St);
+    assert!(text.contains("(* A0.0 = E0.0 and not E0.1 *)\nQ0_0 := I0_0 AND NOT I0_1;\n"));
+    assert!(text.contains("TON_DB100(IN := I0_4, PT := T#5S, Q =&gt; T0, ET =&gt; T1);"));
+}
+
+#[test]
+fn fbd_body_connects_blocks() {
+    let text = plcopen(PROGRAM, PlcopenBody::Fbd);
+    assert!(!text.contains("<ST>"));
+    assert!(text.contains("<block localId=\"4\" typeName=\"NOT\""));
+    assert!(text.contains("<block localId=\"5\" typeName=\"AND\""));
+    assert!(text.contains("<connection refLocalId=\"4\" formalParameter=\"OUT\"/>"));
+    assert!(text.contains("<block localId=\"10\" typeName=\"TON\" instanceName=\"TON_DB100\""));
+    assert!(text.contains("<connection refLocalId=\"10\" formalParameter=\"ET\"/>"));
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::compile;
+use fpp_compiler::lir::{
+    s7::S7Mnemonics,
+    scl::{self, SclSource, WriteScl},
+};
+
+/// Compile a program into the SCL source of the block `Main`
+fn scl(code: &str) -> String {
+    let Ok(mir) = compile(code) else {
+        panic!("The program does not compile");
+    };
+    let Ok(lir) = scl::transformer::transform(&mir) else {
+        panic!("The program has no SCL-LIR");
+    };
+    let mut out = Vec::new();
+    SclSource::new("Main", &lir)
+        .write_scl(&mut out, S7Mnemonics::English)
+        .expect("Write to memory");
+    String::from_utf8(out).expect("ASCII source")
+}
+
+/// Decompile a program that has no SCL equivalent, the message and the quoted source
+fn scl_error(code: &str) -> (String, String) {
+    let Ok(mir) = compile(code) else {
+        panic!("The program does not compile");
+    };
+    let Err(err) = scl::transformer::transform(&mir) else {
+        panic!("The program has a SCL-LIR");
+    };
+    let quote = err.quote();
+    (
+        err.message().to_string(),
+        code[quote.start..quote.end].to_string(),
+    )
+}
+
+#[test]
+fn error_points_at_statement() {
+    let (message, quote) = scl_error("M0.0 = E0.0; MW2 = MW[MD10];");
+    assert_eq!(message, "indirect addressing is specific to STEP 7");
+    assert_eq!(quote, "MW2 = MW[MD10];");
+}
+
+#[test]
+fn error_does_not_name_scl() {
+    let (message, quote) = scl_error("M0.0 = T1;");
+    assert_eq!(message, "S5 timers have no IEC equivalent, use TON instead");
+    assert_eq!(quote, "M0.0 = T1;");
+}
+
+#[test]
+fn logic_is_one_expression_per_region() {
+    let text = scl("A0.0 = E0.0 and not E0.1 or E0.2;");
+    assert!(text.contains(
+        "    REGION A0.0 = E0.0 and not E0.1 or E0.2\n        \
+         %Q0.0 := %I0.0 AND NOT %I0.1 OR %I0.2;\n    END_REGION\n"
+    ));
+}
+
+#[test]
+fn timer_is_static_instance() {
+    let text = scl("M0.1 = TON(E0.3, T#5s);");
+    assert!(text.contains("   VAR\n      TON_DB100 : TON;\n   END_VAR\n"));
+    assert!(text.contains("#TON_DB100(IN := %I0.3, PT := T#5S, Q => #T0, ET => #T1);"));
+}
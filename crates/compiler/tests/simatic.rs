@@ -0,0 +1,94 @@
+mod common;
+
+use common::compile;
+use fpp_compiler::lir::{
+    s7::{self, source::S7SourceKind},
+    simatic::{SimaticBlock, SimaticInstance, SimaticTag},
+};
+
+const PROGRAM: &str = "// Pump\nA0.0 = E0.0 and not E0.1;\nM0.0 = TON(E0.4, T#5s);\nMW2 = EW4;\n";
+
+/// Compile a program into the SimaticML document of `OB 1` and its block
+fn simatic(code: &str, check: impl FnOnce(&SimaticBlock, String)) {
+    let Ok(mir) = compile(code) else {
+        panic!("The program does not compile");
+    };
+    let Ok(lir) = s7::transformer::transform(&mir) else {
+        panic!("The program has no S7-LIR");
+    };
+    let Ok(block) = SimaticBlock::new(S7SourceKind::OrganizationBlock, 1, "Main".into(), &lir)
+    else {
+        panic!("The program has no SimaticML block");
+    };
+    let mut out = Vec::new();
+    block.write_block(&mut out).expect("Write to memory");
+    check(&block, String::from_utf8(out).expect("UTF-8 document"));
+}
+
+fn tag(name: &str, address: &str, r#type: &'static str) -> SimaticTag {
+    SimaticTag {
+        name: name.into(),
+        address: address.into(),
+        r#type,
+    }
+}
+
+#[test]
+fn network_is_compile_unit_with_title_and_comment() {
+    simatic(PROGRAM, |_, text| {
+        assert!(text.contains("<SW.Blocks.OB ID=\"0\">"));
+        assert!(text.contains("<SecondaryType>ProgramCycle</SecondaryType>"));
+        assert_eq!(text.matches("<SW.Blocks.CompileUnit ").count(), 3);
+        assert!(text.contains("CompositionName=\"Comment\">"));
+        assert!(text.contains("<Text> Pump</Text>"));
+        assert!(text.contains("<Text>A0.0 = E0.0 and not E0.1</Text>"));
+    });
+}
+
+#[test]
+fn operands_are_symbols_of_tags() {
+    simatic(PROGRAM, |block, text| {
+        assert!(text.contains("<StlToken Text=\"A_BRACK\" />"));
+        assert!(text.contains("<Component Name=\"Q0_0\" />"));
+        assert_eq!(block.tags[0], tag("I0_0", "%I0.0", "Bool"));
+        assert!(block.tags.contains(&tag("IW4", "%IW4", "Word")));
+        assert!(block.tags.contains(&tag("MW2", "%MW2", "Word")));
+    });
+}
+
+#[test]
+fn called_blocks_get_instance_documents() {
+    simatic(PROGRAM, |block, _| {
+        assert_eq!(
+            block.instances,
+            [SimaticInstance {
+                name: "TON_DB100".into(),
+                number: 100,
+                block: "TON".into(),
+            }]
+        );
+        let mut out = Vec::new();
+        block
+            .write_instance(&mut out, &block.instances[0])
+            .expect("Write to memory");
+        let text = String::from_utf8(out).expect("UTF-8 document");
+        assert!(text.contains("<InstanceOfName>TON</InstanceOfName>"));
+        assert!(text.contains("<Number>100</Number>"));
+    });
+}
+
+#[test]
+fn tag_table_declares_tags() {
+    simatic(PROGRAM, |block, _| {
+        let mut out = Vec::new();
+        block
+            .write_tags(&mut out, "F++ Tags")
+            .expect("Write to memory");
+        let text = String::from_utf8(out).expect("UTF-8 document");
+        assert!(text.contains("<Name>F++ Tags</Name>"));
+        assert!(text.contains(
+            "<DataTypeName>Word</DataTypeName>\n          \
+             <LogicalAddress>%MW2</LogicalAddress>\n          <Name>MW2</Name>"
+        ));
+    });
+}
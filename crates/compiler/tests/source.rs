@@ -0,0 +1,57 @@
+mod common;
+
+use common::{compile, source};
+use fpp_compiler::lir::s7::{
+    self,
+    source::{encode_windows_1252, S7Source, S7SourceKind},
+    S7Mnemonics,
+};
+
+#[test]
+fn organization_block_has_header_and_temporaries() {
+    let text = source("A0.0 = E0.0;");
+    assert!(text.starts_with("ORGANIZATION_BLOCK OB 1\nTITLE = \nVERSION : 0.1\n"));
+    assert!(text.contains("VAR_TEMP\n  OB1_EV_CLASS : BYTE;\n"));
+    assert!(text.contains("BEGIN\nNETWORK\nTITLE = A0.0 = E0.0\n"));
+    assert!(text.ends_with("END_ORGANIZATION_BLOCK\n"));
+}
+
+#[test]
+fn function_is_written_with_crlf() {
+    let Ok(mir) = compile("A0.0 = E0.0;") else {
+        panic!("The program does not compile");
+    };
+    let Ok(lir) = s7::transformer::transform(&mir) else {
+        panic!("The program has no S7-LIR");
+    };
+    let mut block = S7Source::new(S7SourceKind::Function, 10, &lir);
+    block.title = "Test".into();
+    let mut out = Vec::new();
+    block
+        .write_source(&mut out, S7Mnemonics::German)
+        .expect("Write to memory");
+    let text = String::from_utf8(out).expect("ASCII source");
+    assert!(text.starts_with("FUNCTION FC 10 : VOID\r\nTITLE = Test\r\n"));
+    assert!(!text.contains("VAR_TEMP"));
+    assert!(text.ends_with("END_FUNCTION\r\n"));
+}
+
+#[test]
+fn text_is_encoded_as_windows_1252() {
+    assert_eq!(encode_windows_1252("Größe €\n"), b"Gr\xF6\xDFe \x80\r\n");
+    assert_eq!(encode_windows_1252("→"), b"?");
+}
+
+#[test]
+fn block_names_are_parsed() {
+    assert!(matches!(
+        S7Source::parse_block("fc10"),
+        Some((S7SourceKind::Function, 10))
+    ));
+    assert!(matches!(
+        S7Source::parse_block("OB1"),
+        Some((S7SourceKind::OrganizationBlock, 1))
+    ));
+    assert!(S7Source::parse_block("FB1").is_none());
+    assert!(S7Source::parse_block("OB0").is_none());
+}
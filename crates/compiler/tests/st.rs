@@ -0,0 +1,72 @@
+mod common;
+
+use common::compile;
+use fpp_compiler::lir::{
+    scl,
+    st::{StMemory, StSource, WriteSt},
+};
+
+/// Compile a program into the ST source of `PROGRAM Main`
+fn st(code: &str, memory: StMemory) -> Result<String, String> {
+    let Ok(mir) = compile(code) else {
+        panic!("The program does not compile");
+    };
+    let Ok(lir) = scl::transformer::transform(&mir) else {
+        panic!("The program has no SCL-LIR");
+    };
+    let source = StSource::new("Main", &lir, memory).map_err(|err| err.message().to_string())?;
+    let mut out = Vec::new();
+    source.write_st(&mut out).expect("Write to memory");
+    Ok(String::from_utf8(out).expect("ASCII source"))
+}
+
+#[test]
+fn inputs_and_outputs_are_located() {
+    let Ok(text) = st("A0.0 = E0.0 and not E0.1; AW2 = EW4;", StMemory::Variables) else {
+        panic!("The program has no ST");
+    };
+    assert!(text.starts_with("PROGRAM Main\nVAR\n    Q0_0 AT %QX0.0 : BOOL;\n"));
+    assert!(text.contains("    I0_1 AT %IX0.1 : BOOL;\n"));
+    assert!(text.contains("    QW2 AT %QW1 : WORD;\n    IW4 AT %IW2 : WORD;\n"));
+    assert!(text.contains("(* A0.0 = E0.0 and not E0.1 *)\nQ0_0 := I0_0 AND NOT I0_1;\n"));
+    assert!(text.ends_with("END_PROGRAM\n"));
+}
+
+#[test]
+fn allocated_memory_follows_memory_option() {
+    let code = "let m = alloc1(); m = E0.0; A0.0 = m;";
+    let Ok(variables) = st(code, StMemory::Variables) else {
+        panic!("The program has no ST");
+    };
+    assert!(variables.contains("    M0_0 : BOOL;\n"));
+    let Ok(located) = st(code, StMemory::Located) else {
+        panic!("The program has no ST");
+    };
+    assert!(located.contains("    M0_0 AT %MX0.0 : BOOL;\n"));
+}
+
+#[test]
+fn timer_is_standard_block() {
+    let Ok(text) = st("M0.0 = TON(E0.4, T#5s);", StMemory::Variables) else {
+        panic!("The program has no ST");
+    };
+    assert!(text.contains("    TON_DB100 : TON;\n"));
+    assert!(text.contains("TON_DB100(IN := I0_4, PT := T#5S, Q => T0, ET => T1);"));
+}
+
+#[test]
+fn temporaries_keep_their_type() {
+    let Ok(text) = st("M0.0 = TON(E0.4, T#5s);", StMemory::Variables) else {
+        panic!("The program has no ST");
+    };
+    assert!(text.contains("    T0 : BOOL;\n"));
+    assert!(text.contains("    T1 : TIME;\n"));
+}
+
+#[test]
+fn unaligned_words_are_rejected() {
+    assert_eq!(
+        st("MW2 = MW5;", StMemory::Variables),
+        Err("IEC addresses of words and double words must be aligned to their size".into())
+    );
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::{awl, error};
+
+#[test]
+fn binary_literal_is_byte_pattern() {
+    let networks = awl("AB1 = 2#1010_0101;");
+    assert_eq!(networks[0], ["L B#16#A5;", "T AB1;"]);
+}
+
+#[test]
+fn hexadecimal_literals_keep_their_width() {
+    let networks = awl("AW2 = W#16#00FF; MD4 = DW#16#DEAD_BEEF; AB0 = B#16#0F;");
+    assert_eq!(networks[0], ["L W#16#00FF;", "T AW2;"]);
+    assert_eq!(networks[1], ["L DW#16#DEADBEEF;", "T MD4;"]);
+    assert_eq!(networks[2], ["L B#16#0F;", "T AB0;"]);
+}
+
+#[test]
+fn untyped_number_adapts_to_target() {
+    let networks = awl("AW4 = 1000;");
+    assert_eq!(networks[0], ["L W#16#03E8;", "T AW4;"]);
+}
+
+#[test]
+fn wider_pattern_is_not_narrowed() {
+    let (message, quote) = error("AB0 = W#16#01FF;");
+    assert_eq!(message, "this value would be implicitly narrowed");
+    assert_eq!(quote, "W#16#01FF");
+}
+
+#[test]
+fn hash_after_operand_starts_comment() {
+    let networks = awl("let x = E0.2#note\n; let q = A0.0; q = x;");
+    assert_eq!(networks[0], ["U E0.2;", "= A0.0;"]);
+}
+
+#[test]
+fn hash_after_identifier_starts_comment() {
+    let networks = awl("let x = E0.0; let q = A0.0; q = x#note\n;");
+    assert_eq!(networks[0], ["U E0.0;", "= A0.0;"]);
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::{awl, error};
+
+#[test]
+fn timer_name_is_rejected() {
+    let (message, quote) = error("let T1 = E0.0; A0.0 = T1;");
+    assert_eq!(
+        message,
+        "this name is an operand and can not name a variable"
+    );
+    assert_eq!(quote, "T1");
+}
+
+#[test]
+fn output_name_is_rejected() {
+    let (message, quote) = error("let A1 = E0.0;");
+    assert_eq!(
+        message,
+        "this name is an operand and can not name a variable"
+    );
+    assert_eq!(quote, "A1");
+}
+
+#[test]
+fn data_block_name_is_rejected() {
+    let (_, quote) = error("let DB5 = E0.0;");
+    assert_eq!(quote, "DB5");
+}
+
+#[test]
+fn variable_is_read_through() {
+    let networks = awl("let button = E0.0; let led = A0.0; led = button;");
+    assert_eq!(networks[0], ["U E0.0;", "= A0.0;"]);
+}
+
+#[test]
+fn later_declaration_shadows() {
+    let networks =
+        awl("let a = E0.0; let q = A0.0; q = a; let a = a and E0.1; let q = A0.1; q = a;");
+    assert_eq!(networks[0], ["U E0.0;", "= A0.0;"]);
+    assert_eq!(networks[1], ["U E0.0;", "U E0.1;", "= A0.1;"]);
+}
+
+#[test]
+fn bit_access_counts_from_low_byte() {
+    let networks = awl("let s = MW20; let q = A0.0; q = s.3; let q = A0.1; q = s.12;");
+    assert_eq!(networks[0], ["U M21.3;", "= A0.0;"]);
+    assert_eq!(networks[1], ["U M20.4;", "= A0.1;"]);
+}
+
+#[test]
+fn bit_access_is_a_write_target() {
+    let networks = awl("let s = MD4; s.17 = E0.0;");
+    assert_eq!(networks[0], ["U E0.0;", "= M5.1;"]);
+}
+
+#[test]
+fn bit_access_beyond_width_is_rejected() {
+    let (message, quote) = error("let s = MB20; let q = A0.0; q = s.8;");
+    assert_eq!(
+        message,
+        "this bit is out of range for the width of the value"
+    );
+    assert_eq!(quote, "s.8");
+}
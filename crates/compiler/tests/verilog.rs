@@ -0,0 +1,103 @@
+mod common;
+
+use common::compile;
+use fpp_compiler::lir::{
+    scl,
+    verilog::{VerilogDirection, VerilogModule, VerilogSignal, WriteVerilog},
+};
+
+/// Convert a program into the module `Main` and its testbench
+fn verilog(code: &str, check: impl FnOnce(&VerilogModule, String, String)) {
+    let Ok(mir) = compile(code) else {
+        panic!("The program does not compile");
+    };
+    let Ok(lir) = scl::transformer::transform(&mir) else {
+        panic!("The program has no SCL-LIR");
+    };
+    let Ok(module) = VerilogModule::new("Main", &lir) else {
+        panic!("The program has no Verilog module");
+    };
+    let mut out = Vec::new();
+    module.write_verilog(&mut out).expect("Write to memory");
+    let mut testbench = Vec::new();
+    module
+        .write_testbench(&mut testbench, 256)
+        .expect("Write to memory");
+    check(
+        &module,
+        String::from_utf8(out).expect("ASCII module"),
+        String::from_utf8(testbench).expect("ASCII testbench"),
+    );
+}
+
+fn signal(name: &str, width: u16, direction: VerilogDirection) -> VerilogSignal {
+    VerilogSignal {
+        name: name.into(),
+        width,
+        direction,
+    }
+}
+
+#[test]
+fn operands_become_ports() {
+    verilog(
+        "A0.0 = E0.0 and not E0.1 or E0.2; AW2 = EW4;",
+        |module, text, _| {
+            assert_eq!(
+                module.signals[0],
+                signal("Q0_0", 1, VerilogDirection::Output)
+            );
+            assert!(module
+                .signals
+                .contains(&signal("IW4", 16, VerilogDirection::Input)));
+            assert!(text.starts_with(
+                "module Main (\n    input wire clk,\n    input wire rst,\n    input wire scan,\n"
+            ));
+            assert!(text.contains("    input wire [15:0] IW4"));
+            assert!(text.contains("Q0_0 = I0_0 & ~I0_1 | I0_2;"));
+        },
+    );
+}
+
+#[test]
+fn flip_flop_is_register_of_scan() {
+    verilog("A0.1 = rs(E0.3, E0.4);", |module, text, _| {
+        assert!(module
+            .signals
+            .contains(&signal("M0_0", 1, VerilogDirection::Internal)));
+        assert!(text.contains("    reg M0_0;\n"));
+        assert!(text.contains("    always @(posedge clk) begin\n        if (rst) begin\n"));
+        assert!(text.contains("end else if (scan) begin"));
+        assert!(text.contains(
+            "if (I0_3) begin\n                M0_0 = 1'b0;\n            end\n            \
+             if (I0_4) begin\n                M0_0 = 1'b1;\n            end\n"
+        ));
+    });
+}
+
+#[test]
+fn small_testbench_is_exhaustive() {
+    verilog("A0.1 = rs(E0.3, E0.4);", |_, _, testbench| {
+        assert!(testbench.contains("module Main_tb;"));
+        assert!(testbench.contains("Main dut (.clk(clk), .rst(rst), .scan(scan),"));
+        assert!(testbench.contains("for (i = 0; i < 4; i = i + 1) begin"));
+        assert!(testbench.contains("{I0_3, I0_4} = i;"));
+    });
+}
+
+#[test]
+fn timers_are_rejected() {
+    let Ok(mir) = compile("M0.0 = TON(E0.4, T#5s);") else {
+        panic!("The program does not compile");
+    };
+    let Ok(lir) = scl::transformer::transform(&mir) else {
+        panic!("The program has no SCL-LIR");
+    };
+    let Err(err) = VerilogModule::new("Main", &lir) else {
+        panic!("The program has a Verilog module");
+    };
+    assert_eq!(
+        err.message(),
+        "timers, counters and function blocks have no hardware equivalent"
+    );
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use common::awl;
+
+#[test]
+fn word_operands_use_accumulator_logic() {
+    let networks = awl("MW0 = MW2 & W#16#00FF; MW20 = MW2 or W#16#0F00; MW0 = MW2 xor MW4;");
+    assert_eq!(networks[0], ["L MW2;", "L W#16#00FF;", "UW;", "T MW0;"]);
+    assert_eq!(networks[1], ["L MW2;", "L W#16#0F00;", "OW;", "T MW20;"]);
+    assert_eq!(networks[2], ["L MW2;", "L MW4;", "XOW;", "T MW0;"]);
+}
+
+#[test]
+fn not_of_word_inverts_bits() {
+    let networks = awl("MW6 = not MW2;");
+    assert_eq!(networks[0], ["L MW2;", "INVI;", "T MW6;"]);
+}
+
+#[test]
+fn boolean_operands_keep_rlo_logic() {
+    let networks = awl("A0.0 = E0.0 and E0.1;");
+    assert_eq!(networks[0], ["U E0.0;", "U E0.1;", "= A0.0;"]);
+}
+
+#[test]
+fn shifts_and_rotations_take_amount() {
+    let networks = awl("MW4 = MW2 << 3; MW8 = MW2 >> 2; MD8 = MD12 rotl 4; MD12 = MD16 rotr 1;");
+    assert_eq!(networks[0], ["L MW2;", "SLW 3;", "T MW4;"]);
+    assert_eq!(networks[1], ["L MW2;", "SRW 2;", "T MW8;"]);
+    assert_eq!(networks[2], ["L MD12;", "RLD 4;", "T MD8;"]);
+    assert_eq!(networks[3], ["L MD16;", "RRD 1;", "T MD12;"]);
+}
@@ -0,0 +1,313 @@
+//! Embed F++ programs in Rust as structs with typed inputs, outputs and a `scan` method
+//!
+//! ```ignore
+//! fpp! {
+//!     pub struct Press {
+//!         /// Two-hand control
+//!         A0.0 = E0.0 and E0.1;
+//!     }
+//! }
+//!
+//! include_fpp!("plant.fpp");
+//! include_fpp!(pub(crate) struct Line, "line.fpp");
+//! ```
+
+use std::{env::var_os, fs::read_to_string, path::PathBuf, rc::Rc};
+
+use fpp_compiler::{
+    build::{generate, GenerateError},
+    util::Source,
+};
+use proc_macro::{Delimiter, Group, Literal, Span, TokenStream, TokenTree};
+
+/// Compile the F++ program in the braces, the struct is `pub struct Plc` if it is not declared
+#[proc_macro]
+pub fn fpp(input: TokenStream) -> TokenStream {
+    fpp_compiler::init(false);
+    let tokens: Vec<_> = input.into_iter().collect();
+    let (header, program) = match parse_header(&tokens) {
+        Some((header, [TokenTree::Group(group)])) if group.delimiter() == Delimiter::Brace => {
+            (header, group.stream())
+        }
+        Some((_, rest)) => {
+            let span = rest.first().map_or(Span::call_site(), TokenTree::span);
+            return error("expected the program in braces", span);
+        }
+        None => {
+            let header = Header {
+                visibility: "pub".to_string(),
+                name: "Plc".to_string(),
+            };
+            (header, tokens.into_iter().collect())
+        }
+    };
+    let mut code = Code::default();
+    code.push_stream(program);
+    let source = Rc::new(Source::new("fpp!", code.text.as_str()));
+    match generate(source, &header.name, &header.visibility) {
+        Ok(rust) => rust.parse().expect("Generated Rust"),
+        Err(GenerateError::Source(err)) => {
+            let message = match err.note() {
+                Some(note) => format!("{}\nnote: {note}", err.message()),
+                None => err.message().to_string(),
+            };
+            error(&message, code.span(err.quote().start))
+        }
+        Err(err) => error(err.message(), Span::call_site()),
+    }
+}
+
+/// Compile an F++ file relative to the manifest of the crate
+///
+/// The struct is named after the file (`press_line.fpp` is `pub struct PressLine`) unless it is
+/// declared before the path.
+#[proc_macro]
+pub fn include_fpp(input: TokenStream) -> TokenStream {
+    fpp_compiler::init(false);
+    let tokens: Vec<_> = input.into_iter().collect();
+    let (header, rest) = match parse_header(&tokens) {
+        Some((header, [TokenTree::Punct(comma), rest @ ..])) if comma.as_char() == ',' => {
+            (Some(header), rest)
+        }
+        Some((_, rest)) => {
+            let span = rest.first().map_or(Span::call_site(), TokenTree::span);
+            return error("expected `,` followed by the path", span);
+        }
+        None => (None, &tokens[..]),
+    };
+    let [TokenTree::Literal(literal)] = rest else {
+        return error("expected the path of an F++ file", Span::call_site());
+    };
+    let Some(path) = string(literal) else {
+        return error("expected the path as string", literal.span());
+    };
+    let dir = var_os("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let path = PathBuf::from(dir).join(path);
+    let code = match read_to_string(&path) {
+        Ok(code) => code,
+        Err(err) => return error(&format!("{}: {err}", path.display()), literal.span()),
+    };
+    let header = header.unwrap_or_else(|| Header {
+        visibility: "pub".to_string(),
+        name: camel_case(&path.file_stem().unwrap_or_default().to_string_lossy()),
+    });
+    let source = Rc::new(Source::new(path.to_string_lossy(), code));
+    match generate(source, &header.name, &header.visibility) {
+        Ok(rust) => {
+            let mut tokens: TokenStream = rust.parse().expect("Generated Rust");
+            // Compile again whenever the file changes
+            let dependency = format!("const _: &[u8] = ::core::include_bytes!({path:?});");
+            tokens.extend(dependency.parse::<TokenStream>());
+            tokens
+        }
+        Err(GenerateError::Source(err)) => {
+            // The message quotes the file, the span only points at its path
+            let message = err.to_string();
+            error(message.trim_start_matches("error: "), literal.span())
+        }
+        Err(err) => {
+            let message = format!("{}: {}", path.display(), err.message());
+            error(&message, literal.span())
+        }
+    }
+}
+
+/// Name and visibility of the generated struct
+struct Header {
+    visibility: String,
+    name: String,
+}
+
+/// `pub(crate) struct Name` followed by the remaining tokens
+fn parse_header(tokens: &[TokenTree]) -> Option<(Header, &[TokenTree])> {
+    let (visibility, rest) = match tokens {
+        [TokenTree::Ident(vis), TokenTree::Group(group), rest @ ..]
+            if vis.to_string() == "pub" && group.delimiter() == Delimiter::Parenthesis =>
+        {
+            (format!("pub{group}"), rest)
+        }
+        [TokenTree::Ident(vis), rest @ ..] if vis.to_string() == "pub" => ("pub".to_string(), rest),
+        rest => (String::new(), rest),
+    };
+    match rest {
+        [TokenTree::Ident(keyword), TokenTree::Ident(name), rest @ ..]
+            if keyword.to_string() == "struct" =>
+        {
+            let name = name.to_string();
+            Some((Header { visibility, name }, rest))
+        }
+        _ => None,
+    }
+}
+
+/// F++ source rebuilt from the tokens at their lines and columns, so it reads as written
+#[derive(Default)]
+struct Code {
+    text: String,
+    line: usize,
+    column: usize,
+    /// Offset in the text and span of every token
+    spans: Vec<(usize, Span)>,
+}
+
+impl Code {
+    fn push_stream(&mut self, stream: TokenStream) {
+        let mut tokens = stream.into_iter().peekable();
+        while let Some(token) = tokens.next() {
+            match token {
+                TokenTree::Group(group) => self.push_group(group),
+                // `///` comments are titles of networks, Rust passes them as attributes
+                TokenTree::Punct(punct) if punct.as_char() == '#' => {
+                    let doc = match tokens.peek() {
+                        Some(TokenTree::Group(group)) => doc(group),
+                        _ => None,
+                    };
+                    match doc {
+                        Some(doc) => {
+                            tokens.next();
+                            self.push(&format!("///{doc}"), punct.span());
+                        }
+                        None => self.push("#", punct.span()),
+                    }
+                }
+                token => self.push(&token.to_string(), token.span()),
+            }
+        }
+    }
+
+    fn push_group(&mut self, group: Group) {
+        let (open, close) = match group.delimiter() {
+            Delimiter::Parenthesis => ("(", ")"),
+            Delimiter::Brace => ("{", "}"),
+            Delimiter::Bracket => ("[", "]"),
+            Delimiter::None => return self.push_stream(group.stream()),
+        };
+        self.push(open, group.span_open());
+        self.push_stream(group.stream());
+        self.push(close, group.span_close());
+    }
+
+    fn push(&mut self, text: &str, span: Span) {
+        let (line, column) = (span.line(), span.column());
+        if self.spans.is_empty() {
+            (self.line, self.column) = (line, column);
+        }
+        if line > self.line {
+            self.text.extend((self.line..line).map(|_| '\n'));
+            (self.line, self.column) = (line, 1);
+        }
+        if column > self.column {
+            self.text.extend((self.column..column).map(|_| ' '));
+            self.column = column;
+        } else if !self.spans.is_empty() && column < self.column {
+            // Tokens from a `macro_rules!` expansion have no place in the source
+            self.text.push(' ');
+            self.column += 1;
+        }
+        self.spans.push((self.text.len(), span));
+        self.text.push_str(text);
+        match text.rfind('\n') {
+            Some(end) => {
+                self.line += text.matches('\n').count();
+                self.column = text[end + 1..].chars().count() + 1;
+            }
+            None => self.column += text.chars().count(),
+        }
+    }
+
+    /// Span of the token at the offset in the text
+    fn span(&self, offset: usize) -> Span {
+        self.spans
+            .iter()
+            .rev()
+            .find(|it| it.0 <= offset)
+            .map_or(Span::call_site(), |it| it.1)
+    }
+}
+
+/// Text of a `doc = ".."` attribute
+fn doc(group: &Group) -> Option<String> {
+    if group.delimiter() != Delimiter::Bracket {
+        return None;
+    }
+    let tokens: Vec<_> = group.stream().into_iter().collect();
+    match &tokens[..] {
+        [TokenTree::Ident(ident), TokenTree::Punct(punct), TokenTree::Literal(literal)]
+            if ident.to_string() == "doc" && punct.as_char() == '=' =>
+        {
+            string(literal)
+        }
+        _ => None,
+    }
+}
+
+/// Value of a string literal, `None` for other literals
+fn string(literal: &Literal) -> Option<String> {
+    let literal = literal.to_string();
+    if let Some(raw) = literal.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let raw = &raw[hashes..raw.len() - hashes];
+        return Some(raw.strip_prefix('"')?.strip_suffix('"')?.to_string());
+    }
+    let quoted = literal.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::new();
+    let mut chars = quoted.chars();
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            value.push(char);
+            continue;
+        }
+        match chars.next()? {
+            'n' => value.push('\n'),
+            'r' => value.push('\r'),
+            't' => value.push('\t'),
+            '0' => value.push('\0'),
+            'x' => {
+                let code = chars.by_ref().take(2).collect::<String>();
+                value.push(u8::from_str_radix(&code, 16).ok()? as char);
+            }
+            'u' => {
+                let code: String = chars.by_ref().take_while(|&it| it != '}').collect();
+                let code = u32::from_str_radix(code.trim_start_matches('{'), 16).ok()?;
+                value.push(char::from_u32(code)?);
+            }
+            '\n' => {
+                let rest = chars.as_str().trim_start();
+                chars = rest.chars();
+            }
+            char => value.push(char),
+        }
+    }
+    Some(value)
+}
+
+/// `press_line` is `PressLine`
+fn camel_case(name: &str) -> String {
+    name.split(|it: char| !it.is_ascii_alphanumeric())
+        .flat_map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().map(|it| it.to_ascii_uppercase());
+            first.into_iter().chain(chars)
+        })
+        .collect()
+}
+
+/// `compile_error!` pointing at the span
+fn error(message: &str, span: Span) -> TokenStream {
+    let tokens: TokenStream = format!("::core::compile_error!({message:?});")
+        .parse()
+        .expect("Error macro");
+    tokens.into_iter().map(|it| respan(it, span)).collect()
+}
+
+fn respan(token: TokenTree, span: Span) -> TokenTree {
+    let mut token = match token {
+        TokenTree::Group(group) => {
+            let stream = group.stream().into_iter().map(|it| respan(it, span));
+            TokenTree::Group(Group::new(group.delimiter(), stream.collect()))
+        }
+        token => token,
+    };
+    token.set_span(span);
+    token
+}
@@ -0,0 +1,51 @@
+use std::{
+    fs::{create_dir_all, write},
+    path::PathBuf,
+    process::Command,
+};
+
+/// Build a binary crate using the macros, the short diagnostics of the build
+fn build(name: &str, main: &str) -> String {
+    let tmp = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let dir = tmp.join(name);
+    create_dir_all(dir.join("src")).expect("Create crate");
+    let macros = env!("CARGO_MANIFEST_DIR").replace('\\', "/");
+    let manifest = format!(
+        "[package]\nname = {name:?}\nversion = \"0.0.0\"\nedition = \"2021\"\n\n\
+         [dependencies]\nfpp-macros = {{ path = {macros:?} }}\n\n[workspace]\n"
+    );
+    write(dir.join("Cargo.toml"), manifest).expect("Write manifest");
+    write(dir.join("src/main.rs"), main).expect("Write program");
+    let output = Command::new(env!("CARGO"))
+        .args(["build", "--offline", "--quiet", "--message-format=short"])
+        .current_dir(&dir)
+        .env("CARGO_TARGET_DIR", tmp.join("target"))
+        .output()
+        .expect("Run cargo");
+    assert!(!output.status.success(), "The crate compiles");
+    String::from_utf8(output.stderr).expect("UTF-8 diagnostics")
+}
+
+#[test]
+fn error_points_at_token() {
+    let main = "fpp_macros::fpp! {\n    A0.0 = E0.0 and start;\n}\n\nfn main() {}\n";
+    let stderr = build("fpp_error_span", main);
+    assert!(
+        stderr.contains("src/main.rs:2:21: error: this variable does not exist"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn error_note_is_reported() {
+    let main = "fpp_macros::fpp! {\n    M0.0 = TON(MW2, MD4);\n}\n\nfn main() {}\n";
+    let stderr = build("fpp_error_note", main);
+    assert!(
+        stderr.contains("src/main.rs:2:16: error: this argument does not match"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("note: parameter `IN` expects BOOL, the argument is WORD"),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,23 @@
+use fpp_macros::include_fpp;
+
+include_fpp!("tests/press_line.fpp");
+include_fpp!(pub(crate) struct Line, "tests/press_line.fpp");
+
+#[test]
+fn struct_is_named_after_file() {
+    let mut press = PressLine::new();
+    (press.e0_0, press.e0_1) = (true, false);
+    press.scan();
+    assert!(!press.a0_0);
+    press.e0_1 = true;
+    press.scan();
+    assert!(press.a0_0);
+}
+
+#[test]
+fn struct_is_declared_before_path() {
+    let mut line = Line::new();
+    (line.e0_0, line.e0_1) = (true, true);
+    line.scan();
+    assert!(line.a0_0);
+}
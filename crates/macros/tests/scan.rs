@@ -0,0 +1,46 @@
+use fpp_macros::fpp;
+
+fpp! {
+    struct Logic {
+        A0.0 = (E0.0 or E0.1) and E0.2;
+        A0.1 = E0.0 or E0.1 and !E0.2;
+        A0.2 = !(E0.0 or E0.1) xor E0.2;
+    }
+}
+
+fpp! {
+    pub struct Motor {
+        /// Latching motor control
+        A0.0 = sr(E0.0, E0.1);
+    }
+}
+
+#[test]
+fn scan_matches_precedence() {
+    let mut plc = Logic::new();
+    for bits in 0..8 {
+        let [a, b, c] = [bits & 1 != 0, bits & 2 != 0, bits & 4 != 0];
+        (plc.e0_0, plc.e0_1, plc.e0_2) = (a, b, c);
+        plc.scan();
+        assert_eq!(plc.a0_0, (a || b) && c, "{a} {b} {c}");
+        assert_eq!(plc.a0_1, a || (b && !c), "{a} {b} {c}");
+        assert_eq!(plc.a0_2, (a || b) == c, "{a} {b} {c}");
+    }
+}
+
+#[test]
+fn flip_flop_is_kept_between_scans() {
+    let mut motor = Motor::new();
+    motor.e0_0 = true;
+    motor.scan();
+    assert!(motor.a0_0);
+    motor.e0_0 = false;
+    motor.scan();
+    assert!(motor.a0_0);
+    motor.e0_1 = true;
+    motor.scan();
+    assert!(!motor.a0_0);
+    motor.e0_1 = false;
+    motor.scan();
+    assert!(!motor.a0_0);
+}
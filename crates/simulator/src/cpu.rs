@@ -0,0 +1,515 @@
+use fpp_compiler::lir::s7::{S7Address, S7AddressIndex, S7AddressType, S7Constant, S7Instruction};
+
+use crate::{
+    error::{Error, Result},
+    memory::{area, Area},
+    Simulator,
+};
+
+/// Depth of the nesting stack of an S7-300 CPU
+const STACK_DEPTH: usize = 7;
+
+/// Status word and registers of the CPU
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Status {
+    /// `/ER`, cleared at the first check of a logic string
+    pub er: bool,
+    pub rlo: bool,
+    /// Value of the last bit operand
+    pub sta: bool,
+    pub or: bool,
+    pub br: bool,
+    pub accu1: u32,
+    pub accu2: u32,
+    pub ar1: u32,
+    /// Number of the opened data block, 0 if none is open
+    pub data_block: u16,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Logic {
+    And,
+    Or,
+    Xor,
+}
+
+/// Entry of the nesting stack pushed by `U(`, `O(` and `X(`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Nesting {
+    rlo: bool,
+    or: bool,
+    er: bool,
+    logic: Logic,
+    negated: bool,
+}
+
+/// Operand resolved at runtime
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Location {
+    Area {
+        area: Area,
+        byte: u32,
+        bit: u8,
+        bits: u32,
+    },
+    Counter(u16),
+    Timer(u16),
+}
+
+impl Simulator<'_> {
+    pub(crate) fn execute(&mut self, instruction: &S7Instruction) -> Result<()> {
+        match *instruction {
+            S7Instruction::And { addr } => self.check(addr, Logic::And, false)?,
+            S7Instruction::AndNot { addr } => self.check(addr, Logic::And, true)?,
+            S7Instruction::Or { addr } => self.check(addr, Logic::Or, false)?,
+            S7Instruction::OrNot { addr } => self.check(addr, Logic::Or, true)?,
+            S7Instruction::Xor { addr } => self.check(addr, Logic::Xor, false)?,
+            S7Instruction::XorNot { addr } => self.check(addr, Logic::Xor, true)?,
+            S7Instruction::AndStart => self.start(Logic::And, false)?,
+            S7Instruction::AndNotStart => self.start(Logic::And, true)?,
+            S7Instruction::OrStart => self.start(Logic::Or, false)?,
+            S7Instruction::OrNotStart => self.start(Logic::Or, true)?,
+            S7Instruction::XorStart => self.start(Logic::Xor, false)?,
+            S7Instruction::XorNotStart => self.start(Logic::Xor, true)?,
+            S7Instruction::End => self.end()?,
+            S7Instruction::AssignBit { addr } => {
+                self.write_bit(addr, self.status.rlo)?;
+                self.status.sta = self.status.rlo;
+                self.terminate();
+            }
+            S7Instruction::SetBit { addr } | S7Instruction::ResetBit { addr } => {
+                let value = matches!(instruction, S7Instruction::SetBit { .. });
+                if self.status.rlo {
+                    self.write_bit(addr, value)?;
+                }
+                self.status.sta = self.read_bit(addr)?;
+                self.terminate();
+            }
+            // As on the CPU, `NOT` keeps the OR bit and sets STA, so `U a; O b; NOT; U c`
+            // computes `a or (not (a or b) and c)`
+            S7Instruction::Not => {
+                self.status.rlo = !self.status.rlo;
+                self.status.sta = true;
+            }
+            S7Instruction::Set | S7Instruction::Clear => {
+                let value = matches!(instruction, S7Instruction::Set);
+                self.status.rlo = value;
+                self.status.sta = value;
+                self.terminate();
+            }
+            S7Instruction::Save => self.status.br = self.status.rlo,
+            S7Instruction::CounterLoadInt { addr } => {
+                let counter = self.counter(addr)?;
+                self.load(self.memory.counters.get(&counter).map_or(0, |it| it.value) as u32);
+            }
+            S7Instruction::CounterLoadBcd { addr } => {
+                let counter = self.counter(addr)?;
+                let value = self.memory.counters.get(&counter).map_or(0, |it| it.value);
+                self.load(int_to_bcd(value as u32));
+            }
+            S7Instruction::CounterForward { addr } => {
+                let rlo = self.status.rlo;
+                let counter = self.counter(addr)?;
+                let counter = self.memory.counters.entry(counter).or_default();
+                if rlo && !counter.up && counter.value < 999 {
+                    counter.value += 1;
+                }
+                counter.up = rlo;
+                self.terminate();
+            }
+            S7Instruction::CounterBackward { addr } => {
+                let rlo = self.status.rlo;
+                let counter = self.counter(addr)?;
+                let counter = self.memory.counters.entry(counter).or_default();
+                if rlo && !counter.down && counter.value > 0 {
+                    counter.value -= 1;
+                }
+                counter.down = rlo;
+                self.terminate();
+            }
+            S7Instruction::CounterSet { addr } => {
+                let (rlo, accu) = (self.status.rlo, self.status.accu1);
+                let counter = self.counter(addr)?;
+                let counter = self.memory.counters.entry(counter).or_default();
+                if rlo && !counter.set {
+                    counter.value = bcd_to_int(accu & 0xFFF)? as u16;
+                }
+                counter.set = rlo;
+                self.terminate();
+            }
+            S7Instruction::CounterReset { addr } => {
+                let rlo = self.status.rlo;
+                let counter = self.counter(addr)?;
+                if rlo {
+                    self.memory.counters.entry(counter).or_default().value = 0;
+                }
+                self.terminate();
+            }
+            S7Instruction::Load { addr } => {
+                let location = self.locate(addr)?;
+                self.open(addr);
+                let value = match location {
+                    Location::Area { bits: 1, .. } => return Err(Error::NonByteAddress),
+                    Location::Area {
+                        area, byte, bits, ..
+                    } => self.memory.read(area, byte, bits / 8)?,
+                    Location::Counter(counter) => {
+                        self.memory.counters.get(&counter).map_or(0, |it| it.value) as u32
+                    }
+                    Location::Timer(timer) => {
+                        self.memory.timers.get(&timer).map_or(0, |it| it.value) as u32
+                    }
+                };
+                self.load(value);
+            }
+            S7Instruction::LoadConst { value } => self.load(constant(value)),
+            S7Instruction::Transfer { addr } => self.write_address(addr, self.status.accu1)?,
+            S7Instruction::AndWord => self.status.accu1 &= self.status.accu2 | 0xFFFF0000,
+            S7Instruction::OrWord => self.status.accu1 |= self.status.accu2 & 0xFFFF,
+            S7Instruction::XorWord => self.status.accu1 ^= self.status.accu2 & 0xFFFF,
+            S7Instruction::AndDWord => self.status.accu1 &= self.status.accu2,
+            S7Instruction::OrDWord => self.status.accu1 |= self.status.accu2,
+            S7Instruction::XorDWord => self.status.accu1 ^= self.status.accu2,
+            S7Instruction::InvertInt => self.status.accu1 ^= 0xFFFF,
+            S7Instruction::InvertDInt => self.status.accu1 = !self.status.accu1,
+            S7Instruction::ShiftLeftWord { amount } => {
+                let (accu, amount) = (self.status.accu1, self.amount(amount));
+                let value = match amount {
+                    0..=15 => (accu & 0xFFFF) << amount,
+                    _ => 0,
+                };
+                self.status.accu1 = low_word(accu, value);
+            }
+            S7Instruction::ShiftRightWord { amount } => {
+                let (accu, amount) = (self.status.accu1, self.amount(amount));
+                let value = match amount {
+                    0..=15 => (accu & 0xFFFF) >> amount,
+                    _ => 0,
+                };
+                self.status.accu1 = low_word(accu, value);
+            }
+            S7Instruction::ShiftLeftDWord { amount } => {
+                let amount = self.amount(amount);
+                self.status.accu1 = self.status.accu1.checked_shl(amount).unwrap_or(0);
+            }
+            S7Instruction::ShiftRightDWord { amount } => {
+                let amount = self.amount(amount);
+                self.status.accu1 = self.status.accu1.checked_shr(amount).unwrap_or(0);
+            }
+            S7Instruction::RotateLeftDWord { amount } => {
+                let amount = self.amount(amount);
+                self.status.accu1 = self.status.accu1.rotate_left(amount);
+            }
+            S7Instruction::RotateRightDWord { amount } => {
+                let amount = self.amount(amount);
+                self.status.accu1 = self.status.accu1.rotate_right(amount);
+            }
+            S7Instruction::BcdToInt => {
+                let accu = self.status.accu1;
+                let value = bcd_to_int(accu & 0xFFF)?;
+                let value = match accu & 0x8000 {
+                    0 => value,
+                    _ => value.wrapping_neg(),
+                };
+                self.status.accu1 = low_word(accu, value);
+            }
+            S7Instruction::IntToBcd => {
+                let accu = self.status.accu1;
+                let value = accu as u16 as i16;
+                // Integers outside of -999 to 999 overflow and leave the accumulator unchanged
+                if (-999..=999).contains(&value) {
+                    let sign = if value < 0 { 0xF000 } else { 0 };
+                    let bcd = int_to_bcd(value.unsigned_abs() as u32) | sign;
+                    self.status.accu1 = low_word(accu, bcd);
+                }
+            }
+            S7Instruction::IntToDInt => {
+                self.status.accu1 = self.status.accu1 as u16 as i16 as i32 as u32;
+            }
+            S7Instruction::DIntToReal => {
+                self.status.accu1 = (self.status.accu1 as i32 as f32).to_bits();
+            }
+            S7Instruction::Round => {
+                let real = f32::from_bits(self.status.accu1).round_ties_even();
+                if let Some(value) = real_to_dint(real) {
+                    self.status.accu1 = value;
+                }
+            }
+            S7Instruction::Trunc => {
+                let real = f32::from_bits(self.status.accu1).trunc();
+                if let Some(value) = real_to_dint(real) {
+                    self.status.accu1 = value;
+                }
+            }
+            S7Instruction::LoadAddressRegister => self.status.ar1 = self.status.accu1,
+            S7Instruction::TransferAddressRegister => self.load(self.status.ar1),
+            S7Instruction::AddAddressRegister { offset } => {
+                let offset = offset.unwrap_or(self.status.accu1 as u16 as i16 as i32 as u32);
+                self.status.ar1 = add_pointer(self.status.ar1, offset);
+            }
+            S7Instruction::OpenDataBlock { db } => self.status.data_block = db,
+            S7Instruction::Call(ref call) => {
+                self.call(call)?;
+                self.terminate();
+            }
+        }
+        Ok(())
+    }
+
+    /// Combine the RLO with a bit operand, the first check of a logic string loads it
+    fn check(&mut self, addr: S7Address, logic: Logic, negated: bool) -> Result<()> {
+        let value = self.read_bit(addr)?;
+        self.status.sta = value;
+        self.logic(logic, value != negated);
+        Ok(())
+    }
+
+    fn logic(&mut self, logic: Logic, value: bool) {
+        let status = &mut self.status;
+        if !status.er {
+            status.er = true;
+            status.rlo = value;
+            return;
+        }
+        match logic {
+            Logic::And => status.rlo = status.or || (status.rlo && value),
+            Logic::Or => {
+                status.or = status.rlo;
+                status.rlo = status.rlo || value;
+            }
+            Logic::Xor => {
+                status.rlo = status.rlo != value;
+                status.or = false;
+            }
+        }
+    }
+
+    fn start(&mut self, logic: Logic, negated: bool) -> Result<()> {
+        if self.stack.len() == STACK_DEPTH {
+            return Err(Error::InvalidStack);
+        }
+        self.stack.push(Nesting {
+            rlo: self.status.rlo,
+            or: self.status.or,
+            er: self.status.er,
+            logic,
+            negated,
+        });
+        self.status.er = false;
+        self.status.or = false;
+        Ok(())
+    }
+
+    fn end(&mut self) -> Result<()> {
+        let nesting = self.stack.pop().ok_or(Error::InvalidStack)?;
+        let nested = self.status.rlo != nesting.negated;
+        self.status.rlo = nesting.rlo;
+        self.status.or = nesting.or;
+        self.status.er = nesting.er;
+        self.logic(nesting.logic, nested);
+        Ok(())
+    }
+
+    /// End the logic string, the next check is a first check
+    fn terminate(&mut self) {
+        self.status.er = false;
+        self.status.or = false;
+    }
+
+    fn load(&mut self, value: u32) {
+        self.status.accu2 = self.status.accu1;
+        self.status.accu1 = value;
+    }
+
+    /// Amount of a shift, taken from `ACCU2-L-L` if it is not given
+    fn amount(&self, amount: Option<u8>) -> u32 {
+        amount.map_or(self.status.accu2 & 0xFF, u32::from)
+    }
+
+    fn counter(&self, addr: S7Address) -> Result<u16> {
+        match self.locate(addr)? {
+            Location::Counter(counter) => Ok(counter),
+            _ => Err(Error::NonCounterAddress),
+        }
+    }
+
+    /// Resolve the operand with the address register, memory pointers and opened data block
+    pub(crate) fn locate(&self, addr: S7Address) -> Result<Location> {
+        let Some((area, bits)) = area(addr.r#type, self.data_block(addr)?) else {
+            let number = match addr.index {
+                S7AddressIndex::Memory { ptr } => {
+                    self.memory.read(Area::Memory, ptr as u32, 2)? as u16
+                }
+                _ => addr.ptr,
+            };
+            return match addr.r#type {
+                S7AddressType::Counter => Ok(Location::Counter(number)),
+                _ => Ok(Location::Timer(number)),
+            };
+        };
+        let offset = (addr.ptr as u32) << 3 | addr.bit as u32;
+        let pointer = match addr.index {
+            S7AddressIndex::Direct => offset,
+            S7AddressIndex::Memory { ptr } => {
+                self.memory.read(Area::Memory, ptr as u32, 4)? & 0x7FFFF
+            }
+            S7AddressIndex::Register => (self.status.ar1 & 0x7FFFF) + offset,
+        };
+        if bits != 1 && pointer & 7 != 0 {
+            return Err(Error::NonByteAddress);
+        }
+        Ok(Location::Area {
+            area,
+            byte: pointer >> 3,
+            bit: (pointer & 7) as u8,
+            bits,
+        })
+    }
+
+    /// Data block of the operand, a qualified operand opens its data block
+    fn data_block(&self, addr: S7Address) -> Result<u16> {
+        let data_block = matches!(
+            addr.r#type,
+            S7AddressType::DataBlock1
+                | S7AddressType::DataBlock8
+                | S7AddressType::DataBlock16
+                | S7AddressType::DataBlock32
+        );
+        match (data_block, addr.db, self.status.data_block) {
+            (false, _, _) => Ok(0),
+            (true, 0, 0) => Err(Error::NoDataBlock),
+            (true, 0, db) | (true, db, _) => Ok(db),
+        }
+    }
+
+    /// Open the data block of a qualified operand
+    fn open(&mut self, addr: S7Address) {
+        if addr.db != 0 {
+            self.status.data_block = addr.db;
+        }
+    }
+
+    /// Bit operand of a logic operation, counters are set while their value is not zero
+    fn read_bit(&mut self, addr: S7Address) -> Result<bool> {
+        let location = self.locate(addr)?;
+        self.open(addr);
+        match location {
+            Location::Area {
+                area,
+                byte,
+                bit,
+                bits: 1,
+            } => self.memory.read_bit(area, byte, bit),
+            Location::Counter(counter) => Ok(self
+                .memory
+                .counters
+                .get(&counter)
+                .is_some_and(|it| it.value != 0)),
+            Location::Timer(timer) => Ok(self.memory.timers.get(&timer).is_some_and(|it| it.bit)),
+            Location::Area { .. } => Err(Error::NonBitAddress),
+        }
+    }
+
+    fn write_bit(&mut self, addr: S7Address, value: bool) -> Result<()> {
+        let location = self.locate(addr)?;
+        self.open(addr);
+        let Location::Area {
+            area,
+            byte,
+            bit,
+            bits: 1,
+        } = location
+        else {
+            return Err(Error::NonBitAddress);
+        };
+        self.memory.write_bit(area, byte, bit, value)?;
+        // Direct output modules also update the process image
+        if area == Area::PeripheralOutputs {
+            self.memory.write_bit(Area::Outputs, byte, bit, value)?;
+        }
+        Ok(())
+    }
+
+    /// Value of a bit, byte, word or double word operand
+    pub(crate) fn read_address(&mut self, addr: S7Address) -> Result<u32> {
+        let location = self.locate(addr)?;
+        self.open(addr);
+        match location {
+            Location::Area {
+                area,
+                byte,
+                bit,
+                bits: 1,
+            } => self.memory.read_bit(area, byte, bit).map(u32::from),
+            Location::Area {
+                area, byte, bits, ..
+            } => self.memory.read(area, byte, bits / 8),
+            _ => Err(Error::NonByteAddress),
+        }
+    }
+
+    pub(crate) fn write_address(&mut self, addr: S7Address, value: u32) -> Result<()> {
+        if addr.is_bit_address() {
+            return self.write_bit(addr, value != 0);
+        }
+        let location = self.locate(addr)?;
+        self.open(addr);
+        let Location::Area {
+            area, byte, bits, ..
+        } = location
+        else {
+            return Err(Error::NonByteAddress);
+        };
+        self.memory.write(area, byte, bits / 8, value)?;
+        if area == Area::PeripheralOutputs {
+            self.memory.write(Area::Outputs, byte, bits / 8, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Bits of a constant as loaded into `ACCU1`
+pub(crate) fn constant(value: S7Constant) -> u32 {
+    match value {
+        S7Constant::Byte(value) => value as u32,
+        S7Constant::Word(value) => value as u32,
+        S7Constant::DWord(value) | S7Constant::Pointer(value) => value,
+        S7Constant::Int(value) => value as u16 as u32,
+        S7Constant::DInt(value) | S7Constant::Time(value) => value as u32,
+        S7Constant::Real(value) => value.to_bits(),
+        S7Constant::Bool(value) => value as u32,
+    }
+}
+
+/// Word operations only change the low word of `ACCU1`
+fn low_word(accu: u32, value: u32) -> u32 {
+    accu & 0xFFFF0000 | value & 0xFFFF
+}
+
+/// Add a bit offset to an area-internal pointer, keeping the area
+fn add_pointer(pointer: u32, offset: u32) -> u32 {
+    pointer & 0xFF000000 | pointer.wrapping_add(offset) & 0x00FFFFFF
+}
+
+/// Three BCD digits, the CPU stops on a digit above 9
+pub(crate) fn bcd_to_int(bcd: u32) -> Result<u32> {
+    (0..3)
+        .rev()
+        .try_fold(0, |value, digit| match bcd >> (digit * 4) & 0xF {
+            digit @ 0..=9 => Ok(value * 10 + digit),
+            _ => Err(Error::InvalidBcd),
+        })
+}
+
+/// Three BCD digits of a value below 1000
+pub(crate) fn int_to_bcd(value: u32) -> u32 {
+    (value / 100) << 8 | (value / 10 % 10) << 4 | (value % 10)
+}
+
+/// Reals outside of the DINT range overflow and leave the accumulator unchanged
+fn real_to_dint(real: f32) -> Option<u32> {
+    match (-2147483648.0..2147483648.0).contains(&real) {
+        true => Some(real as i32 as u32),
+        false => None,
+    }
+}
@@ -0,0 +1,63 @@
+use std::fmt::Display;
+
+use fpp_compiler::lir::s7::{S7Block, S7Mnemonics, WriteAwl};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The text is not a direct operand (`E0.0`, `MW10`)
+    InvalidOperand(String),
+    /// The operand is not in the input image or periphery
+    NotAnInput(String),
+    NotAnOutput(String),
+    /// The width of the value does not match the operand
+    WidthMismatch(String),
+    /// A bracket was opened on a full nesting stack or closed on an empty one
+    InvalidStack,
+    NonBitAddress,
+    NonByteAddress,
+    NonCounterAddress,
+    /// An operand of a data block was accessed before a data block was opened
+    NoDataBlock,
+    /// The operand exceeds its area
+    PointerOutOfRange,
+    /// `BTI` or a counter preset read a digit above 9, the CPU would stop
+    InvalidBcd,
+    /// Only system blocks are simulated, other blocks are not part of the program
+    UnknownBlock(S7Block),
+    /// Line of a stimulus file and the problem with it
+    InvalidStimulus(usize, String),
+    /// Error while executing the network at the index
+    Network(usize, Box<Error>),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidOperand(operand) => write!(f, "`{operand}` is not a valid operand"),
+            Self::NotAnInput(operand) => write!(f, "`{operand}` is not an input"),
+            Self::NotAnOutput(operand) => write!(f, "`{operand}` is not an output"),
+            Self::WidthMismatch(operand) => {
+                write!(f, "the value does not match the width of `{operand}`")
+            }
+            Self::InvalidStack => write!(f, "the nesting of the logic operations is invalid"),
+            Self::NonBitAddress => write!(f, "expected a bit address"),
+            Self::NonByteAddress => write!(f, "expected a byte, word or double word address"),
+            Self::NonCounterAddress => write!(f, "expected a counter address"),
+            Self::NoDataBlock => write!(f, "no data block is open"),
+            Self::PointerOutOfRange => write!(f, "the operand exceeds its area"),
+            Self::InvalidBcd => write!(f, "the accumulator is not a valid BCD number"),
+            Self::UnknownBlock(block) => {
+                let mut name = Vec::new();
+                block
+                    .write_awl(&mut name, S7Mnemonics::German)
+                    .expect("Write to memory");
+                let name = String::from_utf8_lossy(&name);
+                write!(f, "`{name}` is not a system block and can not be simulated")
+            }
+            Self::InvalidStimulus(line, message) => write!(f, "line {line}: {message}"),
+            Self::Network(network, err) => write!(f, "network {}: {err}", network + 1),
+        }
+    }
+}
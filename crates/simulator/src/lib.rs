@@ -0,0 +1,404 @@
+//! Simulator executing the [S7Lir] of a program instruction by instruction
+//!
+//! The status word, accumulators and nesting stack behave as on an S7-300 CPU. Inputs are read
+//! into the process image at the start of a scan and outputs written at its end.
+//!
+//! ```ignore
+//! let mut plc = Simulator::new(&lir);
+//! plc.set_input("E0.0", true)?;
+//! plc.scan()?;
+//! assert!(plc.get_output::<bool>("A0.0")?);
+//! ```
+
+use fpp_compiler::lir::s7::{
+    S7Address, S7AddressType, S7Instruction, S7Lir, S7Mnemonics, S7Operand, WriteAwl,
+};
+
+pub mod error;
+pub mod stimulus;
+pub mod test;
+pub mod vcd;
+pub mod verify;
+
+mod cpu;
+mod memory;
+mod system;
+
+pub use cpu::Status;
+
+use cpu::Nesting;
+use error::{Error, Result};
+use memory::{area, Area, Memory};
+
+/// Value of an operand, the width of the type must match the operand
+pub trait Value: Copy {
+    const BITS: u32;
+
+    fn from_bits(bits: u32) -> Self;
+    fn to_bits(self) -> u32;
+}
+
+impl Value for bool {
+    const BITS: u32 = 1;
+
+    fn from_bits(bits: u32) -> Self {
+        bits != 0
+    }
+
+    fn to_bits(self) -> u32 {
+        self as u32
+    }
+}
+
+macro_rules! impl_value {
+    ($($type:ty: $bits:literal),*) => {
+        $(impl Value for $type {
+            const BITS: u32 = $bits;
+
+            fn from_bits(bits: u32) -> Self {
+                bits as Self
+            }
+
+            fn to_bits(self) -> u32 {
+                self as u32
+            }
+        })*
+    };
+}
+
+impl_value!(u8: 8, u16: 16, i16: 16, u32: 32, i32: 32);
+
+impl Value for f32 {
+    const BITS: u32 = 32;
+
+    fn from_bits(bits: u32) -> Self {
+        f32::from_bits(bits)
+    }
+
+    fn to_bits(self) -> u32 {
+        f32::to_bits(self)
+    }
+}
+
+/// CPU executing the networks of a program once per scan
+#[derive(Debug)]
+pub struct Simulator<'a> {
+    lir: &'a S7Lir,
+    /// Duration of a scan in milliseconds, advances IEC timers and `TIME_TCK`
+    pub cycle_time: u32,
+    /// Milliseconds since the start of the CPU at the beginning of the next scan
+    time: u64,
+    scans: u64,
+    status: Status,
+    stack: Vec<Nesting>,
+    memory: Memory,
+}
+
+impl<'a> Simulator<'a> {
+    pub fn new(lir: &'a S7Lir) -> Self {
+        Self {
+            lir,
+            cycle_time: 10,
+            time: 0,
+            scans: 0,
+            status: Status::default(),
+            stack: Vec::new(),
+            memory: Memory::default(),
+        }
+    }
+
+    /// Set an input module (`E0.0`, `PEW256`), the program reads it in the next scan
+    pub fn set_input(&mut self, operand: &str, value: impl Value) -> Result<()> {
+        match Self::parse(operand, value)? {
+            Operand::Area {
+                area: Area::Inputs | Area::PeripheralInputs,
+                byte,
+                bit,
+                bits,
+            } => self.write_area(Area::PeripheralInputs, byte, bit, bits, value.to_bits()),
+            _ => Err(Error::NotAnInput(operand.to_string())),
+        }
+    }
+
+    /// Read an output module (`A0.0`, `PAW256`) as written at the end of the last scan
+    pub fn get_output<V: Value>(&self, operand: &str) -> Result<V> {
+        match Self::parse(operand, V::from_bits(0))? {
+            Operand::Area {
+                area: Area::Outputs | Area::PeripheralOutputs,
+                byte,
+                bit,
+                bits,
+            } => self
+                .read_area(Area::PeripheralOutputs, byte, bit, bits)
+                .map(V::from_bits),
+            _ => Err(Error::NotAnOutput(operand.to_string())),
+        }
+    }
+
+    /// Read any operand as seen by the program, timers and counters as bit or value
+    pub fn read<V: Value>(&self, operand: &str) -> Result<V> {
+        let value = match Self::parse(operand, V::from_bits(0))? {
+            Operand::Area {
+                area,
+                byte,
+                bit,
+                bits,
+            } => self.read_area(area, byte, bit, bits)?,
+            Operand::Counter(number) => {
+                let value = self.memory.counters.get(&number).map_or(0, |it| it.value);
+                match V::BITS {
+                    1 => (value != 0) as u32,
+                    _ => value as u32,
+                }
+            }
+            Operand::Timer(number) => {
+                let timer = self.memory.timers.get(&number).copied().unwrap_or_default();
+                match V::BITS {
+                    1 => timer.bit as u32,
+                    _ => timer.value as u32,
+                }
+            }
+        };
+        Ok(V::from_bits(value))
+    }
+
+    /// Write any operand as the program would, e.g. to preset memory, timers or counters
+    pub fn write<V: Value>(&mut self, operand: &str, value: V) -> Result<()> {
+        let bits = value.to_bits();
+        match Self::parse(operand, value)? {
+            Operand::Area {
+                area,
+                byte,
+                bit,
+                bits: width,
+            } => self.write_area(area, byte, bit, width, bits)?,
+            Operand::Counter(number) if V::BITS == 16 => {
+                let counter = self.memory.counters.entry(number).or_default();
+                counter.value = (bits as u16).min(999);
+            }
+            Operand::Counter(_) => return Err(Error::WidthMismatch(operand.to_string())),
+            Operand::Timer(number) => {
+                let timer = self.memory.timers.entry(number).or_default();
+                match V::BITS {
+                    1 => timer.bit = bits != 0,
+                    _ => timer.value = bits as u16,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Execute one scan and advance the clock by the cycle time
+    pub fn scan(&mut self) -> Result<()> {
+        self.memory.read_inputs();
+        self.status = Status::default();
+        self.stack.clear();
+        for (i, network) in self.lir.networks.iter().enumerate() {
+            for instruction in &network.instructions {
+                self.execute(instruction)
+                    .map_err(|err| Error::Network(i, Box::new(err)))?;
+            }
+        }
+        self.memory.write_outputs();
+        self.time += self.cycle_time as u64;
+        self.scans += 1;
+        Ok(())
+    }
+
+    /// Execute a single network on the process images, the clock is not advanced
+    pub fn execute_network(&mut self, index: usize) -> Result<()> {
+        self.status = Status::default();
+        self.stack.clear();
+        for instruction in &self.lir.networks[index].instructions {
+            self.execute(instruction)?;
+        }
+        Ok(())
+    }
+
+    /// Status word and registers after the last instruction
+    pub fn status(&self) -> &Status {
+        &self.status
+    }
+
+    /// Milliseconds since the start of the CPU
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// Number of completed scans
+    pub fn scans(&self) -> u64 {
+        self.scans
+    }
+
+    /// Width of a direct operand in bits, 16 for the value of a counter and 1 for a timer
+    pub fn bits(operand: &str) -> Result<u32> {
+        Ok(match Self::operand(operand)? {
+            Operand::Area { bits, .. } => bits,
+            Operand::Counter(_) => 16,
+            Operand::Timer(_) => 1,
+        })
+    }
+
+    /// Set an input module to the value truncated to the [bits](Self::bits) of the operand
+    pub fn set_input_bits(&mut self, operand: &str, value: u32) -> Result<()> {
+        match Self::operand(operand)? {
+            Operand::Area {
+                area: Area::Inputs | Area::PeripheralInputs,
+                byte,
+                bit,
+                bits,
+            } => self.write_area(Area::PeripheralInputs, byte, bit, bits, value),
+            _ => Err(Error::NotAnInput(operand.to_string())),
+        }
+    }
+
+    /// Value of an input module as set by the simulation, read by the program in the next scan
+    pub fn input_bits(&self, operand: &str) -> Result<u32> {
+        match Self::operand(operand)? {
+            Operand::Area {
+                area: Area::Inputs | Area::PeripheralInputs,
+                byte,
+                bit,
+                bits,
+            } => self.read_area(Area::PeripheralInputs, byte, bit, bits),
+            _ => Err(Error::NotAnInput(operand.to_string())),
+        }
+    }
+
+    /// Checks if the operand is an input module or in the process image of the inputs
+    pub fn is_input(operand: &str) -> bool {
+        matches!(
+            Self::operand(operand),
+            Ok(Operand::Area {
+                area: Area::Inputs | Area::PeripheralInputs,
+                ..
+            })
+        )
+    }
+
+    /// Read any operand as seen by the program with its natural [bits](Self::bits)
+    pub fn read_bits(&self, operand: &str) -> Result<u32> {
+        match Self::operand(operand)? {
+            Operand::Counter(_) => self.read::<u16>(operand).map(u32::from),
+            Operand::Timer(_) => self.read::<bool>(operand).map(u32::from),
+            Operand::Area {
+                area,
+                byte,
+                bit,
+                bits,
+            } => self.read_area(area, byte, bit, bits),
+        }
+    }
+
+    /// Write any operand as the program would with its natural [bits](Self::bits)
+    pub fn write_bits(&mut self, operand: &str, value: u32) -> Result<()> {
+        match Self::operand(operand)? {
+            Operand::Counter(_) => self.write(operand, value as u16),
+            Operand::Timer(_) => self.write(operand, value != 0),
+            Operand::Area {
+                area,
+                byte,
+                bit,
+                bits,
+            } => self.write_area(area, byte, bit, bits, value),
+        }
+    }
+
+    /// Direct inputs and outputs accessed by the program, in the order of their first access
+    pub fn io(&self) -> Vec<String> {
+        let mut io = Vec::new();
+        for network in &self.lir.networks {
+            for instruction in &network.instructions {
+                let addrs: Vec<_> = match instruction {
+                    S7Instruction::Call(call) => call
+                        .params
+                        .iter()
+                        .filter_map(|param| match param.value {
+                            S7Operand::Address(addr) => Some(addr),
+                            _ => None,
+                        })
+                        .collect(),
+                    instruction => instruction.addr().into_iter().collect(),
+                };
+                for addr in addrs {
+                    let is_io = matches!(
+                        area(addr.r#type, addr.db),
+                        Some((
+                            Area::Inputs
+                                | Area::Outputs
+                                | Area::PeripheralInputs
+                                | Area::PeripheralOutputs,
+                            _
+                        ))
+                    );
+                    if !is_io || addr.is_indirect() {
+                        continue;
+                    }
+                    let mut name = Vec::new();
+                    addr.write_awl(&mut name, S7Mnemonics::German)
+                        .expect("Write to memory");
+                    let name = String::from_utf8(name).expect("Operands are ASCII");
+                    if !io.contains(&name) {
+                        io.push(name);
+                    }
+                }
+            }
+        }
+        io
+    }
+
+    /// Parse a direct operand and check it against the width of the value
+    fn parse<V: Value>(operand: &str, _: V) -> Result<Operand> {
+        let parsed = Self::operand(operand)?;
+        let bits = match parsed {
+            Operand::Area { bits, .. } => bits == V::BITS,
+            Operand::Counter(_) | Operand::Timer(_) => V::BITS == 1 || V::BITS == 16,
+        };
+        match bits {
+            true => Ok(parsed),
+            false => Err(Error::WidthMismatch(operand.to_string())),
+        }
+    }
+
+    fn operand(operand: &str) -> Result<Operand> {
+        let Some(addr) = S7Address::parse(operand) else {
+            return Err(Error::InvalidOperand(operand.to_string()));
+        };
+        match area(addr.r#type, addr.db) {
+            Some((area, bits)) => Ok(Operand::Area {
+                area,
+                byte: addr.ptr as u32,
+                bit: addr.bit,
+                bits,
+            }),
+            None if addr.r#type == S7AddressType::Counter => Ok(Operand::Counter(addr.ptr)),
+            None => Ok(Operand::Timer(addr.ptr)),
+        }
+    }
+
+    fn read_area(&self, area: Area, byte: u32, bit: u8, bits: u32) -> Result<u32> {
+        match bits {
+            1 => self.memory.read_bit(area, byte, bit).map(u32::from),
+            bits => self.memory.read(area, byte, bits / 8),
+        }
+    }
+
+    fn write_area(&mut self, area: Area, byte: u32, bit: u8, bits: u32, value: u32) -> Result<()> {
+        match bits {
+            1 => self.memory.write_bit(area, byte, bit, value != 0),
+            bits => self.memory.write(area, byte, bits / 8, value),
+        }
+    }
+}
+
+/// Operand of the simulation API
+enum Operand {
+    Area {
+        area: Area,
+        byte: u32,
+        bit: u8,
+        bits: u32,
+    },
+    Counter(u16),
+    Timer(u16),
+}
@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+
+use fpp_compiler::lir::s7::S7AddressType;
+
+use crate::error::{Error, Result};
+
+/// Size of every byte area, operands address up to 65535 bytes
+const AREA_BYTES: u32 = 0x10000;
+
+/// Byte area of the CPU
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Area {
+    /// Process image of the inputs, copied from the periphery at the start of a scan
+    Inputs,
+    /// Process image of the outputs, copied to the periphery at the end of a scan
+    Outputs,
+    /// Input modules, set by the simulation
+    PeripheralInputs,
+    /// Output modules, read by the simulation
+    PeripheralOutputs,
+    Memory,
+    DataBlock(u16),
+    /// Temporaries of the program block, kept between scans
+    Temp,
+}
+
+/// Area and width in bits of a byte or bit operand, `None` for timers and counters
+pub(crate) fn area(r#type: S7AddressType, db: u16) -> Option<(Area, u32)> {
+    let area = match r#type {
+        S7AddressType::Input1 => (Area::Inputs, 1),
+        S7AddressType::Input8 => (Area::Inputs, 8),
+        S7AddressType::Input16 => (Area::Inputs, 16),
+        S7AddressType::Input32 => (Area::Inputs, 32),
+        S7AddressType::Output1 => (Area::Outputs, 1),
+        S7AddressType::Output8 => (Area::Outputs, 8),
+        S7AddressType::Output16 => (Area::Outputs, 16),
+        S7AddressType::Output32 => (Area::Outputs, 32),
+        S7AddressType::Memory1 => (Area::Memory, 1),
+        S7AddressType::Memory8 => (Area::Memory, 8),
+        S7AddressType::Memory16 => (Area::Memory, 16),
+        S7AddressType::Memory32 => (Area::Memory, 32),
+        S7AddressType::PeripheralInput8 => (Area::PeripheralInputs, 8),
+        S7AddressType::PeripheralInput16 => (Area::PeripheralInputs, 16),
+        S7AddressType::PeripheralInput32 => (Area::PeripheralInputs, 32),
+        S7AddressType::PeripheralOutput8 => (Area::PeripheralOutputs, 8),
+        S7AddressType::PeripheralOutput16 => (Area::PeripheralOutputs, 16),
+        S7AddressType::PeripheralOutput32 => (Area::PeripheralOutputs, 32),
+        S7AddressType::DataBlock1 => (Area::DataBlock(db), 1),
+        S7AddressType::DataBlock8 => (Area::DataBlock(db), 8),
+        S7AddressType::DataBlock16 => (Area::DataBlock(db), 16),
+        S7AddressType::DataBlock32 => (Area::DataBlock(db), 32),
+        S7AddressType::Temp1 => (Area::Temp, 1),
+        S7AddressType::Temp8 => (Area::Temp, 8),
+        S7AddressType::Temp16 => (Area::Temp, 16),
+        S7AddressType::Temp32 => (Area::Temp, 32),
+        S7AddressType::Timer | S7AddressType::Counter => return None,
+    };
+    Some(area)
+}
+
+/// S5 counter, counts on rising edges from 0 to 999
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Counter {
+    pub value: u16,
+    /// Edge memories of the count and set inputs
+    pub up: bool,
+    pub down: bool,
+    pub set: bool,
+}
+
+/// S5 timer, the program can read but not start it
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Timer {
+    pub value: u16,
+    pub bit: bool,
+}
+
+/// Memory of the CPU, bytes that were never written read zero
+#[derive(Debug, Default)]
+pub(crate) struct Memory {
+    inputs: Vec<u8>,
+    outputs: Vec<u8>,
+    peripheral_inputs: Vec<u8>,
+    peripheral_outputs: Vec<u8>,
+    memory: Vec<u8>,
+    data_blocks: BTreeMap<u16, Vec<u8>>,
+    temps: Vec<u8>,
+    pub counters: BTreeMap<u16, Counter>,
+    pub timers: BTreeMap<u16, Timer>,
+}
+
+impl Memory {
+    fn bytes(&self, area: Area) -> &[u8] {
+        match area {
+            Area::Inputs => &self.inputs,
+            Area::Outputs => &self.outputs,
+            Area::PeripheralInputs => &self.peripheral_inputs,
+            Area::PeripheralOutputs => &self.peripheral_outputs,
+            Area::Memory => &self.memory,
+            Area::DataBlock(db) => self.data_blocks.get(&db).map_or(&[], |it| it),
+            Area::Temp => &self.temps,
+        }
+    }
+
+    /// Bytes of the area, grown to at least `len` bytes
+    fn bytes_mut(&mut self, area: Area, len: usize) -> &mut [u8] {
+        let bytes = match area {
+            Area::Inputs => &mut self.inputs,
+            Area::Outputs => &mut self.outputs,
+            Area::PeripheralInputs => &mut self.peripheral_inputs,
+            Area::PeripheralOutputs => &mut self.peripheral_outputs,
+            Area::Memory => &mut self.memory,
+            Area::DataBlock(db) => self.data_blocks.entry(db).or_default(),
+            Area::Temp => &mut self.temps,
+        };
+        if bytes.len() < len {
+            bytes.resize(len, 0);
+        }
+        bytes
+    }
+
+    fn check(byte: u32, bytes: u32) -> Result<()> {
+        if byte + bytes > AREA_BYTES {
+            return Err(Error::PointerOutOfRange);
+        }
+        Ok(())
+    }
+
+    pub fn read_bit(&self, area: Area, byte: u32, bit: u8) -> Result<bool> {
+        Self::check(byte, 1)?;
+        let value = self.bytes(area).get(byte as usize).copied().unwrap_or(0);
+        Ok(value >> bit & 1 != 0)
+    }
+
+    pub fn write_bit(&mut self, area: Area, byte: u32, bit: u8, value: bool) -> Result<()> {
+        Self::check(byte, 1)?;
+        let it = &mut self.bytes_mut(area, byte as usize + 1)[byte as usize];
+        match value {
+            true => *it |= 1 << bit,
+            false => *it &= !(1 << bit),
+        }
+        Ok(())
+    }
+
+    /// Big-endian value of 1, 2 or 4 bytes
+    pub fn read(&self, area: Area, byte: u32, bytes: u32) -> Result<u32> {
+        Self::check(byte, bytes)?;
+        let memory = self.bytes(area);
+        let value = (byte..byte + bytes).fold(0, |value, byte| {
+            value << 8 | memory.get(byte as usize).copied().unwrap_or(0) as u32
+        });
+        Ok(value)
+    }
+
+    pub fn write(&mut self, area: Area, byte: u32, bytes: u32, value: u32) -> Result<()> {
+        Self::check(byte, bytes)?;
+        let end = (byte + bytes) as usize;
+        let memory = &mut self.bytes_mut(area, end)[byte as usize..end];
+        memory.copy_from_slice(&value.to_be_bytes()[4 - bytes as usize..]);
+        Ok(())
+    }
+
+    pub fn read_bytes(&self, area: Area, byte: u32, bytes: u32) -> Result<Vec<u8>> {
+        Self::check(byte, bytes)?;
+        let memory = self.bytes(area);
+        let bytes = (byte..byte + bytes)
+            .map(|byte| memory.get(byte as usize).copied().unwrap_or(0))
+            .collect();
+        Ok(bytes)
+    }
+
+    pub fn write_bytes(&mut self, area: Area, byte: u32, value: &[u8]) -> Result<()> {
+        Self::check(byte, value.len() as u32)?;
+        let end = byte as usize + value.len();
+        self.bytes_mut(area, end)[byte as usize..end].copy_from_slice(value);
+        Ok(())
+    }
+
+    /// Copy the input modules into the process image
+    pub fn read_inputs(&mut self) {
+        self.inputs.clone_from(&self.peripheral_inputs);
+    }
+
+    /// Copy the process image into the output modules
+    pub fn write_outputs(&mut self) {
+        let len = self.outputs.len().max(self.peripheral_outputs.len());
+        self.peripheral_outputs.resize(len, 0);
+        self.peripheral_outputs[..self.outputs.len()].copy_from_slice(&self.outputs);
+    }
+}
@@ -0,0 +1,140 @@
+//! Input changes read from a CSV file
+//!
+//! The first column is the scan (`cycle`) or the millisecond (`ms`) of the changes, the other
+//! columns are inputs. Empty cells keep the input unchanged, `#` starts a comment line.
+//!
+//! ```text
+//! ms,E0.0,start,EW2
+//! 0,1,0,0
+//! 500,,1,16#FF
+//! 6000,0,,-12
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    Simulator,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeUnit {
+    /// Number of completed scans
+    Cycle,
+    Millisecond,
+}
+
+/// Input set to a value once the scan or time is reached
+#[derive(Clone, Copy, Debug)]
+pub struct Change {
+    pub time: u64,
+    /// Index of the input in [Stimulus::inputs]
+    pub input: usize,
+    /// Bits of the value, truncated to the width of the input
+    pub value: u32,
+}
+
+#[derive(Debug)]
+pub struct Stimulus {
+    pub unit: TimeUnit,
+    /// Operands or names of the inputs as written in the header
+    pub inputs: Vec<String>,
+    /// Changes ordered by time
+    pub changes: Vec<Change>,
+}
+
+impl Stimulus {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+        let Some((line, header)) = lines.next() else {
+            return Err(Error::InvalidStimulus(1, "expected a header".to_string()));
+        };
+        let mut columns = header.split(',').map(str::trim);
+        let unit = match columns.next() {
+            Some("cycle") => TimeUnit::Cycle,
+            Some("ms") => TimeUnit::Millisecond,
+            _ => {
+                let message = "expected `cycle` or `ms` as first column".to_string();
+                return Err(Error::InvalidStimulus(line, message));
+            }
+        };
+        let inputs: Vec<_> = columns.map(str::to_string).collect();
+        if let Some(input) = inputs.iter().find(|it| it.is_empty()) {
+            let message = format!("expected the name of an input, found `{input}`");
+            return Err(Error::InvalidStimulus(line, message));
+        }
+        let mut changes = Vec::new();
+        for (line, row) in lines {
+            let invalid = |message: String| Error::InvalidStimulus(line, message);
+            let mut cells = row.split(',').map(str::trim);
+            let time = cells.next().unwrap_or_default();
+            let time = time
+                .parse::<u64>()
+                .map_err(|_| invalid(format!("`{time}` is not a time")))?;
+            if changes.last().is_some_and(|it: &Change| it.time > time) {
+                return Err(invalid("the times must not decrease".to_string()));
+            }
+            let cells: Vec<_> = cells.collect();
+            if cells.len() != inputs.len() {
+                let message = format!("expected {} values, found {}", inputs.len(), cells.len());
+                return Err(invalid(message));
+            }
+            for (input, cell) in cells.into_iter().enumerate() {
+                if cell.is_empty() {
+                    continue;
+                }
+                let value =
+                    parse_value(cell).ok_or_else(|| invalid(format!("`{cell}` is not a value")))?;
+                changes.push(Change { time, input, value });
+            }
+        }
+        Ok(Self {
+            unit,
+            inputs,
+            changes,
+        })
+    }
+
+    /// Scan or millisecond of the last change
+    pub fn end(&self) -> u64 {
+        self.changes.last().map_or(0, |it| it.time)
+    }
+
+    /// Set the inputs changed up to the next scan, starting at the change `next`
+    ///
+    /// Returns the index of the first change that is not due yet. The inputs must be operands.
+    pub fn apply(&self, simulator: &mut Simulator, next: usize) -> Result<usize> {
+        let now = match self.unit {
+            TimeUnit::Cycle => simulator.scans(),
+            TimeUnit::Millisecond => simulator.time(),
+        };
+        let mut next = next;
+        while let Some(change) = self.changes.get(next).filter(|it| it.time <= now) {
+            simulator.set_input_bits(&self.inputs[change.input], change.value)?;
+            next += 1;
+        }
+        Ok(next)
+    }
+}
+
+/// `1`, `true`, `-12`, `16#FF`, `2#1010` or `1.5`
+fn parse_value(text: &str) -> Option<u32> {
+    match text {
+        "true" | "TRUE" => return Some(1),
+        "false" | "FALSE" => return Some(0),
+        _ => {}
+    }
+    if let Some((radix, digits)) = text.split_once('#') {
+        let radix = radix.parse().ok().filter(|it| matches!(it, 2 | 8 | 16))?;
+        return u32::from_str_radix(&digits.replace('_', ""), radix).ok();
+    }
+    if text.contains(['.', 'e', 'E']) {
+        return text.parse::<f32>().ok().map(f32::to_bits);
+    }
+    match text.strip_prefix('-') {
+        Some(_) => text.parse::<i32>().ok().map(|it| it as u32),
+        None => text.parse::<u32>().ok(),
+    }
+}
@@ -0,0 +1,194 @@
+use fpp_compiler::lir::s7::{S7BlockKind, S7Call, S7DataType, S7Operand};
+
+use crate::{
+    cpu::{constant, Location},
+    error::{Error, Result},
+    memory::Area,
+    Simulator,
+};
+
+/// Layout of the instance data block of `SFB 4`
+const TON_IN: u32 = 0;
+const TON_PT: u32 = 2;
+const TON_Q: u32 = 6;
+const TON_ET: u32 = 8;
+const TON_STIME: u32 = 14;
+
+impl Simulator<'_> {
+    /// Execute a system block, other blocks are not part of the program
+    pub(crate) fn call(&mut self, call: &S7Call) -> Result<()> {
+        match (call.block.kind, call.block.number) {
+            (S7BlockKind::SystemFunction, 1) => self.read_clk(call),
+            (S7BlockKind::SystemFunction, 20) => self.blkmov(call),
+            (S7BlockKind::SystemFunction, 21) => self.fill(call),
+            (S7BlockKind::SystemFunction, 64) => self.time_tck(call),
+            (S7BlockKind::SystemFunctionBlock, 4) => self.ton(call),
+            _ => Err(Error::UnknownBlock(call.block)),
+        }
+    }
+
+    /// `SFC 1`: the clock starts at 1990-01-01 00:00 with the CPU
+    fn read_clk(&mut self, call: &S7Call) -> Result<()> {
+        let (area, byte, _) = self.block(param(call, "CDT"))?;
+        self.memory
+            .write_bytes(area, byte, &date_and_time(self.time))?;
+        self.ret_val(call, 0)
+    }
+
+    /// `SFC 20`: copy the source area, at most as many bytes as fit into the destination
+    fn blkmov(&mut self, call: &S7Call) -> Result<()> {
+        let (src_area, src, src_bytes) = self.block(param(call, "SRCBLK"))?;
+        let (dst_area, dst, dst_bytes) = self.block(param(call, "DSTBLK"))?;
+        let bytes = self
+            .memory
+            .read_bytes(src_area, src, src_bytes.min(dst_bytes))?;
+        self.memory.write_bytes(dst_area, dst, &bytes)?;
+        self.ret_val(call, 0)
+    }
+
+    /// `SFC 21`: repeat the value area over the destination
+    fn fill(&mut self, call: &S7Call) -> Result<()> {
+        let (src_area, src, src_bytes) = self.block(param(call, "BVAL"))?;
+        let (dst_area, dst, dst_bytes) = self.block(param(call, "BLK"))?;
+        let value = self.memory.read_bytes(src_area, src, src_bytes)?;
+        let bytes: Vec<_> = value
+            .iter()
+            .copied()
+            .cycle()
+            .take(dst_bytes as usize)
+            .collect();
+        self.memory.write_bytes(dst_area, dst, &bytes)?;
+        self.ret_val(call, 0)
+    }
+
+    /// `SFC 64`: milliseconds since the start of the CPU, wrapping after `T#24D20H31M23S647MS`
+    fn time_tck(&mut self, call: &S7Call) -> Result<()> {
+        self.ret_val(call, (self.time & 0x7FFFFFFF) as u32)
+    }
+
+    /// `SFB 4`: `Q` is set once `IN` was set for `PT`, its state is kept in the instance
+    fn ton(&mut self, call: &S7Call) -> Result<()> {
+        let area = Area::DataBlock(call.instance.expect("Instance of a function block"));
+        let now = self.time as u32;
+        let previous = self.memory.read_bit(area, TON_IN, 0)?;
+        // Parameters that are not given keep the value of the instance
+        if let Some(operand) = find(call, "IN") {
+            let value = self.input(operand)?;
+            self.memory.write_bit(area, TON_IN, 0, value != 0)?;
+        }
+        if let Some(operand) = find(call, "PT") {
+            let value = self.input(operand)?;
+            self.memory.write(area, TON_PT, 4, value)?;
+        }
+        let input = self.memory.read_bit(area, TON_IN, 0)?;
+        let preset = (self.memory.read(area, TON_PT, 4)? as i32).max(0) as u32;
+        if input && !previous {
+            self.memory.write(area, TON_STIME, 4, now)?;
+        }
+        let elapsed = match input {
+            true => now
+                .wrapping_sub(self.memory.read(area, TON_STIME, 4)?)
+                .min(preset),
+            false => 0,
+        };
+        let output = input && elapsed >= preset;
+        self.memory.write_bit(area, TON_Q, 0, output)?;
+        self.memory.write(area, TON_ET, 4, elapsed)?;
+        if let Some(operand) = find(call, "Q") {
+            self.output(operand, output as u32)?;
+        }
+        if let Some(operand) = find(call, "ET") {
+            self.output(operand, elapsed)?;
+        }
+        Ok(())
+    }
+
+    fn ret_val(&mut self, call: &S7Call, value: u32) -> Result<()> {
+        match find(call, "RET_VAL") {
+            Some(operand) => self.output(operand, value),
+            None => Ok(()),
+        }
+    }
+
+    fn input(&mut self, operand: S7Operand) -> Result<u32> {
+        match operand {
+            S7Operand::Address(addr) => self.read_address(addr),
+            S7Operand::Constant(value) => Ok(constant(value)),
+            S7Operand::Any(_) => Err(Error::NonByteAddress),
+        }
+    }
+
+    fn output(&mut self, operand: S7Operand, value: u32) -> Result<()> {
+        match operand {
+            S7Operand::Address(addr) => self.write_address(addr, value),
+            _ => Err(Error::NonByteAddress),
+        }
+    }
+
+    /// Area, first byte and size in bytes of an `ANY` pointer
+    fn block(&self, operand: S7Operand) -> Result<(Area, u32, u32)> {
+        let S7Operand::Any(any) = operand else {
+            return Err(Error::NonByteAddress);
+        };
+        let bits = match any.r#type {
+            S7DataType::Bool => 1,
+            S7DataType::Byte => 8,
+            S7DataType::Word | S7DataType::Int => 16,
+            S7DataType::DWord | S7DataType::DInt | S7DataType::Real | S7DataType::Time => 32,
+        };
+        match self.locate(any.addr)? {
+            Location::Area {
+                area, byte, bit: 0, ..
+            } => Ok((area, byte, (bits * any.count as u32).div_ceil(8))),
+            _ => Err(Error::NonByteAddress),
+        }
+    }
+}
+
+fn find(call: &S7Call, name: &str) -> Option<S7Operand> {
+    call.params
+        .iter()
+        .find(|it| &*it.name == name)
+        .map(|it| it.value)
+}
+
+fn param(call: &S7Call, name: &str) -> S7Operand {
+    find(call, name).expect("System block parameter")
+}
+
+/// BCD `DATE_AND_TIME` of the milliseconds since 1990-01-01, a Monday
+fn date_and_time(time: u64) -> [u8; 8] {
+    let bcd = |value: u64| ((value / 10) << 4 | (value % 10)) as u8;
+    let leap = |year: u64| {
+        year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+    };
+    let seconds = time / 1000;
+    let mut days = seconds / 86400;
+    // Sunday is the first day of the week
+    let weekday = (days + 1) % 7 + 1;
+    let mut year = 1990;
+    while days >= 365 + leap(year) as u64 {
+        days -= 365 + leap(year) as u64;
+        year += 1;
+    }
+    let february = 28 + leap(year) as u64;
+    let mut month = 1;
+    for length in [31, february, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31] {
+        if days < length {
+            break;
+        }
+        days -= length;
+        month += 1;
+    }
+    let millis = time % 1000;
+    [
+        bcd(year % 100),
+        bcd(month),
+        bcd(days + 1),
+        bcd(seconds / 3600 % 24),
+        bcd(seconds / 60 % 60),
+        bcd(seconds % 60),
+        bcd(millis / 10),
+        ((millis % 10) << 4 | weekday) as u8,
+    ]
+}
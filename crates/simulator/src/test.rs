@@ -0,0 +1,107 @@
+//! Runner of the tests of a program, every test starts on a fresh CPU
+
+use fpp_compiler::{
+    lir::s7::{transformer::transform_address, S7Lir, S7Mnemonics, WriteAwl},
+    mir::{
+        test::{MirScanLength, MirTest, MirTestStep, MirTestValue},
+        value::MirAddress,
+    },
+    util::Quote,
+};
+
+use crate::{error, Simulator};
+
+/// Step of a test that failed and why
+#[derive(Debug)]
+pub struct Failure {
+    pub quote: Quote,
+    pub message: String,
+}
+
+impl Failure {
+    fn new(quote: &Quote, message: impl Into<String>) -> Self {
+        Self {
+            quote: quote.clone(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Execute the steps of the test, scans advance the clock by the cycle time
+pub fn run(lir: &S7Lir, test: &MirTest, cycle_time: u32) -> Result<(), Failure> {
+    let mut simulator = Simulator::new(lir);
+    simulator.cycle_time = cycle_time;
+    for step in &test.steps {
+        match step {
+            MirTestStep::Set { quote, addr, value } => set(&mut simulator, *addr, *value)
+                .map_err(|err| Failure::new(quote, err.to_string()))?,
+            MirTestStep::Scan { quote, length } => {
+                let scans = match *length {
+                    MirScanLength::Scans(scans) => scans,
+                    MirScanLength::Duration(millis) => millis.div_ceil(cycle_time as usize),
+                };
+                for _ in 0..scans {
+                    simulator
+                        .scan()
+                        .map_err(|err| Failure::new(quote, err.to_string()))?;
+                }
+            }
+            MirTestStep::Assert {
+                quote,
+                addr,
+                value,
+                equal,
+            } => {
+                let actual = read(&simulator, *addr, *value)
+                    .map_err(|err| Failure::new(quote, err.to_string()))?;
+                if (actual == *value) != *equal {
+                    let message = match equal {
+                        true => format!("expected {}, found {}", show(*value), show(actual)),
+                        false => format!("expected a value other than {}", show(*value)),
+                    };
+                    return Err(Failure::new(quote, message));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Inputs are set on their module and read by the program in the next scan
+fn set(simulator: &mut Simulator, addr: MirAddress, value: MirTestValue) -> error::Result<()> {
+    let operand = operand(addr);
+    match (Simulator::is_input(&operand), value) {
+        (true, MirTestValue::Bool(value)) => simulator.set_input(&operand, value),
+        (true, MirTestValue::Bits(value)) => simulator.set_input_bits(&operand, value),
+        (false, MirTestValue::Bool(value)) => simulator.write(&operand, value),
+        (false, MirTestValue::Bits(value)) => simulator.write_bits(&operand, value),
+    }
+}
+
+/// Read the operand as the kind of the expected value
+fn read(
+    simulator: &Simulator,
+    addr: MirAddress,
+    expected: MirTestValue,
+) -> error::Result<MirTestValue> {
+    let operand = operand(addr);
+    match expected {
+        MirTestValue::Bool(_) => simulator.read(&operand).map(MirTestValue::Bool),
+        MirTestValue::Bits(_) => simulator.read_bits(&operand).map(MirTestValue::Bits),
+    }
+}
+
+fn operand(addr: MirAddress) -> String {
+    let mut operand = Vec::new();
+    transform_address(addr)
+        .write_awl(&mut operand, S7Mnemonics::German)
+        .expect("Write to memory");
+    String::from_utf8(operand).expect("Operands are ASCII")
+}
+
+fn show(value: MirTestValue) -> String {
+    match value {
+        MirTestValue::Bool(value) => format!("`{value}`"),
+        MirTestValue::Bits(value) => format!("`{value}` (`16#{value:X}`)"),
+    }
+}
@@ -0,0 +1,138 @@
+//! Value change dump of simulated signals, timed in milliseconds of the virtual clock
+
+use std::io::{Result, Write};
+
+use fpp_compiler::{
+    lir::s7::{self, S7Mnemonics, WriteAwl},
+    mir::{ops::MirOp, value::MirValue, Mir},
+};
+
+use crate::{error, Simulator};
+
+/// Operand recorded under a name, inputs are recorded as set by the simulation
+#[derive(Clone, Debug)]
+pub struct Signal {
+    pub name: String,
+    pub operand: String,
+    bits: u32,
+    input: bool,
+}
+
+impl Signal {
+    pub fn new(name: impl Into<String>, operand: impl Into<String>) -> error::Result<Self> {
+        let operand = operand.into();
+        let bits = Simulator::bits(&operand)?;
+        let input = Simulator::is_input(&operand);
+        Ok(Self {
+            name: name.into(),
+            operand,
+            bits,
+            input,
+        })
+    }
+}
+
+/// Signals of the named variables, flip-flops are recorded by their state bit
+///
+/// Variables holding an expression have no operand, they are computed where they are used and
+/// are not recorded.
+pub fn variables(mir: &Mir) -> Vec<Signal> {
+    let mut signals: Vec<Signal> = Vec::new();
+    for variable in &mir.variables {
+        let addr = match &variable.value {
+            MirValue::Address(addr) => *addr,
+            MirValue::Ops(ops) => match ops.ops.last() {
+                Some(MirOp::And { addr }) => *addr,
+                _ => continue,
+            },
+            _ => continue,
+        };
+        let addr = s7::transformer::transform_address(addr);
+        if addr.is_indirect() {
+            continue;
+        }
+        let mut operand = Vec::new();
+        addr.write_awl(&mut operand, S7Mnemonics::German)
+            .expect("Write to memory");
+        let operand = String::from_utf8(operand).expect("Operands are ASCII");
+        let name = &mir.source.code[&variable.name];
+        let exists = signals
+            .iter()
+            .any(|it| it.name == name && it.operand == operand);
+        if !exists {
+            signals.push(Signal::new(name, operand).expect("Operand of a variable"));
+        }
+    }
+    signals
+}
+
+/// Writer of a VCD file, the header is written on creation
+pub struct Vcd<W: Write> {
+    out: W,
+    signals: Vec<Signal>,
+    /// Values of the last sample, `None` before the first one
+    values: Vec<Option<u32>>,
+}
+
+impl<W: Write> Vcd<W> {
+    pub fn new(mut out: W, signals: Vec<Signal>) -> Result<Self> {
+        writeln!(out, "$version F++ simulator $end")?;
+        writeln!(out, "$timescale 1ms $end")?;
+        writeln!(out, "$scope module plc $end")?;
+        for (i, signal) in signals.iter().enumerate() {
+            // Names must not contain whitespace
+            let name: String = signal.name.split_whitespace().collect::<Vec<_>>().join("_");
+            writeln!(out, "$var wire {} {} {name} $end", signal.bits, id(i))?;
+        }
+        writeln!(out, "$upscope $end")?;
+        writeln!(out, "$enddefinitions $end")?;
+        let values = vec![None; signals.len()];
+        Ok(Self {
+            out,
+            signals,
+            values,
+        })
+    }
+
+    /// Record the signals at the current time of the simulator, only changes are written
+    pub fn sample(&mut self, simulator: &Simulator) -> Result<()> {
+        let mut time = Some(simulator.time());
+        for (i, signal) in self.signals.iter().enumerate() {
+            let value = match signal.input {
+                true => simulator.input_bits(&signal.operand),
+                false => simulator.read_bits(&signal.operand),
+            };
+            let value = value.expect("Operand of a signal");
+            if self.values[i] == Some(value) {
+                continue;
+            }
+            if let Some(time) = time.take() {
+                writeln!(self.out, "#{time}")?;
+            }
+            match signal.bits {
+                1 => writeln!(self.out, "{}{}", value, id(i))?,
+                _ => writeln!(self.out, "b{value:b} {}", id(i))?,
+            }
+            self.values[i] = Some(value);
+        }
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// Identifier code of the signal at the index, printable characters from `!` to `~`
+fn id(index: usize) -> String {
+    let mut id = String::new();
+    let mut index = index;
+    loop {
+        id.push((b'!' + (index % 94) as u8) as char);
+        index /= 94;
+        if index == 0 {
+            return id;
+        }
+        index -= 1;
+    }
+}
@@ -0,0 +1,219 @@
+//! Equivalence check of the boolean outputs of a program and their S7 instructions
+//!
+//! The value every output is written from is enumerated over all combinations of the bits it
+//! reads, once evaluated on the MIR value and once by executing the network of the statement.
+//!
+//! The MIR is the reference, so only the MIR writer and the S7 backend are checked. A value the
+//! parser or the MIR transformer got wrong, like an `or` parsed as `and`, is verified as correct.
+
+use fpp_compiler::{
+    lir::s7::{transformer::transform_address, S7Lir, S7Mnemonics, WriteAwl},
+    mir::{
+        value::{MirAddress, MirAddressIndex, MirValue},
+        Mir, MirAction, MirOutputAction,
+    },
+    util::Quote,
+};
+
+use crate::Simulator;
+
+/// Outputs reading more bits are not enumerated
+pub const MAX_OPERANDS: usize = 16;
+
+/// Output whose instructions compute another function than its source
+#[derive(Debug)]
+pub struct Mismatch {
+    pub quote: Quote,
+    /// Values of the operands the results differ for
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Number of outputs written by the program
+    pub outputs: usize,
+    /// Number of boolean outputs checked
+    pub verified: usize,
+    /// Statements with an output that is no boolean function of bit operands, like flip-flops,
+    /// timers and indirect reads, they are not checked
+    pub skipped: Vec<Quote>,
+    /// Statements with an output reading more than [MAX_OPERANDS] bits, they are not checked
+    pub too_large: Vec<Quote>,
+    pub mismatches: Vec<Mismatch>,
+}
+
+/// Result of checking a single output
+enum Check {
+    Verified,
+    Skipped,
+    TooLarge,
+    Mismatch(String),
+}
+
+/// Boolean function of the source over the operands of a [Function]
+enum Expression {
+    Constant(bool),
+    Operand(usize),
+    Not(Box<Expression>),
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+    Xor(Box<Expression>, Box<Expression>),
+}
+
+impl Expression {
+    /// Value for the operand bits, bit `i` is the value of operand `i`
+    fn evaluate(&self, bits: u32) -> bool {
+        match self {
+            Self::Constant(value) => *value,
+            Self::Operand(index) => bits >> index & 1 != 0,
+            Self::Not(value) => !value.evaluate(bits),
+            Self::And(left, right) => left.evaluate(bits) && right.evaluate(bits),
+            Self::Or(left, right) => left.evaluate(bits) || right.evaluate(bits),
+            Self::Xor(left, right) => left.evaluate(bits) != right.evaluate(bits),
+        }
+    }
+}
+
+/// Resolver of the operands read by an output
+struct Function<'a> {
+    mir: &'a Mir,
+    operands: Vec<String>,
+}
+
+impl<'a> Function<'a> {
+    /// Operand of a bit, timers are read as their status
+    fn operand(&mut self, addr: MirAddress) -> Option<Expression> {
+        let operand = operand(addr);
+        if addr.index != MirAddressIndex::Direct || Simulator::bits(&operand).ok()? != 1 {
+            return None;
+        }
+        let index = match self.operands.iter().position(|it| *it == operand) {
+            Some(index) => index,
+            None => {
+                self.operands.push(operand);
+                self.operands.len() - 1
+            }
+        };
+        Some(Expression::Operand(index))
+    }
+
+    /// Function of a value, `None` if it is not a boolean expression of bit operands
+    fn expression(&mut self, value: &MirValue) -> Option<Expression> {
+        let mir = self.mir;
+        let binary = |function: &mut Self, left, right| {
+            Some((
+                Box::new(function.expression(left)?),
+                Box::new(function.expression(right)?),
+            ))
+        };
+        Some(match value {
+            MirValue::Bool(bool) => Expression::Constant(bool.value),
+            MirValue::Address(addr) => self.operand(*addr)?,
+            MirValue::VarRef(var) => self.expression(&mir.variables[var.index].value)?,
+            MirValue::Not(not) => Expression::Not(Box::new(self.expression(&not.value)?)),
+            MirValue::And(and) => {
+                let (left, right) = binary(self, &and.left, &and.right)?;
+                Expression::And(left, right)
+            }
+            MirValue::Or(or) => {
+                let (left, right) = binary(self, &or.left, &or.right)?;
+                Expression::Or(left, right)
+            }
+            MirValue::Xor(xor) => {
+                let (left, right) = binary(self, &xor.left, &xor.right)?;
+                Expression::Xor(left, right)
+            }
+            _ => return None,
+        })
+    }
+}
+
+/// Enumerate the operands of the output and compare its value with the executed network
+fn verify_output(mir: &Mir, lir: &S7Lir, network: usize, output: &MirOutputAction) -> Check {
+    let mut function = Function {
+        mir,
+        operands: Vec::new(),
+    };
+    let target = operand(output.address);
+    let direct = output.address.index == MirAddressIndex::Direct;
+    if !direct || Simulator::bits(&target).ok() != Some(1) {
+        return Check::Skipped;
+    }
+    let Some(expression) = function.expression(&output.value) else {
+        return Check::Skipped;
+    };
+    let operands = &function.operands;
+    if operands.len() > MAX_OPERANDS {
+        return Check::TooLarge;
+    }
+    for bits in 0..1u32 << operands.len() {
+        let mut simulator = Simulator::new(lir);
+        let result = operands
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, operand)| simulator.write(operand, bits >> i & 1 != 0))
+            .and_then(|()| simulator.execute_network(network))
+            .and_then(|()| simulator.read::<bool>(&target));
+        let actual = match result {
+            Ok(actual) => actual,
+            Err(err) => {
+                return Check::Mismatch(format!("the instructions can not be executed: {err}"))
+            }
+        };
+        let expected = expression.evaluate(bits);
+        if actual != expected {
+            let values: Vec<_> = operands
+                .iter()
+                .enumerate()
+                .map(|(i, operand)| format!("`{operand}` = `{}`", bits >> i & 1 != 0))
+                .collect();
+            let condition = match values.is_empty() {
+                true => String::new(),
+                false => format!("for {}, ", values.join(", ")),
+            };
+            return Check::Mismatch(format!(
+                "{condition}`{target}` should be `{expected}`, the instructions yield `{actual}`"
+            ));
+        }
+    }
+    Check::Verified
+}
+
+/// Compare the boolean outputs of every network with the values they were written from
+pub fn verify(mir: &Mir, lir: &S7Lir) -> Report {
+    let mut report = Report::default();
+    for (index, network) in mir.networks.iter().enumerate() {
+        // Statements with several unchecked outputs are listed once
+        let (mut skipped, mut too_large) = (false, false);
+        for action in &mir.actions[network.actions.clone()] {
+            let MirAction::Output(output) = action else {
+                continue;
+            };
+            report.outputs += 1;
+            match verify_output(mir, lir, index, output) {
+                Check::Verified => report.verified += 1,
+                Check::Skipped => skipped = true,
+                Check::TooLarge => too_large = true,
+                Check::Mismatch(message) => report.mismatches.push(Mismatch {
+                    quote: network.quote.clone(),
+                    message,
+                }),
+            }
+        }
+        if skipped {
+            report.skipped.push(network.quote.clone());
+        }
+        if too_large {
+            report.too_large.push(network.quote.clone());
+        }
+    }
+    report
+}
+
+fn operand(addr: MirAddress) -> String {
+    let mut operand = Vec::new();
+    transform_address(addr)
+        .write_awl(&mut operand, S7Mnemonics::German)
+        .expect("Write to memory");
+    String::from_utf8(operand).expect("Operands are ASCII")
+}
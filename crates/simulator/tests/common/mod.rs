@@ -0,0 +1,54 @@
+//! Helpers shared by the integration tests
+
+#![allow(dead_code)]
+
+use std::rc::Rc;
+
+use fpp_compiler::{
+    lir::s7::{self, S7Lir},
+    mir::{self, Mir},
+    parser::Parser,
+    util::Source,
+};
+use fpp_simulator::Simulator;
+
+/// Parse and transform a program into MIR and S7-LIR
+pub fn compile(code: &str) -> (Mir, S7Lir) {
+    let source = Rc::new(Source::new("test.fpp", code));
+    let Ok(hir) = Parser::new(source).parse() else {
+        panic!("The program does not parse");
+    };
+    let Ok(mir) = mir::transformer::transform(hir) else {
+        panic!("The program does not transform");
+    };
+    let Ok(lir) = s7::transformer::transform(&mir) else {
+        panic!("The program has no S7-LIR");
+    };
+    (mir, lir)
+}
+
+/// Check `output` after a scan for every combination of `inputs` against `expected`
+pub fn assert_truth_table(
+    code: &str,
+    inputs: &[&str],
+    output: &str,
+    expected: impl Fn(&[bool]) -> bool,
+) {
+    let (_, lir) = compile(code);
+    for combination in 0..1u32 << inputs.len() {
+        let values: Vec<bool> = (0..inputs.len())
+            .map(|bit| combination & (1 << bit) != 0)
+            .collect();
+        let mut simulator = Simulator::new(&lir);
+        for (input, &value) in inputs.iter().zip(&values) {
+            simulator.set_input(input, value).expect("Set input");
+        }
+        simulator.scan().expect("Scan");
+        let actual: bool = simulator.get_output(output).expect("Get output");
+        assert_eq!(
+            actual,
+            expected(&values),
+            "{output} for {inputs:?} = {values:?}"
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use fpp_compiler::lir::s7::{S7Address, S7Instruction, S7Lir, S7Network};
+use fpp_simulator::Simulator;
+
+fn addr(text: &str) -> S7Address {
+    S7Address::parse(text).expect("Valid operand")
+}
+
+fn lir(instructions: Vec<S7Instruction>) -> S7Lir {
+    S7Lir {
+        networks: vec![S7Network {
+            instructions,
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn not_keeps_or_bit() {
+    let lir = lir(vec![
+        S7Instruction::And { addr: addr("E0.0") },
+        S7Instruction::Or { addr: addr("E0.1") },
+        S7Instruction::Not,
+        S7Instruction::And { addr: addr("E0.2") },
+        S7Instruction::AssignBit { addr: addr("A0.0") },
+    ]);
+    for inputs in 0..8u8 {
+        let [a, b, c] = [0, 1, 2].map(|bit| inputs & (1 << bit) != 0);
+        let mut simulator = Simulator::new(&lir);
+        simulator.set_input("EB0", inputs).unwrap();
+        simulator.scan().unwrap();
+        // `a or (not (a or b) and c)`
+        let expected = a || (!b && c);
+        assert_eq!(simulator.get_output::<bool>("A0.0").unwrap(), expected);
+    }
+}
+
+#[test]
+fn not_sets_sta() {
+    let lir = lir(vec![
+        S7Instruction::And { addr: addr("E0.0") },
+        S7Instruction::Or { addr: addr("E0.1") },
+        S7Instruction::Not,
+    ]);
+    let mut simulator = Simulator::new(&lir);
+    simulator.set_input("E0.0", true).unwrap();
+    simulator.scan().unwrap();
+    let status = simulator.status();
+    assert!(!status.rlo);
+    assert!(status.or);
+    assert!(status.sta);
+}
@@ -0,0 +1,18 @@
+mod common;
+
+use common::compile;
+use fpp_simulator::Simulator;
+
+#[test]
+fn copied_pointer_follows_its_source() {
+    let (_, lir) = compile(
+        "let p = M[DB5.DBD0];
+         DB5.DBD0 = P#10.0;
+         M10.0 = E0.0;
+         A0.0 = p;",
+    );
+    let mut simulator = Simulator::new(&lir);
+    simulator.set_input("E0.0", true).expect("Set input");
+    simulator.scan().expect("Scan");
+    assert!(simulator.get_output::<bool>("A0.0").expect("Get output"));
+}
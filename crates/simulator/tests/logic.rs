@@ -0,0 +1,57 @@
+mod common;
+
+use common::assert_truth_table;
+
+const INPUTS: [&str; 3] = ["E0.0", "E0.1", "E0.2"];
+
+#[test]
+fn or_before_and() {
+    assert_truth_table("A0.0 = (E0.0 or E0.1) and E0.2;", &INPUTS, "A0.0", |v| {
+        (v[0] || v[1]) && v[2]
+    });
+}
+
+#[test]
+fn or_after_and() {
+    assert_truth_table("A0.0 = E0.0 and (E0.1 or E0.2);", &INPUTS, "A0.0", |v| {
+        v[0] && (v[1] || v[2])
+    });
+}
+
+#[test]
+fn xor_before_or() {
+    assert_truth_table("A0.0 = (E0.0 xor E0.1) or E0.2;", &INPUTS, "A0.0", |v| {
+        (v[0] ^ v[1]) || v[2]
+    });
+}
+
+#[test]
+fn xor_before_and() {
+    assert_truth_table("A0.0 = (E0.0 xor E0.1) and E0.2;", &INPUTS, "A0.0", |v| {
+        (v[0] ^ v[1]) && v[2]
+    });
+}
+
+#[test]
+fn or_before_xor() {
+    assert_truth_table("A0.0 = (E0.0 or E0.1) xor E0.2;", &INPUTS, "A0.0", |v| {
+        (v[0] || v[1]) ^ v[2]
+    });
+}
+
+#[test]
+fn negated_or_before_and() {
+    assert_truth_table("A0.0 = !(E0.0 or E0.1) and E0.2;", &INPUTS, "A0.0", |v| {
+        !(v[0] || v[1]) && v[2]
+    });
+}
+
+#[test]
+fn and_of_two_or_terms() {
+    assert_truth_table(
+        "A0.0 = (E0.0 or E0.1) and (E0.2 or E0.3);",
+        &["E0.0", "E0.1", "E0.2", "E0.3"],
+        "A0.0",
+        |v| (v[0] || v[1]) && (v[2] || v[3]),
+    );
+}
@@ -0,0 +1,92 @@
+use std::rc::Rc;
+
+use fpp_compiler::{
+    lir::s7::{self, S7Lir},
+    mir::{self, test::MirTest, Mir},
+    parser::Parser,
+    util::Source,
+};
+use fpp_simulator::test::run;
+
+/// Compile a program with its test blocks, the tests are not part of the program
+fn compile(code: &str) -> (Mir, S7Lir, Vec<MirTest>) {
+    let source = Rc::new(Source::new("test.fpp", code));
+    let Ok(mut hir) = Parser::new(source).parse() else {
+        panic!("The program does not parse");
+    };
+    let tests = std::mem::take(&mut hir.tests);
+    let Ok(mut mir) = mir::transformer::transform(hir) else {
+        panic!("The program does not transform");
+    };
+    let Ok(tests) = mir::transformer::test::transform_tests(&mut mir, tests) else {
+        panic!("The tests do not transform");
+    };
+    let Ok(lir) = s7::transformer::transform(&mir) else {
+        panic!("The program has no S7-LIR");
+    };
+    (mir, lir, tests)
+}
+
+const PROGRAM: &str = r#"A0.0 = E0.0 and not E0.1;
+
+test "output follows button" {
+    set E0.0 = true;
+    scan;
+    assert A0.0 == true;
+    set E0.1 = true;
+    scan;
+    assert A0.0 == false;
+}
+
+test "broken" {
+    set E0.0 = true;
+    scan;
+    assert A0.0 == false;
+}
+"#;
+
+#[test]
+fn tests_are_compiled_separately() {
+    let (mir, lir, tests) = compile(PROGRAM);
+    assert_eq!(mir.networks.len(), 1);
+    assert_eq!(lir.networks.len(), 1);
+    assert_eq!(tests.len(), 2);
+    let name = &tests[0].name;
+    assert_eq!(&PROGRAM[name.start..name.end], "output follows button");
+}
+
+#[test]
+fn passing_test_passes() {
+    let (_, lir, tests) = compile(PROGRAM);
+    assert!(run(&lir, &tests[0], 10).is_ok());
+}
+
+#[test]
+fn failing_assertion_is_reported() {
+    let (_, lir, tests) = compile(PROGRAM);
+    let Err(failure) = run(&lir, &tests[1], 10) else {
+        panic!("The test passes");
+    };
+    assert_eq!(failure.message, "expected `false`, found `true`");
+    let quote = failure.quote;
+    assert_eq!(&PROGRAM[quote.start..quote.end], "assert A0.0 == false;");
+}
+
+#[test]
+fn scan_duration_advances_timers() {
+    let code = r#"M0.0 = TON(E0.0, T#50ms);
+A0.0 = M0.0;
+
+test "timer elapses" {
+    set E0.0 = true;
+    scan;
+    assert A0.0 == false;
+    scan 30ms;
+    assert A0.0 == false;
+    scan 30ms;
+    assert A0.0 == true;
+}
+"#;
+    let (_, lir, tests) = compile(code);
+    assert!(run(&lir, &tests[0], 10).is_ok());
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use common::compile;
+use fpp_simulator::{
+    vcd::{self, Vcd},
+    Simulator,
+};
+
+#[test]
+fn flip_flop_variables_are_recorded() {
+    let (mir, _) = compile("let s = sr(E0.0, E0.1); A0.0 = s;");
+    let signals = vcd::variables(&mir);
+    assert_eq!(signals.len(), 1);
+    assert_eq!(signals[0].name, "s");
+    assert!(signals[0].operand.starts_with('M'));
+}
+
+#[test]
+fn expression_variables_are_not_recorded() {
+    let (mir, _) = compile("let a = E0.0; let b = a and E0.1; A0.0 = b;");
+    let names: Vec<_> = vcd::variables(&mir).into_iter().map(|it| it.name).collect();
+    assert_eq!(names, ["a"]);
+}
+
+#[test]
+fn flip_flop_is_sampled() {
+    let (mir, lir) = compile("let s = sr(E0.0, E0.1); A0.0 = s;");
+    let mut simulator = Simulator::new(&lir);
+    let mut vcd = Vcd::new(Vec::new(), vcd::variables(&mir)).expect("Write to memory");
+    simulator.scan().expect("Scan");
+    vcd.sample(&simulator).expect("Write to memory");
+    simulator.set_input("E0.0", true).expect("Input");
+    simulator.scan().expect("Scan");
+    vcd.sample(&simulator).expect("Write to memory");
+    let text = String::from_utf8(vcd.into_inner()).expect("ASCII VCD");
+    assert!(text.contains("$var wire 1 ! s $end"));
+    assert!(text.ends_with("0!\n#20\n1!\n"), "{text}");
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use common::{assert_truth_table, compile};
+use fpp_compiler::lir::s7::{S7Address, S7Instruction};
+use fpp_simulator::verify::{self, MAX_OPERANDS};
+
+fn addr(text: &str) -> S7Address {
+    S7Address::parse(text).expect("Valid operand")
+}
+
+#[test]
+fn correct_outputs_are_verified() {
+    let (mir, lir) = compile("A0.0 = (E0.0 or E0.1) and E0.2; A0.1 = E0.0 xor !E0.1;");
+    let report = verify::verify(&mir, &lir);
+    assert_eq!(report.verified, 2);
+    assert!(report.mismatches.is_empty());
+}
+
+// The verifier compares against the MIR, a wrong operation of the parser is only found
+// against the truth table of the source
+#[test]
+fn or_and_xor_keep_their_operation() {
+    let code = "A0.0 = E0.0 or E0.1; A0.1 = E0.0 xor E0.1;";
+    let (mir, lir) = compile(code);
+    assert_eq!(verify::verify(&mir, &lir).verified, 2);
+    let inputs = ["E0.0", "E0.1"];
+    assert_truth_table(code, &inputs, "A0.0", |v| v[0] || v[1]);
+    assert_truth_table(code, &inputs, "A0.1", |v| v[0] != v[1]);
+}
+
+#[test]
+fn bracketless_or_before_and_is_a_mismatch() {
+    let code = "A0.0 = (E0.0 or E0.1) and E0.2;";
+    let (mir, mut lir) = compile(code);
+    // `U a; O b; U c` computes `a or (b and c)`
+    lir.networks[0].instructions = vec![
+        S7Instruction::And { addr: addr("E0.0") },
+        S7Instruction::Or { addr: addr("E0.1") },
+        S7Instruction::And { addr: addr("E0.2") },
+        S7Instruction::AssignBit { addr: addr("A0.0") },
+    ];
+    let report = verify::verify(&mir, &lir);
+    assert_eq!(report.mismatches.len(), 1);
+    let mismatch = &report.mismatches[0];
+    assert_eq!(&code[mismatch.quote.start..mismatch.quote.end], code);
+    assert!(mismatch.message.contains("`A0.0` should be `false`"));
+}
+
+#[test]
+fn shadowed_variables_are_resolved() {
+    let (mir, lir) = compile("let a = E0.0; let a = !a; A0.0 = a and E0.1;");
+    let report = verify::verify(&mir, &lir);
+    assert_eq!(report.verified, 1);
+    assert!(report.mismatches.is_empty());
+}
+
+#[test]
+fn outputs_through_variables_are_verified() {
+    let (mir, lir) = compile("let q = A0.1; q = (E0.0 or E0.1) and E0.2;");
+    assert_eq!(verify::verify(&mir, &lir).verified, 1);
+}
+
+#[test]
+fn flip_flops_are_skipped() {
+    let code = "A0.0 = sr(E0.0, E0.1);";
+    let (mir, lir) = compile(code);
+    let report = verify::verify(&mir, &lir);
+    assert_eq!((report.outputs, report.verified), (1, 0));
+    assert_eq!(report.skipped.len(), 1);
+    let quote = &report.skipped[0];
+    assert_eq!(&code[quote.start..quote.end], code);
+}
+
+#[test]
+fn timers_and_indirect_reads_are_skipped() {
+    let code = "A0.0 = E0.0 and E0.1; A0.1 = TON(E0.0, T#5s); A0.2 = M[DB5.DBD0];";
+    let (mir, lir) = compile(code);
+    let report = verify::verify(&mir, &lir);
+    assert_eq!((report.outputs, report.verified), (3, 1));
+    let skipped: Vec<_> = report
+        .skipped
+        .iter()
+        .map(|quote| &code[quote.start..quote.end])
+        .collect();
+    assert_eq!(skipped, ["A0.1 = TON(E0.0, T#5s);", "A0.2 = M[DB5.DBD0];"]);
+}
+
+#[test]
+fn large_outputs_are_reported() {
+    let operands: Vec<_> = (0..=MAX_OPERANDS)
+        .map(|bit| format!("E{}.{}", bit / 8, bit % 8))
+        .collect();
+    let (mir, lir) = compile(&format!("A0.0 = {};", operands.join(" and ")));
+    let report = verify::verify(&mir, &lir);
+    assert_eq!(report.too_large.len(), 1);
+    assert_eq!(report.verified, 0);
+}